@@ -0,0 +1,48 @@
+//! Mesh a GSI elevation tile natively, e.g. from a server handler
+//!
+//!     cargo run --example mesh_tile -- tile.png
+//!
+//! Without an argument a synthetic cone is meshed instead.
+
+use peak_vista_wasm::{ElevationTile, Error, MeshGenerator, Mosaic};
+
+fn main() -> Result<(), Error> {
+    let tile = match std::env::args().nth(1) {
+        Some(path) => {
+            let data = std::fs::read(&path)
+                .map_err(|e| Error::Decode(format!("Failed to read {}: {}", path, e)))?;
+            ElevationTile::from_png(&data)?
+        }
+        None => ElevationTile::new(cone())?,
+    };
+
+    let generator = MeshGenerator::new(1.0);
+    let mesh = generator
+        .generate(tile.elevations(), 1000.0, 1)?
+        .into_buffers();
+    println!(
+        "{} vertices, {} indices, bounds {:?} - {:?}",
+        mesh.vertices.len() / 3,
+        mesh.indices.len().max(mesh.indices_u16.len()),
+        mesh.bounds_min,
+        mesh.bounds_max
+    );
+
+    // Tiles moved into a mosaic can be sampled by lat/lon
+    let mut mosaic = Mosaic::new(12);
+    mosaic.insert_tile(3626, 1617, tile);
+    if let Some(elevation) = mosaic.elevation_at(35.36, 138.73) {
+        println!("elevation at 35.36N 138.73E: {:.1} m", elevation);
+    }
+    Ok(())
+}
+
+/// A 2000 m cone centered on the tile
+fn cone() -> Vec<f32> {
+    (0..256 * 256)
+        .map(|i| {
+            let (x, y) = ((i % 256) as f32 - 127.5, (i / 256) as f32 - 127.5);
+            (2000.0 - (x * x + y * y).sqrt() * 15.0).max(0.0)
+        })
+        .collect()
+}
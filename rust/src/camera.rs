@@ -0,0 +1,229 @@
+use glam::{DVec3, Mat4, Vec3, Vec4};
+use wasm_bindgen::prelude::*;
+
+use crate::error::Error;
+use crate::mesh_generator::MeshGenerator;
+use crate::validation::{check_positive, check_zoom};
+
+/// Most candidate tiles visible_tiles() tests against the frustum
+const MAX_CANDIDATE_TILES: u64 = 1 << 20;
+
+/// Perspective camera in world units (x east, y up, z south), matching the renderer
+/// Positions are kept in f64 so world coordinates far from the origin stay exact
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct Camera {
+    position: DVec3,
+    target: DVec3,
+    fov: f32,
+    aspect: f32,
+    near: f32,
+    far: f32,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Camera {
+            position: DVec3::new(0.0, 1000.0, 2000.0),
+            target: DVec3::ZERO,
+            fov: 60.0,
+            aspect: 1.0,
+            near: 0.1,
+            far: 100000.0,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Camera {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Camera {
+        Camera::default()
+    }
+
+    #[wasm_bindgen]
+    pub fn set_position(&mut self, x: f64, y: f64, z: f64) {
+        self.position = DVec3::new(x, y, z);
+    }
+
+    #[wasm_bindgen]
+    pub fn set_target(&mut self, x: f64, y: f64, z: f64) {
+        self.target = DVec3::new(x, y, z);
+    }
+
+    /// fov: vertical field of view in degrees
+    /// aspect: viewport width / height
+    #[wasm_bindgen]
+    pub fn set_perspective(&mut self, fov: f32, aspect: f32, near: f32, far: f32) {
+        self.fov = fov.clamp(1.0, 179.0);
+        self.aspect = aspect.max(f32::EPSILON);
+        self.near = near.max(f32::EPSILON);
+        self.far = far.max(self.near * 2.0);
+    }
+
+    /// Get camera position [x, y, z]
+    #[wasm_bindgen]
+    pub fn get_position(&self) -> Vec<f64> {
+        self.position.to_array().to_vec()
+    }
+
+    /// Get camera target [x, y, z]
+    #[wasm_bindgen]
+    pub fn get_target(&self) -> Vec<f64> {
+        self.target.to_array().to_vec()
+    }
+
+    /// Tiles of a zoom level intersecting the view frustum, nearest first, as
+    /// [zoom, x, y, lod] quadruples with the LOD MeshGenerator::select_lod()
+    /// picks at the tile's nearest point
+    /// Tiles sit where generate() meshes placed at tile_x_to_world_x() and
+    /// tile_y_to_world_z() do, spanning min_height to max_height in world units
+    /// (elevation range times exaggeration)
+    /// lod_errors: geometric error per LOD level, coarsest first (lod_errors())
+    /// viewport_height: viewport height in pixels
+    #[wasm_bindgen]
+    #[allow(clippy::too_many_arguments)]
+    pub fn visible_tiles(
+        &self,
+        zoom: u8,
+        tile_size: f64,
+        min_height: f64,
+        max_height: f64,
+        lod_errors: &[f32],
+        viewport_height: f32,
+        max_pixel_error: f32,
+    ) -> Result<Vec<u32>, Error> {
+        check_zoom(zoom)?;
+        check_positive("tile_size", tile_size)?;
+        if lod_errors.is_empty() {
+            return Err(Error::InvalidArgument("No LOD errors given".into()));
+        }
+
+        // Camera-relative, so f32 keeps its precision far from the world origin
+        let view_projection = self.view_projection(self.position);
+        let inverse = view_projection.inverse();
+        let (mut low, mut high) = (DVec3::INFINITY, DVec3::NEG_INFINITY);
+        for corner in 0..8 {
+            let ndc = Vec3::new(
+                if corner & 1 == 0 { -1.0 } else { 1.0 },
+                if corner & 2 == 0 { -1.0 } else { 1.0 },
+                if corner & 4 == 0 { -1.0 } else { 1.0 },
+            );
+            let point = self.position + inverse.project_point3(ndc).as_dvec3();
+            low = low.min(point);
+            high = high.max(point);
+        }
+        if high.y < min_height || low.y > max_height {
+            return Ok(Vec::new());
+        }
+
+        // Tile (x, y) spans x * tile_size +- tile_size / 2 (and so along z)
+        let tiles = (1u64 << zoom.min(31)) as f64;
+        let index_range = |low: f64, high: f64| {
+            let first = (low / tile_size + 0.5).floor().clamp(0.0, tiles);
+            let last = (high / tile_size + 0.5).floor().clamp(-1.0, tiles - 1.0);
+            (first as i64, last as i64)
+        };
+        let (x0, x1) = index_range(low.x, high.x);
+        let (y0, y1) = index_range(low.z, high.z);
+        let candidates = (x1 - x0 + 1).max(0) as u64 * (y1 - y0 + 1).max(0) as u64;
+        if candidates > MAX_CANDIDATE_TILES {
+            return Err(Error::InvalidArgument(format!(
+                "View covers {} candidate tiles at zoom {}, more than {}",
+                candidates, zoom, MAX_CANDIDATE_TILES
+            )));
+        }
+
+        let rows = view_projection.transpose();
+        let planes = [
+            rows.w_axis + rows.x_axis,
+            rows.w_axis - rows.x_axis,
+            rows.w_axis + rows.y_axis,
+            rows.w_axis - rows.y_axis,
+            rows.w_axis + rows.z_axis,
+            rows.w_axis - rows.z_axis,
+        ];
+        let half = tile_size / 2.0;
+        let mut visible = Vec::new();
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                let center = DVec3::new(x as f64 * tile_size, 0.0, y as f64 * tile_size);
+                let box_low = DVec3::new(center.x - half, min_height, center.z - half);
+                let box_high = DVec3::new(center.x + half, max_height, center.z + half);
+                let (box_low, box_high) = (
+                    (box_low - self.position).as_vec3(),
+                    (box_high - self.position).as_vec3(),
+                );
+                let culled = planes.iter().any(|&p| outside(p, box_low, box_high));
+                if culled {
+                    continue;
+                }
+                // Nearest point of the box to the camera (at the camera origin)
+                let distance = Vec3::ZERO.clamp(box_low, box_high).length();
+                visible.push((distance, x as u32, y as u32));
+            }
+        }
+        visible.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let fov_deg = self.fov;
+        let mut result = Vec::with_capacity(visible.len() * 4);
+        for (distance, x, y) in visible {
+            let lod = MeshGenerator::select_lod(
+                lod_errors,
+                distance,
+                fov_deg,
+                viewport_height,
+                max_pixel_error,
+            )?;
+            result.extend([zoom as u32, x, y, lod as u32]);
+        }
+        Ok(result)
+    }
+}
+
+/// Whether a box lies entirely on the negative side of a plane (a, b, c, d)
+fn outside(plane: Vec4, low: Vec3, high: Vec3) -> bool {
+    // Box corner furthest along the plane normal
+    let corner = Vec3::select(plane.truncate().cmpge(Vec3::ZERO), high, low);
+    plane.truncate().dot(corner) + plane.w < 0.0
+}
+
+impl Camera {
+    pub(crate) fn position(&self) -> DVec3 {
+        self.position
+    }
+
+    pub(crate) fn target(&self) -> DVec3 {
+        self.target
+    }
+
+    pub(crate) fn near(&self) -> f32 {
+        self.near
+    }
+
+    /// View matrix for world positions given relative to `origin`
+    pub(crate) fn view_matrix(&self, origin: DVec3) -> Mat4 {
+        let eye = (self.position - origin).as_vec3();
+        let mut forward = (self.target - self.position).as_vec3();
+        if forward.length_squared() == 0.0 {
+            forward = glam::Vec3::NEG_Z;
+        }
+        // Looking straight up or down needs a different up vector
+        let up = if forward.normalize().y.abs() > 0.999 {
+            glam::Vec3::NEG_Z
+        } else {
+            glam::Vec3::Y
+        };
+        Mat4::look_to_rh(eye, forward, up)
+    }
+
+    /// OpenGL-style projection (clip z in -1..1), as used by three.js
+    pub(crate) fn projection_matrix(&self) -> Mat4 {
+        Mat4::perspective_rh_gl(self.fov.to_radians(), self.aspect, self.near, self.far)
+    }
+
+    /// Projection * view for world positions given relative to `origin`
+    pub(crate) fn view_projection(&self, origin: DVec3) -> Mat4 {
+        self.projection_matrix() * self.view_matrix(origin)
+    }
+}
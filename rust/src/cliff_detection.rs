@@ -0,0 +1,155 @@
+use wasm_bindgen::prelude::*;
+
+use crate::corridor::Corridor;
+use crate::error::Error;
+use crate::heightfield::Heightfield;
+use crate::hydrology::D8_OFFSETS;
+use crate::mosaic::Mosaic;
+
+/// Route segments checked against nearby cliff bands
+#[wasm_bindgen]
+pub struct CliffSegments {
+    /// 1 where the segment's corridor touches a cliff band, per route segment
+    flags: Vec<u8>,
+    /// Height in meters of the tallest cliff band touching each segment (0 if none)
+    heights: Vec<f32>,
+    band_count: usize,
+}
+
+#[wasm_bindgen]
+impl CliffSegments {
+    /// Number of route segments (vertices - 1)
+    #[wasm_bindgen]
+    pub fn count(&self) -> usize {
+        self.flags.len()
+    }
+
+    /// Number of distinct cliff bands touching the route
+    #[wasm_bindgen]
+    pub fn band_count(&self) -> usize {
+        self.band_count
+    }
+
+    /// Get exposure flags per segment (1 = exposed)
+    #[wasm_bindgen]
+    pub fn get_flags(&self) -> Vec<u8> {
+        self.flags.clone()
+    }
+
+    /// Get tallest touching cliff band height per segment in meters
+    #[wasm_bindgen]
+    pub fn get_heights(&self) -> Vec<f32> {
+        self.heights.clone()
+    }
+}
+
+/// Finds cliff bands (connected steep cells spanning a minimum height) near a route
+#[wasm_bindgen]
+pub struct CliffDetector {
+    min_slope: f32,
+    min_height: f32,
+    corridor_width: f64,
+}
+
+#[wasm_bindgen]
+impl CliffDetector {
+    /// min_slope: slope in degrees from which terrain counts as cliff (e.g. 45)
+    /// min_height: vertical extent in meters a steep band needs to count as a cliff
+    #[wasm_bindgen(constructor)]
+    pub fn new(min_slope: f32, min_height: f32) -> CliffDetector {
+        CliffDetector {
+            min_slope,
+            min_height: min_height.max(0.0),
+            corridor_width: 30.0,
+        }
+    }
+
+    /// Distance in meters either side of the route searched for cliffs
+    #[wasm_bindgen]
+    pub fn set_corridor_width(&mut self, meters: f64) {
+        self.corridor_width = meters.max(0.0);
+    }
+
+    /// Flag route segments whose corridor touches a cliff band
+    /// Band heights are measured within the analysis window around the route
+    /// lats, lons: route vertices in degrees
+    #[wasm_bindgen]
+    pub fn detect(
+        &self,
+        mosaic: &Mosaic,
+        lats: &[f64],
+        lons: &[f64],
+    ) -> Result<CliffSegments, Error> {
+        if lats.len() != lons.len() {
+            return Err(Error::InvalidArgument(format!(
+                "Coordinate length mismatch: {} latitudes, {} longitudes",
+                lats.len(),
+                lons.len()
+            )));
+        }
+        let corridor = Corridor::new(mosaic, lats, lons, self.corridor_width)?;
+        let (width, height) = (corridor.width, corridor.height);
+        let heightmap = Heightfield::new(&corridor.elevations, width, height)
+            .ok_or_else(|| Error::InvalidArgument("Route corridor is empty".into()))?;
+
+        let min_gradient = self.min_slope.to_radians().tan();
+        let steep: Vec<bool> = (0..width * height)
+            .map(|index| {
+                let (dzdx, dzdy) =
+                    heightmap.gradient(index % width, index / width, corridor.cell_size as f32);
+                // NaN gradients (missing tiles) fail the comparison
+                (dzdx * dzdx + dzdy * dzdy).sqrt() >= min_gradient
+            })
+            .collect();
+
+        // Label 8-connected steep bands and measure their vertical extent
+        let mut band = vec![usize::MAX; width * height];
+        let mut band_heights = Vec::new();
+        for seed in 0..width * height {
+            if !steep[seed] || band[seed] != usize::MAX {
+                continue;
+            }
+            let label = band_heights.len();
+            let (mut low, mut high) = (f32::INFINITY, f32::NEG_INFINITY);
+            let mut stack = vec![seed];
+            band[seed] = label;
+            while let Some(index) = stack.pop() {
+                low = low.min(corridor.elevations[index]);
+                high = high.max(corridor.elevations[index]);
+                let (x, y) = ((index % width) as isize, (index / width) as isize);
+                for (dx, dy) in D8_OFFSETS {
+                    let (nx, ny) = (x + dx, y + dy);
+                    if nx < 0 || ny < 0 || nx >= width as isize || ny >= height as isize {
+                        continue;
+                    }
+                    let neighbor = ny as usize * width + nx as usize;
+                    if steep[neighbor] && band[neighbor] == usize::MAX {
+                        band[neighbor] = label;
+                        stack.push(neighbor);
+                    }
+                }
+            }
+            band_heights.push(high - low);
+        }
+
+        let segments = corridor.route.len() - 1;
+        let mut result = CliffSegments {
+            flags: vec![0; segments],
+            heights: vec![0.0; segments],
+            band_count: 0,
+        };
+        let mut touched = vec![false; band_heights.len()];
+        for segment in 0..segments {
+            corridor.for_each_segment_cell(segment, |index| {
+                let label = band[index];
+                if label != usize::MAX && band_heights[label] >= self.min_height {
+                    result.flags[segment] = 1;
+                    result.heights[segment] = result.heights[segment].max(band_heights[label]);
+                    touched[label] = true;
+                }
+            });
+        }
+        result.band_count = touched.iter().filter(|&&t| t).count();
+        Ok(result)
+    }
+}
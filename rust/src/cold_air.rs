@@ -0,0 +1,59 @@
+use wasm_bindgen::prelude::*;
+
+use crate::error::Error;
+use crate::heightfield::Heightfield;
+use crate::hydrology::fill_depressions;
+
+/// Cold-air pooling estimate for frost hollows
+/// Cold air drains downhill at night and collects in basins up to the level of
+/// the lowest saddle that lets it spill out. The pool depth at a cell is how far
+/// it lies below that spill level, i.e. the depth of a depression fill.
+#[wasm_bindgen]
+pub struct ColdAirPooling {
+    full_depth: f32,
+}
+
+#[wasm_bindgen]
+impl ColdAirPooling {
+    /// full_depth: pool depth in meters at which the frost risk index reaches 1
+    #[wasm_bindgen(constructor)]
+    pub fn new(full_depth: f32) -> ColdAirPooling {
+        ColdAirPooling {
+            full_depth: full_depth.max(f32::EPSILON),
+        }
+    }
+
+    /// Depth in meters of pooled cold air over each cell (0 outside basins)
+    /// elevations: width x height heightmap
+    #[wasm_bindgen]
+    pub fn compute_depth(
+        &self,
+        elevations: &[f32],
+        width: u32,
+        height: u32,
+    ) -> Result<Vec<f32>, Error> {
+        let heightmap = Heightfield::from_slice(elevations, width, height)?;
+        let filled = fill_depressions(&heightmap, 0.0);
+        Ok(filled
+            .iter()
+            .zip(elevations)
+            .map(|(&spill, &ground)| (spill - ground).max(0.0))
+            .collect())
+    }
+
+    /// Frost risk index per cell: pool depth scaled by full_depth and clamped to 0..1
+    /// elevations: width x height heightmap
+    #[wasm_bindgen]
+    pub fn compute_risk(
+        &self,
+        elevations: &[f32],
+        width: u32,
+        height: u32,
+    ) -> Result<Vec<f32>, Error> {
+        let mut depth = self.compute_depth(elevations, width, height)?;
+        for value in &mut depth {
+            *value = (*value / self.full_depth).min(1.0);
+        }
+        Ok(depth)
+    }
+}
@@ -0,0 +1,151 @@
+use wasm_bindgen::prelude::*;
+
+use crate::error::{tile_length_error, Error};
+use crate::heightfield::Heightfield;
+use crate::validation::check_positive;
+
+/// Regular height grid of a tile for physics engine heightfield colliders
+/// Grid rows run north to south along z and columns west to east along x,
+/// n x n samples spanning the mesh (generate() placement) with uniform spacing.
+/// For Rapier: ColliderDesc.heightfield(n - 1, n - 1, get_heights_column_major(),
+/// {x: width, y: 1, z: depth}) translated to (center_x, 0, center_z).
+/// For Ammo: btHeightfieldTerrainShape(n, n, get_heights(), 1, min_height,
+/// max_height, 1, PHY_FLOAT, false) scaled by the sample spacing and
+/// translated to (center_x, center_y, center_z).
+#[wasm_bindgen]
+pub struct HeightfieldCollider {
+    samples: usize,
+    heights: Vec<f32>,
+    width: f32,
+    origin_x: f32,
+    origin_z: f32,
+    min_height: f32,
+    max_height: f32,
+}
+
+#[wasm_bindgen]
+impl HeightfieldCollider {
+    /// elevations: 256x256 heightmap (65536 values)
+    /// tile_size: size of tile in world units (as for the terrain mesh)
+    /// samples: grid samples per side, 2 to 256 (256 keeps every pixel);
+    /// fewer samples are interpolated bilinearly
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        elevations: &[f32],
+        tile_size: f32,
+        samples: u32,
+    ) -> Result<HeightfieldCollider, Error> {
+        check_positive("tile_size", tile_size as f64)?;
+        let heightmap = Heightfield::new(elevations, 256, 256)
+            .ok_or_else(|| tile_length_error(elevations.len()))?;
+        if !(2..=256).contains(&samples) {
+            return Err(Error::InvalidArgument(format!(
+                "Invalid sample count: {}, expected 2 to 256",
+                samples
+            )));
+        }
+
+        let samples = samples as usize;
+        let spacing = 255.0 / (samples - 1) as f32;
+        let mut heights = Vec::with_capacity(samples * samples);
+        for row in 0..samples {
+            for column in 0..samples {
+                heights.push(heightmap.sample(column as f32 * spacing, row as f32 * spacing));
+            }
+        }
+        let min_height = heights.iter().copied().fold(f32::INFINITY, f32::min);
+        let max_height = heights.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+
+        let pixel_size = tile_size / 256.0;
+        Ok(HeightfieldCollider {
+            samples,
+            heights,
+            width: 255.0 * pixel_size,
+            origin_x: -tile_size / 2.0,
+            origin_z: -tile_size / 2.0,
+            min_height,
+            max_height,
+        })
+    }
+
+    /// Samples per row and per column
+    #[wasm_bindgen]
+    pub fn samples(&self) -> usize {
+        self.samples
+    }
+
+    /// Extent along x in world units
+    #[wasm_bindgen]
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    /// Extent along z in world units
+    #[wasm_bindgen]
+    pub fn depth(&self) -> f32 {
+        self.width
+    }
+
+    /// Distance between neighboring samples in world units
+    #[wasm_bindgen]
+    pub fn spacing(&self) -> f32 {
+        self.width / (self.samples - 1) as f32
+    }
+
+    #[wasm_bindgen]
+    pub fn center_x(&self) -> f32 {
+        self.origin_x + self.width / 2.0
+    }
+
+    /// Midpoint of the height range, where Ammo centers its heightfield
+    #[wasm_bindgen]
+    pub fn center_y(&self) -> f32 {
+        (self.min_height + self.max_height) / 2.0
+    }
+
+    #[wasm_bindgen]
+    pub fn center_z(&self) -> f32 {
+        self.origin_z + self.width / 2.0
+    }
+
+    #[wasm_bindgen]
+    pub fn min_height(&self) -> f32 {
+        self.min_height
+    }
+
+    #[wasm_bindgen]
+    pub fn max_height(&self) -> f32 {
+        self.max_height
+    }
+
+    /// Heights row by row (north to south), each row west to east
+    #[wasm_bindgen]
+    pub fn get_heights(&self) -> Vec<f32> {
+        self.heights.clone()
+    }
+
+    /// Heights column by column (west to east), each column north to south,
+    /// the matrix layout Rapier expects
+    #[wasm_bindgen]
+    pub fn get_heights_column_major(&self) -> Vec<f32> {
+        let n = self.samples;
+        (0..n * n)
+            .map(|i| self.heights[(i % n) * n + i / n])
+            .collect()
+    }
+
+    /// Height of the collision surface at a point in mesh coordinates
+    /// (interpolated over the grid triangles), or undefined outside the grid
+    #[wasm_bindgen]
+    pub fn sample(&self, x: f32, z: f32) -> Option<f32> {
+        let spacing = self.spacing();
+        let column = (x - self.origin_x) / spacing;
+        let row = (z - self.origin_z) / spacing;
+        let last = (self.samples - 1) as f32;
+        if !(0.0..=last).contains(&column) || !(0.0..=last).contains(&row) {
+            return None;
+        }
+        let grid = Heightfield::new(&self.heights, self.samples, self.samples)?;
+        Some(grid.sample_triangulated(column, row))
+    }
+}
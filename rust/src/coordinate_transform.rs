@@ -1,6 +1,22 @@
 use wasm_bindgen::prelude::*;
+use glam::DVec3;
 use std::f64::consts::PI;
 
+use crate::error::Error;
+use crate::globe::{ecef_to_geodetic, enu_frame, geodetic_to_ecef, WGS84_A, WGS84_B};
+use crate::transverse_mercator::TransverseMercator;
+use crate::validation::{
+    check_latitude, check_longitude, check_mercator_latitude, check_positive, check_tile_index,
+    check_zoom, MAX_LATITUDE,
+};
+
+/// Heightmap pixels per tile side
+const TILE_PIXELS: f64 = 256.0;
+/// Half the Web Mercator world width in meters (pi times the WGS84 semi-major axis)
+const MERCATOR_HALF_WIDTH: f64 = PI * WGS84_A;
+/// Mean earth radius of the spherical distance and bearing functions
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
 #[wasm_bindgen]
 pub struct CoordinateTransform;
 
@@ -8,33 +24,516 @@ pub struct CoordinateTransform;
 impl CoordinateTransform {
     /// Convert latitude to tile Y coordinate at given zoom level
     #[wasm_bindgen]
-    pub fn latlon_to_tile_x(lon: f64, zoom: u8) -> u32 {
-        let n = (1u32 << zoom) as f64;
-        ((lon + 180.0) / 360.0 * n) as u32
+    pub fn latlon_to_tile_x(lon: f64, zoom: u8) -> Result<u32, Error> {
+        Ok(CoordinateTransform::latlon_to_tile_fx(lon, zoom)? as u32)
     }
 
     /// Convert longitude to tile X coordinate at given zoom level
     #[wasm_bindgen]
-    pub fn latlon_to_tile_y(lat: f64, zoom: u8) -> u32 {
-        let n = (1u32 << zoom) as f64;
-        let lat_rad = lat * PI / 180.0;
-        ((1.0 - (lat_rad.tan() + lat_rad.sec()).ln() / PI) / 2.0 * n) as u32
+    pub fn latlon_to_tile_y(lat: f64, zoom: u8) -> Result<u32, Error> {
+        Ok(CoordinateTransform::latlon_to_tile_fy(lat, zoom)? as u32)
+    }
+
+    /// Fractional tile X coordinate of a longitude: the integer part is the tile,
+    /// the fraction the position across it from its western edge
+    #[wasm_bindgen]
+    pub fn latlon_to_tile_fx(lon: f64, zoom: u8) -> Result<f64, Error> {
+        check_zoom(zoom)?;
+        check_longitude("lon", lon)?;
+        Ok(lon_to_tile_fx(lon, zoom))
+    }
+
+    /// Fractional tile Y coordinate of a latitude: the integer part is the tile,
+    /// the fraction the position down it from its northern edge
+    #[wasm_bindgen]
+    pub fn latlon_to_tile_fy(lat: f64, zoom: u8) -> Result<f64, Error> {
+        check_zoom(zoom)?;
+        check_mercator_latitude("lat", lat)?;
+        Ok(lat_to_tile_fy(lat, zoom))
+    }
+
+    /// Fractional tile coordinates [x, y] of a point at given zoom level
+    #[wasm_bindgen]
+    pub fn latlon_to_tile(lat: f64, lon: f64, zoom: u8) -> Result<Vec<f64>, Error> {
+        Ok(vec![
+            CoordinateTransform::latlon_to_tile_fx(lon, zoom)?,
+            CoordinateTransform::latlon_to_tile_fy(lat, zoom)?,
+        ])
+    }
+
+    /// Tile and in-tile pixel position of a point: [tile_x, tile_y, pixel_x, pixel_y]
+    /// with fractional pixels in 0..256; the heightmap cell holding the point is
+    /// floor(pixel_y) * 256 + floor(pixel_x)
+    #[wasm_bindgen]
+    pub fn latlon_to_pixel(lat: f64, lon: f64, zoom: u8) -> Result<Vec<f64>, Error> {
+        let fx = CoordinateTransform::latlon_to_tile_fx(lon, zoom)?;
+        let fy = CoordinateTransform::latlon_to_tile_fy(lat, zoom)?;
+        let (tile_x, tile_y) = (fx.floor(), fy.floor());
+        Ok(vec![
+            tile_x,
+            tile_y,
+            (fx - tile_x) * TILE_PIXELS,
+            (fy - tile_y) * TILE_PIXELS,
+        ])
+    }
+
+    /// Lat/lon [lat, lon] of a fractional pixel position in a tile
+    /// (inverse of latlon_to_pixel; pixels outside 0..256 reach into neighbors)
+    #[wasm_bindgen]
+    pub fn pixel_to_latlon(
+        tile_x: u32,
+        tile_y: u32,
+        pixel_x: f64,
+        pixel_y: f64,
+        zoom: u8,
+    ) -> Result<Vec<f64>, Error> {
+        check_zoom(zoom)?;
+        check_tile_index("tile_x", tile_x, zoom)?;
+        check_tile_index("tile_y", tile_y, zoom)?;
+        let fx = tile_x as f64 + pixel_x / TILE_PIXELS;
+        let fy = tile_y as f64 + pixel_y / TILE_PIXELS;
+        Ok(vec![tile_fy_to_lat(fy, zoom), tile_fx_to_lon(fx, zoom)])
+    }
+
+    /// Tile extent in degrees: [west, south, east, north]
+    #[wasm_bindgen]
+    pub fn tile_bounds(tile_x: u32, tile_y: u32, zoom: u8) -> Result<Vec<f64>, Error> {
+        check_zoom(zoom)?;
+        check_tile_index("tile_x", tile_x, zoom)?;
+        check_tile_index("tile_y", tile_y, zoom)?;
+        let (x, y) = (tile_x as f64, tile_y as f64);
+        Ok(vec![
+            tile_fx_to_lon(x, zoom),
+            tile_fy_to_lat(y + 1.0, zoom),
+            tile_fx_to_lon(x + 1.0, zoom),
+            tile_fy_to_lat(y, zoom),
+        ])
+    }
+
+    /// Tile extent in Web Mercator (EPSG:3857) meters: [min_x, min_y, max_x, max_y]
+    #[wasm_bindgen]
+    pub fn tile_bounds_mercator(tile_x: u32, tile_y: u32, zoom: u8) -> Result<Vec<f64>, Error> {
+        check_zoom(zoom)?;
+        check_tile_index("tile_x", tile_x, zoom)?;
+        check_tile_index("tile_y", tile_y, zoom)?;
+        let span = 2.0 * MERCATOR_HALF_WIDTH / (1u64 << zoom) as f64;
+        let (x, y) = (tile_x as f64, tile_y as f64);
+        Ok(vec![
+            x * span - MERCATOR_HALF_WIDTH,
+            MERCATOR_HALF_WIDTH - (y + 1.0) * span,
+            (x + 1.0) * span - MERCATOR_HALF_WIDTH,
+            MERCATOR_HALF_WIDTH - y * span,
+        ])
+    }
+
+    /// Web Mercator (EPSG:3857) position [x, y] in meters of a lat/lon
+    #[wasm_bindgen]
+    pub fn latlon_to_mercator(lat: f64, lon: f64) -> Result<Vec<f64>, Error> {
+        check_mercator_latitude("lat", lat)?;
+        check_longitude("lon", lon)?;
+        let y = (PI / 4.0 + lat.to_radians() / 2.0).tan().ln() * WGS84_A;
+        Ok(vec![lon.to_radians() * WGS84_A, y])
+    }
+
+    /// Lat/lon [lat, lon] of a Web Mercator (EPSG:3857) position in meters
+    #[wasm_bindgen]
+    pub fn mercator_to_latlon(x: f64, y: f64) -> Result<Vec<f64>, Error> {
+        if !x.is_finite() || !y.is_finite() {
+            return Err(Error::InvalidArgument(
+                "Mercator coordinates must be finite".into(),
+            ));
+        }
+        let lat = (2.0 * (y / WGS84_A).exp().atan() - PI / 2.0).to_degrees();
+        Ok(vec![lat, (x / WGS84_A).to_degrees()])
+    }
+
+    /// Earth-centered, earth-fixed position [x, y, z] in meters of a WGS84
+    /// lat/lon and ellipsoidal height (meters)
+    #[wasm_bindgen]
+    pub fn latlon_to_ecef(lat: f64, lon: f64, height: f64) -> Result<Vec<f64>, Error> {
+        check_latitude("lat", lat)?;
+        check_longitude("lon", lon)?;
+        Ok(geodetic_to_ecef(lon, lat, height).to_array().to_vec())
+    }
+
+    /// WGS84 [lat, lon, height] (degrees, degrees, meters above the ellipsoid)
+    /// of an earth-centered, earth-fixed position in meters
+    #[wasm_bindgen]
+    pub fn ecef_to_latlon(x: f64, y: f64, z: f64) -> Result<Vec<f64>, Error> {
+        if !(x.is_finite() && y.is_finite() && z.is_finite()) {
+            return Err(Error::InvalidArgument(
+                "ECEF coordinates must be finite".into(),
+            ));
+        }
+        let (lon, lat, height) = ecef_to_geodetic(DVec3::new(x, y, z));
+        Ok(vec![lat, lon, height])
+    }
+
+    /// East, north, up offsets [e, n, u] in meters of a point from an anchor,
+    /// in the anchor's local tangent plane (through ECEF, so the curvature of
+    /// the earth shows as a falling u over long distances)
+    /// Latitudes and longitudes in degrees, heights above the ellipsoid in meters
+    #[wasm_bindgen]
+    pub fn latlon_to_enu(
+        lat: f64,
+        lon: f64,
+        height: f64,
+        anchor_lat: f64,
+        anchor_lon: f64,
+        anchor_height: f64,
+    ) -> Result<Vec<f64>, Error> {
+        check_latitude("lat", lat)?;
+        check_longitude("lon", lon)?;
+        check_latitude("anchor_lat", anchor_lat)?;
+        check_longitude("anchor_lon", anchor_lon)?;
+        let offset = geodetic_to_ecef(lon, lat, height)
+            - geodetic_to_ecef(anchor_lon, anchor_lat, anchor_height);
+        let (east, north, up) = enu_frame(anchor_lon, anchor_lat);
+        Ok(vec![offset.dot(east), offset.dot(north), offset.dot(up)])
+    }
+
+    /// [lat, lon, height] of east, north, up offsets in meters from an anchor
+    /// (inverse of latlon_to_enu)
+    #[wasm_bindgen]
+    pub fn enu_to_latlon(
+        east: f64,
+        north: f64,
+        up: f64,
+        anchor_lat: f64,
+        anchor_lon: f64,
+        anchor_height: f64,
+    ) -> Result<Vec<f64>, Error> {
+        check_latitude("anchor_lat", anchor_lat)?;
+        check_longitude("anchor_lon", anchor_lon)?;
+        let (e, n, u) = enu_frame(anchor_lon, anchor_lat);
+        let anchor = geodetic_to_ecef(anchor_lon, anchor_lat, anchor_height);
+        let ecef = anchor + e * east + n * north + u * up;
+        let (lon, lat, height) = ecef_to_geodetic(ecef);
+        Ok(vec![lat, lon, height])
+    }
+
+    /// UTM zone (1-60) of a point, including the Norway and Svalbard exceptions
+    #[wasm_bindgen]
+    pub fn utm_zone(lat: f64, lon: f64) -> Result<u8, Error> {
+        check_latitude("lat", lat)?;
+        check_longitude("lon", lon)?;
+        Ok(utm_zone(lat, lon))
+    }
+
+    /// UTM coordinates [easting, northing, zone] of a point in meters
+    /// zone: 1-60, or undefined for the point's own zone (utm_zone()); a
+    /// neighboring zone is fine for points near its edge, anything further is
+    /// rejected. UTM covers 80°S to 84°N; the polar caps use UPS instead.
+    /// Northings south of the equator carry the 10,000,000 m false northing
+    #[wasm_bindgen]
+    pub fn latlon_to_utm(lat: f64, lon: f64, zone: Option<u8>) -> Result<Vec<f64>, Error> {
+        check_latitude("lat", lat)?;
+        check_longitude("lon", lon)?;
+        if !(-80.0..=84.0).contains(&lat) {
+            return Err(Error::InvalidArgument(format!(
+                "Latitude {} is outside UTM's 80°S to 84°N",
+                lat
+            )));
+        }
+        if !lon.is_finite() {
+            return Err(Error::InvalidArgument(format!(
+                "Invalid longitude: {}",
+                lon
+            )));
+        }
+        let own = utm_zone(lat, lon);
+        let zone = match zone {
+            Some(zone) => {
+                let zone = check_utm_zone(zone)?;
+                // Zones wrap around the antimeridian; the Norway and Svalbard
+                // exceptions border both their own and the regular zone
+                let regular = utm_zone(0.0, lon);
+                let apart = |other: u8| {
+                    let difference = zone.abs_diff(other);
+                    difference.min(60 - difference)
+                };
+                if apart(own).min(apart(regular)) > 1 {
+                    return Err(Error::InvalidArgument(format!(
+                        "UTM zone {} is not next to zone {} of longitude {}",
+                        zone, own, lon
+                    )));
+                }
+                zone
+            }
+            None => own,
+        };
+        let (easting, northing) = utm_projection(zone, lat >= 0.0).forward(lat, lon);
+        Ok(vec![easting, northing, zone as f64])
+    }
+
+    /// [lat, lon] of UTM coordinates in meters (inverse of latlon_to_utm)
+    /// northern: false for southern-hemisphere northings
+    #[wasm_bindgen]
+    pub fn utm_to_latlon(
+        easting: f64,
+        northing: f64,
+        zone: u8,
+        northern: bool,
+    ) -> Result<Vec<f64>, Error> {
+        let zone = check_utm_zone(zone)?;
+        let (lat, lon) = utm_projection(zone, northern).inverse(easting, northing);
+        Ok(vec![lat, lon])
+    }
+
+    /// Japan Plane Rectangular coordinates [x, y] of a point in meters (JGD2011)
+    /// zone: 1-19 (I-XIX, assigned by prefecture). Follows the GSI convention:
+    /// x is northing and y easting from the zone origin
+    #[wasm_bindgen]
+    pub fn latlon_to_jprcs(lat: f64, lon: f64, zone: u8) -> Result<Vec<f64>, Error> {
+        check_latitude("lat", lat)?;
+        check_longitude("lon", lon)?;
+        let (y, x) = jprcs_projection(zone)?.forward(lat, lon);
+        Ok(vec![x, y])
+    }
+
+    /// [lat, lon] of Japan Plane Rectangular coordinates in meters
+    /// (inverse of latlon_to_jprcs; x northing, y easting)
+    #[wasm_bindgen]
+    pub fn jprcs_to_latlon(x: f64, y: f64, zone: u8) -> Result<Vec<f64>, Error> {
+        let (lat, lon) = jprcs_projection(zone)?.inverse(y, x);
+        Ok(vec![lat, lon])
+    }
+
+    /// Tile [x, y, zoom] one level up that contains a tile
+    #[wasm_bindgen]
+    pub fn tile_parent(tile_x: u32, tile_y: u32, zoom: u8) -> Result<Vec<u32>, Error> {
+        check_zoom(zoom)?;
+        check_tile_index("tile_x", tile_x, zoom)?;
+        check_tile_index("tile_y", tile_y, zoom)?;
+        if zoom == 0 {
+            return Err(Error::InvalidArgument(
+                "The zoom 0 tile has no parent".into(),
+            ));
+        }
+        Ok(vec![tile_x / 2, tile_y / 2, zoom as u32 - 1])
+    }
+
+    /// The four tiles one level down as [x, y, zoom] triples, in quadrant order
+    /// (northwest, northeast, southwest, southeast, as TilePyramid::upsample_child)
+    #[wasm_bindgen]
+    pub fn tile_children(tile_x: u32, tile_y: u32, zoom: u8) -> Result<Vec<u32>, Error> {
+        check_zoom(zoom)?;
+        check_tile_index("tile_x", tile_x, zoom)?;
+        check_tile_index("tile_y", tile_y, zoom)?;
+        let child_zoom = zoom
+            .checked_add(1)
+            .filter(|&z| z < 32)
+            .ok_or_else(|| Error::InvalidArgument(format!("No child tiles below zoom {}", zoom)))?;
+        check_zoom(child_zoom)?;
+        // Without strict validation the index may be past the zoom's last tile
+        let child = |parameter: &str, index: u32, offset: u32| {
+            u32::try_from(index as u64 * 2 + offset as u64).map_err(|_| {
+                Error::InvalidArgument(format!(
+                    "{} {} has no children that fit in u32",
+                    parameter, index
+                ))
+            })
+        };
+        let mut children = Vec::with_capacity(12);
+        for quadrant in 0..4u32 {
+            children.extend([
+                child("tile_x", tile_x, quadrant & 1)?,
+                child("tile_y", tile_y, quadrant >> 1)?,
+                child_zoom as u32,
+            ]);
+        }
+        Ok(children)
+    }
+
+    /// Tiles covering a bounding box at a zoom level, as [x, y] pairs row by row
+    /// from the northwest; min_lon above max_lon crosses the antimeridian.
+    /// Latitudes are clamped to the Web Mercator limit, and tiles the box only
+    /// touches along an edge are left out
+    #[wasm_bindgen]
+    pub fn tiles_in_bbox(
+        min_lat: f64,
+        min_lon: f64,
+        max_lat: f64,
+        max_lon: f64,
+        zoom: u8,
+    ) -> Result<Vec<u32>, Error> {
+        const MAX_TILES: u64 = 1 << 20;
+
+        check_zoom(zoom)?;
+        for (parameter, lat) in [("min_lat", min_lat), ("max_lat", max_lat)] {
+            check_latitude(parameter, lat)?;
+        }
+        for (parameter, lon) in [("min_lon", min_lon), ("max_lon", max_lon)] {
+            check_longitude(parameter, lon)?;
+        }
+        if min_lat > max_lat {
+            return Err(Error::InvalidArgument(format!(
+                "min_lat {} is above max_lat {}",
+                min_lat, max_lat
+            )));
+        }
+
+        let tiles = 1u64 << zoom.min(31);
+        let last = tiles as f64 - 1.0;
+        // First and last tile touched by a span of fractional tile coordinates
+        let range = |start: f64, end: f64| {
+            let first = start.floor().clamp(0.0, last);
+            (first, (end.ceil() - 1.0).clamp(first, last))
+        };
+        let (top, bottom) = range(
+            lat_to_tile_fy(max_lat.min(MAX_LATITUDE), zoom),
+            lat_to_tile_fy(min_lat.max(-MAX_LATITUDE), zoom),
+        );
+        // Column spans as half-open ranges; one box crossing the antimeridian
+        // has a western and an eastern part
+        let spans: [(u64, u64); 2] = if min_lon <= max_lon {
+            let (west, east) = range(lon_to_tile_fx(min_lon, zoom), lon_to_tile_fx(max_lon, zoom));
+            [(west as u64, east as u64 + 1), (0, 0)]
+        } else {
+            let (west, _) = range(lon_to_tile_fx(min_lon, zoom), tiles as f64);
+            let (_, east) = range(0.0, lon_to_tile_fx(max_lon, zoom));
+            if west as u64 <= east as u64 {
+                // The two parts overlap: every column
+                [(0, tiles), (0, 0)]
+            } else {
+                [(west as u64, tiles), (0, east as u64 + 1)]
+            }
+        };
+
+        // Counted before allocating, so a huge box is rejected cheaply
+        let column_count: u64 = spans.iter().map(|(start, end)| end - start).sum();
+        let count = column_count * (bottom - top + 1.0) as u64;
+        if count > MAX_TILES {
+            return Err(Error::InvalidArgument(format!(
+                "Bounding box covers {} tiles at zoom {}, more than {}",
+                count, zoom, MAX_TILES
+            )));
+        }
+        let columns: Vec<u64> = spans.iter().flat_map(|&(start, end)| start..end).collect();
+        let mut pairs = Vec::with_capacity(count as usize * 2);
+        for y in top as u32..=bottom as u32 {
+            for &x in &columns {
+                pairs.extend([x as u32, y]);
+            }
+        }
+        Ok(pairs)
+    }
+
+    /// Tiles with any part within a distance of a point, as [x, y] pairs
+    /// ordered nearest first (the tile under the point leads); distances are
+    /// those of distance_km() to the nearest point of each tile
+    #[wasm_bindgen]
+    pub fn tiles_in_radius(
+        lat: f64,
+        lon: f64,
+        radius_km: f64,
+        zoom: u8,
+    ) -> Result<Vec<u32>, Error> {
+        check_latitude("lat", lat)?;
+        check_longitude("lon", lon)?;
+        check_positive("radius_km", radius_km)?;
+        let angle = (radius_km / EARTH_RADIUS_KM).to_degrees();
+        let (south, north) = ((lat - angle).max(-90.0), (lat + angle).min(90.0));
+        // Widest longitude reach of the circle, unless it covers a pole
+        let (west, east) = match (angle.to_radians().sin() / lat.to_radians().cos()).abs() {
+            reach if north < 90.0 && south > -90.0 && reach < 1.0 => {
+                let reach = reach.asin().to_degrees();
+                let wrap = |lon: f64| (lon + 180.0).rem_euclid(360.0) - 180.0;
+                (wrap(lon - reach), wrap(lon + reach))
+            }
+            _ => (-180.0, 180.0),
+        };
+        let candidates = CoordinateTransform::tiles_in_bbox(south, west, north, east, zoom)?;
+
+        let mut tiles: Vec<(f64, u32, u32)> = candidates
+            .chunks_exact(2)
+            .map(|tile| {
+                let (fx, fy) = (tile[0] as f64, tile[1] as f64);
+                let (tile_west, tile_east) =
+                    (tile_fx_to_lon(fx, zoom), tile_fx_to_lon(fx + 1.0, zoom));
+                let nearest_lat =
+                    lat.clamp(tile_fy_to_lat(fy + 1.0, zoom), tile_fy_to_lat(fy, zoom));
+                let center = (tile_west + tile_east) / 2.0;
+                let half_width = (tile_east - tile_west) / 2.0;
+                let offset = ((lon - center + 180.0).rem_euclid(360.0) - 180.0)
+                    .clamp(-half_width, half_width);
+                let distance =
+                    CoordinateTransform::haversine_km(lat, lon, nearest_lat, center + offset);
+                (distance, tile[0], tile[1])
+            })
+            .filter(|&(distance, ..)| distance <= radius_km)
+            .collect();
+        tiles.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Ok(tiles.into_iter().flat_map(|(_, x, y)| [x, y]).collect())
+    }
+
+    /// Quadkey of a tile (Bing Maps tile addressing): one digit 0-3 per zoom
+    /// level, interleaving the x and y bits from the top level down
+    #[wasm_bindgen]
+    pub fn tile_to_quadkey(tile_x: u32, tile_y: u32, zoom: u8) -> Result<String, Error> {
+        check_zoom(zoom)?;
+        check_tile_index("tile_x", tile_x, zoom)?;
+        check_tile_index("tile_y", tile_y, zoom)?;
+        Ok((1..=zoom.min(32) as u32)
+            .rev()
+            .map(|level| {
+                let bit = 1u64 << (level - 1);
+                let digit = (tile_x as u64 & bit != 0) as u8 + 2 * (tile_y as u64 & bit != 0) as u8;
+                (b'0' + digit) as char
+            })
+            .collect())
+    }
+
+    /// Tile [x, y, zoom] of a quadkey (inverse of tile_to_quadkey)
+    #[wasm_bindgen]
+    pub fn quadkey_to_tile(quadkey: &str) -> Result<Vec<u32>, Error> {
+        if quadkey.len() > 32 {
+            return Err(Error::InvalidArgument(format!(
+                "Quadkey too long: {} digits, expected at most 32",
+                quadkey.len()
+            )));
+        }
+        let (mut tile_x, mut tile_y) = (0u32, 0u32);
+        for digit in quadkey.chars() {
+            let digit = digit
+                .to_digit(4)
+                .ok_or_else(|| Error::InvalidArgument(format!("Invalid quadkey: {:?}", quadkey)))?;
+            tile_x = tile_x << 1 | (digit & 1);
+            tile_y = tile_y << 1 | (digit >> 1);
+        }
+        check_zoom(quadkey.len() as u8)?;
+        Ok(vec![tile_x, tile_y, quadkey.len() as u32])
+    }
+
+    /// Ground width of a tile in meters at a latitude and zoom level
+    /// Web Mercator is conformal, so the tile is as tall as it is wide there;
+    /// pass e.g. the tile center latitude and use it as tile_size to give the
+    /// mesh the same horizontal scale as its elevations
+    #[wasm_bindgen]
+    pub fn tile_ground_size(zoom: u8, lat: f64) -> Result<f64, Error> {
+        check_zoom(zoom)?;
+        check_mercator_latitude("lat", lat)?;
+        Ok(2.0 * MERCATOR_HALF_WIDTH * lat.to_radians().cos() / (1u64 << zoom) as f64)
     }
 
     /// Convert tile X coordinate to longitude (tile center)
     #[wasm_bindgen]
-    pub fn tile_x_to_lon(tile_x: u32, zoom: u8) -> f64 {
+    pub fn tile_x_to_lon(tile_x: u32, zoom: u8) -> Result<f64, Error> {
+        check_zoom(zoom)?;
+        check_tile_index("tile_x", tile_x, zoom)?;
         let n = (1u32 << zoom) as f64;
         let x = (tile_x as f64 + 0.5) / n;
-        x * 360.0 - 180.0
+        Ok(x * 360.0 - 180.0)
     }
 
     /// Convert tile Y coordinate to latitude (tile center)
     #[wasm_bindgen]
-    pub fn tile_y_to_lat(tile_y: u32, zoom: u8) -> f64 {
+    pub fn tile_y_to_lat(tile_y: u32, zoom: u8) -> Result<f64, Error> {
+        check_zoom(zoom)?;
+        check_tile_index("tile_y", tile_y, zoom)?;
         let n = (1u32 << zoom) as f64;
         let y = (tile_y as f64 + 0.5) / n;
-        (2.0 * PI * (y - 0.5)).atan().to_degrees()
+        Ok((2.0 * PI * (y - 0.5)).atan().to_degrees())
     }
 
     /// Get world X position of tile at given zoom level
@@ -49,6 +548,14 @@ impl CoordinateTransform {
         tile_y as f32 * tile_size
     }
 
+    /// World position [x, y, z] of a tile in double precision, as
+    /// tile_x_to_world_x() and tile_y_to_world_z() without the f32 rounding;
+    /// generate_tile() meshes are relative to it (MeshData::origin)
+    #[wasm_bindgen]
+    pub fn tile_origin(tile_x: u32, tile_y: u32, tile_size: f64) -> Vec<f64> {
+        tile_origin(tile_x, tile_y, tile_size).to_vec()
+    }
+
     /// Scale elevation value (apply vertical exaggeration if needed)
     #[wasm_bindgen]
     pub fn scale_elevation(elevation_m: f32, exaggeration: f32) -> f32 {
@@ -57,9 +564,152 @@ impl CoordinateTransform {
 
     /// Calculate distance between two lat/lon points in kilometers (Haversine formula)
     #[wasm_bindgen]
-    pub fn distance_km(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
-        const EARTH_RADIUS_KM: f64 = 6371.0;
+    pub fn distance_km(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> Result<f64, Error> {
+        check_latitude("lat1", lat1)?;
+        check_longitude("lon1", lon1)?;
+        check_latitude("lat2", lat2)?;
+        check_longitude("lon2", lon2)?;
+        Ok(CoordinateTransform::haversine_km(lat1, lon1, lat2, lon2))
+    }
+
+    /// Distance in kilometers along the WGS84 ellipsoid (Vincenty's inverse
+    /// formula, accurate to well under a millimeter), for measurements where
+    /// the 0.5% error of the spherical distance_km() matters
+    /// Nearly antipodal points, where the iteration does not converge, are
+    /// measured through a waypoint on the geodesic instead
+    #[wasm_bindgen]
+    pub fn geodesic_distance_km(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> Result<f64, Error> {
+        check_latitude("lat1", lat1)?;
+        check_longitude("lon1", lon1)?;
+        check_latitude("lat2", lat2)?;
+        check_longitude("lon2", lon2)?;
+        let meters = vincenty_m(lat1, lon1, lat2, lon2)
+            .unwrap_or_else(|| antipodal_geodesic_m(lat1, lon1, lat2, lon2));
+        Ok(meters / 1000.0)
+    }
+
+    /// Straight-line distance in kilometers between two points at elevations
+    /// in meters: the distance_km() ground distance combined with the climb
+    /// (Mosaic::surface_distance_km() follows the terrain between them)
+    #[wasm_bindgen]
+    pub fn surface_distance_km(
+        lat1: f64,
+        lon1: f64,
+        elevation1: f64,
+        lat2: f64,
+        lon2: f64,
+        elevation2: f64,
+    ) -> Result<f64, Error> {
+        let ground = CoordinateTransform::distance_km(lat1, lon1, lat2, lon2)?;
+        Ok(ground.hypot((elevation2 - elevation1) / 1000.0))
+    }
+
+    /// count points (at least 2, endpoints included) evenly spaced along the
+    /// great circle between two points, as interleaved [lat, lon]
+    /// Longitudes stay within -180..180, so a path across the antimeridian
+    /// jumps there
+    #[wasm_bindgen]
+    pub fn great_circle_points(
+        lat1: f64,
+        lon1: f64,
+        lat2: f64,
+        lon2: f64,
+        count: u32,
+    ) -> Result<Vec<f64>, Error> {
+        check_latitude("lat1", lat1)?;
+        check_longitude("lon1", lon1)?;
+        check_latitude("lat2", lat2)?;
+        check_longitude("lon2", lon2)?;
+        if count < 2 {
+            return Err(Error::InvalidArgument(format!(
+                "Invalid point count: {}, expected at least 2",
+                count
+            )));
+        }
+        let unit = |lat: f64, lon: f64| {
+            let (sin_lat, cos_lat) = lat.to_radians().sin_cos();
+            let (sin_lon, cos_lon) = lon.to_radians().sin_cos();
+            DVec3::new(cos_lat * cos_lon, cos_lat * sin_lon, sin_lat)
+        };
+        let (start, end) = (unit(lat1, lon1), unit(lat2, lon2));
+        let angle = start.angle_between(end);
+        if (PI - angle).abs() < 1e-9 {
+            return Err(Error::InvalidArgument(
+                "The great circle between antipodal points is undefined".into(),
+            ));
+        }
+
+        let mut points = Vec::with_capacity(count as usize * 2);
+        for i in 0..count {
+            let t = i as f64 / (count - 1) as f64;
+            // Spherical interpolation; the endpoints themselves when they coincide
+            let point = if angle < 1e-12 {
+                start
+            } else {
+                (start * ((1.0 - t) * angle).sin() + end * (t * angle).sin()) / angle.sin()
+            };
+            points.push(point.z.clamp(-1.0, 1.0).asin().to_degrees());
+            points.push(point.y.atan2(point.x).to_degrees());
+        }
+        Ok(points)
+    }
+
+    /// Initial bearing in degrees clockwise from north (0-360) of the great
+    /// circle from the first point to the second
+    #[wasm_bindgen]
+    pub fn initial_bearing(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> Result<f64, Error> {
+        check_latitude("lat1", lat1)?;
+        check_longitude("lon1", lon1)?;
+        check_latitude("lat2", lat2)?;
+        check_longitude("lon2", lon2)?;
+        Ok(CoordinateTransform::bearing(lat1, lon1, lat2, lon2))
+    }
+
+    /// [lat, lon] reached by following a great circle from a point for a
+    /// distance in kilometers, leaving at a bearing in degrees from north
+    /// (the sphere of distance_km(), so the two agree)
+    #[wasm_bindgen]
+    pub fn destination_point(
+        lat: f64,
+        lon: f64,
+        bearing: f64,
+        distance_km: f64,
+    ) -> Result<Vec<f64>, Error> {
+        check_latitude("lat", lat)?;
+        check_longitude("lon", lon)?;
+        let (sin_lat, cos_lat) = lat.to_radians().sin_cos();
+        let (sin_bearing, cos_bearing) = bearing.to_radians().sin_cos();
+        let (sin_angle, cos_angle) = (distance_km / EARTH_RADIUS_KM).sin_cos();
+
+        let sin_lat2 = sin_lat * cos_angle + cos_lat * sin_angle * cos_bearing;
+        let lat2 = sin_lat2.clamp(-1.0, 1.0).asin();
+        let delta_lon = (sin_bearing * sin_angle * cos_lat).atan2(cos_angle - sin_lat * sin_lat2);
+        let lon2 = (lon + delta_lon.to_degrees() + 180.0).rem_euclid(360.0) - 180.0;
+        Ok(vec![lat2.to_degrees(), lon2])
+    }
 
+    /// Bearing in degrees (0-360) on arrival at the second point along the
+    /// great circle from the first
+    #[wasm_bindgen]
+    pub fn final_bearing(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> Result<f64, Error> {
+        let reverse = CoordinateTransform::initial_bearing(lat2, lon2, lat1, lon1)?;
+        Ok((reverse + 180.0) % 360.0)
+    }
+}
+
+impl CoordinateTransform {
+    /// initial_bearing() without the strict-mode checks, for internal callers
+    pub(crate) fn bearing(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+        let (lat1_rad, lat2_rad) = (lat1.to_radians(), lat2.to_radians());
+        let delta_lon = (lon2 - lon1).to_radians();
+
+        let y = delta_lon.sin() * lat2_rad.cos();
+        let x = lat1_rad.cos() * lat2_rad.sin() - lat1_rad.sin() * lat2_rad.cos() * delta_lon.cos();
+        y.atan2(x).to_degrees().rem_euclid(360.0)
+    }
+
+    /// distance_km() without the strict-mode checks, for internal callers
+    pub(crate) fn haversine_km(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
         let lat1_rad = lat1 * PI / 180.0;
         let lat2_rad = lat2 * PI / 180.0;
         let delta_lat = (lat2 - lat1) * PI / 180.0;
@@ -73,6 +723,169 @@ impl CoordinateTransform {
     }
 }
 
+/// Vincenty's inverse solution on WGS84 in meters, None if it does not converge
+fn vincenty_m(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> Option<f64> {
+    let f = 1.0 - WGS84_B / WGS84_A;
+    let (u1, u2) = (
+        ((1.0 - f) * lat1.to_radians().tan()).atan(),
+        ((1.0 - f) * lat2.to_radians().tan()).atan(),
+    );
+    let (sin_u1, cos_u1) = u1.sin_cos();
+    let (sin_u2, cos_u2) = u2.sin_cos();
+    let l = (lon2 - lon1).to_radians();
+
+    let mut lambda = l;
+    for _ in 0..200 {
+        let (sin_lambda, cos_lambda) = lambda.sin_cos();
+        let sin_sigma = (cos_u2 * sin_lambda).hypot(cos_u1 * sin_u2 - sin_u1 * cos_u2 * cos_lambda);
+        if sin_sigma == 0.0 {
+            return Some(0.0);
+        }
+        let cos_sigma = sin_u1 * sin_u2 + cos_u1 * cos_u2 * cos_lambda;
+        let sigma = sin_sigma.atan2(cos_sigma);
+        let sin_alpha = cos_u1 * cos_u2 * sin_lambda / sin_sigma;
+        let cos2_alpha = 1.0 - sin_alpha * sin_alpha;
+        // Zero on equatorial lines
+        let cos_2sigma_m = if cos2_alpha == 0.0 {
+            0.0
+        } else {
+            cos_sigma - 2.0 * sin_u1 * sin_u2 / cos2_alpha
+        };
+        let c = f / 16.0 * cos2_alpha * (4.0 + f * (4.0 - 3.0 * cos2_alpha));
+        let previous = lambda;
+        let correction = sigma
+            + c * sin_sigma * (cos_2sigma_m + c * cos_sigma * (2.0 * cos_2sigma_m.powi(2) - 1.0));
+        lambda = l + (1.0 - c) * f * sin_alpha * correction;
+
+        if (lambda - previous).abs() < 1e-12 {
+            let ep2 = (WGS84_A * WGS84_A - WGS84_B * WGS84_B) / (WGS84_B * WGS84_B);
+            let u2 = cos2_alpha * ep2;
+            let a = 1.0 + u2 / 16384.0 * (4096.0 + u2 * (-768.0 + u2 * (320.0 - 175.0 * u2)));
+            let b = u2 / 1024.0 * (256.0 + u2 * (-128.0 + u2 * (74.0 - 47.0 * u2)));
+            let delta_sigma = b
+                * sin_sigma
+                * (cos_2sigma_m
+                    + b / 4.0
+                        * (cos_sigma * (2.0 * cos_2sigma_m.powi(2) - 1.0)
+                            - b / 6.0
+                                * cos_2sigma_m
+                                * (4.0 * sin_sigma.powi(2) - 3.0)
+                                * (4.0 * cos_2sigma_m.powi(2) - 3.0)));
+            return Some(WGS84_B * a * (sigma - delta_sigma));
+        }
+    }
+    None
+}
+
+/// Geodesic length in meters between nearly antipodal points, as the shortest
+/// path through a waypoint: both legs are then far from antipodal, so
+/// Vincenty converges on them. The length is stationary on the geodesic, so a
+/// waypoint a small distance e off it adds only O(e^2)
+fn antipodal_geodesic_m(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let through = |(lat, lon): (f64, f64)| {
+        let first = vincenty_m(lat1, lon1, lat, lon).unwrap_or(f64::INFINITY);
+        first + vincenty_m(lat, lon, lat2, lon2).unwrap_or(f64::INFINITY)
+    };
+    // Coarse start over the globe (the geodesic may run over a pole or along
+    // the equator either way), then a pattern search refining the waypoint
+    let mut best = (0.0, 0.0);
+    for lat in (-80..=80).step_by(20) {
+        for lon in (-180..180).step_by(30) {
+            let candidate = (lat as f64, lon as f64);
+            if through(candidate) < through(best) {
+                best = candidate;
+            }
+        }
+    }
+    let mut length = through(best);
+    let mut step = 10.0;
+    while step > 1e-9 {
+        let (lat, lon) = best;
+        let moves = [(step, 0.0), (-step, 0.0), (0.0, step), (0.0, -step)];
+        let better = moves
+            .iter()
+            .map(|&(dlat, dlon)| ((lat + dlat).clamp(-90.0, 90.0), lon + dlon))
+            .map(|waypoint| (waypoint, through(waypoint)))
+            .filter(|&(_, candidate)| candidate < length)
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        match better {
+            Some((waypoint, candidate)) => (best, length) = (waypoint, candidate),
+            None => step /= 2.0,
+        }
+    }
+    length
+}
+
+/// Standard 6-degree zone of a point, widened to 32V over southwestern Norway
+/// and to the odd-numbered 31X-37X over Svalbard
+fn utm_zone(lat: f64, lon: f64) -> u8 {
+    let zone = (((lon + 180.0) / 6.0).floor() as i32).clamp(0, 59) as u8 + 1;
+    if (56.0..64.0).contains(&lat) && (3.0..12.0).contains(&lon) {
+        return 32;
+    }
+    if (72.0..=84.0).contains(&lat) && (0.0..42.0).contains(&lon) {
+        return match lon {
+            lon if lon < 9.0 => 31,
+            lon if lon < 21.0 => 33,
+            lon if lon < 33.0 => 35,
+            _ => 37,
+        };
+    }
+    zone
+}
+
+fn check_utm_zone(zone: u8) -> Result<u8, Error> {
+    if !(1..=60).contains(&zone) {
+        return Err(Error::InvalidArgument(format!(
+            "Invalid UTM zone: {}, expected 1 to 60",
+            zone
+        )));
+    }
+    Ok(zone)
+}
+
+fn utm_projection(zone: u8, northern: bool) -> TransverseMercator {
+    let central_meridian = zone as f64 * 6.0 - 183.0;
+    let false_northing = if northern { 0.0 } else { 10_000_000.0 };
+    TransverseMercator::wgs84(central_meridian, 0.0, 0.9996, 500_000.0, false_northing)
+}
+
+/// Origins (latitude, longitude in degrees) of Japan Plane Rectangular zones I-XIX
+const JPRCS_ORIGINS: [(f64, f64); 19] = [
+    (33.0, 129.5),
+    (33.0, 131.0),
+    (36.0, 132.0 + 10.0 / 60.0),
+    (33.0, 133.5),
+    (36.0, 134.0 + 20.0 / 60.0),
+    (36.0, 136.0),
+    (36.0, 137.0 + 10.0 / 60.0),
+    (36.0, 138.5),
+    (36.0, 139.0 + 50.0 / 60.0),
+    (40.0, 140.0 + 50.0 / 60.0),
+    (44.0, 140.25),
+    (44.0, 142.25),
+    (44.0, 144.25),
+    (26.0, 142.0),
+    (26.0, 127.5),
+    (26.0, 124.0),
+    (26.0, 131.0),
+    (20.0, 136.0),
+    (26.0, 154.0),
+];
+
+fn jprcs_projection(zone: u8) -> Result<TransverseMercator, Error> {
+    let &(lat0, lon0) = (zone as usize)
+        .checked_sub(1)
+        .and_then(|i| JPRCS_ORIGINS.get(i))
+        .ok_or_else(|| {
+            Error::InvalidArgument(format!(
+                "Invalid plane rectangular zone: {}, expected 1 to 19",
+                zone
+            ))
+        })?;
+    Ok(TransverseMercator::grs80(lon0, lat0, 0.9999))
+}
+
 trait SecantExt {
     fn sec(self) -> f64;
 }
@@ -82,3 +895,88 @@ impl SecantExt for f64 {
         1.0 / self.cos()
     }
 }
+
+/// tile_origin() for internal callers
+pub(crate) fn tile_origin(tile_x: u32, tile_y: u32, tile_size: f64) -> [f64; 3] {
+    [tile_x as f64 * tile_size, 0.0, tile_y as f64 * tile_size]
+}
+
+/// latlon_to_tile_fx() for internal callers
+pub(crate) fn lon_to_tile_fx(lon: f64, zoom: u8) -> f64 {
+    let n = (1u64 << zoom) as f64;
+    (lon + 180.0) / 360.0 * n
+}
+
+/// latlon_to_tile_fy() for internal callers
+pub(crate) fn lat_to_tile_fy(lat: f64, zoom: u8) -> f64 {
+    let n = (1u64 << zoom) as f64;
+    let lat_rad = lat * PI / 180.0;
+    (1.0 - (lat_rad.tan() + lat_rad.sec()).ln() / PI) / 2.0 * n
+}
+
+/// Longitude of a fractional tile X coordinate
+pub(crate) fn tile_fx_to_lon(fx: f64, zoom: u8) -> f64 {
+    fx / (1u64 << zoom) as f64 * 360.0 - 180.0
+}
+
+/// Latitude of a fractional tile Y coordinate
+pub(crate) fn tile_fy_to_lat(fy: f64, zoom: u8) -> f64 {
+    let y = fy / (1u64 << zoom) as f64;
+    (PI * (1.0 - 2.0 * y)).sinh().atan().to_degrees()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn geodesic_distance_near_antipodes() {
+        // Karney (2013), where Vincenty's iteration fails to converge
+        let km = CoordinateTransform::geodesic_distance_km(0.0, 0.0, 0.5, 179.7).unwrap();
+        assert!((km - 19944.127421).abs() < 1e-5, "{}", km);
+        // Exactly antipodal: half a meridian ellipse, over either pole
+        let km = CoordinateTransform::geodesic_distance_km(0.0, 0.0, 0.0, 180.0).unwrap();
+        assert!((km - 20003.931458).abs() < 1e-5, "{}", km);
+    }
+
+    #[test]
+    fn children_past_u32_are_rejected() {
+        assert_eq!(
+            CoordinateTransform::tile_children(3, 1, 2).unwrap(),
+            [6, 2, 3, 7, 2, 3, 6, 3, 3, 7, 3, 3]
+        );
+        // Out-of-range indices get through when strict validation is off
+        assert!(CoordinateTransform::tile_children(u32::MAX, 0, 30).is_err());
+    }
+
+    #[test]
+    fn huge_bounding_boxes_are_rejected_before_allocating() {
+        for zoom in [24, 31] {
+            assert!(CoordinateTransform::tiles_in_bbox(-80.0, -180.0, 80.0, 180.0, zoom).is_err());
+            assert!(CoordinateTransform::tiles_in_bbox(0.0, 10.0, 1.0, 9.0, zoom).is_err());
+        }
+        // A box crossing the antimeridian at zoom 0 lists the one tile once
+        assert_eq!(
+            CoordinateTransform::tiles_in_bbox(0.0, 170.0, 1.0, -170.0, 0).unwrap(),
+            [0, 0]
+        );
+        assert_eq!(
+            CoordinateTransform::tiles_in_bbox(1.0, 170.0, 2.0, -170.0, 2).unwrap(),
+            [3, 1, 0, 1]
+        );
+    }
+
+    #[test]
+    fn utm_rejects_polar_caps_and_distant_zones() {
+        assert!(CoordinateTransform::latlon_to_utm(84.5, 10.0, None).is_err());
+        assert!(CoordinateTransform::latlon_to_utm(-80.5, 10.0, None).is_err());
+        assert!(CoordinateTransform::latlon_to_utm(f64::NAN, 10.0, None).is_err());
+        // Tokyo is in zone 54; 53 and 55 are neighbors, 50 is not
+        for zone in [53, 54, 55] {
+            assert!(CoordinateTransform::latlon_to_utm(35.68, 139.77, Some(zone)).is_ok());
+        }
+        assert!(CoordinateTransform::latlon_to_utm(35.68, 139.77, Some(50)).is_err());
+        // Zones 60 and 1 meet at the antimeridian
+        assert!(CoordinateTransform::latlon_to_utm(0.0, 179.5, Some(1)).is_ok());
+    }
+}
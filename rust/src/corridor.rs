@@ -0,0 +1,163 @@
+use crate::error::Error;
+use crate::mosaic::Mosaic;
+
+/// Most cells a corridor window may hold (about 2048 x 2048)
+const MAX_CELLS: u64 = 1 << 22;
+
+/// Local raster window around a route, cut from the mosaic
+/// Cells on tiles that are not loaded hold NaN
+pub(crate) struct Corridor {
+    /// Global pixel coordinates of the window's top-left cell
+    pub origin: (i64, i64),
+    pub width: usize,
+    pub height: usize,
+    /// Ground distance between cells in meters (at the route's mid latitude)
+    pub cell_size: f64,
+    /// Corridor half-width in cells
+    pub radius: f64,
+    /// Route vertices in window cell coordinates
+    pub route: Vec<(f64, f64)>,
+    pub elevations: Vec<f32>,
+    /// Cells within `radius` of the route
+    pub mask: Vec<bool>,
+}
+
+impl Corridor {
+    /// half_width: corridor half-width in meters
+    /// Fails when fewer than 2 vertices are given, a vertex or the half-width is
+    /// not finite, or the window would exceed MAX_CELLS
+    pub fn new(
+        mosaic: &Mosaic,
+        lats: &[f64],
+        lons: &[f64],
+        half_width: f64,
+    ) -> Result<Corridor, Error> {
+        if lats.len() < 2 || lats.len() != lons.len() {
+            return Err(Error::InvalidArgument(format!(
+                "Route needs at least 2 points with one lon per lat, got {} lats and {} lons",
+                lats.len(),
+                lons.len()
+            )));
+        }
+        if !half_width.is_finite() {
+            return Err(Error::InvalidArgument(format!(
+                "Invalid corridor half-width: {}",
+                half_width
+            )));
+        }
+        let global: Vec<(f64, f64)> = lats
+            .iter()
+            .zip(lons)
+            .map(|(&lat, &lon)| mosaic.latlon_to_pixel(lat, lon))
+            .collect::<Option<_>>()
+            .ok_or_else(|| Error::InvalidArgument("Route has a non-finite point".into()))?;
+        let mid_lat = (lats.iter().cloned().fold(f64::INFINITY, f64::min)
+            + lats.iter().cloned().fold(f64::NEG_INFINITY, f64::max))
+            / 2.0;
+        let cell_size = mosaic.meters_per_pixel(mid_lat);
+        let radius = half_width.max(0.0) / cell_size;
+
+        let min_x = global.iter().map(|p| p.0).fold(f64::INFINITY, f64::min) - radius - 1.0;
+        let min_y = global.iter().map(|p| p.1).fold(f64::INFINITY, f64::min) - radius - 1.0;
+        let max_x = global.iter().map(|p| p.0).fold(f64::NEG_INFINITY, f64::max) + radius + 1.0;
+        let max_y = global.iter().map(|p| p.1).fold(f64::NEG_INFINITY, f64::max) + radius + 1.0;
+        let origin = (min_x.floor().max(0.0) as i64, min_y.floor().max(0.0) as i64);
+        let width = (max_x.ceil() as i64 - origin.0 + 1) as u64;
+        let height = (max_y.ceil() as i64 - origin.1 + 1) as u64;
+        let Some(cells) = width
+            .checked_mul(height)
+            .filter(|&cells| cells <= MAX_CELLS)
+        else {
+            return Err(Error::InvalidArgument(format!(
+                "Route corridor of {}x{} cells is larger than {}",
+                width, height, MAX_CELLS
+            )));
+        };
+        let (width, height, cells) = (width as usize, height as usize, cells as usize);
+
+        let mut elevations = Vec::with_capacity(cells);
+        for y in 0..height as i64 {
+            for x in 0..width as i64 {
+                let elevation = mosaic.pixel(origin.0 + x, origin.1 + y);
+                elevations.push(elevation.unwrap_or(f32::NAN));
+            }
+        }
+
+        let route: Vec<(f64, f64)> = global
+            .iter()
+            .map(|&(px, py)| (px - origin.0 as f64, py - origin.1 as f64))
+            .collect();
+        let mut corridor = Corridor {
+            origin,
+            width,
+            height,
+            cell_size,
+            radius,
+            route,
+            elevations,
+            mask: Vec::new(),
+        };
+        let mut mask = vec![false; cells];
+        for segment in 0..corridor.route.len() - 1 {
+            corridor.for_each_segment_cell(segment, |index| mask[index] = true);
+        }
+        corridor.mask = mask;
+        Ok(corridor)
+    }
+
+    /// Cell index nearest to a point in window coordinates
+    pub fn cell_of(&self, (x, y): (f64, f64)) -> usize {
+        let x = (x.round().max(0.0) as usize).min(self.width - 1);
+        let y = (y.round().max(0.0) as usize).min(self.height - 1);
+        y * self.width + x
+    }
+
+    /// Visit every cell within the corridor radius of route segment i (vertex i to i + 1)
+    pub fn for_each_segment_cell(&self, segment: usize, mut visit: impl FnMut(usize)) {
+        let (a, b) = (self.route[segment], self.route[segment + 1]);
+        let x0 = (a.0.min(b.0) - self.radius).floor().max(0.0) as usize;
+        let y0 = (a.1.min(b.1) - self.radius).floor().max(0.0) as usize;
+        let x1 = ((a.0.max(b.0) + self.radius).ceil() as usize).min(self.width - 1);
+        let y1 = ((a.1.max(b.1) + self.radius).ceil() as usize).min(self.height - 1);
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                if point_segment_distance((x as f64, y as f64), a, b) <= self.radius {
+                    visit(y * self.width + x);
+                }
+            }
+        }
+    }
+
+    /// Lat/lon of a point in window coordinates
+    pub fn to_latlon(&self, mosaic: &Mosaic, (x, y): (f64, f64)) -> (f64, f64) {
+        mosaic.pixel_to_latlon(x + self.origin.0 as f64, y + self.origin.1 as f64)
+    }
+}
+
+pub(crate) fn point_segment_distance(p: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length_sq = dx * dx + dy * dy;
+    let t = if length_sq > 0.0 {
+        (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / length_sq).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let (cx, cy) = (a.0 + dx * t, a.1 + dy * t);
+    ((p.0 - cx).powi(2) + (p.1 - cy).powi(2)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn oversized_windows_are_rejected() {
+        let mosaic = Mosaic::new(14);
+        let short = Corridor::new(&mosaic, &[35.36, 35.37], &[138.72, 138.73], 50.0).unwrap();
+        assert_eq!(short.mask.len(), short.width * short.height);
+        // About 100 km apart at zoom 14
+        assert!(Corridor::new(&mosaic, &[35.0, 35.6], &[138.0, 138.8], 50.0).is_err());
+        let infinite = Corridor::new(&mosaic, &[35.36, 35.37], &[138.72, 138.73], f64::INFINITY);
+        assert!(infinite.is_err());
+    }
+}
@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+
+use crate::heightfield::Heightfield;
+use crate::rtin::Rtin;
+
+/// A rectangle to drape over the terrain, in the mesh's world units
+/// (x east, z south, origin at the tile center)
+pub(crate) struct Decal {
+    pub center: (f32, f32),
+    /// Extent along the decal's own u and v axes
+    pub size: (f32, f32),
+    /// Radians counter-clockwise seen from above; 0 keeps u east and v south
+    pub rotation: f32,
+    /// Height added to every vertex, against z-fighting with the terrain
+    pub lift: f32,
+}
+
+/// Vertices, triangles and decal-space uvs (0..1 across the rectangle)
+pub(crate) struct DecalGeometry {
+    pub vertices: Vec<f32>,
+    pub indices: Vec<u32>,
+    pub uvs: Vec<f32>,
+}
+
+/// Corner of a triangle while clipping: x, z, u, v
+type Corner = [f32; 4];
+
+/// Drape a decal over a heightmap meshed at tile_size (generate_sized placement)
+/// The rectangle is sampled on a grid about one pixel apart and simplified with
+/// RTIN to max_error, so flat ground gets few triangles; the parts beyond the
+/// heightmap are clipped away.
+pub(crate) fn drape_decal(
+    heightmap: &Heightfield,
+    tile_size: f32,
+    decal: &Decal,
+    max_error: f32,
+) -> DecalGeometry {
+    let pixel_size = tile_size / heightmap.width as f32;
+    let half = tile_size / 2.0;
+    let (width, depth) = decal.size;
+    let segments = ((width.max(depth) / pixel_size).ceil() as usize)
+        .next_power_of_two()
+        .clamp(2, 256);
+    let grid_size = segments + 1;
+
+    let (sin, cos) = decal.rotation.sin_cos();
+    let place = |u: f32, v: f32| -> Corner {
+        let (du, dv) = ((u - 0.5) * width, (v - 0.5) * depth);
+        [
+            decal.center.0 + cos * du + sin * dv,
+            decal.center.1 - sin * du + cos * dv,
+            u,
+            v,
+        ]
+    };
+    let height_at = |x: f32, z: f32| {
+        heightmap.sample((x + half) / pixel_size, (z + half) / pixel_size) + decal.lift
+    };
+
+    let mut grid = Vec::with_capacity(grid_size * grid_size);
+    let mut heights = Vec::with_capacity(grid_size * grid_size);
+    for gy in 0..grid_size {
+        for gx in 0..grid_size {
+            let corner = place(gx as f32 / segments as f32, gy as f32 / segments as f32);
+            heights.push(height_at(corner[0], corner[1]));
+            grid.push(corner);
+        }
+    }
+    let rtin = Rtin::new(grid_size);
+    let selected = rtin.mesh(&rtin.errors(&heights), max_error);
+
+    // Vertices are shared by position, including the ones cut on the clip lines
+    let mut geometry = DecalGeometry {
+        vertices: Vec::new(),
+        indices: Vec::new(),
+        uvs: Vec::new(),
+    };
+    let mut welded: HashMap<[u32; 2], u32> = HashMap::new();
+    let mut emit = |corner: Corner, geometry: &mut DecalGeometry| {
+        *welded
+            .entry([corner[0].to_bits(), corner[1].to_bits()])
+            .or_insert_with(|| {
+                geometry.vertices.extend_from_slice(&[
+                    corner[0],
+                    height_at(corner[0], corner[1]),
+                    corner[1],
+                ]);
+                geometry.uvs.extend_from_slice(&[corner[2], corner[3]]);
+                (geometry.uvs.len() / 2 - 1) as u32
+            })
+    };
+
+    let limits = [
+        (0, -half, true),
+        (0, (heightmap.width - 1) as f32 * pixel_size - half, false),
+        (1, -half, true),
+        (1, (heightmap.height - 1) as f32 * pixel_size - half, false),
+    ];
+    for triangle in selected.triangles.chunks_exact(3) {
+        let mut polygon: Vec<Corner> = triangle
+            .iter()
+            .map(|&i| {
+                let (gx, gy) = selected.vertices[i as usize];
+                grid[gy as usize * grid_size + gx as usize]
+            })
+            .collect();
+        for &(axis, bound, keep_above) in &limits {
+            polygon = clip(&polygon, axis, bound, keep_above);
+        }
+        if polygon.len() < 3 {
+            continue;
+        }
+        let fan: Vec<u32> = polygon.iter().map(|&c| emit(c, &mut geometry)).collect();
+        for i in 1..fan.len() - 1 {
+            let (a, b, c) = (fan[0], fan[i], fan[i + 1]);
+            let corner = |index: u32| {
+                let i = index as usize * 3;
+                (geometry.vertices[i], geometry.vertices[i + 2])
+            };
+            let ((ax, az), (bx, bz), (cx, cz)) = (corner(a), corner(b), corner(c));
+            // Slivers left where the clip line runs along a triangle edge
+            if ((bx - ax) * (cz - az) - (bz - az) * (cx - ax)).abs() > 1e-9 * tile_size * tile_size
+            {
+                geometry.indices.extend_from_slice(&[a, b, c]);
+            }
+        }
+    }
+    geometry
+}
+
+/// One Sutherland-Hodgman pass: keep the side of x (axis 0) or z (axis 1) = bound
+fn clip(polygon: &[Corner], axis: usize, bound: f32, keep_above: bool) -> Vec<Corner> {
+    let inside = |c: &Corner| (c[axis] >= bound) == keep_above || c[axis] == bound;
+    let mut output = Vec::with_capacity(polygon.len() + 1);
+    for (i, current) in polygon.iter().enumerate() {
+        let previous = &polygon[(i + polygon.len() - 1) % polygon.len()];
+        if inside(current) != inside(previous) {
+            output.push(intersect(previous, current, axis, bound));
+        }
+        if inside(current) {
+            output.push(*current);
+        }
+    }
+    output
+}
+
+/// Point where an edge crosses the clip line; the same for either direction of
+/// the edge, so neighboring triangles share the new vertex
+fn intersect(a: &Corner, b: &Corner, axis: usize, bound: f32) -> Corner {
+    let (a, b) = if a.map(f32::to_bits) < b.map(f32::to_bits) {
+        (a, b)
+    } else {
+        (b, a)
+    };
+    let t = (bound - a[axis]) / (b[axis] - a[axis]);
+    let mut point = [0.0; 4];
+    for (k, value) in point.iter_mut().enumerate() {
+        *value = a[k] + (b[k] - a[k]) * t;
+    }
+    point[axis] = bound;
+    point
+}
@@ -0,0 +1,435 @@
+use crate::heightfield::Heightfield;
+
+/// Greedy-insertion Delaunay TIN over a heightmap (Garland-Heckbert style)
+/// The point with the largest vertical error is inserted one at a time and the
+/// triangulation is kept Delaunay with edge flips.
+pub(crate) struct DelaunayTin<'a> {
+    heightmap: Heightfield<'a>,
+    /// Vertex grid coordinates (x, y)
+    pub coords: Vec<(i32, i32)>,
+    /// Triangle list (three vertex ids per triangle, counter-clockwise in raster space)
+    pub triangles: Vec<usize>,
+    /// Opposite halfedge for each triangle edge (-1 on the boundary)
+    halfedges: Vec<isize>,
+    /// Worst-error pixel found inside each triangle
+    candidates: Vec<(i32, i32)>,
+    /// Max-heap of triangle ids ordered by candidate error
+    queue: Vec<usize>,
+    errors: Vec<f32>,
+    /// Position of each triangle in the heap (-1 when not queued)
+    queue_indices: Vec<isize>,
+    /// Triangles whose candidate must be recomputed
+    pending: Vec<usize>,
+}
+
+impl<'a> DelaunayTin<'a> {
+    pub fn new(heightmap: Heightfield<'a>) -> DelaunayTin<'a> {
+        let mut tin = DelaunayTin {
+            heightmap,
+            coords: Vec::new(),
+            triangles: Vec::new(),
+            halfedges: Vec::new(),
+            candidates: Vec::new(),
+            queue: Vec::new(),
+            errors: Vec::new(),
+            queue_indices: Vec::new(),
+            pending: Vec::new(),
+        };
+
+        let x1 = heightmap.width as i32 - 1;
+        let y1 = heightmap.height as i32 - 1;
+        let p0 = tin.add_point(0, 0);
+        let p1 = tin.add_point(x1, 0);
+        let p2 = tin.add_point(0, y1);
+        let p3 = tin.add_point(x1, y1);
+
+        let t0 = tin.add_triangle(p3, p0, p2, -1, -1, -1, None);
+        tin.add_triangle(p0, p3, p1, t0 as isize, -1, -1, None);
+        tin.flush();
+        tin
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.triangles.len() / 3
+    }
+
+    /// Largest remaining vertical error over the whole heightmap
+    pub fn max_error(&self) -> f32 {
+        self.errors.first().copied().unwrap_or(0.0)
+    }
+
+    /// Insert points until the error drops to max_error or the triangle budget is reached
+    /// (max_triangles = 0 means no budget)
+    pub fn run(&mut self, max_error: f32, max_triangles: usize) {
+        while !self.queue.is_empty() && self.max_error() > max_error {
+            // Each insertion adds at most two triangles
+            if max_triangles > 0 && self.triangle_count() + 2 > max_triangles {
+                break;
+            }
+            self.step();
+            self.flush();
+        }
+    }
+
+    fn height_at(&self, x: i32, y: i32) -> f32 {
+        self.heightmap.data[y as usize * self.heightmap.width + x as usize]
+    }
+
+    fn add_point(&mut self, x: i32, y: i32) -> usize {
+        self.coords.push((x, y));
+        self.coords.len() - 1
+    }
+
+    /// Write a triangle at edge slot `e` (or append one) and link its halfedges
+    #[allow(clippy::too_many_arguments)]
+    fn add_triangle(
+        &mut self,
+        a: usize,
+        b: usize,
+        c: usize,
+        ab: isize,
+        bc: isize,
+        ca: isize,
+        e: Option<usize>,
+    ) -> usize {
+        let e = e.unwrap_or(self.triangles.len());
+        let t = e / 3;
+
+        if e == self.triangles.len() {
+            self.triangles.extend_from_slice(&[a, b, c]);
+            self.halfedges.extend_from_slice(&[ab, bc, ca]);
+            self.candidates.push((0, 0));
+            self.queue_indices.push(-1);
+        } else {
+            self.triangles[e..e + 3].copy_from_slice(&[a, b, c]);
+            self.halfedges[e..e + 3].copy_from_slice(&[ab, bc, ca]);
+            self.candidates[t] = (0, 0);
+            self.queue_indices[t] = -1;
+        }
+
+        for (offset, &opposite) in [ab, bc, ca].iter().enumerate() {
+            if opposite >= 0 {
+                self.halfedges[opposite as usize] = (e + offset) as isize;
+            }
+        }
+
+        self.pending.push(t);
+        e
+    }
+
+    fn flush(&mut self) {
+        let pending = std::mem::take(&mut self.pending);
+        for t in pending {
+            self.find_candidate(t);
+        }
+    }
+
+    /// Rasterize a triangle and record the pixel with the largest interpolation error
+    fn find_candidate(&mut self, t: usize) {
+        let (p0x, p0y) = self.coords[self.triangles[t * 3]];
+        let (p1x, p1y) = self.coords[self.triangles[t * 3 + 1]];
+        let (p2x, p2y) = self.coords[self.triangles[t * 3 + 2]];
+
+        let min_x = p0x.min(p1x).min(p2x);
+        let min_y = p0y.min(p1y).min(p2y);
+        let max_x = p0x.max(p1x).max(p2x);
+        let max_y = p0y.max(p1y).max(p2y);
+
+        // Edge functions (barycentric weights scaled by twice the triangle area)
+        let mut w00 = orient(p1x, p1y, p2x, p2y, min_x, min_y);
+        let mut w01 = orient(p2x, p2y, p0x, p0y, min_x, min_y);
+        let mut w02 = orient(p0x, p0y, p1x, p1y, min_x, min_y);
+
+        let a01 = p1y - p0y;
+        let b01 = p0x - p1x;
+        let a12 = p2y - p1y;
+        let b12 = p1x - p2x;
+        let a20 = p0y - p2y;
+        let b20 = p2x - p0x;
+
+        let area = orient(p0x, p0y, p1x, p1y, p2x, p2y) as f32;
+        let z0 = self.height_at(p0x, p0y) / area;
+        let z1 = self.height_at(p1x, p1y) / area;
+        let z2 = self.height_at(p2x, p2y) / area;
+
+        let mut max_error = 0.0f32;
+        let mut candidate = (p0x, p0y);
+
+        for y in min_y..=max_y {
+            // Skip ahead to the first pixel inside the triangle on this row
+            let mut dx = 0;
+            if w00 < 0 && a12 != 0 {
+                dx = dx.max((-w00).div_euclid(a12));
+            }
+            if w01 < 0 && a20 != 0 {
+                dx = dx.max((-w01).div_euclid(a20));
+            }
+            if w02 < 0 && a01 != 0 {
+                dx = dx.max((-w02).div_euclid(a01));
+            }
+
+            let mut w0 = w00 + a12 * dx;
+            let mut w1 = w01 + a20 * dx;
+            let mut w2 = w02 + a01 * dx;
+            let mut was_inside = false;
+
+            for x in (min_x + dx)..=max_x {
+                if w0 >= 0 && w1 >= 0 && w2 >= 0 {
+                    was_inside = true;
+                    let z = z0 * w0 as f32 + z1 * w1 as f32 + z2 * w2 as f32;
+                    let dz = (z - self.height_at(x, y)).abs();
+                    if dz > max_error {
+                        max_error = dz;
+                        candidate = (x, y);
+                    }
+                } else if was_inside {
+                    break;
+                }
+                w0 += a12;
+                w1 += a20;
+                w2 += a01;
+            }
+
+            w00 += b12;
+            w01 += b20;
+            w02 += b01;
+        }
+
+        if candidate == (p0x, p0y) || candidate == (p1x, p1y) || candidate == (p2x, p2y) {
+            max_error = 0.0;
+        }
+
+        self.candidates[t] = candidate;
+        self.queue_push(t, max_error);
+    }
+
+    /// Insert the worst candidate point, splitting its triangle
+    fn step(&mut self) {
+        let t = self.queue_pop();
+        let e0 = t * 3;
+        let e1 = e0 + 1;
+        let e2 = e0 + 2;
+
+        let p0 = self.triangles[e0];
+        let p1 = self.triangles[e1];
+        let p2 = self.triangles[e2];
+        let (ax, ay) = self.coords[p0];
+        let (bx, by) = self.coords[p1];
+        let (cx, cy) = self.coords[p2];
+        let (px, py) = self.candidates[t];
+        let pn = self.add_point(px, py);
+
+        if orient(ax, ay, bx, by, px, py) == 0 {
+            self.handle_collinear(pn, e0);
+        } else if orient(bx, by, cx, cy, px, py) == 0 {
+            self.handle_collinear(pn, e1);
+        } else if orient(cx, cy, ax, ay, px, py) == 0 {
+            self.handle_collinear(pn, e2);
+        } else {
+            let h0 = self.halfedges[e0];
+            let h1 = self.halfedges[e1];
+            let h2 = self.halfedges[e2];
+
+            let t0 = self.add_triangle(p0, p1, pn, h0, -1, -1, Some(e0));
+            let t1 = self.add_triangle(p1, p2, pn, h1, -1, t0 as isize + 1, None);
+            let t2 = self.add_triangle(p2, p0, pn, h2, t0 as isize + 2, t1 as isize + 1, None);
+
+            self.legalize(t0);
+            self.legalize(t1);
+            self.legalize(t2);
+        }
+    }
+
+    /// Flip edge `a` if the adjacent pair of triangles violates the Delaunay condition
+    fn legalize(&mut self, a: usize) {
+        let b = self.halfedges[a];
+        if b < 0 {
+            return;
+        }
+        let b = b as usize;
+
+        let a0 = a - a % 3;
+        let b0 = b - b % 3;
+        let al = a0 + (a + 1) % 3;
+        let ar = a0 + (a + 2) % 3;
+        let bl = b0 + (b + 2) % 3;
+        let br = b0 + (b + 1) % 3;
+        let p0 = self.triangles[ar];
+        let pr = self.triangles[a];
+        let pl = self.triangles[al];
+        let p1 = self.triangles[bl];
+
+        if !in_circle(
+            self.coords[p0],
+            self.coords[pr],
+            self.coords[pl],
+            self.coords[p1],
+        ) {
+            return;
+        }
+
+        let hal = self.halfedges[al];
+        let har = self.halfedges[ar];
+        let hbl = self.halfedges[bl];
+        let hbr = self.halfedges[br];
+
+        self.queue_remove(a0 / 3);
+        self.queue_remove(b0 / 3);
+
+        let t0 = self.add_triangle(p0, p1, pl, -1, hbl, hal, Some(a0));
+        let t1 = self.add_triangle(p1, p0, pr, t0 as isize, har, hbr, Some(b0));
+
+        self.legalize(t0 + 1);
+        self.legalize(t1 + 2);
+    }
+
+    /// Split the triangle(s) sharing edge `a` when the new point lies on it
+    fn handle_collinear(&mut self, pn: usize, a: usize) {
+        let a0 = a - a % 3;
+        let al = a0 + (a + 1) % 3;
+        let ar = a0 + (a + 2) % 3;
+        let p0 = self.triangles[ar];
+        let pr = self.triangles[a];
+        let pl = self.triangles[al];
+        let hal = self.halfedges[al];
+        let har = self.halfedges[ar];
+
+        let b = self.halfedges[a];
+        if b < 0 {
+            let t0 = self.add_triangle(pn, p0, pr, -1, har, -1, Some(a0));
+            let t1 = self.add_triangle(p0, pn, pl, t0 as isize, -1, hal, None);
+            self.legalize(t0 + 1);
+            self.legalize(t1 + 2);
+            return;
+        }
+        let b = b as usize;
+
+        let b0 = b - b % 3;
+        let bl = b0 + (b + 2) % 3;
+        let br = b0 + (b + 1) % 3;
+        let p1 = self.triangles[bl];
+        let hbl = self.halfedges[bl];
+        let hbr = self.halfedges[br];
+
+        self.queue_remove(b0 / 3);
+
+        let t0 = self.add_triangle(p0, pr, pn, har, -1, -1, Some(a0));
+        let t1 = self.add_triangle(pr, p1, pn, hbr, -1, t0 as isize + 1, Some(b0));
+        let t2 = self.add_triangle(p1, pl, pn, hbl, -1, t1 as isize + 1, None);
+        let t3 = self.add_triangle(pl, p0, pn, hal, t0 as isize + 2, t2 as isize + 1, None);
+
+        self.legalize(t0);
+        self.legalize(t1);
+        self.legalize(t2);
+        self.legalize(t3);
+    }
+
+    fn queue_push(&mut self, t: usize, error: f32) {
+        let i = self.queue.len();
+        self.queue_indices[t] = i as isize;
+        self.queue.push(t);
+        self.errors.push(error);
+        self.queue_up(i);
+    }
+
+    fn queue_pop(&mut self) -> usize {
+        let n = self.queue.len() - 1;
+        self.queue_swap(0, n);
+        self.queue_down(0, n);
+        self.queue_pop_back()
+    }
+
+    fn queue_pop_back(&mut self) -> usize {
+        let t = self.queue.pop().unwrap();
+        self.errors.pop();
+        self.queue_indices[t] = -1;
+        t
+    }
+
+    /// Drop a triangle from the heap (or from the pending list if not yet rasterized)
+    fn queue_remove(&mut self, t: usize) {
+        let i = self.queue_indices[t];
+        if i < 0 {
+            if let Some(position) = self.pending.iter().position(|&p| p == t) {
+                self.pending.swap_remove(position);
+            }
+            return;
+        }
+        let i = i as usize;
+        let n = self.queue.len() - 1;
+        if n != i {
+            self.queue_swap(i, n);
+            if !self.queue_down(i, n) {
+                self.queue_up(i);
+            }
+        }
+        self.queue_pop_back();
+    }
+
+    fn queue_less(&self, i: usize, j: usize) -> bool {
+        self.errors[i] > self.errors[j]
+    }
+
+    fn queue_swap(&mut self, i: usize, j: usize) {
+        let pi = self.queue[i];
+        let pj = self.queue[j];
+        self.queue[i] = pj;
+        self.queue[j] = pi;
+        self.queue_indices[pi] = j as isize;
+        self.queue_indices[pj] = i as isize;
+        self.errors.swap(i, j);
+    }
+
+    fn queue_up(&mut self, mut j: usize) {
+        while j > 0 {
+            let i = (j - 1) / 2;
+            if !self.queue_less(j, i) {
+                break;
+            }
+            self.queue_swap(i, j);
+            j = i;
+        }
+    }
+
+    fn queue_down(&mut self, i0: usize, n: usize) -> bool {
+        let mut i = i0;
+        loop {
+            let j1 = 2 * i + 1;
+            if j1 >= n {
+                break;
+            }
+            let j2 = j1 + 1;
+            let j = if j2 < n && self.queue_less(j2, j1) {
+                j2
+            } else {
+                j1
+            };
+            if !self.queue_less(j, i) {
+                break;
+            }
+            self.queue_swap(i, j);
+            i = j;
+        }
+        i > i0
+    }
+}
+
+fn orient(ax: i32, ay: i32, bx: i32, by: i32, cx: i32, cy: i32) -> i32 {
+    (bx - cx) * (ay - cy) - (by - cy) * (ax - cx)
+}
+
+/// True when p lies inside the circumcircle of (a, b, c)
+fn in_circle(a: (i32, i32), b: (i32, i32), c: (i32, i32), p: (i32, i32)) -> bool {
+    let dx = (a.0 - p.0) as f64;
+    let dy = (a.1 - p.1) as f64;
+    let ex = (b.0 - p.0) as f64;
+    let ey = (b.1 - p.1) as f64;
+    let fx = (c.0 - p.0) as f64;
+    let fy = (c.1 - p.1) as f64;
+
+    let ap = dx * dx + dy * dy;
+    let bp = ex * ex + ey * ey;
+    let cp = fx * fx + fy * fy;
+
+    dx * (ey * cp - bp * fy) - dy * (ex * cp - bp * fx) + ap * (ex * fy - ey * fx) < 0.0
+}
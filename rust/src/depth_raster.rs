@@ -0,0 +1,363 @@
+use glam::{DVec3, Mat4, Vec3, Vec4};
+use wasm_bindgen::prelude::*;
+
+use crate::camera::Camera;
+use crate::error::Error;
+use crate::mesh_generator::grid_samples;
+
+/// Id of pixels not covered by any tile
+const NO_TILE: u32 = u32::MAX;
+/// Largest raster accepted, 4096 x 4096 pixels
+const MAX_PIXELS: usize = 4096 * 4096;
+
+/// Marker state against the terrain depth raster
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MarkerStatus {
+    Visible = 0,
+    /// Hidden behind terrain
+    Occluded = 1,
+    /// Behind the camera or outside the view
+    Offscreen = 2,
+}
+
+/// Occlusion results for a marker list, one entry per marker
+#[wasm_bindgen]
+pub struct MarkerVisibility {
+    status: Vec<u8>,
+    /// Normalized screen position (x, y in 0..1, y down); NaN when offscreen
+    screen: Vec<f32>,
+    /// View-space depth in world units; NaN when offscreen
+    depths: Vec<f32>,
+}
+
+#[wasm_bindgen]
+impl MarkerVisibility {
+    #[wasm_bindgen]
+    pub fn count(&self) -> usize {
+        self.status.len()
+    }
+
+    /// Get marker status values (see MarkerStatus)
+    #[wasm_bindgen]
+    pub fn get_status(&self) -> Vec<u8> {
+        self.status.clone()
+    }
+
+    /// Get normalized screen positions (x, y per marker)
+    #[wasm_bindgen]
+    pub fn get_screen_positions(&self) -> Vec<f32> {
+        self.screen.clone()
+    }
+
+    /// Get view-space depths, for sorting labels front to back
+    #[wasm_bindgen]
+    pub fn get_depths(&self) -> Vec<f32> {
+        self.depths.clone()
+    }
+}
+
+/// Low-resolution CPU depth and tile-id buffer of the terrain seen from a camera
+/// Used for occlusion tests of labels and markers without reading back WebGL buffers.
+/// Depth is the view-space distance along the camera axis in world units.
+#[wasm_bindgen]
+pub struct DepthRaster {
+    width: usize,
+    height: usize,
+    depth: Vec<f32>,
+    ids: Vec<u32>,
+    /// World positions are made relative to the camera before projecting
+    origin: DVec3,
+    view_projection: Mat4,
+}
+
+#[wasm_bindgen]
+impl DepthRaster {
+    /// width, height: raster size in pixels (e.g. a quarter of the canvas size),
+    /// at most 4096 x 4096 pixels in all
+    #[wasm_bindgen(constructor)]
+    pub fn new(width: u32, height: u32) -> Result<DepthRaster, Error> {
+        if width == 0 || height == 0 {
+            return Err(Error::InvalidArgument(
+                "Raster size must be positive".into(),
+            ));
+        }
+        let (width, height) = (width as usize, height as usize);
+        let pixels = width
+            .checked_mul(height)
+            .filter(|&pixels| pixels <= MAX_PIXELS)
+            .ok_or_else(|| {
+                Error::InvalidArgument(format!(
+                    "Raster of {}x{} pixels is larger than {}",
+                    width, height, MAX_PIXELS
+                ))
+            })?;
+        Ok(DepthRaster {
+            width,
+            height,
+            depth: vec![f32::INFINITY; pixels],
+            ids: vec![NO_TILE; pixels],
+            origin: DVec3::ZERO,
+            view_projection: Mat4::IDENTITY,
+        })
+    }
+
+    /// Clear the buffers and start a frame from the given camera
+    /// The camera's aspect ratio should match the raster's
+    #[wasm_bindgen]
+    pub fn begin(&mut self, camera: &Camera) {
+        self.origin = camera.position();
+        self.view_projection = camera.view_projection(self.origin);
+        self.depth.fill(f32::INFINITY);
+        self.ids.fill(NO_TILE);
+    }
+
+    /// Rasterize one tile as a grid of triangles
+    /// elevations: 256x256 heightmap (65536 values)
+    /// tile_size: size of tile in world units
+    /// center_x, center_z: world position of the tile center
+    /// id: written to the id buffer where this tile is nearest
+    /// step: grid spacing in heightmap pixels (larger is faster and coarser)
+    #[wasm_bindgen]
+    pub fn add_tile(
+        &mut self,
+        elevations: &[f32],
+        tile_size: f32,
+        center_x: f64,
+        center_z: f64,
+        id: u32,
+        step: u32,
+    ) -> Result<(), Error> {
+        if elevations.len() != 65536 {
+            return Err(Error::InvalidElevations(format!(
+                "Invalid elevation array length: {}, expected 65536",
+                elevations.len()
+            )));
+        }
+        if step == 0 || step > 256 {
+            return Err(Error::InvalidArgument(format!(
+                "Invalid grid step: {}, expected 1-256",
+                step
+            )));
+        }
+
+        let step = step as usize;
+        let samples = grid_samples(256, step);
+        let grid_size = samples.len();
+        let pixel_size = tile_size as f64 / 256.0;
+        let corner_x = center_x - tile_size as f64 / 2.0 - self.origin.x;
+        let corner_z = center_z - tile_size as f64 / 2.0 - self.origin.z;
+        let mut clip = Vec::with_capacity(grid_size * grid_size);
+        for &sy in &samples {
+            for &sx in &samples {
+                let position = Vec3::new(
+                    (corner_x + sx as f64 * pixel_size) as f32,
+                    (elevations[sy * 256 + sx] as f64 - self.origin.y) as f32,
+                    (corner_z + sy as f64 * pixel_size) as f32,
+                );
+                clip.push(self.view_projection * position.extend(1.0));
+            }
+        }
+
+        for y in 0..grid_size - 1 {
+            for x in 0..grid_size - 1 {
+                let top_left = y * grid_size + x;
+                let bottom_left = top_left + grid_size;
+                self.draw_triangle([clip[top_left], clip[bottom_left], clip[top_left + 1]], id);
+                self.draw_triangle(
+                    [clip[top_left + 1], clip[bottom_left], clip[bottom_left + 1]],
+                    id,
+                );
+            }
+        }
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[wasm_bindgen]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Get the depth buffer (row-major, row 0 at the top; Infinity where no terrain)
+    #[wasm_bindgen]
+    pub fn get_depth(&self) -> Vec<f32> {
+        self.depth.clone()
+    }
+
+    /// Get the tile id buffer (0xFFFFFFFF where no terrain)
+    #[wasm_bindgen]
+    pub fn get_ids(&self) -> Vec<u32> {
+        self.ids.clone()
+    }
+
+    /// Terrain depth at a raster pixel (Infinity outside the raster or where no terrain)
+    #[wasm_bindgen]
+    pub fn depth_at(&self, x: u32, y: u32) -> f32 {
+        let (x, y) = (x as usize, y as usize);
+        if x >= self.width || y >= self.height {
+            return f32::INFINITY;
+        }
+        self.depth[y * self.width + x]
+    }
+
+    /// Whether a world point is in front of the terrain as seen from the camera
+    /// tolerance: depth slack in world units, so points on the surface count as visible
+    /// Points outside the view are reported as not visible
+    #[wasm_bindgen]
+    pub fn is_visible(&self, x: f64, y: f64, z: f64, tolerance: f32) -> bool {
+        match self.project(DVec3::new(x, y, z)) {
+            Some((px, py, depth)) => depth <= self.terrain_depth(px, py) + tolerance,
+            None => false,
+        }
+    }
+
+    /// Classify a list of markers as visible, occluded by terrain or offscreen
+    /// positions: flat [x0, y0, z0, x1, y1, z1, ...] world positions
+    /// tolerance: depth slack in world units, so markers on the surface count as visible
+    #[wasm_bindgen]
+    pub fn markers(&self, positions: &[f64], tolerance: f32) -> Result<MarkerVisibility, Error> {
+        if !positions.len().is_multiple_of(3) {
+            return Err(Error::InvalidArgument(
+                "Marker positions must be triples of (x, y, z) values".into(),
+            ));
+        }
+
+        let count = positions.len() / 3;
+        let mut result = MarkerVisibility {
+            status: Vec::with_capacity(count),
+            screen: Vec::with_capacity(count * 2),
+            depths: Vec::with_capacity(count),
+        };
+        for position in positions.chunks_exact(3) {
+            let point = DVec3::new(position[0], position[1], position[2]);
+            let Some((px, py, depth)) = self.project(point) else {
+                result.status.push(MarkerStatus::Offscreen as u8);
+                result.screen.extend_from_slice(&[f32::NAN, f32::NAN]);
+                result.depths.push(f32::NAN);
+                continue;
+            };
+            let status = if depth <= self.terrain_depth(px, py) + tolerance {
+                MarkerStatus::Visible
+            } else {
+                MarkerStatus::Occluded
+            };
+            result.status.push(status as u8);
+            result.screen.push(px / self.width as f32);
+            result.screen.push(py / self.height as f32);
+            result.depths.push(depth);
+        }
+        Ok(result)
+    }
+}
+
+impl DepthRaster {
+    /// Raster coordinates (pixels, y down) and view depth of a world point,
+    /// or None when it is behind the camera or outside the frustum
+    pub(crate) fn project(&self, point: DVec3) -> Option<(f32, f32, f32)> {
+        let clip = self.view_projection * (point - self.origin).as_vec3().extend(1.0);
+        if clip.w <= 0.0 || clip.z < -clip.w || clip.z > clip.w {
+            return None;
+        }
+        let (px, py) = self.to_screen(clip);
+        if px < 0.0 || py < 0.0 || px >= self.width as f32 || py >= self.height as f32 {
+            return None;
+        }
+        Some((px, py, clip.w))
+    }
+
+    /// Terrain depth under fractional raster coordinates
+    pub(crate) fn terrain_depth(&self, px: f32, py: f32) -> f32 {
+        self.depth_at(px as u32, py as u32)
+    }
+
+    fn to_screen(&self, clip: Vec4) -> (f32, f32) {
+        let ndc_x = clip.x / clip.w;
+        let ndc_y = clip.y / clip.w;
+        (
+            (ndc_x + 1.0) * 0.5 * self.width as f32,
+            (1.0 - ndc_y) * 0.5 * self.height as f32,
+        )
+    }
+
+    /// Clip a triangle against the near plane and rasterize the resulting polygon
+    fn draw_triangle(&mut self, triangle: [Vec4; 3], id: u32) {
+        // Sutherland-Hodgman against z >= -w (OpenGL near plane)
+        let mut polygon: Vec<Vec4> = Vec::with_capacity(4);
+        for i in 0..3 {
+            let (a, b) = (triangle[i], triangle[(i + 1) % 3]);
+            let (da, db) = (a.z + a.w, b.z + b.w);
+            if da >= 0.0 {
+                polygon.push(a);
+            }
+            if (da >= 0.0) != (db >= 0.0) {
+                polygon.push(a + (b - a) * (da / (da - db)));
+            }
+        }
+        if polygon.len() < 3 {
+            return;
+        }
+
+        // Screen position and 1/w, which interpolates linearly in screen space
+        let screen: Vec<(f32, f32, f32)> = polygon
+            .iter()
+            .map(|&v| {
+                let (x, y) = self.to_screen(v);
+                (x, y, 1.0 / v.w)
+            })
+            .collect();
+        for i in 1..screen.len() - 1 {
+            self.fill([screen[0], screen[i], screen[i + 1]], id);
+        }
+    }
+
+    fn fill(&mut self, [a, b, c]: [(f32, f32, f32); 3], id: u32) {
+        let area = (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0);
+        if area.abs() < 1e-12 || !area.is_finite() {
+            return;
+        }
+        let min_x = a.0.min(b.0).min(c.0).floor().max(0.0) as usize;
+        let min_y = a.1.min(b.1).min(c.1).floor().max(0.0) as usize;
+        let max_x = (a.0.max(b.0).max(c.0).ceil().max(0.0) as usize).min(self.width);
+        let max_y = (a.1.max(b.1).max(c.1).ceil().max(0.0) as usize).min(self.height);
+
+        let edge = |p: (f32, f32, f32), q: (f32, f32, f32), x: f32, y: f32| {
+            (q.0 - p.0) * (y - p.1) - (q.1 - p.1) * (x - p.0)
+        };
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                // Sample at the pixel center; either winding is accepted
+                let (cx, cy) = (x as f32 + 0.5, y as f32 + 0.5);
+                let wa = edge(b, c, cx, cy) / area;
+                let wb = edge(c, a, cx, cy) / area;
+                let wc = 1.0 - wa - wb;
+                if wa < 0.0 || wb < 0.0 || wc < 0.0 {
+                    continue;
+                }
+                let depth = 1.0 / (wa * a.2 + wb * b.2 + wc * c.2);
+                let index = y * self.width + x;
+                if depth < self.depth[index] {
+                    self.depth[index] = depth;
+                    self.ids[index] = id;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn oversized_rasters_are_rejected() {
+        assert!(DepthRaster::new(4096, 16).is_ok());
+        assert!(DepthRaster::new(4097, 4096).is_err());
+        assert!(DepthRaster::new(65536, 65536).is_err());
+        assert!(DepthRaster::new(u32::MAX, 2).is_err());
+    }
+}
@@ -1,5 +1,7 @@
 use wasm_bindgen::prelude::*;
 
+use crate::error::Error;
+
 #[wasm_bindgen]
 pub struct ElevationParser;
 
@@ -9,21 +11,21 @@ impl ElevationParser {
     /// PNG format: (R*256^2 + G*256 + B) * 0.01 - 10000
     /// 256x256 image = 65536 elevation values
     #[wasm_bindgen]
-    pub fn parse_png(data: &[u8]) -> Result<Vec<f32>, JsValue> {
+    pub fn parse_png(data: &[u8]) -> Result<Vec<f32>, Error> {
         // Use image crate to decode PNG
         let reader = image::io::Reader::new(std::io::Cursor::new(data))
             .with_guessed_format()
-            .map_err(|e| JsValue::from_str(&format!("Failed to read PNG: {}", e)))?;
+            .map_err(|e| Error::Decode(format!("Failed to read PNG: {}", e)))?;
 
         let image = reader
             .decode()
-            .map_err(|e| JsValue::from_str(&format!("Failed to decode PNG: {}", e)))?;
+            .map_err(|e| Error::Decode(format!("Failed to decode PNG: {}", e)))?;
 
         let rgb_image = image.to_rgb8();
 
         // Ensure we have exactly 256x256 pixels
         if rgb_image.width() != 256 || rgb_image.height() != 256 {
-            return Err(JsValue::from_str(&format!(
+            return Err(Error::InvalidElevations(format!(
                 "Invalid image size: {}x{}, expected 256x256",
                 rgb_image.width(),
                 rgb_image.height()
@@ -56,7 +58,7 @@ impl ElevationParser {
     /// Parse text-encoded elevation data from GSI
     /// Format: 256 comma-separated values per line, 256 lines
     #[wasm_bindgen]
-    pub fn parse_txt(data: &str) -> Result<Vec<f32>, JsValue> {
+    pub fn parse_txt(data: &str) -> Result<Vec<f32>, Error> {
         let mut elevations = Vec::with_capacity(65536);
 
         for line in data.lines() {
@@ -75,7 +77,7 @@ impl ElevationParser {
                     match value_str.parse::<f32>() {
                         Ok(elevation) => elevations.push(elevation),
                         Err(_) => {
-                            return Err(JsValue::from_str(&format!(
+                            return Err(Error::Decode(format!(
                                 "Failed to parse elevation value: {}",
                                 value_str
                             )))
@@ -86,7 +88,7 @@ impl ElevationParser {
         }
 
         if elevations.len() != 65536 {
-            return Err(JsValue::from_str(&format!(
+            return Err(Error::InvalidElevations(format!(
                 "Invalid number of elevation values: {}, expected 65536",
                 elevations.len()
             )));
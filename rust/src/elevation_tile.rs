@@ -0,0 +1,70 @@
+use crate::elevation_parser::ElevationParser;
+use crate::error::{tile_length_error, Error};
+
+/// Pixels per tile edge
+const TILE_DIM: usize = 256;
+
+/// One decoded 256x256 elevation tile, for native Rust callers
+/// Row-major heights in meters, row 0 at the north edge; the length is checked
+/// once here so the tile can be handed to Mosaic and the generators as is.
+/// With the serialize feature it (de)serializes as the bare height list, and
+/// deserializing checks the length like new().
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "Vec<f32>", into = "Vec<f32>")
+)]
+pub struct ElevationTile {
+    elevations: Vec<f32>,
+}
+
+impl ElevationTile {
+    /// Wrap 65536 heights
+    pub fn new(elevations: Vec<f32>) -> Result<ElevationTile, Error> {
+        if elevations.len() != TILE_DIM * TILE_DIM {
+            return Err(tile_length_error(elevations.len()));
+        }
+        Ok(ElevationTile { elevations })
+    }
+
+    /// Decode a GSI PNG elevation tile
+    pub fn from_png(data: &[u8]) -> Result<ElevationTile, Error> {
+        ElevationTile::new(ElevationParser::parse_png(data)?)
+    }
+
+    /// Decode a GSI text elevation tile
+    pub fn from_txt(data: &str) -> Result<ElevationTile, Error> {
+        ElevationTile::new(ElevationParser::parse_txt(data)?)
+    }
+
+    /// Height at pixel (x, y), or None outside the tile
+    pub fn get(&self, x: usize, y: usize) -> Option<f32> {
+        if x >= TILE_DIM || y >= TILE_DIM {
+            return None;
+        }
+        Some(self.elevations[y * TILE_DIM + x])
+    }
+
+    pub fn elevations(&self) -> &[f32] {
+        &self.elevations
+    }
+
+    pub fn into_vec(self) -> Vec<f32> {
+        self.elevations
+    }
+}
+
+impl TryFrom<Vec<f32>> for ElevationTile {
+    type Error = Error;
+
+    fn try_from(elevations: Vec<f32>) -> Result<ElevationTile, Error> {
+        ElevationTile::new(elevations)
+    }
+}
+
+impl From<ElevationTile> for Vec<f32> {
+    fn from(tile: ElevationTile) -> Vec<f32> {
+        tile.elevations
+    }
+}
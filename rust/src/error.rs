@@ -0,0 +1,80 @@
+use std::fmt;
+
+use wasm_bindgen::JsValue;
+
+/// Error returned by the core meshing and raster APIs
+/// Converts into a JavaScript error string at the wasm boundary, so native
+/// callers never see wasm-bindgen types
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Elevation data has the wrong size or shape
+    InvalidElevations(String),
+    /// A parameter is outside its accepted range
+    InvalidArgument(String),
+    /// Encoded input (PNG, text) could not be decoded
+    Decode(String),
+    /// A parameter failed a strict-mode check (see Validation::set_strict)
+    InvalidParameter {
+        parameter: &'static str,
+        value: String,
+        expected: String,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidElevations(message)
+            | Error::InvalidArgument(message)
+            | Error::Decode(message) => f.write_str(message),
+            Error::InvalidParameter {
+                parameter,
+                value,
+                expected,
+            } => write!(
+                f,
+                "Invalid {}: {} (expected {})",
+                parameter, value, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<Error> for JsValue {
+    /// Parameter errors become an Error object with parameter, value and
+    /// expected properties; the others stay plain strings
+    fn from(error: Error) -> JsValue {
+        match &error {
+            Error::InvalidParameter {
+                parameter,
+                value,
+                expected,
+            } => {
+                let object = js_sys::Error::new(&error.to_string());
+                for (key, property) in [
+                    ("parameter", *parameter),
+                    ("value", value),
+                    ("expected", expected),
+                ] {
+                    let _ = js_sys::Reflect::set(
+                        &object,
+                        &JsValue::from_str(key),
+                        &JsValue::from_str(property),
+                    );
+                }
+                object.into()
+            }
+            _ => JsValue::from_str(&error.to_string()),
+        }
+    }
+}
+
+/// The error for a tile heightmap that is not 256x256
+pub(crate) fn tile_length_error(len: usize) -> Error {
+    Error::InvalidElevations(format!(
+        "Invalid elevation array length: {}, expected 65536",
+        len
+    ))
+}
@@ -0,0 +1,90 @@
+use crate::heightfield::Heightfield;
+
+/// Vertical deviation of a mesh from a heightmap, over the pixels it covers
+pub(crate) struct SurfaceError {
+    pub max: f32,
+    pub sum: f64,
+    pub squared_sum: f64,
+    pub samples: u64,
+}
+
+impl SurfaceError {
+    pub fn mean(&self) -> f32 {
+        if self.samples == 0 {
+            return 0.0;
+        }
+        (self.sum / self.samples as f64) as f32
+    }
+}
+
+/// Vertical deviation of a mesh from its heightmap at every covered pixel
+/// Vertices are placed as the generator places them for tile_size: pixel x at
+/// x * pixel_size - tile_size / 2 and likewise along z. Vertical triangles
+/// (skirts) cover no pixel, and no-data pixels are skipped
+pub(crate) fn surface_error(
+    heightmap: &Heightfield,
+    vertices: &[f32],
+    indices: &[u32],
+    tile_size: f32,
+) -> SurfaceError {
+    let (width, height) = (heightmap.width, heightmap.height);
+    let mut error = SurfaceError {
+        max: 0.0,
+        sum: 0.0,
+        squared_sum: 0.0,
+        samples: 0,
+    };
+    // Pixels on shared edges are measured once, by the first triangle reaching them
+    let mut measured = vec![false; width * height];
+    let pixel_size = tile_size / width as f32;
+    let (offset_x, offset_z) = (tile_size / 2.0, pixel_size * height as f32 / 2.0);
+    let corner = |index: u32| {
+        let i = index as usize * 3;
+        (
+            (vertices[i] + offset_x) / pixel_size,
+            (vertices[i + 2] + offset_z) / pixel_size,
+            vertices[i + 1],
+        )
+    };
+
+    for triangle in indices.chunks_exact(3) {
+        let (a, b, c) = (
+            corner(triangle[0]),
+            corner(triangle[1]),
+            corner(triangle[2]),
+        );
+        let area = (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0);
+        if area.abs() < 1e-6 {
+            continue;
+        }
+        let min_x = a.0.min(b.0).min(c.0).ceil().max(0.0) as usize;
+        let min_y = a.1.min(b.1).min(c.1).ceil().max(0.0) as usize;
+        let max_x = (a.0.max(b.0).max(c.0).floor().max(0.0) as usize).min(width - 1);
+        let max_y = (a.1.max(b.1).max(c.1).floor().max(0.0) as usize).min(height - 1);
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                let index = y * width + x;
+                if measured[index] {
+                    continue;
+                }
+                let (px, py) = (x as f32, y as f32);
+                let wa = ((b.0 - px) * (c.1 - py) - (b.1 - py) * (c.0 - px)) / area;
+                let wb = ((c.0 - px) * (a.1 - py) - (c.1 - py) * (a.0 - px)) / area;
+                let wc = 1.0 - wa - wb;
+                if wa < -1e-4 || wb < -1e-4 || wc < -1e-4 {
+                    continue;
+                }
+                let deviation = (wa * a.2 + wb * b.2 + wc * c.2 - heightmap.data[index]).abs();
+                if !deviation.is_finite() {
+                    continue;
+                }
+                measured[index] = true;
+                error.max = error.max.max(deviation);
+                error.sum += deviation as f64;
+                error.squared_sum += (deviation as f64).powi(2);
+                error.samples += 1;
+            }
+        }
+    }
+    error
+}
@@ -0,0 +1,94 @@
+use glam::{DVec3, Vec3};
+use wasm_bindgen::prelude::*;
+
+use crate::camera::Camera;
+use crate::mosaic::Mosaic;
+
+/// Most steps a view ray is marched over its length
+const MAX_RAY_STEPS: f64 = 4096.0;
+
+/// Where the edges of the camera view meet the terrain, as a lat/lon polygon
+/// for minimap view cones and for restricting analyses to the visible area
+#[wasm_bindgen]
+pub struct ViewFootprint;
+
+#[wasm_bindgen]
+impl ViewFootprint {
+    /// Footprint polygon of the camera frustum on a mosaic, as interleaved
+    /// [lat, lon] counter-clockwise on screen from the bottom-left corner
+    /// Rays go through rays_per_edge points along each viewport edge and stop
+    /// at the first terrain they hit; rays that reach the far plane first (sky,
+    /// or tiles not loaded) end below their far-plane point.
+    /// tile_size: world size of a tile, as for the meshes the camera looks at
+    /// (tile x, y placed at tile_x_to_world_x(), tile_y_to_world_z())
+    /// exaggeration: vertical scale applied to the mesh elevations
+    #[wasm_bindgen]
+    pub fn compute(
+        camera: &Camera,
+        mosaic: &Mosaic,
+        tile_size: f64,
+        exaggeration: f32,
+        rays_per_edge: u32,
+    ) -> Vec<f64> {
+        if tile_size.is_nan() || tile_size <= 0.0 {
+            return Vec::new();
+        }
+        let pixel_size = tile_size / 256.0;
+        let rays = rays_per_edge.max(1);
+        // Camera-relative, so f32 keeps its precision far from the world origin
+        let inverse = camera.view_projection(camera.position()).inverse();
+        let to_world = |ndc: Vec3| camera.position() + inverse.project_point3(ndc).as_dvec3();
+        // Terrain height under a world position, if its tile is loaded
+        let terrain = |point: DVec3| {
+            let px = (point.x + tile_size / 2.0) / pixel_size;
+            let py = (point.z + tile_size / 2.0) / pixel_size;
+            mosaic
+                .sample_pixel(px, py)
+                .map(|elevation| (elevation * exaggeration) as f64)
+        };
+        let above = |point: DVec3| terrain(point).map(|height| point.y >= height);
+
+        let mut polygon = Vec::with_capacity(rays as usize * 8);
+        for edge in 0..4 {
+            for i in 0..rays {
+                let t = i as f32 / rays as f32 * 2.0 - 1.0;
+                let (x, y) = match edge {
+                    0 => (t, -1.0),
+                    1 => (1.0, t),
+                    2 => (-t, 1.0),
+                    _ => (-1.0, -t),
+                };
+                let near = to_world(Vec3::new(x, y, -1.0));
+                let far = to_world(Vec3::new(x, y, 1.0));
+
+                let steps = ((far - near).length() / pixel_size).clamp(1.0, MAX_RAY_STEPS);
+                let mut end = far;
+                let mut previous = near;
+                for step in 1..=steps as u32 {
+                    let point = near.lerp(far, step as f64 / steps);
+                    if above(point) == Some(false) && above(previous) != Some(false) {
+                        // Bisect the crossing between the last two samples
+                        let (mut outside, mut inside) = (previous, point);
+                        for _ in 0..20 {
+                            let middle = (outside + inside) / 2.0;
+                            if above(middle) == Some(false) {
+                                inside = middle;
+                            } else {
+                                outside = middle;
+                            }
+                        }
+                        end = inside;
+                        break;
+                    }
+                    previous = point;
+                }
+
+                let px = (end.x + tile_size / 2.0) / pixel_size;
+                let py = (end.z + tile_size / 2.0) / pixel_size;
+                let (lat, lon) = mosaic.pixel_to_latlon(px, py);
+                polygon.extend([lat, lon]);
+            }
+        }
+        polygon
+    }
+}
@@ -0,0 +1,199 @@
+use glam::{Mat4, Vec3};
+use wasm_bindgen::prelude::*;
+
+use crate::camera::Camera;
+use crate::solar_irradiance::{beam_irradiance, sun_position};
+
+/// Floats in the packed uniform block (13 vec4s, std140-compatible)
+const FLOAT_COUNT: usize = 52;
+
+/// Scene settings feeding the terrain shader uniforms
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct FrameConfig {
+    exaggeration: f32,
+    latitude: f32,
+    longitude: f32,
+    transmittance: f32,
+    fog_color: [f32; 3],
+    fog_near: f32,
+    fog_far: f32,
+    shadow_extent: f32,
+    shadow_bias: f32,
+    shadow_strength: f32,
+    origin_snap: f64,
+}
+
+impl Default for FrameConfig {
+    fn default() -> Self {
+        FrameConfig {
+            exaggeration: 1.0,
+            latitude: 36.5,
+            longitude: 138.0,
+            transmittance: 0.7,
+            fog_color: [0.75, 0.82, 0.9],
+            fog_near: 5000.0,
+            fog_far: 80000.0,
+            shadow_extent: 10000.0,
+            shadow_bias: 0.0005,
+            shadow_strength: 0.6,
+            origin_snap: 1024.0,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl FrameConfig {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> FrameConfig {
+        FrameConfig::default()
+    }
+
+    /// Vertical exaggeration applied to elevations in the vertex shader
+    #[wasm_bindgen]
+    pub fn set_exaggeration(&mut self, exaggeration: f32) {
+        self.exaggeration = exaggeration.max(0.0);
+    }
+
+    /// Location of the scene in degrees, used for the sun position
+    #[wasm_bindgen]
+    pub fn set_location(&mut self, lat: f32, lon: f32) {
+        self.latitude = lat.clamp(-90.0, 90.0);
+        self.longitude = lon;
+    }
+
+    /// Atmospheric transmittance for the beam at zenith (0..1, clear sky ~0.7)
+    #[wasm_bindgen]
+    pub fn set_transmittance(&mut self, transmittance: f32) {
+        self.transmittance = transmittance.clamp(0.0, 1.0);
+    }
+
+    /// Linear fog between near and far distance (world units); color components 0..1
+    #[wasm_bindgen]
+    pub fn set_fog(&mut self, near: f32, far: f32, r: f32, g: f32, b: f32) {
+        self.fog_near = near.max(0.0);
+        self.fog_far = far.max(self.fog_near);
+        self.fog_color = [r, g, b];
+    }
+
+    /// extent: half-size of the square shadow map footprint around the camera target
+    /// bias: depth bias in shadow map units
+    /// strength: how much shadowed areas darken (0..1)
+    #[wasm_bindgen]
+    pub fn set_shadow(&mut self, extent: f32, bias: f32, strength: f32) {
+        self.shadow_extent = extent.max(f32::EPSILON);
+        self.shadow_bias = bias;
+        self.shadow_strength = strength.clamp(0.0, 1.0);
+    }
+
+    /// Grid the floating render origin snaps to, in world units
+    /// The origin follows the camera so shader-side positions stay small
+    #[wasm_bindgen]
+    pub fn set_origin_snap(&mut self, snap: f64) {
+        self.origin_snap = snap.max(1.0);
+    }
+}
+
+/// Per-frame uniform block for the terrain shader
+///
+/// Layout of compute() (floats, column-major matrices):
+/// - 0..16: camera view-projection for positions relative to the origin
+/// - 16..32: sun shadow view-projection for positions relative to the origin
+/// - 32..36: camera position relative to the origin, exaggeration
+/// - 36..40: origin offset (subtract from world positions), animation time in seconds
+/// - 40..44: unit direction toward the sun, relative beam intensity (0 at night)
+/// - 44..48: fog color rgb, camera near plane
+/// - 48..52: fog near, fog far, shadow bias, shadow strength
+#[wasm_bindgen]
+pub struct FrameUniforms;
+
+#[wasm_bindgen]
+impl FrameUniforms {
+    /// Number of floats returned by compute()
+    #[wasm_bindgen]
+    pub fn float_count() -> usize {
+        FLOAT_COUNT
+    }
+
+    /// Pack all terrain shader uniforms for one frame
+    /// time: wall-clock time in milliseconds since the Unix epoch (Date.now())
+    #[wasm_bindgen]
+    pub fn compute(camera: &Camera, time: f64, config: &FrameConfig) -> Vec<f32> {
+        let origin = (camera.position() / config.origin_snap).round() * config.origin_snap;
+        let sun = sun_vector(time, config);
+        let (day, _) = day_and_hours(time);
+        let altitude = sun.y.clamp(-1.0, 1.0).asin();
+        let intensity = if altitude > 0.0 {
+            beam_irradiance(day, altitude, config.transmittance)
+                / beam_irradiance(day, 90f32.to_radians(), 1.0)
+        } else {
+            0.0
+        };
+
+        // Orthographic sun view around the camera target, far enough back to enclose it
+        let extent = config.shadow_extent;
+        let center = (camera.target() - origin).as_vec3();
+        let up = if sun.y.abs() > 0.999 {
+            Vec3::NEG_Z
+        } else {
+            Vec3::Y
+        };
+        let light_view = Mat4::look_to_rh(center + sun * extent * 2.0, -sun, up);
+        let light_projection =
+            Mat4::orthographic_rh_gl(-extent, extent, -extent, extent, 0.0, extent * 4.0);
+
+        let eye = (camera.position() - origin).as_vec3();
+        let mut uniforms = Vec::with_capacity(FLOAT_COUNT);
+        uniforms.extend_from_slice(&camera.view_projection(origin).to_cols_array());
+        uniforms.extend_from_slice(&(light_projection * light_view).to_cols_array());
+        uniforms.extend_from_slice(&[eye.x, eye.y, eye.z, config.exaggeration]);
+        let clock = (time / 1000.0).rem_euclid(3600.0) as f32;
+        uniforms.extend_from_slice(&[origin.x as f32, origin.y as f32, origin.z as f32, clock]);
+        uniforms.extend_from_slice(&[sun.x, sun.y, sun.z, intensity]);
+        uniforms.extend_from_slice(&config.fog_color);
+        uniforms.push(camera.near());
+        uniforms.extend_from_slice(&[
+            config.fog_near,
+            config.fog_far,
+            config.shadow_bias,
+            config.shadow_strength,
+        ]);
+        uniforms
+    }
+}
+
+/// Unit world-space vector toward the sun (x east, y up, z south)
+fn sun_vector(time: f64, config: &FrameConfig) -> Vec3 {
+    let (day, utc_hours) = day_and_hours(time);
+    // Local solar time from longitude, ignoring the equation of time
+    let solar_time = (utc_hours + config.longitude / 15.0).rem_euclid(24.0);
+    let (altitude, azimuth) = sun_position(config.latitude, day, solar_time);
+    let horizontal = altitude.cos();
+    Vec3::new(
+        horizontal * azimuth.sin(),
+        altitude.sin(),
+        -horizontal * azimuth.cos(),
+    )
+}
+
+/// Day of year (1-366) and UTC hour for a Unix time in milliseconds
+fn day_and_hours(time: f64) -> (u32, f32) {
+    let days = (time / 86_400_000.0).floor() as i64;
+    let hours = ((time / 3_600_000.0).rem_euclid(24.0)) as f32;
+    let year = year_of(days);
+    ((days - days_before_year(year)) as u32 + 1, hours)
+}
+
+/// Days from 1970-01-01 to January 1st of a year
+fn days_before_year(year: i64) -> i64 {
+    let y = year - 1;
+    365 * (year - 1970) + (y / 4 - y / 100 + y / 400) - (1969 / 4 - 1969 / 100 + 1969 / 400)
+}
+
+fn year_of(days: i64) -> i64 {
+    let mut year = 1970 + days.div_euclid(366);
+    while days_before_year(year + 1) <= days {
+        year += 1;
+    }
+    year
+}
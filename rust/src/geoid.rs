@@ -0,0 +1,189 @@
+use wasm_bindgen::prelude::*;
+
+use crate::error::Error;
+
+/// Grid value GSI uses for cells outside the model
+const NO_DATA: f64 = 999.0;
+
+/// Largest grid accepted, well above GSIGEO2011's 1801 x 1201 points
+const MAX_GRID_POINTS: usize = 1 << 24;
+
+/// Geoid undulation grid for converting between ellipsoidal (GPS) heights and
+/// the orthometric heights of the GSI DEM tiles: orthometric = ellipsoidal - N
+/// Loaded from the GSI geoid model file (GSIGEO2011 .asc, published by GSI)
+/// No grid, not even a coarse one, ships with this crate: GSI distributes the model
+/// to registered users under its own terms of use rather than an open licence, so it
+/// cannot be redistributed here, and the full 1801 x 1201 grid would add about 10 MB
+/// of text to the bundle. Callers fetch the file from GSI once and cache it.
+#[wasm_bindgen]
+pub struct GeoidModel {
+    /// Southwest grid point and spacing, in degrees
+    lat0: f64,
+    lon0: f64,
+    dlat: f64,
+    dlon: f64,
+    rows: usize,
+    columns: usize,
+    /// Undulations in meters, rows south to north, each row west to east;
+    /// NaN outside the model
+    undulations: Vec<f32>,
+}
+
+#[wasm_bindgen]
+impl GeoidModel {
+    /// Parse a GSI geoid grid file
+    /// Format: a header "lat0 lon0 dlat dlon rows columns ikind version", then
+    /// rows x columns whitespace-separated values, 999 where there is no data
+    #[wasm_bindgen]
+    pub fn from_gsigeo(data: &str) -> Result<GeoidModel, Error> {
+        let mut lines = data.lines();
+        let header: Vec<&str> = lines
+            .next()
+            .unwrap_or_default()
+            .split_whitespace()
+            .collect();
+        let number = |i: usize| {
+            header
+                .get(i)
+                .and_then(|value| value.parse::<f64>().ok())
+                .ok_or_else(|| Error::Decode(format!("Invalid geoid grid header: {:?}", header)))
+        };
+        let (lat0, lon0, dlat, dlon) = (number(0)?, number(1)?, number(2)?, number(3)?);
+        let count = |value: f64| {
+            (value.fract() == 0.0 && (2.0..=MAX_GRID_POINTS as f64).contains(&value))
+                .then_some(value as usize)
+        };
+        let size = count(number(4)?).zip(count(number(5)?));
+        let valid =
+            [lat0, lon0, dlat, dlon].iter().all(|v| v.is_finite()) && dlat > 0.0 && dlon > 0.0;
+        let Some((rows, columns)) = size.filter(|_| valid) else {
+            return Err(Error::Decode(format!(
+                "Invalid geoid grid header: {:?}",
+                header
+            )));
+        };
+        let points = rows
+            .checked_mul(columns)
+            .filter(|&points| points <= MAX_GRID_POINTS)
+            .ok_or_else(|| {
+                Error::Decode(format!(
+                    "Geoid grid of {} x {} points is larger than {}",
+                    rows, columns, MAX_GRID_POINTS
+                ))
+            })?;
+
+        // Every value takes at least two bytes, so a lying header cannot make
+        // this reserve more than the text could fill
+        let mut undulations = Vec::with_capacity(points.min(data.len() / 2));
+        for value_str in lines.flat_map(str::split_whitespace) {
+            match value_str.parse::<f64>() {
+                Ok(value) if value >= NO_DATA => undulations.push(f32::NAN),
+                Ok(value) => undulations.push(value as f32),
+                Err(_) => {
+                    return Err(Error::Decode(format!(
+                        "Failed to parse geoid value: {}",
+                        value_str
+                    )))
+                }
+            }
+        }
+        if undulations.len() != points {
+            return Err(Error::Decode(format!(
+                "Invalid number of geoid values: {}, expected {}",
+                undulations.len(),
+                points
+            )));
+        }
+
+        Ok(GeoidModel {
+            lat0,
+            lon0,
+            dlat,
+            dlon,
+            rows,
+            columns,
+            undulations,
+        })
+    }
+
+    /// Geoid height N above the ellipsoid in meters at a lat/lon (bilinear),
+    /// or undefined outside the model
+    #[wasm_bindgen]
+    pub fn undulation(&self, lat: f64, lon: f64) -> Option<f64> {
+        let row = (lat - self.lat0) / self.dlat;
+        let column = (lon - self.lon0) / self.dlon;
+        let (last_row, last_column) = ((self.rows - 1) as f64, (self.columns - 1) as f64);
+        if !(0.0..=last_row).contains(&row) || !(0.0..=last_column).contains(&column) {
+            return None;
+        }
+        let (r0, c0) = (
+            (row.floor() as usize).min(self.rows - 2),
+            (column.floor() as usize).min(self.columns - 2),
+        );
+        let (tr, tc) = (row - r0 as f64, column - c0 as f64);
+        let value = |r: usize, c: usize| self.undulations[r * self.columns + c] as f64;
+
+        let south = value(r0, c0) * (1.0 - tc) + value(r0, c0 + 1) * tc;
+        let north = value(r0 + 1, c0) * (1.0 - tc) + value(r0 + 1, c0 + 1) * tc;
+        Some(south * (1.0 - tr) + north * tr).filter(|n| n.is_finite())
+    }
+
+    /// Orthometric height (above the geoid, as the DEM) of an ellipsoidal height
+    #[wasm_bindgen]
+    pub fn to_orthometric(&self, lat: f64, lon: f64, ellipsoidal_height: f64) -> Option<f64> {
+        Some(ellipsoidal_height - self.undulation(lat, lon)?)
+    }
+
+    /// Ellipsoidal height (as GPS reports) of an orthometric height
+    #[wasm_bindgen]
+    pub fn to_ellipsoidal(&self, lat: f64, lon: f64, orthometric_height: f64) -> Option<f64> {
+        Some(orthometric_height + self.undulation(lat, lon)?)
+    }
+
+    /// Convert a track of ellipsoidal heights to orthometric in place
+    /// lat_lon: interleaved [lat, lon] per height; points outside the model
+    /// keep their height. Returns how many points were converted
+    #[wasm_bindgen]
+    pub fn track_to_orthometric(&self, lat_lon: &[f64], heights: &mut [f64]) -> usize {
+        let mut converted = 0;
+        for (point, height) in lat_lon.chunks_exact(2).zip(heights.iter_mut()) {
+            if let Some(n) = self.undulation(point[0], point[1]) {
+                *height -= n;
+                converted += 1;
+            }
+        }
+        converted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_a_small_grid() {
+        let data = "35.0 139.0 0.5 0.5 2 3 1 ver2.1\n36.0 37.0 38.0\n40.0 41.0 999.0\n";
+        let model = GeoidModel::from_gsigeo(data).unwrap();
+        assert_eq!(model.undulation(35.0, 139.0), Some(36.0));
+        assert_eq!(model.undulation(35.25, 139.25), Some(38.5));
+        assert_eq!(model.undulation(35.25, 139.75), None);
+        assert_eq!(model.undulation(34.0, 139.0), None);
+    }
+
+    #[test]
+    fn rejects_oversized_headers() {
+        for header in [
+            "35.0 139.0 0.5 0.5 100000 100000 1 ver2.1",
+            "35.0 139.0 0.5 0.5 1e300 2 1 ver2.1",
+            "35.0 139.0 0.5 0.5 -3 2 1 ver2.1",
+            "35.0 139.0 0.5 0.5 2.5 2 1 ver2.1",
+            "35.0 139.0 NaN 0.5 2 2 1 ver2.1",
+        ] {
+            let data = format!("{}\n1 2 3 4\n", header);
+            assert!(matches!(
+                GeoidModel::from_gsigeo(&data),
+                Err(Error::Decode(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use std::f64::consts::PI;
+
+use glam::DVec3;
+
+use crate::mesh_generator::MeshBuffers;
+
+/// WGS84 ellipsoid
+pub(crate) const WGS84_A: f64 = 6_378_137.0;
+pub(crate) const WGS84_B: f64 = 6_356_752.314_245_179;
+
+/// Earth-centered, earth-fixed position (meters) of a geodetic point in degrees
+pub(crate) fn geodetic_to_ecef(lon: f64, lat: f64, height: f64) -> DVec3 {
+    let (lon, lat) = (lon.to_radians(), lat.to_radians());
+    let e2 = 1.0 - (WGS84_B * WGS84_B) / (WGS84_A * WGS84_A);
+    let n = WGS84_A / (1.0 - e2 * lat.sin().powi(2)).sqrt();
+    DVec3::new(
+        (n + height) * lat.cos() * lon.cos(),
+        (n + height) * lat.cos() * lon.sin(),
+        (n * (1.0 - e2) + height) * lat.sin(),
+    )
+}
+
+/// Geodetic (lon, lat in degrees, height in meters) of an ECEF position,
+/// by Bowring's formula (sub-millimeter from the deep earth to orbit)
+pub(crate) fn ecef_to_geodetic(ecef: DVec3) -> (f64, f64, f64) {
+    let e2 = 1.0 - (WGS84_B * WGS84_B) / (WGS84_A * WGS84_A);
+    let ep2 = (WGS84_A * WGS84_A) / (WGS84_B * WGS84_B) - 1.0;
+    let p = ecef.x.hypot(ecef.y);
+    let theta = (ecef.z * WGS84_A).atan2(p * WGS84_B);
+    let (sin_theta, cos_theta) = theta.sin_cos();
+    let lat =
+        (ecef.z + ep2 * WGS84_B * sin_theta.powi(3)).atan2(p - e2 * WGS84_A * cos_theta.powi(3));
+    let (sin_lat, cos_lat) = lat.sin_cos();
+    let n = WGS84_A / (1.0 - e2 * sin_lat * sin_lat).sqrt();
+    // Exact for the latitude, and stable at the poles unlike p / cos(lat) - n
+    let height = p * cos_lat + ecef.z * sin_lat - WGS84_A * WGS84_A / n;
+    (ecef.y.atan2(ecef.x).to_degrees(), lat.to_degrees(), height)
+}
+
+/// Unit east, north and up vectors in ECEF at a geodetic point in degrees
+pub(crate) fn enu_frame(lon: f64, lat: f64) -> (DVec3, DVec3, DVec3) {
+    let (sin_lon, cos_lon) = lon.to_radians().sin_cos();
+    let (sin_lat, cos_lat) = lat.to_radians().sin_cos();
+    (
+        DVec3::new(-sin_lon, cos_lon, 0.0),
+        DVec3::new(-sin_lat * cos_lon, -sin_lat * sin_lon, cos_lat),
+        DVec3::new(cos_lat * cos_lon, cos_lat * sin_lon, sin_lat),
+    )
+}
+
+/// Places a tile mesh built in meters on the ellipsoid
+/// The mesh is first built flat, with x east, y up and z south and a pixel as
+/// wide as the tile center's ground pixel; projection then moves every vertex
+/// to the ECEF position of its pixel and elevation, relative to the ECEF
+/// position of the tile center at height 0.
+pub(crate) struct GlobeTile {
+    tile_x: u32,
+    tile_y: u32,
+    /// Pixels across the world at this zoom
+    world_pixels: f64,
+    center_latitude: f64,
+}
+
+impl GlobeTile {
+    pub fn new(tile_x: u32, tile_y: u32, zoom: u8) -> GlobeTile {
+        let world_pixels = 256.0 * (1u64 << zoom) as f64;
+        let mut tile = GlobeTile {
+            tile_x,
+            tile_y,
+            world_pixels,
+            center_latitude: 0.0,
+        };
+        tile.center_latitude = tile.latitude(127.5);
+        tile
+    }
+
+    /// Width of the tile's ground pixels at its center, times 256 (meters)
+    pub fn ground_size(&self) -> f64 {
+        2.0 * PI * WGS84_A * self.center_latitude.to_radians().cos() / self.world_pixels * 256.0
+    }
+
+    pub fn origin(&self) -> DVec3 {
+        geodetic_to_ecef(self.longitude(127.5), self.center_latitude, 0.0)
+    }
+
+    fn longitude(&self, px: f64) -> f64 {
+        (self.tile_x as f64 * 256.0 + px) / self.world_pixels * 360.0 - 180.0
+    }
+
+    fn latitude(&self, py: f64) -> f64 {
+        let y = (self.tile_y as f64 * 256.0 + py) / self.world_pixels;
+        (PI * (1.0 - 2.0 * y)).sinh().atan().to_degrees()
+    }
+
+    /// Move a flat mesh of ground_size() onto the ellipsoid: positions and
+    /// morph targets exactly, normals and tangents through the local east,
+    /// north, up frame (scaled for the Mercator pixel size at their latitude)
+    pub fn project(&self, mesh: &mut MeshBuffers) {
+        let tile_size = self.ground_size();
+        let pixel_size = tile_size / 256.0;
+        let origin = self.origin();
+        let center_scale = self.center_latitude.to_radians().cos();
+
+        let frame = |x: f64, z: f64| {
+            let px = (x + tile_size / 2.0) / pixel_size;
+            let py = (z + tile_size / 2.0) / pixel_size;
+            let (lon, lat) = (self.longitude(px), self.latitude(py));
+            let (east, north, up) = enu_frame(lon, lat);
+            let scale = lat.to_radians().cos() / center_scale;
+            (lon, lat, east, north, up, scale)
+        };
+        let position = |point: &mut [f32]| {
+            let (lon, lat, ..) = frame(point[0] as f64, point[2] as f64);
+            let ecef = geodetic_to_ecef(lon, lat, point[1] as f64) - origin;
+            point.copy_from_slice(&[ecef.x as f32, ecef.y as f32, ecef.z as f32]);
+        };
+        // Flat (east, up, south) components to ECEF
+        let rotate = |east: DVec3, north: DVec3, up: DVec3, v: DVec3| {
+            (east * v.x + up * v.y - north * v.z).normalize_or_zero()
+        };
+
+        for (i, vertex) in mesh.vertices.chunks_exact(3).enumerate() {
+            let (_, _, east, north, up, scale) = frame(vertex[0] as f64, vertex[2] as f64);
+            let n = &mut mesh.normals[i * 3..i * 3 + 3];
+            let normal = DVec3::new(n[0] as f64 / scale, n[1] as f64, n[2] as f64 / scale);
+            let normal = rotate(east, north, up, normal);
+            n.copy_from_slice(&[normal.x as f32, normal.y as f32, normal.z as f32]);
+            if let Some(t) = mesh.tangents.get_mut(i * 4..i * 4 + 3) {
+                let tangent = DVec3::new(t[0] as f64 * scale, t[1] as f64, t[2] as f64 * scale);
+                let tangent = rotate(east, north, up, tangent);
+                t.copy_from_slice(&[tangent.x as f32, tangent.y as f32, tangent.z as f32]);
+            }
+        }
+        mesh.vertices.chunks_exact_mut(3).for_each(position);
+        mesh.morph_targets.chunks_exact_mut(3).for_each(position);
+        mesh.origin = origin.to_array();
+    }
+}
@@ -0,0 +1,177 @@
+use wasm_bindgen::prelude::*;
+
+use crate::error::Error;
+use crate::heightfield::Heightfield;
+use crate::hydrology::{d8_receivers, fill_depressions, flow_accumulation};
+
+/// Quantity visualized by the glyphs
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GlyphMode {
+    /// Fall line: glyph points downhill, magnitude is the slope gradient (rise / run)
+    Downslope = 0,
+    /// Drainage: glyph follows D8 flow, magnitude is log-scaled flow accumulation (0..1)
+    FlowAccumulation = 1,
+}
+
+/// Instanced glyph field for drawing fall lines or drainage arrows
+#[wasm_bindgen]
+pub struct GlyphField {
+    positions: Vec<f32>,
+    rotations: Vec<f32>,
+    magnitudes: Vec<f32>,
+}
+
+#[wasm_bindgen]
+impl GlyphField {
+    /// Number of glyph instances
+    #[wasm_bindgen]
+    pub fn count(&self) -> usize {
+        self.rotations.len()
+    }
+
+    /// Get pointer to glyph positions (x, y, z per instance)
+    #[wasm_bindgen]
+    pub fn positions_ptr(&self) -> *const f32 {
+        self.positions.as_ptr()
+    }
+
+    /// Get pointer to glyph rotations (radians about +Y per instance)
+    #[wasm_bindgen]
+    pub fn rotations_ptr(&self) -> *const f32 {
+        self.rotations.as_ptr()
+    }
+
+    /// Get pointer to glyph magnitudes (one per instance)
+    #[wasm_bindgen]
+    pub fn magnitudes_ptr(&self) -> *const f32 {
+        self.magnitudes.as_ptr()
+    }
+
+    /// Get positions as a copied array (for JavaScript)
+    #[wasm_bindgen]
+    pub fn get_positions(&self) -> Vec<f32> {
+        self.positions.clone()
+    }
+
+    /// Get rotations as a copied array (for JavaScript)
+    /// A rotation of θ points the glyph along (cos θ, 0, sin θ) in world XZ
+    #[wasm_bindgen]
+    pub fn get_rotations(&self) -> Vec<f32> {
+        self.rotations.clone()
+    }
+
+    /// Get magnitudes as a copied array (for JavaScript)
+    #[wasm_bindgen]
+    pub fn get_magnitudes(&self) -> Vec<f32> {
+        self.magnitudes.clone()
+    }
+}
+
+#[wasm_bindgen]
+pub struct GlyphFieldGenerator {
+    spacing: u32,
+    mode: GlyphMode,
+}
+
+#[wasm_bindgen]
+impl GlyphFieldGenerator {
+    /// spacing: distance between glyphs in heightmap pixels
+    #[wasm_bindgen(constructor)]
+    pub fn new(spacing: u32) -> GlyphFieldGenerator {
+        GlyphFieldGenerator {
+            spacing: spacing.max(1),
+            mode: GlyphMode::Downslope,
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn set_mode(&mut self, mode: GlyphMode) {
+        self.mode = mode;
+    }
+
+    /// Generate glyph instances over a heightmap
+    /// elevations: width x height heightmap
+    /// cell_size: ground distance between pixels in meters (for slope)
+    /// tile_size: size of the heightmap in world units (positions match MeshGenerator)
+    #[wasm_bindgen]
+    pub fn generate(
+        &self,
+        elevations: &[f32],
+        width: u32,
+        height: u32,
+        cell_size: f32,
+        tile_size: f32,
+    ) -> Result<GlyphField, Error> {
+        let heightmap = Heightfield::from_slice(elevations, width, height)?;
+        if cell_size <= 0.0 {
+            return Err(Error::InvalidArgument("Cell size must be positive".into()));
+        }
+
+        let (width, height) = (width as usize, height as usize);
+        let pixel_size = tile_size / width as f32;
+        let spacing = self.spacing as usize;
+
+        // Flow routing is computed once over the full raster
+        let flow = match self.mode {
+            GlyphMode::Downslope => None,
+            GlyphMode::FlowAccumulation => {
+                let filled = fill_depressions(&heightmap, 1e-3);
+                let receivers = d8_receivers(&filled, width, height);
+                let accumulation = flow_accumulation(&filled, &receivers);
+                let max_log = accumulation
+                    .iter()
+                    .fold(1.0f32, |max, &a| max.max(a))
+                    .ln()
+                    .max(f32::EPSILON);
+                Some((receivers, accumulation, max_log))
+            }
+        };
+
+        let mut field = GlyphField {
+            positions: Vec::new(),
+            rotations: Vec::new(),
+            magnitudes: Vec::new(),
+        };
+
+        // Center the sample lattice within the raster
+        let offset = spacing / 2;
+        for y in (offset..height).step_by(spacing) {
+            for x in (offset..width).step_by(spacing) {
+                let index = y * width + x;
+                let (rotation, magnitude) = match &flow {
+                    None => {
+                        let (dzdx, dzdy) = heightmap.gradient(x, y, cell_size);
+                        let gradient = (dzdx * dzdx + dzdy * dzdy).sqrt();
+                        if gradient < 1e-4 {
+                            continue;
+                        }
+                        ((-dzdy).atan2(-dzdx), gradient)
+                    }
+                    Some((receivers, accumulation, max_log)) => {
+                        let receiver = receivers[index];
+                        if receiver < 0 {
+                            continue;
+                        }
+                        let rx = receiver as usize % width;
+                        let ry = receiver as usize / width;
+                        let direction = (ry as f32 - y as f32).atan2(rx as f32 - x as f32);
+                        (direction, accumulation[index].ln() / max_log)
+                    }
+                };
+
+                field
+                    .positions
+                    .push(x as f32 * pixel_size - tile_size / 2.0);
+                field.positions.push(elevations[index]);
+                field
+                    .positions
+                    .push(y as f32 * pixel_size - tile_size / 2.0);
+                field.rotations.push(rotation);
+                field.magnitudes.push(magnitude);
+            }
+        }
+
+        Ok(field)
+    }
+}
@@ -0,0 +1,128 @@
+use wasm_bindgen::prelude::*;
+
+use crate::error::Error;
+use crate::mesh_generator::{grid_samples, MeshGenerator};
+use crate::mosaic::Mosaic;
+
+/// u32 words per tile descriptor (32 bytes, a valid WGSL array stride)
+const DESCRIPTOR_WORDS: usize = 8;
+/// Tile blocks start on 256-byte boundaries (WebGPU's default
+/// minStorageBufferOffsetAlignment), so one tile can also be bound on its own
+const BLOCK_ALIGNMENT: usize = 64;
+
+/// Heights of many tiles packed into one storage buffer for GPU-driven rendering
+/// Each tile is a row-major grid of its LOD's samples (the vertices generate()
+/// would emit, without skirts), found through one descriptor per tile:
+///
+/// ```wgsl
+/// struct TileDescriptor {
+///     offset: u32,     // first height, in f32 elements
+///     tile_x: u32,
+///     tile_y: u32,
+///     lod: u32,
+///     grid_size: u32,  // samples per row and column
+///     step: u32,       // source pixels between samples
+///     _pad: vec2<u32>,
+/// }
+/// @group(0) @binding(0) var<storage, read> heights: array<f32>;
+/// @group(0) @binding(1) var<storage, read> tiles: array<TileDescriptor>;
+/// ```
+#[wasm_bindgen]
+pub struct TerrainStorage {
+    heights: Vec<f32>,
+    descriptors: Vec<u32>,
+}
+
+#[wasm_bindgen]
+impl TerrainStorage {
+    /// Pack the visible tiles of a mosaic at their LODs
+    /// tiles: flat (tile_x, tile_y, lod_level) triplets; lod_level indexes the
+    /// generator's LOD table. Tiles not loaded in the mosaic are skipped
+    #[wasm_bindgen]
+    pub fn pack(
+        mosaic: &Mosaic,
+        generator: &MeshGenerator,
+        tiles: &[u32],
+    ) -> Result<TerrainStorage, Error> {
+        if !tiles.len().is_multiple_of(3) {
+            return Err(Error::InvalidArgument(format!(
+                "Invalid tile list length: {}, expected (x, y, lod) triplets",
+                tiles.len()
+            )));
+        }
+
+        let mut storage = TerrainStorage {
+            heights: Vec::new(),
+            descriptors: Vec::with_capacity(tiles.len() / 3 * DESCRIPTOR_WORDS),
+        };
+        for tile in tiles.chunks_exact(3) {
+            let (tile_x, tile_y, lod) = (tile[0], tile[1], tile[2]);
+            let step = generator.lod_step(lod.min(u8::MAX as u32) as u8)?;
+            let Some(elevations) = mosaic.tile(tile_x, tile_y) else {
+                continue;
+            };
+
+            let offset = storage.heights.len();
+            let samples = grid_samples(256, step);
+            for &y in &samples {
+                storage
+                    .heights
+                    .extend(samples.iter().map(|&x| elevations[y * 256 + x]));
+            }
+            let padded = storage.heights.len().next_multiple_of(BLOCK_ALIGNMENT);
+            storage.heights.resize(padded, 0.0);
+
+            storage.descriptors.extend_from_slice(&[
+                offset as u32,
+                tile_x,
+                tile_y,
+                lod,
+                samples.len() as u32,
+                step as u32,
+                0,
+                0,
+            ]);
+        }
+        Ok(storage)
+    }
+
+    /// Number of packed tiles
+    #[wasm_bindgen]
+    pub fn tile_count(&self) -> usize {
+        self.descriptors.len() / DESCRIPTOR_WORDS
+    }
+
+    /// Get pointer to the heights buffer (f32)
+    #[wasm_bindgen]
+    pub fn heights_ptr(&self) -> *const f32 {
+        self.heights.as_ptr()
+    }
+
+    /// Get number of height values (including block padding)
+    #[wasm_bindgen]
+    pub fn heights_len(&self) -> usize {
+        self.heights.len()
+    }
+
+    /// Get pointer to the descriptor table (8 u32 per tile)
+    #[wasm_bindgen]
+    pub fn descriptors_ptr(&self) -> *const u32 {
+        self.descriptors.as_ptr()
+    }
+
+    /// Get number of descriptor words
+    #[wasm_bindgen]
+    pub fn descriptors_len(&self) -> usize {
+        self.descriptors.len()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_heights(&self) -> Vec<f32> {
+        self.heights.clone()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_descriptors(&self) -> Vec<u32> {
+        self.descriptors.clone()
+    }
+}
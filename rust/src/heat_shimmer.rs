@@ -0,0 +1,190 @@
+use wasm_bindgen::prelude::*;
+
+use crate::error::Error;
+use crate::heightfield::Heightfield;
+
+/// Sine waves summed per displacement axis
+const WAVES: usize = 6;
+
+/// Animated heat-shimmer displacement texture driven by the terrain
+/// The wave pattern tiles seamlessly in space and loops over the frame sequence;
+/// its amplitude follows the terrain under each texel, strongest on steep,
+/// sun-facing rock and fading out on slopes turned away from the sun.
+#[wasm_bindgen]
+pub struct HeatShimmer {
+    size: usize,
+    frames: usize,
+    sun_azimuth: f32,
+    sun_altitude: f32,
+    rock_slope: f32,
+    seed: u32,
+}
+
+#[wasm_bindgen]
+impl HeatShimmer {
+    /// size: texture width and height in pixels (1-1024)
+    /// frames: number of frames in the looping sequence (1-256)
+    #[wasm_bindgen(constructor)]
+    pub fn new(size: u32, frames: u32) -> Result<HeatShimmer, Error> {
+        if !(1..=1024).contains(&size) {
+            return Err(Error::InvalidArgument("Texture size must be 1-1024".into()));
+        }
+        if !(1..=256).contains(&frames) {
+            return Err(Error::InvalidArgument("Frame count must be 1-256".into()));
+        }
+        Ok(HeatShimmer {
+            size: size as usize,
+            frames: frames as usize,
+            sun_azimuth: 180.0,
+            sun_altitude: 60.0,
+            rock_slope: 30.0,
+            seed: 1,
+        })
+    }
+
+    /// Sun direction: azimuth in degrees clockwise from north, altitude above the horizon
+    #[wasm_bindgen]
+    pub fn set_sun(&mut self, azimuth: f32, altitude: f32) {
+        self.sun_azimuth = azimuth;
+        self.sun_altitude = altitude.clamp(-90.0, 90.0);
+    }
+
+    /// Slope in degrees above which terrain counts as bare rock (full shimmer)
+    #[wasm_bindgen]
+    pub fn set_rock_slope(&mut self, degrees: f32) {
+        self.rock_slope = degrees.clamp(0.0, 90.0);
+    }
+
+    /// Seed for the wave pattern; the same seed always gives the same sequence
+    #[wasm_bindgen]
+    pub fn set_seed(&mut self, seed: u32) {
+        self.seed = seed;
+    }
+
+    /// Generate the frame sequence over a heightmap
+    /// elevations: width x height heightmap, stretched over the whole texture
+    /// cell_size: ground distance between pixels in meters
+    /// Returns frames x size x size RGBA pixels, frame after frame: r, g = x, y
+    /// displacement (128 = none), b = amplitude, a = 255
+    #[wasm_bindgen]
+    pub fn generate(
+        &self,
+        elevations: &[f32],
+        width: u32,
+        height: u32,
+        cell_size: f32,
+    ) -> Result<Vec<u8>, Error> {
+        let heightmap = Heightfield::from_slice(elevations, width, height)?;
+        if cell_size <= 0.0 {
+            return Err(Error::InvalidArgument("Cell size must be positive".into()));
+        }
+
+        let amplitude = self.amplitude(&heightmap, cell_size);
+        let waves = self.waves();
+        let size = self.size;
+        let mut pixels = Vec::with_capacity(self.frames * size * size * 4);
+        for frame in 0..self.frames {
+            let t = frame as f32 / self.frames as f32;
+            for y in 0..size {
+                for x in 0..size {
+                    let (u, v) = (x as f32 / size as f32, y as f32 / size as f32);
+                    let strength = amplitude[y * size + x];
+                    let mut offset = [0.0f32; 2];
+                    for (axis, axis_waves) in waves.iter().enumerate() {
+                        for wave in axis_waves {
+                            let angle = wave.kx * u + wave.ky * v + wave.kt * t + wave.phase;
+                            offset[axis] += (angle * std::f32::consts::TAU).sin() / WAVES as f32;
+                        }
+                    }
+                    let encode = |value: f32| (128.0 + value * strength * 127.0).round() as u8;
+                    pixels.extend_from_slice(&[
+                        encode(offset[0]),
+                        encode(offset[1]),
+                        (strength * 255.0).round() as u8,
+                        255,
+                    ]);
+                }
+            }
+        }
+        Ok(pixels)
+    }
+}
+
+/// One periodic wave of the pattern; integer frequencies keep it tileable
+struct Wave {
+    kx: f32,
+    ky: f32,
+    kt: f32,
+    phase: f32,
+}
+
+impl HeatShimmer {
+    /// Shimmer strength (0..1) per texel from slope and sun incidence
+    fn amplitude(&self, heightmap: &Heightfield, cell_size: f32) -> Vec<f32> {
+        let azimuth = self.sun_azimuth.to_radians();
+        let altitude = self.sun_altitude.to_radians();
+        // Unit vector toward the sun in (east, north, up)
+        let sun = (
+            altitude.cos() * azimuth.sin(),
+            altitude.cos() * azimuth.cos(),
+            altitude.sin(),
+        );
+        let rock = self.rock_slope.to_radians();
+
+        let size = self.size;
+        let mut amplitude = Vec::with_capacity(size * size);
+        for y in 0..size {
+            for x in 0..size {
+                let px = ((x as f32 + 0.5) / size as f32 * heightmap.width as f32) as usize;
+                let py = ((y as f32 + 0.5) / size as f32 * heightmap.height as f32) as usize;
+                let (dzdx, dzdy) = heightmap.gradient(px, py, cell_size);
+                let length = (dzdx * dzdx + dzdy * dzdy + 1.0).sqrt();
+                // Raster rows run south, so north is -y
+                let normal = (-dzdx / length, dzdy / length, 1.0 / length);
+                let sunlit = (normal.0 * sun.0 + normal.1 * sun.1 + normal.2 * sun.2).max(0.0);
+
+                let slope = (dzdx * dzdx + dzdy * dzdy).sqrt().atan();
+                let t = if rock > 0.0 {
+                    (slope / rock).min(1.0)
+                } else {
+                    1.0
+                };
+                let bare = t * t * (3.0 - 2.0 * t);
+                // Flat sunlit ground still shimmers a little
+                let strength = sunlit * (0.25 + 0.75 * bare);
+                amplitude.push(if strength.is_finite() { strength } else { 0.0 });
+            }
+        }
+        amplitude
+    }
+
+    /// Deterministic wave sets for the x and y displacement
+    fn waves(&self) -> [Vec<Wave>; 2] {
+        // xorshift32; zero is its only fixed point
+        let mut state = self.seed.max(1);
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state
+        };
+        let mut axis = || {
+            (0..WAVES)
+                .map(|_| {
+                    let mut frequency = || (next() % 9) as f32 - 4.0;
+                    let (mut kx, ky) = (frequency(), frequency());
+                    if kx == 0.0 && ky == 0.0 {
+                        kx = 1.0;
+                    }
+                    Wave {
+                        kx,
+                        ky,
+                        kt: (next() % 3 + 1) as f32,
+                        phase: (next() % 1000) as f32 / 1000.0,
+                    }
+                })
+                .collect()
+        };
+        [axis(), axis()]
+    }
+}
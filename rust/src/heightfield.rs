@@ -0,0 +1,172 @@
+use std::f32::consts::SQRT_2;
+
+use crate::error::Error;
+
+/// Read-only view of a row-major elevation raster
+#[derive(Clone, Copy)]
+pub(crate) struct Heightfield<'a> {
+    pub data: &'a [f32],
+    pub width: usize,
+    pub height: usize,
+}
+
+impl<'a> Heightfield<'a> {
+    /// Returns None when the data length does not match width * height
+    pub fn new(data: &'a [f32], width: usize, height: usize) -> Option<Heightfield<'a>> {
+        if width == 0 || height == 0 || width.checked_mul(height) != Some(data.len()) {
+            return None;
+        }
+        Some(Heightfield {
+            data,
+            width,
+            height,
+        })
+    }
+
+    /// Like new(), but reports a size mismatch as an error
+    pub fn from_slice(data: &'a [f32], width: u32, height: u32) -> Result<Heightfield<'a>, Error> {
+        Heightfield::new(data, width as usize, height as usize).ok_or_else(|| {
+            Error::InvalidElevations(format!(
+                "Invalid elevation array length: {}, expected {}x{}",
+                data.len(),
+                width,
+                height
+            ))
+        })
+    }
+
+    /// Pixel value with coordinates clamped to the raster edges
+    pub fn get(&self, x: isize, y: isize) -> f32 {
+        let x = x.clamp(0, self.width as isize - 1) as usize;
+        let y = y.clamp(0, self.height as isize - 1) as usize;
+        self.data[y * self.width + x]
+    }
+
+    /// Bilinear sample at fractional pixel coordinates
+    pub fn sample(&self, x: f32, y: f32) -> f32 {
+        let x0 = x.floor();
+        let y0 = y.floor();
+        let tx = x - x0;
+        let ty = y - y0;
+        let (x0, y0) = (x0 as isize, y0 as isize);
+
+        let top = self.get(x0, y0) * (1.0 - tx) + self.get(x0 + 1, y0) * tx;
+        let bottom = self.get(x0, y0 + 1) * (1.0 - tx) + self.get(x0 + 1, y0 + 1) * tx;
+        top * (1.0 - ty) + bottom * ty
+    }
+
+    /// Sample at fractional pixel coordinates interpolated over the two triangles
+    /// of each pixel cell, split along the diagonal the grid meshes use (top-right
+    /// to bottom-left), so the value lies on the full-resolution mesh surface
+    pub fn sample_triangulated(&self, x: f32, y: f32) -> f32 {
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as isize, y0 as isize);
+
+        let top_right = self.get(x0 + 1, y0);
+        let bottom_left = self.get(x0, y0 + 1);
+        if tx + ty <= 1.0 {
+            let top_left = self.get(x0, y0);
+            top_left + (top_right - top_left) * tx + (bottom_left - top_left) * ty
+        } else {
+            let bottom_right = self.get(x0 + 1, y0 + 1);
+            bottom_right
+                + (bottom_left - bottom_right) * (1.0 - tx)
+                + (top_right - bottom_right) * (1.0 - ty)
+        }
+    }
+
+    /// Horn's 3x3 gradient at a pixel
+    /// Returns (dz/dx, dz/dy) with x increasing east and y increasing south (raster rows)
+    pub fn gradient(&self, x: usize, y: usize, cell_size: f32) -> (f32, f32) {
+        let (x, y) = (x as isize, y as isize);
+        let a = self.get(x - 1, y - 1);
+        let b = self.get(x, y - 1);
+        let c = self.get(x + 1, y - 1);
+        let d = self.get(x - 1, y);
+        let f = self.get(x + 1, y);
+        let g = self.get(x - 1, y + 1);
+        let h = self.get(x, y + 1);
+        let i = self.get(x + 1, y + 1);
+
+        let dzdx = ((c + 2.0 * f + i) - (a + 2.0 * d + g)) / (8.0 * cell_size);
+        let dzdy = ((g + 2.0 * h + i) - (a + 2.0 * b + c)) / (8.0 * cell_size);
+        (dzdx, dzdy)
+    }
+
+    /// Copy of the raster with land ramped down to 0 m near the sea
+    /// Sea is the parsers' no-data value (exactly 0 m). Land within `distance`
+    /// pixels of it is scaled by a smoothstep of its distance, so the coast
+    /// slopes to the water instead of ending in a wall at the first valid pixel.
+    /// NaN pixels (missing data) are neither sea nor changed.
+    pub fn feather_coast(&self, distance: f32) -> Vec<f32> {
+        let mut output = vec![0.0; self.data.len()];
+        let mut nearest = vec![0.0; self.data.len()];
+        self.feather_coast_into(distance, &mut output, &mut nearest);
+        output
+    }
+
+    /// feather_coast() into caller buffers of width x height values;
+    /// `nearest` is scratch space
+    pub fn feather_coast_into(&self, distance: f32, output: &mut [f32], nearest: &mut [f32]) {
+        let (width, height) = (self.width, self.height);
+        output.copy_from_slice(self.data);
+        if distance <= 0.0 || !self.data.contains(&0.0) {
+            return;
+        }
+
+        // Two-pass chamfer distance to the nearest sea pixel
+        for (d, &v) in nearest.iter_mut().zip(self.data) {
+            *d = if v == 0.0 { 0.0 } else { f32::INFINITY };
+        }
+        let forward = [
+            (-1, 0, 1.0),
+            (-1, -1, SQRT_2),
+            (0, -1, 1.0),
+            (1, -1, SQRT_2),
+        ];
+        let relax = |nearest: &mut [f32], x: usize, y: usize, dx: isize, dy: isize, cost: f32| {
+            let (nx, ny) = (x as isize + dx, y as isize + dy);
+            if nx >= 0 && ny >= 0 && (nx as usize) < width && (ny as usize) < height {
+                let candidate = nearest[ny as usize * width + nx as usize] + cost;
+                let own = &mut nearest[y * width + x];
+                *own = own.min(candidate);
+            }
+        };
+        for y in 0..height {
+            for x in 0..width {
+                for &(dx, dy, cost) in &forward {
+                    relax(nearest, x, y, dx, dy, cost);
+                }
+            }
+        }
+        for y in (0..height).rev() {
+            for x in (0..width).rev() {
+                for &(dx, dy, cost) in &forward {
+                    relax(nearest, x, y, -dx, -dy, cost);
+                }
+            }
+        }
+
+        for (value, &d) in output.iter_mut().zip(&*nearest) {
+            if d < distance {
+                let t = d / distance;
+                *value *= t * t * (3.0 - 2.0 * t);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_whose_product_wraps_are_rejected() {
+        let data = [0.0; 4];
+        assert!(Heightfield::new(&data, 2, 2).is_some());
+        // (2^(bits-1) + 2) * 2 wraps around to 4
+        let width = (1 << (usize::BITS - 1)) + 2;
+        assert!(Heightfield::new(&data, width, 2).is_none());
+    }
+}
@@ -0,0 +1,137 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::heightfield::Heightfield;
+
+/// D8 neighbor offsets, clockwise from east
+pub(crate) const D8_OFFSETS: [(isize, isize); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+
+/// Min-heap entry ordered by elevation
+struct Cell {
+    elevation: f32,
+    index: usize,
+}
+
+impl PartialEq for Cell {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Cell {}
+
+impl PartialOrd for Cell {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Cell {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed so BinaryHeap pops the lowest cell first
+        other
+            .elevation
+            .total_cmp(&self.elevation)
+            .then_with(|| other.index.cmp(&self.index))
+    }
+}
+
+/// Priority-flood depression filling (Barnes et al.)
+/// Every cell drains to the raster edge in the result; `epsilon` adds a tiny
+/// gradient across filled flats so flow directions stay defined
+pub(crate) fn fill_depressions(heightmap: &Heightfield, epsilon: f32) -> Vec<f32> {
+    let (width, height) = (heightmap.width, heightmap.height);
+    let mut filled = heightmap.data.to_vec();
+    let mut visited = vec![false; width * height];
+    let mut queue = BinaryHeap::new();
+
+    for y in 0..height {
+        for x in 0..width {
+            if x == 0 || y == 0 || x == width - 1 || y == height - 1 {
+                let index = y * width + x;
+                visited[index] = true;
+                queue.push(Cell {
+                    elevation: filled[index],
+                    index,
+                });
+            }
+        }
+    }
+
+    while let Some(Cell { elevation, index }) = queue.pop() {
+        let (x, y) = ((index % width) as isize, (index / width) as isize);
+        for (dx, dy) in D8_OFFSETS {
+            let (nx, ny) = (x + dx, y + dy);
+            if nx < 0 || ny < 0 || nx >= width as isize || ny >= height as isize {
+                continue;
+            }
+            let neighbor = ny as usize * width + nx as usize;
+            if visited[neighbor] {
+                continue;
+            }
+            visited[neighbor] = true;
+            filled[neighbor] = filled[neighbor].max(elevation + epsilon);
+            queue.push(Cell {
+                elevation: filled[neighbor],
+                index: neighbor,
+            });
+        }
+    }
+
+    filled
+}
+
+/// Steepest-descent (D8) receiver of each cell, or -1 for cells with no lower neighbor
+pub(crate) fn d8_receivers(heights: &[f32], width: usize, height: usize) -> Vec<isize> {
+    let mut receivers = vec![-1isize; width * height];
+
+    for y in 0..height {
+        for x in 0..width {
+            let index = y * width + x;
+            let mut best_drop = 0.0f32;
+            for (dx, dy) in D8_OFFSETS {
+                let (nx, ny) = (x as isize + dx, y as isize + dy);
+                if nx < 0 || ny < 0 || nx >= width as isize || ny >= height as isize {
+                    continue;
+                }
+                let neighbor = ny as usize * width + nx as usize;
+                let distance = if dx != 0 && dy != 0 {
+                    std::f32::consts::SQRT_2
+                } else {
+                    1.0
+                };
+                let drop = (heights[index] - heights[neighbor]) / distance;
+                if drop > best_drop {
+                    best_drop = drop;
+                    receivers[index] = neighbor as isize;
+                }
+            }
+        }
+    }
+
+    receivers
+}
+
+/// Number of cells (including itself) draining through each cell
+pub(crate) fn flow_accumulation(heights: &[f32], receivers: &[isize]) -> Vec<f32> {
+    let mut order: Vec<usize> = (0..heights.len()).collect();
+    order.sort_by(|&a, &b| heights[b].total_cmp(&heights[a]));
+
+    let mut accumulation = vec![1.0f32; heights.len()];
+    for index in order {
+        let receiver = receivers[index];
+        if receiver >= 0 {
+            accumulation[receiver as usize] += accumulation[index];
+        }
+    }
+    accumulation
+}
@@ -0,0 +1,94 @@
+use crate::error::Error;
+
+/// Elevation to color ramp for hypsometric tinting
+/// Colors are interpolated linearly between stops and held beyond the ends.
+#[derive(Clone)]
+pub(crate) struct ColorRamp {
+    /// (elevation in meters, RGB), by increasing elevation
+    stops: Vec<(f32, [u8; 3])>,
+}
+
+impl Default for ColorRamp {
+    /// Lowland green through tan and brown to rock grey and snow
+    fn default() -> ColorRamp {
+        ColorRamp {
+            stops: vec![
+                (0.0, [94, 140, 74]),
+                (300.0, [160, 180, 100]),
+                (800.0, [200, 170, 110]),
+                (1500.0, [170, 120, 80]),
+                (2500.0, [150, 140, 130]),
+                (3500.0, [245, 245, 245]),
+            ],
+        }
+    }
+}
+
+impl ColorRamp {
+    /// Ramp from flat (elevation, r, g, b) quadruples, by increasing elevation
+    pub fn from_stops(stops: &[f32]) -> Result<ColorRamp, Error> {
+        if stops.is_empty() || !stops.len().is_multiple_of(4) {
+            return Err(Error::InvalidArgument(format!(
+                "Invalid color ramp length: {}, expected (elevation, r, g, b) quadruples",
+                stops.len()
+            )));
+        }
+        let mut ramp = ColorRamp {
+            stops: Vec::with_capacity(stops.len() / 4),
+        };
+        for stop in stops.chunks_exact(4) {
+            if !stop[0].is_finite() || ramp.stops.last().is_some_and(|&(last, _)| stop[0] <= last) {
+                return Err(Error::InvalidArgument(
+                    "Color ramp elevations must be finite and increasing".into(),
+                ));
+            }
+            if stop[1..]
+                .iter()
+                .any(|channel| !(0.0..=255.0).contains(channel))
+            {
+                return Err(Error::InvalidArgument(format!(
+                    "Invalid color at {} m: channels must be 0-255",
+                    stop[0]
+                )));
+            }
+            ramp.stops.push((
+                stop[0],
+                [stop[1], stop[2], stop[3]].map(|c| c.round() as u8),
+            ));
+        }
+        Ok(ramp)
+    }
+
+    /// Stops flattened back to (elevation, r, g, b), for hashing the configuration
+    pub fn to_stops(&self) -> Vec<f32> {
+        self.stops
+            .iter()
+            .flat_map(|&(elevation, [r, g, b])| [elevation, r as f32, g as f32, b as f32])
+            .collect()
+    }
+
+    pub fn color(&self, elevation: f32) -> [u8; 3] {
+        let upper = self.stops.partition_point(|&(stop, _)| stop <= elevation);
+        if upper == 0 {
+            return self.stops[0].1;
+        }
+        if upper == self.stops.len() {
+            return self.stops[upper - 1].1;
+        }
+        let ((low, a), (high, b)) = (self.stops[upper - 1], self.stops[upper]);
+        let t = (elevation - low) / (high - low);
+        let mut rgb = [0; 3];
+        for (k, channel) in rgb.iter_mut().enumerate() {
+            *channel = (a[k] as f32 + (b[k] as f32 - a[k] as f32) * t).round() as u8;
+        }
+        rgb
+    }
+
+    /// RGB per vertex from the y (elevation) of packed xyz positions
+    pub fn vertex_colors(&self, vertices: &[f32]) -> Vec<u8> {
+        vertices
+            .chunks_exact(3)
+            .flat_map(|vertex| self.color(vertex[1]))
+            .collect()
+    }
+}
@@ -0,0 +1,266 @@
+use wasm_bindgen::prelude::*;
+
+use crate::error::Error;
+
+const IDENTIFIER: [u8; 12] = [
+    0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+];
+const VK_FORMAT_R8G8B8A8_UNORM: u32 = 37;
+const VK_FORMAT_R8G8B8A8_SRGB: u32 = 43;
+/// Identifier, header and index, before the level index
+const HEADER_BYTES: usize = 80;
+/// Basic data format descriptor block with four 8-bit samples
+const DFD_BLOCK_BYTES: u32 = 24 + 4 * 16;
+
+/// KTX2 texture containers for the RGBA rasters made here (normal maps,
+/// hillshades), ready for KTX2Loader or a direct texture upload
+/// Levels are stored uncompressed (no supercompression; BasisU needs an
+/// encoder this crate does not bundle).
+#[wasm_bindgen]
+pub struct Ktx2;
+
+#[wasm_bindgen]
+impl Ktx2 {
+    /// Wrap row-major RGBA8 pixels (row 0 at the top) in a KTX2 file
+    /// srgb: tag the data as sRGB color (false for normal maps and other data)
+    /// mipmaps: also store the full chain of 2x2 box-filtered levels down to 1x1
+    #[wasm_bindgen]
+    pub fn encode_rgba8(
+        pixels: &[u8],
+        width: u32,
+        height: u32,
+        srgb: bool,
+        mipmaps: bool,
+    ) -> Result<Vec<u8>, Error> {
+        if width == 0 || height == 0 {
+            return Err(Error::InvalidArgument(format!(
+                "Invalid texture size: {}x{}",
+                width, height
+            )));
+        }
+        let Some(length) = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|texels| texels.checked_mul(4))
+        else {
+            return Err(Error::InvalidArgument(format!(
+                "Texture of {}x{} RGBA is too large",
+                width, height
+            )));
+        };
+        if pixels.len() != length {
+            return Err(Error::InvalidArgument(format!(
+                "Invalid pixel buffer length: {}, expected {} for {}x{} RGBA",
+                pixels.len(),
+                length,
+                width,
+                height
+            )));
+        }
+
+        let mut levels = vec![(pixels.to_vec(), width, height)];
+        if mipmaps {
+            while let Some((source, w, h)) = levels.last().filter(|(_, w, h)| *w > 1 || *h > 1) {
+                let next = downsample(source, *w, *h);
+                levels.push(next);
+            }
+        }
+        Ok(encode(&levels, srgb))
+    }
+}
+
+/// Halve a level (rounding down, at least 1), averaging each 2x2 block
+fn downsample(pixels: &[u8], width: u32, height: u32) -> (Vec<u8>, u32, u32) {
+    let (w, h) = ((width / 2).max(1), (height / 2).max(1));
+    let (width, height) = (width as usize, height as usize);
+    let mut output = Vec::with_capacity(w as usize * h as usize * 4);
+    for y in 0..h as usize {
+        let rows = [(2 * y).min(height - 1), (2 * y + 1).min(height - 1)];
+        for x in 0..w as usize {
+            let columns = [(2 * x).min(width - 1), (2 * x + 1).min(width - 1)];
+            for channel in 0..4 {
+                let mut sum = 0u32;
+                for row in rows {
+                    for column in columns {
+                        sum += pixels[(row * width + column) * 4 + channel] as u32;
+                    }
+                }
+                output.push(((sum + 2) / 4) as u8);
+            }
+        }
+    }
+    (output, w, h)
+}
+
+fn encode(levels: &[(Vec<u8>, u32, u32)], srgb: bool) -> Vec<u8> {
+    let dfd = data_format_descriptor(srgb);
+    let kvd = key_values(&[("KTXorientation", "rd"), ("KTXwriter", "peak-vista")]);
+
+    let level_index_bytes = levels.len() * 24;
+    let dfd_offset = HEADER_BYTES + level_index_bytes;
+    let kvd_offset = dfd_offset + dfd.len();
+    let mut data_offset = (kvd_offset + kvd.len()).next_multiple_of(4);
+
+    // Level data goes smallest first, so a streamed file shows a preview early
+    let mut offsets = vec![0; levels.len()];
+    for (level, (pixels, _, _)) in levels.iter().enumerate().rev() {
+        offsets[level] = data_offset;
+        data_offset = (data_offset + pixels.len()).next_multiple_of(4);
+    }
+
+    let (_, width, height) = levels[0];
+    let format = if srgb {
+        VK_FORMAT_R8G8B8A8_SRGB
+    } else {
+        VK_FORMAT_R8G8B8A8_UNORM
+    };
+    let mut out = Vec::with_capacity(data_offset);
+    out.extend_from_slice(&IDENTIFIER);
+    for word in [format, 1, width, height, 0, 0, 1, levels.len() as u32, 0] {
+        out.extend_from_slice(&word.to_le_bytes());
+    }
+    for word in [
+        dfd_offset as u32,
+        dfd.len() as u32,
+        kvd_offset as u32,
+        kvd.len() as u32,
+    ] {
+        out.extend_from_slice(&word.to_le_bytes());
+    }
+    // No supercompression global data
+    out.extend_from_slice(&[0; 16]);
+    for (level, (pixels, _, _)) in levels.iter().enumerate() {
+        let length = pixels.len() as u64;
+        out.extend_from_slice(&(offsets[level] as u64).to_le_bytes());
+        out.extend_from_slice(&length.to_le_bytes());
+        out.extend_from_slice(&length.to_le_bytes());
+    }
+    out.extend_from_slice(&dfd);
+    out.extend_from_slice(&kvd);
+    for (level, (pixels, _, _)) in levels.iter().enumerate().rev() {
+        out.resize(offsets[level], 0);
+        out.extend_from_slice(pixels);
+    }
+    out
+}
+
+/// Khronos basic descriptor for 8-bit RGBA in one plane
+fn data_format_descriptor(srgb: bool) -> Vec<u8> {
+    const COLOR_MODEL_RGBSDA: u32 = 1;
+    const PRIMARIES_BT709: u32 = 1;
+    const SAMPLE_LINEAR: u32 = 0x10;
+    let transfer = if srgb { 2 } else { 1 };
+
+    let mut words = vec![
+        4 + DFD_BLOCK_BYTES,
+        0,
+        2 | (DFD_BLOCK_BYTES << 16),
+        COLOR_MODEL_RGBSDA | (PRIMARIES_BT709 << 8) | (transfer << 16),
+        0,
+        4,
+        0,
+    ];
+    for (k, channel) in [0u32, 1, 2, 15].into_iter().enumerate() {
+        // Alpha is never sRGB encoded
+        let flags = if srgb && channel == 15 {
+            SAMPLE_LINEAR
+        } else {
+            0
+        };
+        words.extend_from_slice(&[
+            (k as u32 * 8) | (7 << 16) | ((channel | flags) << 24),
+            0,
+            0,
+            255,
+        ]);
+    }
+    words.iter().flat_map(|word| word.to_le_bytes()).collect()
+}
+
+/// Key/value entries: length, NUL-terminated key and value, padded to 4 bytes
+fn key_values(entries: &[(&str, &str)]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for (key, value) in entries {
+        let length = key.len() + value.len() + 2;
+        out.extend_from_slice(&(length as u32).to_le_bytes());
+        out.extend_from_slice(key.as_bytes());
+        out.push(0);
+        out.extend_from_slice(value.as_bytes());
+        out.push(0);
+        out.resize(out.len().next_multiple_of(4), 0);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    fn u64_at(bytes: &[u8], offset: usize) -> usize {
+        u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap()) as usize
+    }
+
+    #[test]
+    fn header_and_levels_follow_the_spec() {
+        let pixels: Vec<u8> = (0..5 * 3 * 4).map(|i| i as u8).collect();
+        let file = Ktx2::encode_rgba8(&pixels, 5, 3, true, true).unwrap();
+
+        assert_eq!(file[..12], IDENTIFIER);
+        let header: Vec<u32> = (12..48).step_by(4).map(|i| u32_at(&file, i)).collect();
+        assert_eq!(header, [VK_FORMAT_R8G8B8A8_SRGB, 1, 5, 3, 0, 0, 1, 3, 0]);
+
+        let (dfd_offset, dfd_length) = (u32_at(&file, 48) as usize, u32_at(&file, 52) as usize);
+        let (kvd_offset, kvd_length) = (u32_at(&file, 56) as usize, u32_at(&file, 60) as usize);
+        assert_eq!(dfd_offset, HEADER_BYTES + 3 * 24);
+        assert_eq!(u32_at(&file, dfd_offset) as usize, dfd_length);
+        assert_eq!(dfd_length, 4 + DFD_BLOCK_BYTES as usize);
+        // Transfer function sRGB, RGBSDA model, BT.709 primaries
+        assert_eq!(u32_at(&file, dfd_offset + 12), 1 | (1 << 8) | (2 << 16));
+        assert_eq!(kvd_offset, dfd_offset + dfd_length);
+        let kvd = &file[kvd_offset..kvd_offset + kvd_length];
+        assert_eq!(&kvd[4..18], b"KTXorientation");
+        assert_eq!(u64_at(&file, 64), 0);
+        assert_eq!(u64_at(&file, 72), 0);
+
+        let sizes = [(5, 3), (2, 1), (1, 1)];
+        let mut end = 0;
+        for (level, (w, h)) in sizes.into_iter().enumerate() {
+            let entry = HEADER_BYTES + level * 24;
+            let (offset, length) = (u64_at(&file, entry), u64_at(&file, entry + 8));
+            assert_eq!(length, w * h * 4);
+            assert_eq!(u64_at(&file, entry + 16), length);
+            assert_eq!(offset % 4, 0);
+            assert!(offset >= kvd_offset + kvd_length);
+            end = end.max(offset + length);
+        }
+        assert_eq!(end, file.len());
+
+        let base = u64_at(&file, HEADER_BYTES);
+        assert_eq!(file[base..base + pixels.len()], pixels);
+        // Level 1 pixel 0 averages pixels (0,0), (1,0), (0,1) and (1,1)
+        let level1 = u64_at(&file, HEADER_BYTES + 24);
+        let expected =
+            (pixels[0] as u32 + pixels[4] as u32 + pixels[20] as u32 + pixels[24] as u32 + 2) / 4;
+        assert_eq!(file[level1] as u32, expected);
+    }
+
+    #[test]
+    fn single_level_data_textures_are_unorm() {
+        let file = Ktx2::encode_rgba8(&[1, 2, 3, 4], 1, 1, false, false).unwrap();
+        assert_eq!(u32_at(&file, 12), VK_FORMAT_R8G8B8A8_UNORM);
+        assert_eq!(u32_at(&file, 40), 1);
+        let offset = u64_at(&file, HEADER_BYTES);
+        assert_eq!(file[offset..], [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn oversized_textures_are_rejected() {
+        assert!(matches!(
+            Ktx2::encode_rgba8(&[0; 4], u32::MAX, u32::MAX, false, false),
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+}
@@ -0,0 +1,103 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::hydrology::D8_OFFSETS;
+
+/// Min-heap entry ordered by accumulated cost
+struct Node {
+    cost: f32,
+    index: usize,
+}
+
+impl PartialEq for Node {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Node {}
+
+impl PartialOrd for Node {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Node {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed so BinaryHeap pops the cheapest node first
+        other
+            .cost
+            .total_cmp(&self.cost)
+            .then_with(|| other.index.cmp(&self.index))
+    }
+}
+
+/// Cheapest 8-connected path between two cells of a width x height raster (Dijkstra)
+/// `step_cost(from, to, cells)` returns the cost of moving between neighboring cells
+/// `cells` apart (1 or √2), or None when the move is not allowed
+/// Returns the cell indices from start to goal, or None when the goal is unreachable
+pub(crate) fn least_cost_path(
+    width: usize,
+    height: usize,
+    start: usize,
+    goal: usize,
+    step_cost: impl Fn(usize, usize, f32) -> Option<f32>,
+) -> Option<Vec<usize>> {
+    let mut cost = vec![f32::INFINITY; width * height];
+    let mut previous = vec![usize::MAX; width * height];
+    let mut queue = BinaryHeap::new();
+    cost[start] = 0.0;
+    queue.push(Node {
+        cost: 0.0,
+        index: start,
+    });
+
+    while let Some(Node {
+        cost: current,
+        index,
+    }) = queue.pop()
+    {
+        if index == goal {
+            break;
+        }
+        if current > cost[index] {
+            continue;
+        }
+        let (x, y) = ((index % width) as isize, (index / width) as isize);
+        for (dx, dy) in D8_OFFSETS {
+            let (nx, ny) = (x + dx, y + dy);
+            if nx < 0 || ny < 0 || nx >= width as isize || ny >= height as isize {
+                continue;
+            }
+            let neighbor = ny as usize * width + nx as usize;
+            let cells = if dx != 0 && dy != 0 {
+                std::f32::consts::SQRT_2
+            } else {
+                1.0
+            };
+            let Some(step) = step_cost(index, neighbor, cells) else {
+                continue;
+            };
+            let next = current + step;
+            if next < cost[neighbor] {
+                cost[neighbor] = next;
+                previous[neighbor] = index;
+                queue.push(Node {
+                    cost: next,
+                    index: neighbor,
+                });
+            }
+        }
+    }
+
+    if !cost[goal].is_finite() {
+        return None;
+    }
+    let mut path = vec![goal];
+    while path[path.len() - 1] != start {
+        path.push(previous[path[path.len() - 1]]);
+    }
+    path.reverse();
+    Some(path)
+}
@@ -1,12 +1,98 @@
 use wasm_bindgen::prelude::*;
 
+mod error;
 mod elevation_parser;
+mod elevation_tile;
 mod mesh_generator;
 mod coordinate_transform;
+mod rtin;
+mod heightfield;
+mod delaunay;
+mod hydrology;
+mod glyph_field;
+mod ridge_lines;
+mod tile_pyramid;
+mod stress_test;
+mod view_state;
+mod tile_cache;
+mod map_export;
+mod wind_exposure;
+mod solar_irradiance;
+mod cold_air;
+mod mosaic;
+mod track_analysis;
+mod route_score;
+mod least_cost;
+mod corridor;
+mod route_snap;
+mod cliff_detection;
+mod footprint;
+mod camera;
+mod frame_uniforms;
+mod depth_raster;
+mod heat_shimmer;
+mod lod_benchmark;
+mod fidelity;
+mod vertex_cache;
+mod mesh_tools;
+mod meshopt;
+mod mesh_export;
+mod decal;
+mod water;
+mod raycast;
+mod collider;
+mod globe;
+mod transverse_mercator;
+mod geoid;
+mod tile_url;
+mod hypsometric;
+mod splat;
+mod validation;
+mod pipeline;
+mod gpu_storage;
+mod ktx2;
+mod staging;
+#[cfg(feature = "sample-data")]
+mod synthetic_sample;
 
+pub use error::Error;
 pub use elevation_parser::ElevationParser;
-pub use mesh_generator::MeshGenerator;
+pub use elevation_tile::ElevationTile;
+pub use mesh_generator::{IndexType, LodChain, MeshBuffers, MeshData, MeshGenerator};
+pub use raycast::RayHit;
+pub use collider::HeightfieldCollider;
 pub use coordinate_transform::CoordinateTransform;
+pub use geoid::GeoidModel;
+pub use tile_url::TileUrlBuilder;
+pub use tile_pyramid::{DownsampleMode, TilePyramid, UpsampleFilter};
+pub use stress_test::{CameraPath, StressReport, StressTest};
+pub use view_state::ViewState;
+pub use tile_cache::TileCache;
+pub use map_export::MapSheet;
+pub use glyph_field::{GlyphField, GlyphFieldGenerator, GlyphMode};
+pub use ridge_lines::{RidgeExtractor, RidgeLines};
+pub use wind_exposure::WindExposure;
+pub use solar_irradiance::SolarIrradiance;
+pub use cold_air::ColdAirPooling;
+pub use mosaic::Mosaic;
+pub use track_analysis::{TrackAnalysis, TrackAnalyzer};
+pub use route_score::{RouteScore, RouteScorer};
+pub use route_snap::RouteSnapper;
+pub use cliff_detection::{CliffDetector, CliffSegments};
+pub use footprint::ViewFootprint;
+pub use camera::Camera;
+pub use frame_uniforms::{FrameConfig, FrameUniforms};
+pub use depth_raster::{DepthRaster, MarkerStatus, MarkerVisibility};
+pub use heat_shimmer::HeatShimmer;
+pub use lod_benchmark::{BenchmarkReport, LodBenchmark, StrategyReport};
+pub use mesh_tools::{MeshTools, MeshValidation};
+pub use validation::Validation;
+pub use pipeline::Pipeline;
+pub use gpu_storage::TerrainStorage;
+pub use ktx2::Ktx2;
+pub use staging::StagingBuffers;
+#[cfg(feature = "sample-data")]
+pub use synthetic_sample::SyntheticSampleData;
 
 // Web console logging for debugging
 #[wasm_bindgen]
@@ -0,0 +1,186 @@
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::elevation_parser::ElevationParser;
+use crate::error::{tile_length_error, Error};
+use crate::fidelity::surface_error;
+use crate::heightfield::Heightfield;
+use crate::mesh_generator::{MeshData, MeshGenerator};
+use crate::stress_test::now_ms;
+
+/// Meshes are generated at one world unit per pixel so vertices map straight to pixels
+const TILE_SIZE: f32 = 256.0;
+
+/// Result of LodBenchmark::run
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct BenchmarkReport {
+    pub tiles: u32,
+    pub strategies: Vec<StrategyReport>,
+}
+
+/// Measurements of one LOD strategy over all registered tiles
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct StrategyReport {
+    pub name: String,
+    pub total_triangles: u64,
+    pub mean_triangles: f64,
+    pub mean_vertices: f64,
+    /// Largest vertical deviation from the source pixels over all tiles, in meters
+    pub max_error: f32,
+    pub rms_error: f32,
+    pub mean_ms: f64,
+    pub max_ms: f64,
+}
+
+/// Compares the LOD strategies of MeshGenerator on caller-supplied tiles
+/// Every uniform grid level, adaptive RTIN and the budgeted Delaunay TIN are
+/// generated per tile and measured for size, accuracy and generation time.
+#[wasm_bindgen]
+pub struct LodBenchmark {
+    tiles: Vec<Vec<f32>>,
+    max_error: f32,
+    triangle_budget: u32,
+}
+
+#[wasm_bindgen]
+impl LodBenchmark {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> LodBenchmark {
+        LodBenchmark {
+            tiles: Vec::new(),
+            max_error: 2.0,
+            triangle_budget: 8192,
+        }
+    }
+
+    /// Error threshold in meters for RTIN and the TIN (as MeshGenerator::new)
+    #[wasm_bindgen]
+    pub fn set_max_error(&mut self, max_error: f32) {
+        self.max_error = max_error;
+    }
+
+    /// Triangle budget per tile for the TIN (0 = limited by max_error only)
+    #[wasm_bindgen]
+    pub fn set_triangle_budget(&mut self, max_triangles: u32) {
+        self.triangle_budget = max_triangles;
+    }
+
+    /// Register a 256x256 heightmap
+    #[wasm_bindgen]
+    pub fn add_tile(&mut self, elevations: &[f32]) -> Result<(), Error> {
+        if elevations.len() != 65536 {
+            return Err(tile_length_error(elevations.len()));
+        }
+        self.tiles.push(elevations.to_vec());
+        Ok(())
+    }
+
+    /// Register a GSI PNG tile; it is decoded once, outside the timed runs
+    #[wasm_bindgen]
+    pub fn add_png_tile(&mut self, data: &[u8]) -> Result<(), Error> {
+        self.tiles.push(ElevationParser::parse_png(data)?);
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn tile_count(&self) -> usize {
+        self.tiles.len()
+    }
+
+    #[wasm_bindgen]
+    pub fn clear_tiles(&mut self) {
+        self.tiles.clear();
+    }
+
+    /// Run every strategy over the registered tiles as a BenchmarkReport object; see run()
+    #[wasm_bindgen(js_name = run)]
+    pub fn run_js(&self) -> Result<JsValue, JsValue> {
+        let report = self.run()?;
+        serde_wasm_bindgen::to_value(&report).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+}
+
+impl Default for LodBenchmark {
+    fn default() -> Self {
+        LodBenchmark::new()
+    }
+}
+
+impl LodBenchmark {
+    /// Run every strategy over the registered tiles
+    /// Returns one entry per strategy ("grid_lod0", ..., "rtin", "tin"):
+    /// triangle and vertex counts, max and RMS error in meters,
+    /// and mean / max generation time in milliseconds
+    pub fn run(&self) -> Result<BenchmarkReport, Error> {
+        if self.tiles.is_empty() {
+            return Err(Error::InvalidArgument(
+                "No benchmark tiles registered".into(),
+            ));
+        }
+        let generator = &MeshGenerator::new(self.max_error);
+        let budget = self.triangle_budget;
+
+        type Strategy<'a> = Box<dyn Fn(&[f32]) -> Result<MeshData, Error> + 'a>;
+        let mut strategies: Vec<(String, Strategy)> = Vec::new();
+        for level in 0..generator.lod_level_count() {
+            strategies.push((
+                format!("grid_lod{}", level),
+                Box::new(move |tile| generator.generate(tile, TILE_SIZE, level as u8)),
+            ));
+        }
+        strategies.push((
+            "rtin".into(),
+            Box::new(move |tile| generator.generate_adaptive(tile, TILE_SIZE)),
+        ));
+        strategies.push((
+            "tin".into(),
+            Box::new(move |tile| generator.generate_tin(tile, TILE_SIZE, budget)),
+        ));
+
+        let mut reports = Vec::with_capacity(strategies.len());
+        for (name, generate) in &strategies {
+            let mut report = StrategyReport {
+                name: name.clone(),
+                ..Default::default()
+            };
+            let (mut vertices, mut squared_error, mut samples) = (0u64, 0.0f64, 0u64);
+            for tile in &self.tiles {
+                let start = now_ms();
+                let mesh = generate(tile)?;
+                let elapsed = now_ms() - start;
+                report.mean_ms += elapsed;
+                report.max_ms = report.max_ms.max(elapsed);
+
+                let indices = triangle_indices(&mesh);
+                report.total_triangles += (indices.len() / 3) as u64;
+                vertices += (mesh.vertices.len() / 3) as u64;
+                let heightmap = Heightfield::new(tile, 256, 256).unwrap();
+                let error = surface_error(&heightmap, &mesh.vertices, &indices, TILE_SIZE);
+                report.max_error = report.max_error.max(error.max);
+                squared_error += error.squared_sum;
+                samples += error.samples;
+            }
+            let tiles = self.tiles.len() as f64;
+            report.mean_ms /= tiles;
+            report.mean_triangles = report.total_triangles as f64 / tiles;
+            report.mean_vertices = vertices as f64 / tiles;
+            if samples > 0 {
+                report.rms_error = (squared_error / samples as f64).sqrt() as f32;
+            }
+            reports.push(report);
+        }
+
+        Ok(BenchmarkReport {
+            tiles: self.tiles.len() as u32,
+            strategies: reports,
+        })
+    }
+}
+
+fn triangle_indices(mesh: &MeshData) -> Vec<u32> {
+    if mesh.indices_u16.is_empty() {
+        mesh.indices.clone()
+    } else {
+        mesh.indices_u16.iter().map(|&i| i as u32).collect()
+    }
+}
@@ -0,0 +1,543 @@
+use std::f64::consts::PI;
+use std::fmt::Write;
+use std::ops::RangeInclusive;
+
+use image::ImageEncoder;
+use wasm_bindgen::prelude::*;
+
+use crate::error::Error;
+use crate::heightfield::Heightfield;
+use crate::validation::MAX_LATITUDE;
+
+const EARTH_RADIUS_M: f64 = 6_378_137.0;
+
+// Upper bounds keeping a sheet to a reasonable size whatever the settings
+const MAX_CONTOUR_LEVELS: f64 = 2000.0;
+const MAX_GRATICULE_LINES: f64 = 500.0;
+
+// Sheet layout in SVG user units (one unit per heightmap pixel)
+const MARGIN: f64 = 48.0;
+const FOOTER: f64 = 72.0;
+
+const CONTOUR_COLOR: &str = "#8b5a2b";
+const GRATICULE_COLOR: &str = "#1f4e9c";
+
+/// Printable 2D topographic sheet (hillshade + contours + graticule + scale bar)
+#[wasm_bindgen]
+pub struct MapSheet {
+    contour_interval: f32,
+    index_every: u32,
+    graticule_spacing: f64,
+    sun_azimuth: f32,
+    sun_altitude: f32,
+}
+
+impl Default for MapSheet {
+    fn default() -> Self {
+        MapSheet::new()
+    }
+}
+
+#[wasm_bindgen]
+impl MapSheet {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> MapSheet {
+        MapSheet {
+            contour_interval: 20.0,
+            index_every: 5,
+            graticule_spacing: 0.0,
+            sun_azimuth: 315.0,
+            sun_altitude: 45.0,
+        }
+    }
+
+    /// Contour interval in meters; every `index_every`-th line is drawn bold
+    #[wasm_bindgen]
+    pub fn set_contour_interval(&mut self, interval_m: f32, index_every: u32) {
+        self.contour_interval = interval_m;
+        self.index_every = index_every.max(1);
+    }
+
+    /// Graticule spacing in degrees (0 = choose automatically from the region size)
+    #[wasm_bindgen]
+    pub fn set_graticule_spacing(&mut self, spacing_deg: f64) {
+        self.graticule_spacing = spacing_deg;
+    }
+
+    /// Light direction for the hillshade (degrees, azimuth clockwise from north)
+    #[wasm_bindgen]
+    pub fn set_sun(&mut self, azimuth_deg: f32, altitude_deg: f32) {
+        self.sun_azimuth = azimuth_deg;
+        self.sun_altitude = altitude_deg;
+    }
+
+    /// Render a region as an SVG document
+    /// elevations: width x height heightmap in Web Mercator pixel order (north up)
+    /// west/south/east/north: region bounds in degrees
+    /// NaN pixels are treated as no data and get no contours; settings giving more than
+    /// 2000 contour levels or 500 graticule lines are rejected
+    #[wasm_bindgen]
+    #[allow(clippy::too_many_arguments)]
+    pub fn export_svg(
+        &self,
+        elevations: &[f32],
+        width: u32,
+        height: u32,
+        west: f64,
+        south: f64,
+        east: f64,
+        north: f64,
+    ) -> Result<String, Error> {
+        let heightmap = Heightfield::from_slice(elevations, width, height)?;
+        if width < 2 || height < 2 {
+            return Err(Error::InvalidArgument(
+                "Region must be at least 2x2 pixels".into(),
+            ));
+        }
+        let finite = [west, south, east, north].iter().all(|v| v.is_finite());
+        let in_range = south >= -MAX_LATITUDE && north <= MAX_LATITUDE;
+        if !(finite && in_range && east > west && north > south) {
+            return Err(Error::InvalidArgument("Invalid region bounds".into()));
+        }
+        if !(self.contour_interval.is_finite() && self.contour_interval > 0.0) {
+            return Err(Error::InvalidArgument(
+                "Contour interval must be positive".into(),
+            ));
+        }
+
+        let bounds = Bounds {
+            west,
+            south,
+            east,
+            north,
+            width: width as f64,
+            height: height as f64,
+        };
+        let levels = self.contour_levels(&heightmap)?;
+        let spacing = self.graticule_spacing_for(&bounds)?;
+        let ground_width_m = (east - west).to_radians()
+            * EARTH_RADIUS_M
+            * ((north + south) / 2.0).to_radians().cos();
+        let cell_size = (ground_width_m / width as f64) as f32;
+
+        let shade_png = self.hillshade_png(&heightmap, cell_size)?;
+
+        let total_width = bounds.width + 2.0 * MARGIN;
+        let total_height = bounds.height + MARGIN + FOOTER;
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" version="1.1" viewBox="0 0 {} {}" width="{}" height="{}">"#,
+            total_width, total_height, total_width, total_height
+        );
+        let _ = writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#);
+        let _ = writeln!(svg, r#"<g transform="translate({} {})">"#, MARGIN, MARGIN);
+        let _ = writeln!(
+            svg,
+            r#"<image x="0" y="0" width="{}" height="{}" preserveAspectRatio="none" href="data:image/png;base64,{}"/>"#,
+            width,
+            height,
+            base64_encode(&shade_png)
+        );
+
+        self.write_contours(&mut svg, &heightmap, levels);
+        self.write_graticule(&mut svg, &bounds, spacing);
+
+        let _ = writeln!(
+            svg,
+            r#"<rect x="0" y="0" width="{}" height="{}" fill="none" stroke="black" stroke-width="1"/>"#,
+            width, height
+        );
+        write_scale_bar(&mut svg, &bounds, ground_width_m);
+        let _ = writeln!(svg, "</g>");
+        let _ = writeln!(svg, "</svg>");
+
+        Ok(svg)
+    }
+}
+
+struct Bounds {
+    west: f64,
+    south: f64,
+    east: f64,
+    north: f64,
+    width: f64,
+    height: f64,
+}
+
+impl Bounds {
+    fn lon_to_x(&self, lon: f64) -> f64 {
+        (lon - self.west) / (self.east - self.west) * self.width
+    }
+
+    fn lat_to_y(&self, lat: f64) -> f64 {
+        let top = mercator_y(self.north);
+        let bottom = mercator_y(self.south);
+        (top - mercator_y(lat)) / (top - bottom) * self.height
+    }
+}
+
+impl MapSheet {
+    fn hillshade_png(&self, heightmap: &Heightfield, cell_size: f32) -> Result<Vec<u8>, Error> {
+        let zenith = (90.0 - self.sun_altitude).to_radians();
+        let azimuth = (360.0 - self.sun_azimuth + 90.0).to_radians();
+
+        let mut pixels = Vec::with_capacity(heightmap.width * heightmap.height);
+        for y in 0..heightmap.height {
+            for x in 0..heightmap.width {
+                let (dzdx, dzdy) = heightmap.gradient(x, y, cell_size);
+                let slope = (dzdx * dzdx + dzdy * dzdy).sqrt().atan();
+                let aspect = dzdy.atan2(-dzdx);
+                let shade = zenith.cos() * slope.cos()
+                    + zenith.sin() * slope.sin() * (azimuth - aspect).cos();
+                // Lift the shadows so contours and labels stay legible in print
+                let value = 80.0 + 175.0 * shade.clamp(0.0, 1.0);
+                pixels.push(value as u8);
+            }
+        }
+
+        let mut png = Vec::new();
+        image::codecs::png::PngEncoder::new(&mut png)
+            .write_image(
+                &pixels,
+                heightmap.width as u32,
+                heightmap.height as u32,
+                image::ColorType::L8,
+            )
+            .map_err(|e| Error::InvalidArgument(format!("Failed to encode hillshade: {}", e)))?;
+        Ok(png)
+    }
+
+    /// Contour steps (multiples of the interval) spanning the finite elevations
+    fn contour_levels(&self, heightmap: &Heightfield) -> Result<RangeInclusive<i64>, Error> {
+        let (min, max) = heightmap
+            .data
+            .iter()
+            .filter(|v| v.is_finite())
+            .fold((f32::MAX, f32::MIN), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+
+        let interval = self.contour_interval as f64;
+        let first = (min as f64 / interval).ceil();
+        let last = (max as f64 / interval).floor();
+        if last - first + 1.0 > MAX_CONTOUR_LEVELS {
+            return Err(Error::InvalidArgument(format!(
+                "Contour interval {} gives more than {} levels",
+                interval, MAX_CONTOUR_LEVELS
+            )));
+        }
+        Ok(first as i64..=last as i64)
+    }
+
+    /// Graticule spacing in degrees, the configured one or one chosen from the region size
+    fn graticule_spacing_for(&self, bounds: &Bounds) -> Result<f64, Error> {
+        let span = (bounds.east - bounds.west).max(bounds.north - bounds.south);
+        if self.graticule_spacing.is_nan() || self.graticule_spacing <= 0.0 {
+            return Ok(auto_graticule_spacing(span));
+        }
+        if span / self.graticule_spacing > MAX_GRATICULE_LINES {
+            return Err(Error::InvalidArgument(format!(
+                "Graticule spacing {} gives more than {} lines",
+                self.graticule_spacing, MAX_GRATICULE_LINES
+            )));
+        }
+        Ok(self.graticule_spacing)
+    }
+
+    fn write_contours(
+        &self,
+        svg: &mut String,
+        heightmap: &Heightfield,
+        levels: RangeInclusive<i64>,
+    ) {
+        let interval = self.contour_interval;
+        let _ = writeln!(
+            svg,
+            r#"<g fill="none" stroke="{}" stroke-linejoin="round">"#,
+            CONTOUR_COLOR
+        );
+        for step in levels {
+            let level = step as f32 * interval;
+            let path = contour_path(heightmap, level);
+            if path.is_empty() {
+                continue;
+            }
+            let is_index = step.rem_euclid(self.index_every as i64) == 0;
+            let _ = writeln!(
+                svg,
+                r#"<path stroke-width="{}" data-elevation="{}" d="{}"/>"#,
+                if is_index { 1.0 } else { 0.4 },
+                level,
+                path
+            );
+        }
+        let _ = writeln!(svg, "</g>");
+    }
+
+    fn write_graticule(&self, svg: &mut String, bounds: &Bounds, spacing: f64) {
+        let decimals = decimals_for(spacing);
+
+        let _ = writeln!(
+            svg,
+            r#"<g stroke="{}" stroke-width="0.5" stroke-dasharray="4 3" font-family="sans-serif" font-size="10" fill="{}">"#,
+            GRATICULE_COLOR, GRATICULE_COLOR
+        );
+
+        let mut lon = (bounds.west / spacing).ceil() * spacing;
+        while lon <= bounds.east {
+            let x = bounds.lon_to_x(lon);
+            let _ = writeln!(
+                svg,
+                r#"<line x1="{:.1}" y1="0" x2="{:.1}" y2="{}"/>"#,
+                x, x, bounds.height
+            );
+            let _ = writeln!(
+                svg,
+                r#"<text x="{:.1}" y="-6" stroke="none" text-anchor="middle">{:.*}°{}</text>"#,
+                x,
+                decimals,
+                lon.abs(),
+                if lon >= 0.0 { "E" } else { "W" }
+            );
+            lon += spacing;
+        }
+
+        let mut lat = (bounds.south / spacing).ceil() * spacing;
+        while lat <= bounds.north {
+            let y = bounds.lat_to_y(lat);
+            let _ = writeln!(
+                svg,
+                r#"<line x1="0" y1="{:.1}" x2="{}" y2="{:.1}"/>"#,
+                y, bounds.width, y
+            );
+            let _ = writeln!(
+                svg,
+                r#"<text x="-4" y="{:.1}" stroke="none" text-anchor="end" dominant-baseline="middle">{:.*}°{}</text>"#,
+                y,
+                decimals,
+                lat.abs(),
+                if lat >= 0.0 { "N" } else { "S" }
+            );
+            lat += spacing;
+        }
+        let _ = writeln!(svg, "</g>");
+    }
+}
+
+/// Marching-squares contour segments for one level as SVG path data
+/// Cells are formed between pixel centers, so coordinates are offset by half a pixel
+fn contour_path(heightmap: &Heightfield, level: f32) -> String {
+    let mut path = String::new();
+
+    for y in 0..heightmap.height - 1 {
+        for x in 0..heightmap.width - 1 {
+            let (xi, yi) = (x as isize, y as isize);
+            let tl = heightmap.get(xi, yi);
+            let tr = heightmap.get(xi + 1, yi);
+            let br = heightmap.get(xi + 1, yi + 1);
+            let bl = heightmap.get(xi, yi + 1);
+            // No-data corners (NaN) leave the cell without contours
+            if ![tl, tr, br, bl].iter().all(|v| v.is_finite()) {
+                continue;
+            }
+
+            let case = ((tl >= level) as u8) << 3
+                | ((tr >= level) as u8) << 2
+                | ((br >= level) as u8) << 1
+                | (bl >= level) as u8;
+            if case == 0 || case == 15 {
+                continue;
+            }
+
+            let (fx, fy) = (x as f32 + 0.5, y as f32 + 0.5);
+            let lerp = |a: f32, b: f32| (level - a) / (b - a);
+            let top = (fx + lerp(tl, tr), fy);
+            let right = (fx + 1.0, fy + lerp(tr, br));
+            let bottom = (fx + lerp(bl, br), fy + 1.0);
+            let left = (fx, fy + lerp(tl, bl));
+            let center_above = (tl + tr + br + bl) / 4.0 >= level;
+
+            let mut segment = |a: (f32, f32), b: (f32, f32)| {
+                let _ = write!(path, "M{:.1} {:.1}L{:.1} {:.1}", a.0, a.1, b.0, b.1);
+            };
+            match case {
+                1 | 14 => segment(left, bottom),
+                2 | 13 => segment(bottom, right),
+                3 | 12 => segment(left, right),
+                4 | 11 => segment(top, right),
+                6 | 9 => segment(top, bottom),
+                7 | 8 => segment(left, top),
+                5 => {
+                    if center_above {
+                        segment(left, top);
+                        segment(bottom, right);
+                    } else {
+                        segment(top, right);
+                        segment(left, bottom);
+                    }
+                }
+                10 => {
+                    if center_above {
+                        segment(top, right);
+                        segment(left, bottom);
+                    } else {
+                        segment(left, top);
+                        segment(bottom, right);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    path
+}
+
+fn write_scale_bar(svg: &mut String, bounds: &Bounds, ground_width_m: f64) {
+    let length_m = nice_number(ground_width_m / 4.0);
+    let bar_width = length_m / ground_width_m * bounds.width;
+    let y = bounds.height + 24.0;
+    let segments = 4;
+
+    let _ = writeln!(
+        svg,
+        r#"<g font-family="sans-serif" font-size="10" stroke="black" stroke-width="0.75">"#
+    );
+    for i in 0..segments {
+        let segment_width = bar_width / segments as f64;
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="6" fill="{}"/>"#,
+            i as f64 * segment_width,
+            y,
+            segment_width,
+            if i % 2 == 0 { "black" } else { "white" }
+        );
+    }
+    let label = if length_m >= 1000.0 {
+        format!("{} km", length_m / 1000.0)
+    } else {
+        format!("{} m", length_m)
+    };
+    let _ = writeln!(
+        svg,
+        r#"<text x="0" y="{:.1}" stroke="none" text-anchor="middle">0</text>"#,
+        y + 20.0
+    );
+    let _ = writeln!(
+        svg,
+        r#"<text x="{:.1}" y="{:.1}" stroke="none" text-anchor="middle">{}</text>"#,
+        bar_width,
+        y + 20.0,
+        label
+    );
+    let _ = writeln!(svg, "</g>");
+}
+
+fn mercator_y(lat: f64) -> f64 {
+    (PI / 4.0 + lat.to_radians() / 2.0).tan().ln()
+}
+
+/// Largest 1/2/5 x 10^n value not exceeding `value`
+fn nice_number(value: f64) -> f64 {
+    let exponent = value.log10().floor();
+    let base = 10f64.powf(exponent);
+    let fraction = value / base;
+    let nice = if fraction >= 5.0 {
+        5.0
+    } else if fraction >= 2.0 {
+        2.0
+    } else {
+        1.0
+    };
+    nice * base
+}
+
+/// Spacing giving roughly 3-8 graticule lines across the region
+fn auto_graticule_spacing(span_deg: f64) -> f64 {
+    const STEPS: [f64; 12] = [
+        0.005, 0.01, 0.02, 0.05, 0.1, 0.25, 0.5, 1.0, 2.0, 5.0, 10.0, 15.0,
+    ];
+    STEPS
+        .iter()
+        .copied()
+        .find(|&step| span_deg / step <= 8.0)
+        .unwrap_or(30.0)
+}
+
+fn decimals_for(spacing: f64) -> usize {
+    let mut decimals = 0;
+    while decimals < 4 && (spacing * 10f64.powi(decimals as i32)).fract().abs() > 1e-6 {
+        decimals += 1;
+    }
+    decimals
+}
+
+fn base64_encode(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        out.push(ALPHABET[(n >> 18) as usize & 63] as char);
+        out.push(ALPHABET[(n >> 12) as usize & 63] as char);
+        out.push(if chunk.len() > 1 {
+            ALPHABET[(n >> 6) as usize & 63] as char
+        } else {
+            '='
+        });
+        out.push(if chunk.len() > 2 {
+            ALPHABET[n as usize & 63] as char
+        } else {
+            '='
+        });
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ramp(width: usize, height: usize) -> Vec<f32> {
+        (0..width * height)
+            .map(|i| (i % width + i / width) as f32 * 10.0)
+            .collect()
+    }
+
+    #[test]
+    fn sheets_skip_no_data_and_bound_their_size() {
+        let mut elevations = ramp(8, 8);
+        elevations[9] = f32::NAN;
+        let sheet = MapSheet::new();
+        let svg = sheet
+            .export_svg(&elevations, 8, 8, 138.7, 35.3, 138.8, 35.4)
+            .unwrap();
+        assert!(svg.contains("data-elevation"));
+        assert!(!svg.contains("NaN"));
+
+        for (west, north) in [(f64::NAN, 35.4), (138.7, f64::INFINITY), (138.7, 89.0)] {
+            assert!(matches!(
+                sheet.export_svg(&elevations, 8, 8, west, 35.3, 138.8, north),
+                Err(Error::InvalidArgument(_))
+            ));
+        }
+
+        let mut dense = MapSheet::new();
+        dense.set_contour_interval(0.01, 5);
+        assert!(matches!(
+            dense.export_svg(&elevations, 8, 8, 138.7, 35.3, 138.8, 35.4),
+            Err(Error::InvalidArgument(_))
+        ));
+
+        let mut dense = MapSheet::new();
+        dense.set_graticule_spacing(1e-6);
+        assert!(matches!(
+            dense.export_svg(&elevations, 8, 8, 138.7, 35.3, 138.8, 35.4),
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+}
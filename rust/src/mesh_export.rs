@@ -0,0 +1,1157 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use glam::{DVec3, Vec3};
+
+use crate::globe::{geodetic_to_ecef, WGS84_A, WGS84_B};
+use crate::mesh_generator::MeshBuffers;
+
+const GLB_MAGIC: u32 = 0x4654_6c67;
+const GLB_VERSION: u32 = 2;
+const CHUNK_JSON: u32 = 0x4e4f_534a;
+const CHUNK_BIN: u32 = 0x004e_4942;
+
+/// glTF bufferView targets and accessor component types
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+const FLOAT: u32 = 5126;
+const UNSIGNED_SHORT: u32 = 5123;
+const UNSIGNED_INT: u32 = 5125;
+
+/// One accessor over its own tightly packed buffer view
+struct View {
+    offset: usize,
+    length: usize,
+    target: u32,
+    component_type: u32,
+    count: usize,
+    kind: &'static str,
+}
+
+/// Binary glTF 2.0 of a mesh: one node, one triangle primitive with
+/// POSITION, NORMAL, TEXCOORD_0 (and TANGENT when present) and its index buffer.
+/// Axes stay as generated (+y up, right-handed), which is glTF's convention.
+pub(crate) fn encode_glb(mesh: &MeshBuffers) -> Vec<u8> {
+    let vertex_count = mesh.vertices.len() / 3;
+    let mut bin: Vec<u8> = Vec::new();
+    let mut views: Vec<View> = Vec::new();
+    let mut add_floats = |bin: &mut Vec<u8>, values: &[f32], kind: &'static str, width: usize| {
+        let offset = bin.len();
+        bin.extend(values.iter().flat_map(|v| v.to_le_bytes()));
+        views.push(View {
+            offset,
+            length: bin.len() - offset,
+            target: ARRAY_BUFFER,
+            component_type: FLOAT,
+            count: values.len() / width,
+            kind,
+        });
+        views.len() - 1
+    };
+
+    let position = add_floats(&mut bin, &mesh.vertices, "VEC3", 3);
+    let mut attributes = format!("\"POSITION\":{}", position);
+    if mesh.normals.len() == vertex_count * 3 {
+        let normal = add_floats(&mut bin, &mesh.normals, "VEC3", 3);
+        let _ = write!(attributes, ",\"NORMAL\":{}", normal);
+    }
+    if mesh.uvs.len() == vertex_count * 2 {
+        let uv = add_floats(&mut bin, &mesh.uvs, "VEC2", 2);
+        let _ = write!(attributes, ",\"TEXCOORD_0\":{}", uv);
+    }
+    if mesh.tangents.len() == vertex_count * 4 {
+        let tangent = add_floats(&mut bin, &mesh.tangents, "VEC4", 4);
+        let _ = write!(attributes, ",\"TANGENT\":{}", tangent);
+    }
+
+    // Flat-shaded meshes are non-indexed
+    let mut indices = String::new();
+    if !mesh.indices.is_empty() || !mesh.indices_u16.is_empty() {
+        let offset = bin.len();
+        let (component_type, count) = if mesh.indices_u16.is_empty() {
+            bin.extend(mesh.indices.iter().flat_map(|i| i.to_le_bytes()));
+            (UNSIGNED_INT, mesh.indices.len())
+        } else {
+            bin.extend(mesh.indices_u16.iter().flat_map(|i| i.to_le_bytes()));
+            (UNSIGNED_SHORT, mesh.indices_u16.len())
+        };
+        views.push(View {
+            offset,
+            length: bin.len() - offset,
+            target: ELEMENT_ARRAY_BUFFER,
+            component_type,
+            count,
+            kind: "SCALAR",
+        });
+        let _ = write!(indices, ",\"indices\":{}", views.len() - 1);
+        bin.resize(bin.len().next_multiple_of(4), 0);
+    }
+
+    let mut json = String::new();
+    let _ = write!(
+        json,
+        "{{\"asset\":{{\"version\":\"2.0\",\"generator\":\"peak-vista\"}},\
+         \"scene\":0,\"scenes\":[{{\"nodes\":[0]}}],\"nodes\":[{{\"mesh\":0}}],\
+         \"meshes\":[{{\"primitives\":[{{\"attributes\":{{{}}}{},\"mode\":4}}]}}],\
+         \"buffers\":[{{\"byteLength\":{}}}],\"bufferViews\":[",
+        attributes,
+        indices,
+        bin.len()
+    );
+    for (i, view) in views.iter().enumerate() {
+        let separator = if i == 0 { "" } else { "," };
+        let _ = write!(
+            json,
+            "{}{{\"buffer\":0,\"byteOffset\":{},\"byteLength\":{},\"target\":{}}}",
+            separator, view.offset, view.length, view.target
+        );
+    }
+    json.push_str("],\"accessors\":[");
+    for (i, view) in views.iter().enumerate() {
+        let separator = if i == 0 { "" } else { "," };
+        let _ = write!(
+            json,
+            "{}{{\"bufferView\":{},\"componentType\":{},\"count\":{},\"type\":\"{}\"",
+            separator, i, view.component_type, view.count, view.kind
+        );
+        // POSITION requires its bounds
+        if i == position {
+            let _ = write!(
+                json,
+                ",\"min\":{},\"max\":{}",
+                json_vec3(mesh.bounds_min),
+                json_vec3(mesh.bounds_max)
+            );
+        }
+        json.push('}');
+    }
+    json.push_str("]}");
+    while !json.len().is_multiple_of(4) {
+        json.push(' ');
+    }
+
+    let total = 12 + 8 + json.len() + 8 + bin.len();
+    let mut glb = Vec::with_capacity(total);
+    for word in [GLB_MAGIC, GLB_VERSION, total as u32] {
+        glb.extend_from_slice(&word.to_le_bytes());
+    }
+    glb.extend_from_slice(&(json.len() as u32).to_le_bytes());
+    glb.extend_from_slice(&CHUNK_JSON.to_le_bytes());
+    glb.extend_from_slice(json.as_bytes());
+    glb.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+    glb.extend_from_slice(&CHUNK_BIN.to_le_bytes());
+    glb.extend_from_slice(&bin);
+    glb
+}
+
+/// JSON array of three numbers; non-finite values (NaN no-data) become 0
+fn json_vec3(v: [f32; 3]) -> String {
+    let mut json = String::new();
+    write_json_floats(&mut json, &v);
+    json
+}
+
+/// Append a JSON array of numbers; non-finite values become 0
+fn write_json_floats(json: &mut String, values: &[f32]) {
+    json.push('[');
+    for (i, &value) in values.iter().enumerate() {
+        let separator = if i == 0 { "" } else { "," };
+        let value = if value.is_finite() { value } else { 0.0 };
+        let _ = write!(json, "{}{}", separator, value);
+    }
+    json.push(']');
+}
+
+/// Binary STL of the mesh closed into a solid: the surface, vertical walls from
+/// its open edges down to a flat base `base_thickness` below the lowest vertex,
+/// and the base itself. Vertices are welded by position first, so skirts and
+/// flat-shaded meshes close too. The base is ear-clipped from each loop of the
+/// outline (holes bridged into the loop around them), so masked, clipped and
+/// multi-island meshes close as well; outlines too degenerate to clip get the
+/// surface itself flattened as their base.
+/// Output is z-up (x east, y north), as slicers expect; units are unchanged.
+pub(crate) fn encode_stl(mesh: &MeshBuffers, base_thickness: f32) -> Vec<u8> {
+    let mut positions: Vec<Vec3> = Vec::new();
+    let mut welded: HashMap<[u32; 3], u32> = HashMap::new();
+    let remap: Vec<u32> = mesh
+        .vertices
+        .chunks_exact(3)
+        .map(|p| {
+            let key = [p[0].to_bits(), p[1].to_bits(), p[2].to_bits()];
+            *welded.entry(key).or_insert_with(|| {
+                positions.push(Vec3::from_slice(p));
+                positions.len() as u32 - 1
+            })
+        })
+        .collect();
+
+    let corners: Vec<u32> = if !mesh.indices_u16.is_empty() {
+        mesh.indices_u16.iter().map(|&i| i as u32).collect()
+    } else if !mesh.indices.is_empty() {
+        mesh.indices.clone()
+    } else {
+        (0..remap.len() as u32).collect()
+    };
+    let surface: Vec<[u32; 3]> = corners
+        .chunks_exact(3)
+        .map(|t| {
+            [
+                remap[t[0] as usize],
+                remap[t[1] as usize],
+                remap[t[2] as usize],
+            ]
+        })
+        .filter(|&[a, b, c]| a != b && b != c && a != c)
+        .collect();
+
+    // An edge no triangle runs the other way along is on the outline
+    let edges: HashSet<(u32, u32)> = surface
+        .iter()
+        .flat_map(|&[a, b, c]| [(a, b), (b, c), (c, a)])
+        .collect();
+    // Sorted so the output does not depend on the hash order
+    let mut outline: Vec<(u32, u32)> = edges
+        .iter()
+        .filter(|&&(a, b)| !edges.contains(&(b, a)))
+        .copied()
+        .collect();
+    outline.sort_unstable();
+
+    let base = positions.iter().fold(f32::INFINITY, |low, p| low.min(p.y)) - base_thickness;
+    let drop = |p: Vec3| Vec3::new(p.x, base, p.z);
+
+    let mut triangles: Vec<[Vec3; 3]> = surface
+        .iter()
+        .map(|t| t.map(|i| positions[i as usize]))
+        .collect();
+    for &(a, b) in &outline {
+        let (a, b) = (positions[a as usize], positions[b as usize]);
+        triangles.push([b, a, drop(a)]);
+        triangles.push([b, drop(a), drop(b)]);
+    }
+    // Failing that, the surface flattened onto the base plane fills it exactly
+    let base_faces = base_triangles(&positions, &outline)
+        .unwrap_or_else(|| surface.iter().map(|&[a, b, c]| [a, c, b]).collect());
+    for triangle in base_faces {
+        triangles.push(triangle.map(|i| drop(positions[i as usize])));
+    }
+
+    let z_up = |v: Vec3| [v.x, -v.z, v.y];
+    let mut stl = Vec::with_capacity(84 + triangles.len() * 50);
+    let mut header = [0u8; 80];
+    header[..18].copy_from_slice(b"peak-vista terrain");
+    stl.extend_from_slice(&header);
+    stl.extend_from_slice(&(triangles.len() as u32).to_le_bytes());
+    for [a, b, c] in triangles {
+        let normal = (b - a).cross(c - a).normalize_or_zero();
+        for v in [normal, a, b, c] {
+            stl.extend(z_up(v).iter().flat_map(|x| x.to_le_bytes()));
+        }
+        stl.extend_from_slice(&0u16.to_le_bytes());
+    }
+    stl
+}
+
+/// Triangles filling the outline in the xz plane, wound to face down
+/// Outline edges are chained into loops; loops wound like the largest one are
+/// outer boundaries and the others holes, each bridged into the smallest outer
+/// loop around it before ear clipping. None when a loop is too degenerate to
+/// clip (regions touching along a diagonal)
+fn base_triangles(positions: &[Vec3], outline: &[(u32, u32)]) -> Option<Vec<[u32; 3]>> {
+    let xz = |i: u32| {
+        let p = positions[i as usize];
+        (p.x as f64, p.z as f64)
+    };
+
+    // Walk each edge backwards, against the surface winding, so the clipped
+    // triangles face down
+    let mut previous: HashMap<u32, Vec<u32>> = HashMap::new();
+    for &(a, b) in outline {
+        previous.entry(b).or_default().push(a);
+    }
+    let mut loops: Vec<Vec<u32>> = Vec::new();
+    for &(_, start) in outline {
+        let mut ring = Vec::new();
+        let mut current = start;
+        while let Some(next) = previous.get_mut(&current).and_then(Vec::pop) {
+            // Meeting a vertex again closes a loop (regions touching at a
+            // corner); cut it off so every loop is simple
+            if let Some(j) = ring.iter().position(|&v| v == current) {
+                loops.push(ring.split_off(j));
+            }
+            ring.push(current);
+            current = next;
+        }
+        loops.push(ring);
+    }
+    loops.retain(|ring| ring.len() >= 3);
+
+    let area = |ring: &[u32]| {
+        let twice: f64 = (0..ring.len())
+            .map(|k| {
+                let (p, q) = (xz(ring[k]), xz(ring[(k + 1) % ring.len()]));
+                p.0 * q.1 - q.0 * p.1
+            })
+            .sum();
+        twice / 2.0
+    };
+    let areas: Vec<f64> = loops.iter().map(|ring| area(ring)).collect();
+    let Some(largest) = (0..loops.len()).max_by(|&i, &j| areas[i].abs().total_cmp(&areas[j].abs()))
+    else {
+        return Some(Vec::new());
+    };
+    let sign = areas[largest].signum();
+    let (outer, holes): (Vec<usize>, Vec<usize>) =
+        (0..loops.len()).partition(|&i| areas[i] * sign > 0.0);
+
+    let mut polygons: Vec<Vec<u32>> = outer.iter().map(|&i| loops[i].clone()).collect();
+    let mut holes_of: Vec<Vec<usize>> = vec![Vec::new(); outer.len()];
+    for &hole in &holes {
+        let point = xz(loops[hole][0]);
+        let around = (0..outer.len())
+            .filter(|&k| contains(&polygons[k], point, &xz))
+            .min_by(|&k, &l| areas[outer[k]].abs().total_cmp(&areas[outer[l]].abs()));
+        holes_of[around?].push(hole);
+    }
+
+    let mut triangles = Vec::new();
+    for (polygon, mut holes) in polygons.iter_mut().zip(holes_of) {
+        // Rightmost holes first, so earlier bridges never cross later holes
+        let rightmost = |ring: &[u32]| {
+            (0..ring.len())
+                .max_by(|&i, &j| xz(ring[i]).0.total_cmp(&xz(ring[j]).0))
+                .unwrap()
+        };
+        holes.sort_by(|&i, &j| {
+            let (a, b) = (&loops[i], &loops[j]);
+            xz(b[rightmost(b)]).0.total_cmp(&xz(a[rightmost(a)]).0)
+        });
+        for (k, &hole) in holes.iter().enumerate() {
+            let ring = &loops[hole];
+            let m = rightmost(ring);
+            let from = xz(ring[m]);
+            // A bridge must stay inside the filled region: crossing no edge,
+            // grazing no other vertex, and with its midpoint inside the polygon
+            // and outside the holes still to bridge
+            let blocked = |to: (f64, f64)| {
+                let rings =
+                    || std::iter::once(&*polygon).chain(holes[k..].iter().map(|&h| &loops[h]));
+                let crossing = rings().flat_map(|ring| ring_edges(ring)).any(|(a, b)| {
+                    let (a, b) = (xz(a), xz(b));
+                    crosses(from, to, a, b)
+                });
+                let grazing = rings().flatten().any(|&v| {
+                    let p = xz(v);
+                    p != from && p != to && cross(from, to, p) == 0.0 && {
+                        let t = (p.0 - from.0) * (to.0 - from.0) + (p.1 - from.1) * (to.1 - from.1);
+                        let length = (to.0 - from.0).powi(2) + (to.1 - from.1).powi(2);
+                        t > 0.0 && t < length
+                    }
+                });
+                let middle = ((from.0 + to.0) / 2.0, (from.1 + to.1) / 2.0);
+                let outside = !contains(polygon, middle, &xz)
+                    || holes[k..].iter().any(|&h| contains(&loops[h], middle, &xz));
+                crossing || grazing || outside
+            };
+            let distance = |i: usize| {
+                let to = xz(polygon[i]);
+                (to.0 - from.0).powi(2) + (to.1 - from.1).powi(2)
+            };
+            let mut candidates: Vec<usize> = (0..polygon.len()).collect();
+            candidates.sort_by(|&i, &j| distance(i).total_cmp(&distance(j)));
+            let p = candidates
+                .iter()
+                .copied()
+                .find(|&i| !blocked(xz(polygon[i])))
+                .unwrap_or(candidates[0]);
+
+            // ... P, M, around the hole back to M, P, ...
+            let mut bridged = Vec::with_capacity(polygon.len() + ring.len() + 2);
+            bridged.extend_from_slice(&polygon[..=p]);
+            bridged.extend_from_slice(&ring[m..]);
+            bridged.extend_from_slice(&ring[..=m]);
+            bridged.extend_from_slice(&polygon[p..]);
+            *polygon = bridged;
+        }
+        if !ear_clip(polygon, sign, &xz, &mut triangles) {
+            return None;
+        }
+    }
+    Some(triangles)
+}
+
+/// Consecutive vertex pairs of a closed ring
+fn ring_edges(ring: &[u32]) -> impl Iterator<Item = (u32, u32)> + '_ {
+    (0..ring.len()).map(|k| (ring[k], ring[(k + 1) % ring.len()]))
+}
+
+/// Twice the signed area of triangle abc
+fn cross(a: (f64, f64), b: (f64, f64), c: (f64, f64)) -> f64 {
+    (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0)
+}
+
+/// Whether segments pq and ab cross at a point inside both
+fn crosses(p: (f64, f64), q: (f64, f64), a: (f64, f64), b: (f64, f64)) -> bool {
+    cross(p, q, a) * cross(p, q, b) < 0.0 && cross(a, b, p) * cross(a, b, q) < 0.0
+}
+
+/// Even-odd point in polygon test
+fn contains(ring: &[u32], point: (f64, f64), xz: &impl Fn(u32) -> (f64, f64)) -> bool {
+    let mut inside = false;
+    for k in 0..ring.len() {
+        let (a, b) = (xz(ring[k]), xz(ring[(k + 1) % ring.len()]));
+        if (a.1 > point.1) != (b.1 > point.1)
+            && point.0 < a.0 + (point.1 - a.1) / (b.1 - a.1) * (b.0 - a.0)
+        {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// Ear clipping of a simple polygon wound like `sign` (its area's sign)
+/// Collinear vertices are kept, so every polygon edge stays a triangle edge
+/// and the base meets the walls without T-junctions. False when no ear is
+/// left but the remaining polygon still has area
+fn ear_clip(
+    polygon: &[u32],
+    sign: f64,
+    xz: &impl Fn(u32) -> (f64, f64),
+    triangles: &mut Vec<[u32; 3]>,
+) -> bool {
+    // A hole touching its outer loop is bridged with no length, repeating
+    // the touching vertex
+    let mut ring = polygon.to_vec();
+    ring.dedup();
+    while ring.len() > 1 && ring[0] == ring[ring.len() - 1] {
+        ring.pop();
+    }
+    let (mut i, mut misses) = (0, 0);
+    while ring.len() > 3 {
+        let n = ring.len();
+        let (a, b, c) = (ring[(i + n - 1) % n], ring[i], ring[(i + 1) % n]);
+        let (pa, pb, pc) = (xz(a), xz(b), xz(c));
+        let convex = cross(pa, pb, pc) * sign > 0.0;
+        // Vertices on the triangle's edges block it too, or the clip would
+        // pinch the polygon there
+        let ear = convex
+            && !ring.iter().any(|&v| {
+                let p = xz(v);
+                p != pa
+                    && p != pb
+                    && p != pc
+                    && cross(pa, pb, p) * sign >= 0.0
+                    && cross(pb, pc, p) * sign >= 0.0
+                    && cross(pc, pa, p) * sign >= 0.0
+            });
+        // A full lap without an ear: fine when only a line is left, which
+        // clips into zero-area triangles
+        if !ear && misses >= n {
+            let (p0, p1) = (xz(ring[0]), xz(ring[1]));
+            if !ring.iter().all(|&v| cross(p0, p1, xz(v)) == 0.0) {
+                return false;
+            }
+        }
+        if ear || misses >= n {
+            triangles.push([a, b, c]);
+            ring.remove(i);
+            misses = 0;
+            // The previous vertex may have become an ear
+            i = (i + n - 2) % (n - 1);
+        } else {
+            i = (i + 1) % n;
+            misses += 1;
+        }
+    }
+    if ring.len() == 3 {
+        triangles.push([ring[0], ring[1], ring[2]]);
+    }
+    true
+}
+
+/// Binary little-endian PLY: vertex x, y, z (+ nx, ny, nz, + red, green, blue
+/// when present) and triangle faces. Axes stay as generated (+y up).
+pub(crate) fn encode_ply(mesh: &MeshBuffers) -> Vec<u8> {
+    let vertex_count = mesh.vertices.len() / 3;
+    let normals = mesh.normals.len() == vertex_count * 3;
+    let colors = mesh.colors.len() == vertex_count * 3;
+    let corners: Vec<u32> = if !mesh.indices_u16.is_empty() {
+        mesh.indices_u16.iter().map(|&i| i as u32).collect()
+    } else if !mesh.indices.is_empty() {
+        mesh.indices.clone()
+    } else {
+        // Non-indexed (flat-shaded) triangles use consecutive vertices
+        (0..vertex_count as u32).collect()
+    };
+
+    let mut header =
+        String::from("ply\nformat binary_little_endian 1.0\ncomment peak-vista terrain\n");
+    let _ = writeln!(header, "element vertex {}", vertex_count);
+    header.push_str("property float x\nproperty float y\nproperty float z\n");
+    if normals {
+        header.push_str("property float nx\nproperty float ny\nproperty float nz\n");
+    }
+    if colors {
+        header.push_str("property uchar red\nproperty uchar green\nproperty uchar blue\n");
+    }
+    let _ = writeln!(header, "element face {}", corners.len() / 3);
+    header.push_str("property list uchar uint vertex_indices\nend_header\n");
+
+    let vertex_bytes = 12 + if normals { 12 } else { 0 } + if colors { 3 } else { 0 };
+    let mut ply =
+        Vec::with_capacity(header.len() + vertex_count * vertex_bytes + corners.len() / 3 * 13);
+    ply.extend_from_slice(header.as_bytes());
+    for v in 0..vertex_count {
+        let floats = mesh.vertices[v * 3..v * 3 + 3].iter().chain(
+            normals
+                .then(|| &mesh.normals[v * 3..v * 3 + 3])
+                .into_iter()
+                .flatten(),
+        );
+        ply.extend(floats.flat_map(|x| x.to_le_bytes()));
+        if colors {
+            ply.extend_from_slice(&mesh.colors[v * 3..v * 3 + 3]);
+        }
+    }
+    for triangle in corners.chunks_exact(3) {
+        ply.push(3);
+        ply.extend(triangle.iter().flat_map(|i| i.to_le_bytes()));
+    }
+    ply
+}
+
+/// quantized-mesh stores u, v and height as 0..=32767
+const QUANTIZED_MAX: f64 = 32767.0;
+const OCT_VERTEX_NORMALS: u8 = 1;
+/// Mesh uvs put pixel 255 at 255/256; Cesium tiles must reach their edges to meet
+const EDGE_TO_EDGE: f64 = 256.0 / 255.0;
+
+/// Cesium quantized-mesh-1.0 terrain tile of a mesh generated from GSI tile
+/// (x, y, zoom): header, vertices, high-water-mark indices, the west, south,
+/// east and north edge vertex lists and the oct-encoded normals extension.
+/// Positions come from the mesh uvs (its place in the tile) and elevations;
+/// the 256 pixel samples are stretched to span the tile edge to edge.
+pub(crate) fn encode_quantized_mesh(mesh: &MeshBuffers, x: u32, y: u32, zoom: u8) -> Vec<u8> {
+    let tiles = (1u64 << zoom) as f64;
+    let west = x as f64 / tiles * 360.0 - 180.0;
+    let east = (x + 1) as f64 / tiles * 360.0 - 180.0;
+    // Web Mercator row fraction (0 = north edge of the world) to latitude
+    let latitude = |row: f64| {
+        (std::f64::consts::PI * (1.0 - 2.0 * row / tiles))
+            .sinh()
+            .atan()
+            .to_degrees()
+    };
+    let (north, south) = (latitude(y as f64), latitude(y as f64 + 1.0));
+
+    let vertex_count = mesh.vertices.len() / 3;
+    let corners: Vec<u32> = if !mesh.indices_u16.is_empty() {
+        mesh.indices_u16.iter().map(|&i| i as u32).collect()
+    } else if !mesh.indices.is_empty() {
+        mesh.indices.clone()
+    } else {
+        (0..vertex_count as u32).collect()
+    };
+    // High-water-mark coding needs vertices in order of first use
+    let mut new_index = vec![u32::MAX; vertex_count];
+    let mut order: Vec<usize> = Vec::new();
+    let indices: Vec<u32> = corners
+        .iter()
+        .map(|&i| {
+            let slot = &mut new_index[i as usize];
+            if *slot == u32::MAX {
+                *slot = order.len() as u32;
+                order.push(i as usize);
+            }
+            *slot
+        })
+        .collect();
+
+    let elevation = |v: usize| mesh.vertices[v * 3 + 1] as f64;
+    let (min_height, max_height) = order
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), &v| {
+            (low.min(elevation(v)), high.max(elevation(v)))
+        });
+    let height_range = max_height - min_height;
+
+    let mut quantized: Vec<[u16; 3]> = Vec::with_capacity(order.len());
+    let mut positions: Vec<DVec3> = Vec::with_capacity(order.len());
+    for &v in &order {
+        let u = (mesh.uvs[v * 2] as f64 * EDGE_TO_EDGE).clamp(0.0, 1.0);
+        let row = (mesh.uvs[v * 2 + 1] as f64 * EDGE_TO_EDGE).clamp(0.0, 1.0);
+        let (lon, lat) = (west + u * (east - west), latitude(y as f64 + row));
+        let height = if height_range > 0.0 {
+            (elevation(v) - min_height) / height_range
+        } else {
+            0.0
+        };
+        quantized.push([
+            (u * QUANTIZED_MAX).round() as u16,
+            ((lat - south) / (north - south) * QUANTIZED_MAX)
+                .round()
+                .clamp(0.0, QUANTIZED_MAX) as u16,
+            (height * QUANTIZED_MAX).round() as u16,
+        ]);
+        positions.push(geodetic_to_ecef(lon, lat, elevation(v)));
+    }
+
+    let (mut low, mut high) = (DVec3::INFINITY, DVec3::NEG_INFINITY);
+    for &p in &positions {
+        low = low.min(p);
+        high = high.max(p);
+    }
+    let center = if positions.is_empty() {
+        DVec3::ZERO
+    } else {
+        (low + high) / 2.0
+    };
+    let radius = positions
+        .iter()
+        .map(|p| p.distance(center))
+        .fold(0.0, f64::max);
+    let occlusion = horizon_occlusion_point(&positions, center);
+
+    let mut output = Vec::new();
+    let put_f64 = |output: &mut Vec<u8>, values: &[f64]| {
+        for value in values {
+            output.extend_from_slice(&value.to_le_bytes());
+        }
+    };
+    put_f64(&mut output, &center.to_array());
+    output.extend_from_slice(&(min_height.min(max_height) as f32).to_le_bytes());
+    output.extend_from_slice(&(max_height.max(min_height) as f32).to_le_bytes());
+    put_f64(&mut output, &center.to_array());
+    put_f64(&mut output, &[radius]);
+    put_f64(&mut output, &occlusion.to_array());
+
+    output.extend_from_slice(&(order.len() as u32).to_le_bytes());
+    for component in 0..3 {
+        let mut previous = 0i32;
+        for vertex in &quantized {
+            let value = vertex[component] as i32;
+            let delta = value - previous;
+            output.extend_from_slice(&(((delta << 1) ^ (delta >> 31)) as u16).to_le_bytes());
+            previous = value;
+        }
+    }
+
+    let wide = order.len() > 65536;
+    let put_index = |output: &mut Vec<u8>, index: u32| {
+        if wide {
+            output.extend_from_slice(&index.to_le_bytes());
+        } else {
+            output.extend_from_slice(&(index as u16).to_le_bytes());
+        }
+    };
+    if wide {
+        output.resize(output.len().next_multiple_of(4), 0);
+    }
+    output.extend_from_slice(&((indices.len() / 3) as u32).to_le_bytes());
+    let mut highest = 0;
+    for &index in &indices[..indices.len() / 3 * 3] {
+        put_index(&mut output, highest - index);
+        if index == highest {
+            highest += 1;
+        }
+    }
+
+    let edge = |component: usize, value: u16| -> Vec<u32> {
+        (0..quantized.len() as u32)
+            .filter(|&i| quantized[i as usize][component] == value)
+            .collect()
+    };
+    let max = QUANTIZED_MAX as u16;
+    for vertices in [edge(0, 0), edge(1, 0), edge(0, max), edge(1, max)] {
+        output.extend_from_slice(&(vertices.len() as u32).to_le_bytes());
+        for index in vertices {
+            put_index(&mut output, index);
+        }
+    }
+
+    // Normals from the ECEF surface, so they hold whatever units the mesh used
+    let mut normals = vec![DVec3::ZERO; positions.len()];
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|k| triangle[k] as usize);
+        let face = (positions[b] - positions[a]).cross(positions[c] - positions[a]);
+        for corner in [a, b, c] {
+            normals[corner] += face;
+        }
+    }
+    output.push(OCT_VERTEX_NORMALS);
+    output.extend_from_slice(&((normals.len() * 2) as u32).to_le_bytes());
+    for normal in normals {
+        output.extend_from_slice(&oct_encode(normal));
+    }
+    output
+}
+
+/// Cesium's horizon culling point in ellipsoid-scaled coordinates: while it is
+/// below the horizon, so is every vertex of the tile
+fn horizon_occlusion_point(positions: &[DVec3], center: DVec3) -> DVec3 {
+    let scale = DVec3::new(1.0 / WGS84_A, 1.0 / WGS84_A, 1.0 / WGS84_B);
+    let direction = (center * scale).normalize_or_zero();
+    let mut magnitude: f64 = 0.0;
+    for &position in positions {
+        let scaled = position * scale;
+        let length = scaled.length().max(1.0);
+        let to_point = scaled.normalize_or_zero();
+        let cos_alpha = to_point.dot(direction);
+        let sin_alpha = to_point.cross(direction).length();
+        let cos_beta = 1.0 / length;
+        let sin_beta = (length * length - 1.0).sqrt() * cos_beta;
+        magnitude = magnitude.max(1.0 / (cos_alpha * cos_beta - sin_alpha * sin_beta));
+    }
+    direction * magnitude
+}
+
+/// Unit vector as two bytes on the octahedron (Cesium's AttributeCompression)
+fn oct_encode(normal: DVec3) -> [u8; 2] {
+    let n = normal / (normal.x.abs() + normal.y.abs() + normal.z.abs()).max(f64::MIN_POSITIVE);
+    let (mut x, mut y) = (n.x, n.y);
+    if n.z < 0.0 {
+        let sign = |v: f64| if v < 0.0 { -1.0 } else { 1.0 };
+        (x, y) = ((1.0 - n.y.abs()) * sign(n.x), (1.0 - n.x.abs()) * sign(n.y));
+    }
+    let byte = |v: f64| ((v.clamp(-1.0, 1.0) * 0.5 + 0.5) * 255.0).round() as u8;
+    [byte(x), byte(y)]
+}
+
+/// three.js JSON Object format (THREE.ObjectLoader) of the mesh: a
+/// BufferGeometry with position, normal, uv (tangent and color when present)
+/// and index, a MeshStandardMaterial and the Mesh using them. UUIDs come from
+/// the mesh contents, so different tiles do not collide in one scene.
+pub(crate) fn encode_threejs_json(mesh: &MeshBuffers) -> String {
+    let hash = mesh.content_hash();
+    let uuid = |kind: u64| {
+        let (high, low) = (hash, hash.rotate_left(17) ^ kind);
+        format!(
+            "{:08x}-{:04x}-4{:03x}-8{:03x}-{:012x}",
+            high >> 32,
+            (high >> 16) & 0xffff,
+            high & 0xfff,
+            low >> 52,
+            low & 0xffff_ffff_ffff
+        )
+    };
+    let (geometry, material, object) = (uuid(1), uuid(2), uuid(3));
+
+    let vertex_count = mesh.vertices.len() / 3;
+    let mut attributes = String::new();
+    let mut float_attribute = |name: &str, values: &[f32], item_size: usize| {
+        if values.len() != vertex_count * item_size {
+            return;
+        }
+        if !attributes.is_empty() {
+            attributes.push(',');
+        }
+        let _ = write!(
+            attributes,
+            "\"{}\":{{\"itemSize\":{},\"type\":\"Float32Array\",\"normalized\":false,\"array\":",
+            name, item_size
+        );
+        write_json_floats(&mut attributes, values);
+        attributes.push('}');
+    };
+    float_attribute("position", &mesh.vertices, 3);
+    float_attribute("normal", &mesh.normals, 3);
+    float_attribute("uv", &mesh.uvs, 2);
+    float_attribute("tangent", &mesh.tangents, 4);
+    let colors = mesh.colors.len() == vertex_count * 3;
+    if colors {
+        attributes.push_str(
+            ",\"color\":{\"itemSize\":3,\"type\":\"Uint8Array\",\"normalized\":true,\"array\":",
+        );
+        let _ = write!(attributes, "{:?}}}", mesh.colors);
+    }
+
+    // Flat-shaded meshes are non-indexed
+    let mut index = String::new();
+    if !mesh.indices_u16.is_empty() {
+        let _ = write!(
+            index,
+            ",\"index\":{{\"type\":\"Uint16Array\",\"array\":{:?}}}",
+            mesh.indices_u16
+        );
+    } else if !mesh.indices.is_empty() {
+        let _ = write!(
+            index,
+            ",\"index\":{{\"type\":\"Uint32Array\",\"array\":{:?}}}",
+            mesh.indices
+        );
+    }
+
+    let sphere = mesh.bounding_sphere;
+    let mut json = String::new();
+    let _ = write!(
+        json,
+        "{{\"metadata\":{{\"version\":4.6,\"type\":\"Object\",\"generator\":\"peak-vista\"}},\
+         \"geometries\":[{{\"uuid\":\"{}\",\"type\":\"BufferGeometry\",\"data\":{{\
+         \"attributes\":{{{}}}{},\"boundingSphere\":{{\"center\":{},\"radius\":{}}}}}}}],\
+         \"materials\":[{{\"uuid\":\"{}\",\"type\":\"MeshStandardMaterial\",\"color\":16777215,\
+         \"roughness\":1,\"metalness\":0,\"vertexColors\":{}}}],\
+         \"object\":{{\"uuid\":\"{}\",\"type\":\"Mesh\",\"geometry\":\"{}\",\"material\":\"{}\"}}}}",
+        geometry,
+        attributes,
+        index,
+        json_vec3([sphere[0], sphere[1], sphere[2]]),
+        if sphere[3].is_finite() { sphere[3] } else { 0.0 },
+        material,
+        colors,
+        object,
+        geometry,
+        material
+    );
+    json
+}
+
+/// Mesh buffers in Babylon.js VertexData conventions
+pub(crate) struct BabylonVertexData {
+    pub positions: Vec<f32>,
+    pub indices: Vec<u32>,
+    pub normals: Vec<f32>,
+    pub uvs: Vec<f32>,
+    pub tangents: Vec<f32>,
+    /// RGBA floats per vertex (empty without vertex colors)
+    pub colors: Vec<f32>,
+}
+
+/// Convert to Babylon's default left-handed frame: z is mirrored (+z north),
+/// which alone keeps the triangles front-facing under Babylon's clockwise
+/// convention, so the winding is unchanged. v is flipped for textures loaded
+/// with Babylon's default invertY, and non-indexed meshes get an index list.
+pub(crate) fn babylon_vertex_data(mesh: &MeshBuffers) -> BabylonVertexData {
+    let vertex_count = mesh.vertices.len() / 3;
+    let mirror = |values: &[f32], width: usize, negate: &[usize]| -> Vec<f32> {
+        let mut values = values.to_vec();
+        for item in values.chunks_exact_mut(width) {
+            for &k in negate {
+                item[k] = -item[k];
+            }
+        }
+        values
+    };
+    let indices = if !mesh.indices_u16.is_empty() {
+        mesh.indices_u16.iter().map(|&i| i as u32).collect()
+    } else if !mesh.indices.is_empty() {
+        mesh.indices.clone()
+    } else {
+        (0..vertex_count as u32).collect()
+    };
+    BabylonVertexData {
+        positions: mirror(&mesh.vertices, 3, &[2]),
+        indices,
+        normals: mirror(&mesh.normals, 3, &[2]),
+        uvs: mesh
+            .uvs
+            .chunks_exact(2)
+            .flat_map(|uv| [uv[0], 1.0 - uv[1]])
+            .collect(),
+        // Mirroring also flips the bitangent, so the handedness sign flips
+        tangents: mirror(&mesh.tangents, 4, &[2, 3]),
+        colors: mesh
+            .colors
+            .chunks_exact(3)
+            .flat_map(|rgb| {
+                [
+                    rgb[0] as f32 / 255.0,
+                    rgb[1] as f32 / 255.0,
+                    rgb[2] as f32 / 255.0,
+                    1.0,
+                ]
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use crate::mesh_generator::{MeshData, MeshGenerator};
+
+    fn test_tile() -> Vec<f32> {
+        (0..65536)
+            .map(|i| ((i % 256) as f32 * 0.05).sin() * 40.0 + (i / 256) as f32)
+            .collect()
+    }
+
+    /// Triangles of a binary STL as vertex bit patterns
+    fn stl_triangles(stl: &[u8]) -> Vec<[[u32; 3]; 3]> {
+        let count = u32::from_le_bytes(stl[80..84].try_into().unwrap()) as usize;
+        assert_eq!(stl.len(), 84 + count * 50);
+        let float = |at: usize| u32::from_le_bytes(stl[at..at + 4].try_into().unwrap());
+        (0..count)
+            .map(|t| {
+                let vertex = |v: usize| {
+                    let at = 84 + t * 50 + 12 + v * 12;
+                    [float(at), float(at + 4), float(at + 8)]
+                };
+                [vertex(0), vertex(1), vertex(2)]
+            })
+            .collect()
+    }
+
+    fn u32_at(bytes: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+    }
+
+    fn f32s(bytes: &[u8]) -> Vec<f32> {
+        bytes
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+            .collect()
+    }
+
+    /// Every number following "key": in a JSON text, in order
+    fn json_numbers(json: &str, key: &str) -> Vec<usize> {
+        let pattern = format!("\"{}\":", key);
+        json.match_indices(&pattern)
+            .map(|(at, _)| {
+                let digits = &json[at + pattern.len()..];
+                let end = digits.find(|c: char| !c.is_ascii_digit()).unwrap();
+                digits[..end].parse().unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn stl_of_masked_mesh_is_watertight() {
+        // An L-shaped island with a square hole, and a second island
+        let mask: Vec<u8> = (0..65536)
+            .map(|i| {
+                let (x, y) = (i % 256, i / 256);
+                let l_shape = x < 200 && y < 200 && !(x > 100 && y > 150);
+                let hole = (80..120).contains(&x) && (60..100).contains(&y);
+                let island = x > 220 && y > 220;
+                ((l_shape && !hole) || island) as u8
+            })
+            .collect();
+        let mesh = MeshGenerator::new(1.0)
+            .generate_masked(&test_tile(), &mask, 1000.0, 0)
+            .unwrap();
+        let triangles = stl_triangles(&encode_stl(&mesh, 10.0));
+
+        // The base covers the outline without folding over itself (a fan from
+        // one center would fold over the notch and the hole)
+        let lowest = triangles
+            .iter()
+            .flatten()
+            .map(|v| f32::from_bits(v[2]))
+            .fold(f32::INFINITY, f32::min);
+        let (mut signed, mut unsigned) = (0.0, 0.0);
+        for triangle in &triangles {
+            let [a, b, c] = triangle.map(|v| v.map(f32::from_bits));
+            if [a, b, c].iter().all(|v| v[2] == lowest) {
+                let area = ((b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])) as f64;
+                signed += area;
+                unsigned += area.abs();
+            }
+        }
+        assert!(signed < 0.0, "base must face down");
+        assert!((unsigned + signed).abs() < unsigned * 1e-6);
+
+        // Closed and consistently wound: every edge is met once the other way
+        let mut edges: HashMap<([u32; 3], [u32; 3]), usize> = HashMap::new();
+        for [a, b, c] in triangles {
+            for edge in [(a, b), (b, c), (c, a)] {
+                *edges.entry(edge).or_default() += 1;
+            }
+        }
+        for (&(a, b), &count) in &edges {
+            assert_eq!(count, 1);
+            assert_eq!(edges.get(&(b, a)), Some(&1));
+        }
+    }
+
+    #[test]
+    fn glb_chunks_hold_the_mesh_buffers() {
+        let mut generator = MeshGenerator::new(1.0);
+        generator.set_u16_indices(true);
+        let mesh = generator.generate(&test_tile(), 1000.0, 2).unwrap();
+        let glb = encode_glb(&mesh);
+
+        assert_eq!(u32_at(&glb, 0), GLB_MAGIC);
+        assert_eq!(&glb[..4], b"glTF");
+        assert_eq!(u32_at(&glb, 4), GLB_VERSION);
+        assert_eq!(u32_at(&glb, 8) as usize, glb.len());
+        let json_length = u32_at(&glb, 12) as usize;
+        assert_eq!(json_length % 4, 0);
+        assert_eq!(u32_at(&glb, 16), CHUNK_JSON);
+        let json = std::str::from_utf8(&glb[20..20 + json_length]).unwrap();
+        let bin_header = 20 + json_length;
+        let bin_length = u32_at(&glb, bin_header) as usize;
+        assert_eq!(bin_length % 4, 0);
+        assert_eq!(u32_at(&glb, bin_header + 4), CHUNK_BIN);
+        let bin = &glb[bin_header + 8..];
+        assert_eq!(bin.len(), bin_length);
+
+        // buffers[0].byteLength, then each view's
+        let lengths = json_numbers(json, "byteLength");
+        assert_eq!(lengths[0], bin_length);
+        let offsets = json_numbers(json, "byteOffset");
+        let counts = json_numbers(json, "count");
+        let views: Vec<&[u8]> = offsets
+            .iter()
+            .zip(&lengths[1..])
+            .map(|(&offset, &length)| &bin[offset..offset + length])
+            .collect();
+        assert_eq!(views.len(), 4, "position, normal, uv, indices");
+        assert_eq!(f32s(views[0]), mesh.vertices);
+        assert_eq!(counts[0], mesh.vertices.len() / 3);
+        assert_eq!(f32s(views[1]), mesh.normals);
+        assert_eq!(f32s(views[2]), mesh.uvs);
+        let indices: Vec<u16> = views[3]
+            .chunks_exact(2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]))
+            .collect();
+        assert_eq!(indices, mesh.indices_u16);
+        assert_eq!(counts[3], mesh.indices_u16.len());
+        assert!(json.contains(&format!("\"componentType\":{}", UNSIGNED_SHORT)));
+    }
+
+    #[test]
+    fn glb_of_an_empty_mesh_is_rejected() {
+        let empty = MeshData::from(MeshBuffers::default());
+        assert!(matches!(empty.to_glb(), Err(Error::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn ply_header_describes_the_body() {
+        let mut generator = MeshGenerator::new(1.0);
+        generator.set_hypsometric_tint(true);
+        let mesh = generator.generate(&test_tile(), 1000.0, 2).unwrap();
+        let ply = encode_ply(&mesh);
+
+        let vertex_count = mesh.vertices.len() / 3;
+        let face_count = mesh.indices.len() / 3;
+        let header = format!(
+            "ply\nformat binary_little_endian 1.0\ncomment peak-vista terrain\n\
+             element vertex {}\n\
+             property float x\nproperty float y\nproperty float z\n\
+             property float nx\nproperty float ny\nproperty float nz\n\
+             property uchar red\nproperty uchar green\nproperty uchar blue\n\
+             element face {}\n\
+             property list uchar uint vertex_indices\nend_header\n",
+            vertex_count, face_count
+        );
+        assert_eq!(&ply[..header.len()], header.as_bytes());
+        assert_eq!(
+            ply.len(),
+            header.len() + vertex_count * 27 + face_count * 13
+        );
+
+        let body = &ply[header.len()..];
+        let last = (vertex_count - 1) * 27;
+        assert_eq!(
+            f32s(&body[last..last + 12]),
+            mesh.vertices[vertex_count * 3 - 3..]
+        );
+        assert_eq!(
+            f32s(&body[last + 12..last + 24]),
+            mesh.normals[vertex_count * 3 - 3..]
+        );
+        assert_eq!(
+            body[last + 24..last + 27],
+            mesh.colors[vertex_count * 3 - 3..]
+        );
+        let faces = &body[vertex_count * 27..];
+        for (face, triangle) in faces.chunks_exact(13).zip(mesh.indices.chunks_exact(3)) {
+            assert_eq!(face[0], 3);
+            let corners: Vec<u32> = (0..3).map(|k| u32_at(face, 1 + k * 4)).collect();
+            assert_eq!(corners, triangle);
+        }
+    }
+
+    #[test]
+    fn quantized_mesh_decodes_to_the_mesh() {
+        let mesh = MeshGenerator::new(1.0)
+            .generate(&test_tile(), 1000.0, 2)
+            .unwrap();
+        let terrain = encode_quantized_mesh(&mesh, 14552, 6451, 14);
+        let u16_at = |at: usize| u16::from_le_bytes([terrain[at], terrain[at + 1]]);
+        let float_at = |at: usize| f32::from_le_bytes(terrain[at..at + 4].try_into().unwrap());
+
+        // 88-byte header: center, height range, bounding sphere, occlusion point
+        let (min_height, max_height) = (float_at(24), float_at(28));
+        let elevations = mesh.vertices.iter().skip(1).step_by(3);
+        assert_eq!(
+            min_height,
+            elevations.clone().copied().fold(f32::INFINITY, f32::min)
+        );
+        assert_eq!(
+            max_height,
+            elevations.copied().fold(f32::NEG_INFINITY, f32::max)
+        );
+        let vertex_count = u32_at(&terrain, 88) as usize;
+        assert!(vertex_count <= mesh.vertices.len() / 3);
+
+        // Zigzag delta coded u, v and height arrays
+        let mut at = 92;
+        let mut quantized = vec![[0u16; 3]; vertex_count];
+        for component in 0..3 {
+            let mut value = 0i32;
+            for vertex in quantized.iter_mut() {
+                let zigzag = u16_at(at) as i32;
+                at += 2;
+                value += (zigzag >> 1) ^ -(zigzag & 1);
+                assert!((0..=32767).contains(&value));
+                vertex[component] = value as u16;
+            }
+        }
+
+        // High-water-mark indices, 16-bit for this size
+        let triangle_count = u32_at(&terrain, at) as usize;
+        at += 4;
+        assert_eq!(triangle_count, mesh.indices.len() / 3);
+        let mut highest = 0;
+        let mut indices = Vec::with_capacity(triangle_count * 3);
+        for _ in 0..triangle_count * 3 {
+            let index = highest - u16_at(at) as u32;
+            at += 2;
+            if index == highest {
+                highest += 1;
+            }
+            indices.push(index as usize);
+        }
+        assert_eq!(highest as usize, vertex_count);
+        for (&decoded, &original) in indices.iter().zip(&mesh.indices) {
+            let original = original as usize;
+            let u = (mesh.uvs[original * 2] as f64 * EDGE_TO_EDGE).clamp(0.0, 1.0);
+            assert_eq!(quantized[decoded][0], (u * QUANTIZED_MAX).round() as u16);
+            let (low, high) = (min_height as f64, max_height as f64);
+            let height = (mesh.vertices[original * 3 + 1] as f64 - low) / (high - low);
+            assert_eq!(
+                quantized[decoded][2],
+                (height * QUANTIZED_MAX).round() as u16
+            );
+        }
+
+        // West, south, east and north edge lists
+        for (component, value) in [(0, 0), (1, 0), (0, 32767), (1, 32767)] {
+            let count = u32_at(&terrain, at) as usize;
+            at += 4;
+            let expected: Vec<u16> = (0..vertex_count as u16)
+                .filter(|&i| quantized[i as usize][component] == value)
+                .collect();
+            assert!(!expected.is_empty());
+            let listed: Vec<u16> = (0..count).map(|i| u16_at(at + i * 2)).collect();
+            assert_eq!(listed, expected);
+            at += count * 2;
+        }
+
+        // Oct-encoded normals extension
+        assert_eq!(terrain[at], OCT_VERTEX_NORMALS);
+        assert_eq!(u32_at(&terrain, at + 1) as usize, vertex_count * 2);
+        assert_eq!(terrain.len(), at + 5 + vertex_count * 2);
+    }
+
+    #[test]
+    fn quantized_mesh_of_an_empty_mesh_is_rejected() {
+        let empty = MeshData::from(MeshBuffers::default());
+        assert!(matches!(
+            empty.to_quantized_mesh(0, 0, 0),
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+}
@@ -1,13 +1,191 @@
 use wasm_bindgen::prelude::*;
 use glam::Vec3;
+#[cfg(feature = "serialize")]
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 
+use crate::coordinate_transform::tile_origin;
+use crate::decal::{drape_decal, Decal};
+use crate::delaunay::DelaunayTin;
+use crate::error::{tile_length_error, Error};
+use crate::fidelity::surface_error;
+use crate::globe::GlobeTile;
+use crate::heightfield::Heightfield;
+use crate::hypsometric::ColorRamp;
+use crate::mesh_export::{
+    babylon_vertex_data, encode_glb, encode_ply, encode_quantized_mesh, encode_stl,
+    encode_threejs_json,
+};
+use crate::meshopt::{encode_index_buffer, encode_vertex_buffer};
+use crate::raycast::{raycast, RayHit};
+use crate::rtin::Rtin;
+use crate::splat::{slope_degrees, SplatRules};
+use crate::staging::StagingBuffers;
+use crate::tile_cache::Fnv1a;
+use crate::validation::{check_positive, check_tile_index, check_zoom};
+use crate::vertex_cache::{average_cache_miss_ratio, optimize_vertex_cache};
+use crate::water::water_geometry;
+
+/// Element type of a mesh's index buffer
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IndexType {
+    /// 16-bit indices (gl.UNSIGNED_SHORT)
+    Uint16 = 0,
+    /// 32-bit indices (gl.UNSIGNED_INT)
+    Uint32 = 1,
+}
+
+/// Mesh attribute buffers as plain Rust data, for native callers
+/// Optional buffers are empty unless the generator option that fills them is enabled
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct MeshBuffers {
+    pub vertices: Vec<f32>,
+    /// Index buffer when index_type is Uint32 (empty otherwise)
+    pub indices: Vec<u32>,
+    /// Index buffer when index_type is Uint16 (empty otherwise)
+    pub indices_u16: Vec<u16>,
+    pub normals: Vec<f32>,
+    pub uvs: Vec<f32>,
+    pub tangents: Vec<f32>,
+    /// Hypsometric tint, RGB per vertex (empty unless enabled)
+    pub colors: Vec<u8>,
+    /// Slope angle per vertex in degrees from horizontal (empty unless enabled)
+    pub slopes: Vec<f32>,
+    /// Splat weights per vertex: snow, rock, forest, grass (empty unless enabled)
+    pub splat_weights: Vec<f32>,
+    /// Position, normal and uv per vertex in one buffer (empty unless enabled)
+    pub interleaved: Vec<f32>,
+    /// Positions quantized to u16 over the mesh bounds (empty unless enabled)
+    pub quantized_positions: Vec<u16>,
+    /// Dequantization transform: position = offset + quantized * scale (x, y, z each)
+    pub quantization_offset: [f32; 3],
+    pub quantization_scale: [f32; 3],
+    /// Position of each vertex at the next-coarser LOD (empty unless enabled)
+    pub morph_targets: Vec<f32>,
+    /// Unique triangle edges as vertex index pairs for gl.LINES (empty unless enabled)
+    pub edge_indices: Vec<u32>,
+    /// Grid surface and skirts as triangle strips separated by STRIP_RESTART
+    /// (empty unless enabled)
+    pub strip_indices: Vec<u32>,
+    /// Interleaved vertices (as `interleaved`) in the meshoptimizer vertex codec
+    /// (empty unless enabled)
+    pub meshopt_vertices: Vec<u8>,
+    /// Index buffer in the meshoptimizer triangle codec (empty unless enabled)
+    pub meshopt_indices: Vec<u8>,
+    /// Axis-aligned bounds of the vertices (including skirts)
+    pub bounds_min: [f32; 3],
+    pub bounds_max: [f32; 3],
+    /// Bounding sphere: center x, y, z and radius
+    pub bounding_sphere: [f32; 4],
+    /// The heightmap had one elevation everywhere and the mesh is a single quad
+    pub flat: bool,
+    /// Offset of the vertex coordinates: every vertex is relative to this point
+    /// (the tile's world position for generate_tile(), the ECEF tile center
+    /// for globe meshes, zero otherwise)
+    pub origin: [f64; 3],
+    /// Largest and mean vertical deviation from the source heightmap over the
+    /// pixels the mesh covers (None unless the fidelity report is enabled)
+    pub max_deviation: Option<f32>,
+    pub mean_deviation: Option<f32>,
+}
+
+/// Generated mesh handed to JavaScript; derefs to its MeshBuffers
 #[wasm_bindgen]
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(Serialize, Deserialize),
+    serde(transparent)
+)]
 pub struct MeshData {
-    vertices: Vec<f32>,
-    indices: Vec<u32>,
-    normals: Vec<f32>,
+    buffers: MeshBuffers,
+}
+
+impl MeshData {
+    /// Take the buffers out of the mesh without copying
+    pub fn into_buffers(self) -> MeshBuffers {
+        self.buffers
+    }
+}
+
+impl MeshBuffers {
+    /// Triangle corners as 32-bit indices, whichever index buffer is in use
+    /// (consecutive vertices for flat-shaded meshes)
+    pub(crate) fn triangle_corners(&self) -> Vec<u32> {
+        if !self.indices_u16.is_empty() {
+            self.indices_u16.iter().map(|&i| i as u32).collect()
+        } else if !self.indices.is_empty() {
+            self.indices.clone()
+        } else {
+            (0..(self.vertices.len() / 3) as u32).collect()
+        }
+    }
+
+    /// Hash of the geometry and surface attributes (the other buffers are
+    /// derived from these and the generator configuration)
+    pub(crate) fn content_hash(&self) -> u64 {
+        let mut hasher = Fnv1a::new();
+        hasher.write_f32s(&self.vertices);
+        let indices: Vec<u8> = if self.indices_u16.is_empty() {
+            self.indices.iter().flat_map(|i| i.to_le_bytes()).collect()
+        } else {
+            self.indices_u16
+                .iter()
+                .flat_map(|&i| (i as u32).to_le_bytes())
+                .collect()
+        };
+        hasher.write(&(indices.len() as u64).to_le_bytes());
+        hasher.write(&indices);
+        hasher.write_f32s(&self.normals);
+        hasher.write_f32s(&self.uvs);
+        hasher.write_f32s(&self.tangents);
+        hasher.write(&(self.colors.len() as u64).to_le_bytes());
+        hasher.write(&self.colors);
+        hasher.write(&[self.flat as u8]);
+        for coordinate in self.origin {
+            hasher.write(&coordinate.to_le_bytes());
+        }
+        hasher.finish()
+    }
+}
+
+impl From<MeshBuffers> for MeshData {
+    fn from(buffers: MeshBuffers) -> MeshData {
+        MeshData { buffers }
+    }
+}
+
+impl std::ops::Deref for MeshData {
+    type Target = MeshBuffers;
+
+    fn deref(&self) -> &MeshBuffers {
+        &self.buffers
+    }
+}
+
+#[cfg(feature = "serialize")]
+#[wasm_bindgen]
+impl MeshData {
+    /// All buffers as a plain object of arrays, for JSON.stringify snapshots
+    #[wasm_bindgen]
+    pub fn to_object(&self) -> Result<JsValue, Error> {
+        serde_wasm_bindgen::to_value(&self.buffers)
+            .map_err(|e| Error::InvalidArgument(e.to_string()))
+    }
+
+    /// Rebuild a mesh from the output of to_object (for example after JSON.parse)
+    #[wasm_bindgen]
+    pub fn from_object(value: JsValue) -> Result<MeshData, Error> {
+        serde_wasm_bindgen::from_value(value).map_err(|e| Error::Decode(e.to_string()))
+    }
 }
 
+/// Floats per vertex in the interleaved buffer: position (3), normal (3), uv (2)
+const INTERLEAVED_FLOATS: usize = 8;
+
 #[wasm_bindgen]
 impl MeshData {
     /// Get pointer to vertices array for zero-copy access
@@ -22,16 +200,32 @@ impl MeshData {
         self.vertices.len()
     }
 
-    /// Get pointer to indices array
+    /// Element type of the index buffer
+    #[wasm_bindgen]
+    pub fn index_type(&self) -> IndexType {
+        if self.indices_u16.is_empty() {
+            IndexType::Uint32
+        } else {
+            IndexType::Uint16
+        }
+    }
+
+    /// Get pointer to indices array (valid when index_type is Uint32)
     #[wasm_bindgen]
     pub fn indices_ptr(&self) -> *const u32 {
         self.indices.as_ptr()
     }
 
-    /// Get number of indices
+    /// Get pointer to 16-bit indices array (valid when index_type is Uint16)
+    #[wasm_bindgen]
+    pub fn indices_u16_ptr(&self) -> *const u16 {
+        self.indices_u16.as_ptr()
+    }
+
+    /// Get number of indices (of either index type)
     #[wasm_bindgen]
     pub fn indices_len(&self) -> usize {
-        self.indices.len()
+        self.indices.len() + self.indices_u16.len()
     }
 
     /// Get pointer to normals array
@@ -46,16 +240,196 @@ impl MeshData {
         self.normals.len()
     }
 
+    /// Get pointer to texture coordinates array (u, v per vertex)
+    #[wasm_bindgen]
+    pub fn uvs_ptr(&self) -> *const f32 {
+        self.uvs.as_ptr()
+    }
+
+    /// Get number of texture coordinate values
+    #[wasm_bindgen]
+    pub fn uvs_len(&self) -> usize {
+        self.uvs.len()
+    }
+
+    /// Get pointer to tangents array (x, y, z, w per vertex; empty unless enabled)
+    #[wasm_bindgen]
+    pub fn tangents_ptr(&self) -> *const f32 {
+        self.tangents.as_ptr()
+    }
+
+    /// Get number of tangent values
+    #[wasm_bindgen]
+    pub fn tangents_len(&self) -> usize {
+        self.tangents.len()
+    }
+
+    /// Get pointer to vertex colors (r, g, b bytes per vertex; empty unless enabled)
+    #[wasm_bindgen]
+    pub fn colors_ptr(&self) -> *const u8 {
+        self.colors.as_ptr()
+    }
+
+    /// Get number of color bytes
+    #[wasm_bindgen]
+    pub fn colors_len(&self) -> usize {
+        self.colors.len()
+    }
+
+    /// Get pointer to vertex slopes (degrees per vertex; empty unless enabled)
+    #[wasm_bindgen]
+    pub fn slopes_ptr(&self) -> *const f32 {
+        self.slopes.as_ptr()
+    }
+
+    /// Get number of slope values
+    #[wasm_bindgen]
+    pub fn slopes_len(&self) -> usize {
+        self.slopes.len()
+    }
+
+    /// Get pointer to splat weights (snow, rock, forest, grass per vertex, summing
+    /// to 1; empty unless enabled)
+    #[wasm_bindgen]
+    pub fn splat_weights_ptr(&self) -> *const f32 {
+        self.splat_weights.as_ptr()
+    }
+
+    /// Get number of splat weight values (4 per vertex)
+    #[wasm_bindgen]
+    pub fn splat_weights_len(&self) -> usize {
+        self.splat_weights.len()
+    }
+
+    /// Get pointer to interleaved vertex buffer (empty unless enabled)
+    #[wasm_bindgen]
+    pub fn interleaved_ptr(&self) -> *const f32 {
+        self.interleaved.as_ptr()
+    }
+
+    /// Get number of interleaved float values
+    #[wasm_bindgen]
+    pub fn interleaved_len(&self) -> usize {
+        self.interleaved.len()
+    }
+
+    /// Byte stride between vertices in the interleaved buffer
+    #[wasm_bindgen]
+    pub fn vertex_stride(&self) -> usize {
+        INTERLEAVED_FLOATS * 4
+    }
+
+    /// Byte offset of the position (x, y, z) within an interleaved vertex
+    #[wasm_bindgen]
+    pub fn position_offset(&self) -> usize {
+        0
+    }
+
+    /// Byte offset of the normal (x, y, z) within an interleaved vertex
+    #[wasm_bindgen]
+    pub fn normal_offset(&self) -> usize {
+        12
+    }
+
+    /// Byte offset of the texture coordinate (u, v) within an interleaved vertex
+    #[wasm_bindgen]
+    pub fn uv_offset(&self) -> usize {
+        24
+    }
+
+    /// Get pointer to quantized positions (x, y, z as u16 per vertex; empty unless enabled)
+    #[wasm_bindgen]
+    pub fn quantized_positions_ptr(&self) -> *const u16 {
+        self.quantized_positions.as_ptr()
+    }
+
+    /// Get number of quantized position values
+    #[wasm_bindgen]
+    pub fn quantized_positions_len(&self) -> usize {
+        self.quantized_positions.len()
+    }
+
+    /// Get pointer to morph targets (x, y, z per vertex; empty unless enabled)
+    #[wasm_bindgen]
+    pub fn morph_targets_ptr(&self) -> *const f32 {
+        self.morph_targets.as_ptr()
+    }
+
+    /// Get number of morph target values
+    #[wasm_bindgen]
+    pub fn morph_targets_len(&self) -> usize {
+        self.morph_targets.len()
+    }
+
+    /// Get pointer to edge indices (two per line; empty unless enabled)
+    #[wasm_bindgen]
+    pub fn edge_indices_ptr(&self) -> *const u32 {
+        self.edge_indices.as_ptr()
+    }
+
+    /// Get number of edge index values
+    #[wasm_bindgen]
+    pub fn edge_indices_len(&self) -> usize {
+        self.edge_indices.len()
+    }
+
+    /// Get pointer to the triangle strip indices for gl.TRIANGLE_STRIP
+    /// (empty unless enabled); strips end at 0xFFFFFFFF, the primitive restart
+    /// index WebGL2 always applies to 32-bit indices
+    #[wasm_bindgen]
+    pub fn strip_indices_ptr(&self) -> *const u32 {
+        self.strip_indices.as_ptr()
+    }
+
+    /// Get number of strip index values (restarts included)
+    #[wasm_bindgen]
+    pub fn strip_indices_len(&self) -> usize {
+        self.strip_indices.len()
+    }
+
+    /// Get pointer to the meshopt-encoded vertex stream (empty unless enabled)
+    #[wasm_bindgen]
+    pub fn meshopt_vertices_ptr(&self) -> *const u8 {
+        self.meshopt_vertices.as_ptr()
+    }
+
+    /// Get number of bytes in the meshopt-encoded vertex stream
+    #[wasm_bindgen]
+    pub fn meshopt_vertices_len(&self) -> usize {
+        self.meshopt_vertices.len()
+    }
+
+    /// Get pointer to the meshopt-encoded index stream (empty unless enabled)
+    #[wasm_bindgen]
+    pub fn meshopt_indices_ptr(&self) -> *const u8 {
+        self.meshopt_indices.as_ptr()
+    }
+
+    /// Get number of bytes in the meshopt-encoded index stream
+    #[wasm_bindgen]
+    pub fn meshopt_indices_len(&self) -> usize {
+        self.meshopt_indices.len()
+    }
+
     /// Get vertices as a copied array (for JavaScript)
     #[wasm_bindgen]
     pub fn get_vertices(&self) -> Vec<f32> {
         self.vertices.clone()
     }
 
-    /// Get indices as a copied array (for JavaScript)
+    /// Get indices as a copied 32-bit array (for JavaScript), whatever the index type
     #[wasm_bindgen]
     pub fn get_indices(&self) -> Vec<u32> {
-        self.indices.clone()
+        match self.index_type() {
+            IndexType::Uint32 => self.indices.clone(),
+            IndexType::Uint16 => self.indices_u16.iter().map(|&i| i as u32).collect(),
+        }
+    }
+
+    /// Get 16-bit indices as a copied array (empty unless index_type is Uint16)
+    #[wasm_bindgen]
+    pub fn get_indices_u16(&self) -> Vec<u16> {
+        self.indices_u16.clone()
     }
 
     /// Get normals as a copied array (for JavaScript)
@@ -63,149 +437,2827 @@ impl MeshData {
     pub fn get_normals(&self) -> Vec<f32> {
         self.normals.clone()
     }
-}
 
-#[wasm_bindgen]
-pub struct MeshGenerator {
-    max_error: f32,
-}
+    /// Get texture coordinates as a copied array (for JavaScript)
+    #[wasm_bindgen]
+    pub fn get_uvs(&self) -> Vec<f32> {
+        self.uvs.clone()
+    }
 
-#[wasm_bindgen]
-impl MeshGenerator {
-    #[wasm_bindgen(constructor)]
-    pub fn new(max_error: f32) -> MeshGenerator {
-        MeshGenerator { max_error }
+    /// Get tangents as a copied array (for JavaScript)
+    #[wasm_bindgen]
+    pub fn get_tangents(&self) -> Vec<f32> {
+        self.tangents.clone()
     }
 
-    /// Generate terrain mesh from elevation data
-    /// elevations: 256x256 heightmap (65536 values)
-    /// tile_size: size of tile in world units
-    /// lod_level: 0=far (low detail), 1=mid, 2=near (high detail)
     #[wasm_bindgen]
-    pub fn generate(
-        &self,
-        elevations: &[f32],
-        tile_size: f32,
-        lod_level: u8,
-    ) -> Result<MeshData, JsValue> {
-        if elevations.len() != 65536 {
-            return Err(JsValue::from_str(&format!(
-                "Invalid elevation array length: {}, expected 65536",
-                elevations.len()
-            )));
-        }
+    pub fn get_colors(&self) -> Vec<u8> {
+        self.colors.clone()
+    }
 
-        // Calculate LOD parameters
-        let step = match lod_level {
-            0 => 8,  // Far: sample every 8 pixels (32x32 grid)
-            1 => 4,  // Mid: sample every 4 pixels (64x64 grid)
-            2 => 2,  // Near: sample every 2 pixels (128x128 grid)
-            _ => return Err(JsValue::from_str("Invalid LOD level (0-2)")),
-        };
+    #[wasm_bindgen]
+    pub fn get_slopes(&self) -> Vec<f32> {
+        self.slopes.clone()
+    }
 
-        let mut vertices = Vec::new();
-        let mut indices = Vec::new();
-        let mut normals = Vec::new();
+    #[wasm_bindgen]
+    pub fn get_splat_weights(&self) -> Vec<f32> {
+        self.splat_weights.clone()
+    }
 
-        // Create heightmap grid
-        // Note: grid_size includes the edge vertices to ensure full tile coverage
-        // to prevent gaps between adjacent tiles
-        let grid_size = (256 / step) + 1;
-        let pixel_size = tile_size / 256.0;
+    /// Get interleaved vertex buffer as a copied array (for JavaScript)
+    #[wasm_bindgen]
+    pub fn get_interleaved(&self) -> Vec<f32> {
+        self.interleaved.clone()
+    }
 
-        // Generate vertices
-        for y in 0..grid_size {
-            for x in 0..grid_size {
-                // Clamp to 255 to ensure we don't go beyond the heightmap
-                let sample_x = (x * step).min(255);
-                let sample_y = (y * step).min(255);
+    /// Get quantized positions as a copied array (for JavaScript)
+    #[wasm_bindgen]
+    pub fn get_quantized_positions(&self) -> Vec<u16> {
+        self.quantized_positions.clone()
+    }
 
-                let px = sample_x as f32;
-                let py = sample_y as f32;
+    /// Get morph targets as a copied array (for JavaScript)
+    #[wasm_bindgen]
+    pub fn get_morph_targets(&self) -> Vec<f32> {
+        self.morph_targets.clone()
+    }
 
-                let world_x = px * pixel_size - tile_size / 2.0;
-                let world_z = py * pixel_size - tile_size / 2.0;
+    /// Get edge indices as a copied array (for JavaScript)
+    #[wasm_bindgen]
+    pub fn get_edge_indices(&self) -> Vec<u32> {
+        self.edge_indices.clone()
+    }
 
-                let elevation_idx = (sample_y * 256 + sample_x) as usize;
-                let world_y = elevations[elevation_idx];
+    /// Get triangle strip indices as a copied array (for JavaScript)
+    #[wasm_bindgen]
+    pub fn get_strip_indices(&self) -> Vec<u32> {
+        self.strip_indices.clone()
+    }
 
-                vertices.push(world_x);
-                vertices.push(world_y);
-                vertices.push(world_z);
-            }
-        }
+    /// Get the meshopt-encoded vertex stream as a copied array
+    #[wasm_bindgen]
+    pub fn get_meshopt_vertices(&self) -> Vec<u8> {
+        self.meshopt_vertices.clone()
+    }
 
-        // Generate indices (simple triangle strip)
-        // IMPORTANT: Winding order must be counter-clockwise when viewed from above
-        // to ensure normals point outward (upward for terrain)
-        for y in 0..(grid_size - 1) {
-            for x in 0..(grid_size - 1) {
-                let idx0 = y * grid_size + x;
-                let idx1 = y * grid_size + (x + 1);
-                let idx2 = (y + 1) * grid_size + x;
-                let idx3 = (y + 1) * grid_size + (x + 1);
+    /// Get the meshopt-encoded index stream as a copied array
+    #[wasm_bindgen]
+    pub fn get_meshopt_indices(&self) -> Vec<u8> {
+        self.meshopt_indices.clone()
+    }
 
-                // First triangle (counter-clockwise: 0, 2, 1)
-                indices.push(idx0 as u32);
-                indices.push(idx2 as u32);
-                indices.push(idx1 as u32);
+    /// Export as binary glTF (.glb) with positions, normals, uvs and indices
+    /// (plus tangents when enabled), for Blender or the three.js editor
+    /// Fails for an empty mesh, which glTF cannot hold
+    #[wasm_bindgen]
+    pub fn to_glb(&self) -> Result<Vec<u8>, Error> {
+        if self.vertices.is_empty() {
+            return Err(Error::InvalidArgument(
+                "Cannot export an empty mesh as glTF".into(),
+            ));
+        }
+        Ok(encode_glb(self))
+    }
 
-                // Second triangle (counter-clockwise: 1, 2, 3)
-                indices.push(idx1 as u32);
-                indices.push(idx2 as u32);
-                indices.push(idx3 as u32);
-            }
+    /// Export as a watertight binary STL for 3D printing: the surface closed by
+    /// walls down to a flat base base_thickness below the lowest point (world
+    /// units, > 0). Z-up, in world units; scale to millimetres in the slicer
+    #[wasm_bindgen]
+    pub fn to_stl(&self, base_thickness: f32) -> Result<Vec<u8>, Error> {
+        if !(base_thickness > 0.0 && base_thickness.is_finite()) {
+            return Err(Error::InvalidArgument(format!(
+                "Invalid base thickness: {}, expected a positive number",
+                base_thickness
+            )));
         }
+        Ok(encode_stl(self, base_thickness))
+    }
 
-        // Calculate normals using face normals
-        normals.resize(vertices.len(), 0.0);
+    /// Export as binary little-endian PLY with positions, normals and faces,
+    /// plus vertex colors when hypsometric tinting is enabled (MeshLab, CloudCompare)
+    #[wasm_bindgen]
+    pub fn to_ply(&self) -> Vec<u8> {
+        encode_ply(self)
+    }
 
-        for i in (0..indices.len()).step_by(3) {
-            let idx0 = indices[i] as usize;
-            let idx1 = indices[i + 1] as usize;
-            let idx2 = indices[i + 2] as usize;
+    /// Export as three.js JSON (a Mesh with its BufferGeometry and a standard
+    /// material) for THREE.ObjectLoader().parse(JSON.parse(json))
+    #[wasm_bindgen]
+    pub fn to_threejs_json(&self) -> String {
+        encode_threejs_json(self)
+    }
 
-            let v0 = Vec3::new(
-                vertices[idx0 * 3],
-                vertices[idx0 * 3 + 1],
-                vertices[idx0 * 3 + 2],
-            );
-            let v1 = Vec3::new(
-                vertices[idx1 * 3],
-                vertices[idx1 * 3 + 1],
-                vertices[idx1 * 3 + 2],
-            );
-            let v2 = Vec3::new(
-                vertices[idx2 * 3],
-                vertices[idx2 * 3 + 1],
-                vertices[idx2 * 3 + 2],
-            );
+    /// Buffers shaped for Babylon.js: an object with positions, indices,
+    /// normals, uvs (and tangents, colors when present) as typed arrays, for
+    /// Object.assign(new BABYLON.VertexData(), data). Converted to Babylon's
+    /// default left-handed frame (z mirrored, so +z is north) with v flipped
+    /// for invertY textures; scenes with useRightHandedSystem can use the
+    /// mesh buffers as they are
+    #[wasm_bindgen]
+    pub fn to_babylon_vertex_data(&self) -> JsValue {
+        let data = babylon_vertex_data(self);
+        let object = js_sys::Object::new();
+        let set = |key: &str, value: JsValue| {
+            let _ = js_sys::Reflect::set(&object, &JsValue::from_str(key), &value);
+        };
+        let floats = |values: &[f32]| js_sys::Float32Array::from(values).into();
+        set("positions", floats(&data.positions));
+        let indices = js_sys::Uint32Array::from(&data.indices[..]);
+        set("indices", indices.into());
+        set("normals", floats(&data.normals));
+        set("uvs", floats(&data.uvs));
+        if !data.tangents.is_empty() {
+            set("tangents", floats(&data.tangents));
+        }
+        if !data.colors.is_empty() {
+            set("colors", floats(&data.colors));
+        }
+        object.into()
+    }
 
-            let edge1 = v1 - v0;
-            let edge2 = v2 - v0;
-            let normal = edge1.cross(edge2).normalize();
+    /// Drop the mesh's buffers now instead of when the JS wrapper is garbage
+    /// collected; the mesh is left empty. free() (generated by wasm-bindgen)
+    /// also releases the wrapper itself
+    #[wasm_bindgen]
+    pub fn dispose(&mut self) {
+        self.buffers = MeshBuffers::default();
+    }
 
-            // Accumulate normal to all three vertices
-            for &idx in &[idx0, idx1, idx2] {
-                normals[idx * 3] += normal.x;
-                normals[idx * 3 + 1] += normal.y;
-                normals[idx * 3 + 2] += normal.z;
+    /// Move the buffers out as an object of typed arrays (vertices, indices,
+    /// normals, uvs and, when present, tangents, colors, slopes,
+    /// splat_weights, interleaved, quantized_positions, morph_targets,
+    /// edge_indices, strip_indices, meshopt_vertices, meshopt_indices) and
+    /// release the Rust-side storage, leaving the mesh empty
+    /// indices is a Uint16Array when index_type() was Uint16; the meshopt
+    /// streams are Uint8Arrays
+    #[wasm_bindgen]
+    pub fn take_buffers(&mut self) -> JsValue {
+        let buffers = std::mem::take(&mut self.buffers);
+        let object = js_sys::Object::new();
+        let set = |key: &str, value: JsValue| {
+            let _ = js_sys::Reflect::set(&object, &JsValue::from_str(key), &value);
+        };
+        let floats = |values: &[f32]| -> JsValue { js_sys::Float32Array::from(values).into() };
+        set("vertices", floats(&buffers.vertices));
+        if buffers.indices_u16.is_empty() {
+            let indices = js_sys::Uint32Array::from(&buffers.indices[..]);
+            set("indices", indices.into());
+        } else {
+            let indices = js_sys::Uint16Array::from(&buffers.indices_u16[..]);
+            set("indices", indices.into());
+        }
+        set("normals", floats(&buffers.normals));
+        set("uvs", floats(&buffers.uvs));
+        let optional = [
+            ("tangents", &buffers.tangents),
+            ("slopes", &buffers.slopes),
+            ("splat_weights", &buffers.splat_weights),
+            ("interleaved", &buffers.interleaved),
+            ("morph_targets", &buffers.morph_targets),
+        ];
+        for (key, values) in optional {
+            if !values.is_empty() {
+                set(key, floats(values));
             }
         }
+        if !buffers.colors.is_empty() {
+            let colors = js_sys::Uint8Array::from(&buffers.colors[..]);
+            set("colors", colors.into());
+        }
+        if !buffers.quantized_positions.is_empty() {
+            let positions = js_sys::Uint16Array::from(&buffers.quantized_positions[..]);
+            set("quantized_positions", positions.into());
+        }
+        if !buffers.edge_indices.is_empty() {
+            let edges = js_sys::Uint32Array::from(&buffers.edge_indices[..]);
+            set("edge_indices", edges.into());
+        }
+        if !buffers.strip_indices.is_empty() {
+            let strips = js_sys::Uint32Array::from(&buffers.strip_indices[..]);
+            set("strip_indices", strips.into());
+        }
+        let streams = [
+            ("meshopt_vertices", &buffers.meshopt_vertices),
+            ("meshopt_indices", &buffers.meshopt_indices),
+        ];
+        for (key, bytes) in streams {
+            if !bytes.is_empty() {
+                set(key, js_sys::Uint8Array::from(&bytes[..]).into());
+            }
+        }
+        object.into()
+    }
+
+    /// Nearest hit of a ray with the mesh (for click picking), or undefined
+    /// origin: ray start in the mesh's coordinates
+    /// direction: ray direction, any length
+    #[wasm_bindgen]
+    pub fn raycast(
+        &self,
+        origin_x: f32,
+        origin_y: f32,
+        origin_z: f32,
+        direction_x: f32,
+        direction_y: f32,
+        direction_z: f32,
+    ) -> Option<RayHit> {
+        raycast(
+            self,
+            Vec3::new(origin_x, origin_y, origin_z),
+            Vec3::new(direction_x, direction_y, direction_z),
+        )
+    }
 
-        // Normalize vertex normals
-        for i in (0..normals.len()).step_by(3) {
-            let normal = Vec3::new(normals[i], normals[i + 1], normals[i + 2]);
-            let normalized = normal.normalize();
-            normals[i] = normalized.x;
-            normals[i + 1] = normalized.y;
-            normals[i + 2] = normalized.z;
+    /// Height of the mesh surface at a point in its local x, z, interpolated
+    /// within the triangle under it, or undefined off the mesh. Vertical skirt
+    /// triangles are ignored; the query scans every triangle, so sample
+    /// densely placed objects from the heightmap instead
+    #[wasm_bindgen]
+    pub fn elevation_at(&self, x: f32, z: f32) -> Option<f32> {
+        let position = |i: u32| {
+            let i = i as usize * 3;
+            (self.vertices[i], self.vertices[i + 1], self.vertices[i + 2])
+        };
+        for triangle in self.triangle_corners().chunks_exact(3) {
+            let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(position);
+            let area = (b.0 - a.0) * (c.2 - a.2) - (b.2 - a.2) * (c.0 - a.0);
+            if area.abs() <= f32::EPSILON * (a.0.abs() + a.2.abs() + 1.0) {
+                continue;
+            }
+            let wb = ((x - a.0) * (c.2 - a.2) - (z - a.2) * (c.0 - a.0)) / area;
+            let wc = ((b.0 - a.0) * (z - a.2) - (b.2 - a.2) * (x - a.0)) / area;
+            let tolerance = 1e-5;
+            if wb >= -tolerance && wc >= -tolerance && wb + wc <= 1.0 + tolerance {
+                return Some(a.1 + (b.1 - a.1) * wb + (c.1 - a.1) * wc);
+            }
         }
+        None
+    }
 
-        Ok(MeshData {
-            vertices,
-            indices,
-            normals,
-        })
+    /// Export as a Cesium quantized-mesh-1.0 terrain tile (with edge vertex
+    /// lists and oct-encoded normals) for the GSI tile the mesh was generated
+    /// from. Generate without skirts: Cesium adds its own from the edge lists.
+    /// Serve under a layer.json with "projection": "EPSG:3857" and the tms
+    /// scheme, i.e. at row 2^zoom - 1 - y. Fails for a mesh without triangles
+    #[wasm_bindgen]
+    pub fn to_quantized_mesh(&self, x: u32, y: u32, zoom: u8) -> Result<Vec<u8>, Error> {
+        if zoom > 30 || (x as u64) >> zoom != 0 || (y as u64) >> zoom != 0 {
+            return Err(Error::InvalidArgument(format!(
+                "Invalid tile {}/{}/{}",
+                zoom, x, y
+            )));
+        }
+        if self.triangle_corners().len() < 3 {
+            return Err(Error::InvalidArgument(
+                "Cannot export a mesh without triangles as quantized-mesh".into(),
+            ));
+        }
+        Ok(encode_quantized_mesh(self, x, y, zoom))
+    }
+
+    /// Whether the tile was flat (e.g. open sea) and meshed as a single quad
+    #[wasm_bindgen]
+    pub fn is_flat(&self) -> bool {
+        self.flat
+    }
+
+    /// Largest vertical deviation (meters) of the surface from the source
+    /// heightmap at any pixel, or undefined unless the fidelity report is enabled
+    #[wasm_bindgen]
+    pub fn max_deviation(&self) -> Option<f32> {
+        self.max_deviation
+    }
+
+    /// Mean absolute vertical deviation (meters) over the covered pixels,
+    /// or undefined unless the fidelity report is enabled
+    #[wasm_bindgen]
+    pub fn mean_deviation(&self) -> Option<f32> {
+        self.mean_deviation
+    }
+
+    /// Point the vertices are relative to, [x, y, z] in double precision
+    /// (the tile's world position for generate_tile(), the ECEF tile center
+    /// for globe meshes, zero otherwise); place the mesh
+    /// at origin - camera position to keep it steady in f32
+    #[wasm_bindgen]
+    pub fn origin(&self) -> Vec<f64> {
+        self.origin.to_vec()
+    }
+
+    /// Get the minimum corner of the bounding box [x, y, z]
+    #[wasm_bindgen]
+    pub fn get_bounds_min(&self) -> Vec<f32> {
+        self.bounds_min.to_vec()
+    }
+
+    /// Get the maximum corner of the bounding box [x, y, z]
+    #[wasm_bindgen]
+    pub fn get_bounds_max(&self) -> Vec<f32> {
+        self.bounds_max.to_vec()
+    }
+
+    /// Get the bounding sphere [center x, center y, center z, radius]
+    #[wasm_bindgen]
+    pub fn get_bounding_sphere(&self) -> Vec<f32> {
+        self.bounding_sphere.to_vec()
+    }
+
+    /// Get the dequantization offset (minimum x, y, z of the mesh)
+    #[wasm_bindgen]
+    pub fn get_quantization_offset(&self) -> Vec<f32> {
+        self.quantization_offset.to_vec()
+    }
+
+    /// Get the dequantization scale (world units per quantization step for x, y, z)
+    #[wasm_bindgen]
+    pub fn get_quantization_scale(&self) -> Vec<f32> {
+        self.quantization_scale.to_vec()
+    }
+
+    /// Vertex shader runs per triangle with a FIFO vertex cache of the given size
+    /// (ACMR; 3.0 when no vertex is reused). Flat-shaded meshes always score 3.0
+    #[wasm_bindgen]
+    pub fn cache_miss_ratio(&self, cache_size: u32) -> f32 {
+        let cache_size = cache_size.max(1) as usize;
+        if self.indices.is_empty() && self.indices_u16.is_empty() {
+            return if self.vertices.is_empty() { 0.0 } else { 3.0 };
+        }
+        if self.indices_u16.is_empty() {
+            average_cache_miss_ratio(&self.indices, cache_size)
+        } else {
+            let indices: Vec<u32> = self.indices_u16.iter().map(|&i| i as u32).collect();
+            average_cache_miss_ratio(&indices, cache_size)
+        }
+    }
+}
+
+/// Uniform grid LODs of one tile sharing a single vertex pool
+/// Every level indexes into the finest level's vertices, so switching LOD only
+/// swaps the index buffer and nested levels stay vertically consistent
+#[wasm_bindgen]
+pub struct LodChain {
+    /// Shared vertex attributes; its indices are those of the finest level
+    pool: MeshBuffers,
+    /// Index buffer per level, coarsest first
+    levels: Vec<Vec<u32>>,
+}
+
+#[wasm_bindgen]
+impl LodChain {
+    /// Number of LOD levels (level 0 is the coarsest)
+    #[wasm_bindgen]
+    pub fn level_count(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Get the shared vertex pool as a mesh drawn at the finest level (copied)
+    #[wasm_bindgen]
+    pub fn vertex_pool(&self) -> MeshData {
+        MeshData::from(self.pool.clone())
+    }
+
+    /// Get pointer to the 32-bit index buffer of a level (null if out of range)
+    #[wasm_bindgen]
+    pub fn indices_ptr(&self, level: usize) -> *const u32 {
+        self.levels
+            .get(level)
+            .map_or(std::ptr::null(), |indices| indices.as_ptr())
+    }
+
+    /// Get number of indices of a level (0 if out of range)
+    #[wasm_bindgen]
+    pub fn indices_len(&self, level: usize) -> usize {
+        self.levels.get(level).map_or(0, |indices| indices.len())
+    }
+
+    /// Get the indices of a level as a copied array (empty if out of range)
+    #[wasm_bindgen]
+    pub fn get_indices(&self, level: usize) -> Vec<u32> {
+        self.levels.get(level).cloned().unwrap_or_default()
+    }
+}
+
+#[wasm_bindgen]
+pub struct MeshGenerator {
+    max_error: f32,
+    tangents: bool,
+    skirt_depth: f32,
+    u16_indices: bool,
+    interleaved: bool,
+    quantized_positions: bool,
+    morph_targets: bool,
+    flat_shading: bool,
+    heightmap_normals: bool,
+    edge_indices: bool,
+    triangle_strips: bool,
+    meshopt: bool,
+    vertex_cache: bool,
+    fidelity_report: bool,
+    flat_quads: bool,
+    hypsometric_tint: bool,
+    color_ramp: ColorRamp,
+    slopes: bool,
+    splat_weights: bool,
+    splat_rules: SplatRules,
+    /// Coastal feathering distance in pixels (0 = off)
+    coast_feather: f32,
+    /// Grid sample spacing in pixels per LOD level, coarsest first
+    lod_steps: Vec<usize>,
+}
+
+#[wasm_bindgen]
+impl MeshGenerator {
+    #[wasm_bindgen(constructor)]
+    pub fn new(max_error: f32) -> MeshGenerator {
+        MeshGenerator {
+            max_error,
+            tangents: false,
+            skirt_depth: 0.0,
+            u16_indices: false,
+            interleaved: false,
+            quantized_positions: false,
+            morph_targets: false,
+            flat_shading: false,
+            heightmap_normals: false,
+            edge_indices: false,
+            triangle_strips: false,
+            meshopt: false,
+            vertex_cache: false,
+            fidelity_report: false,
+            flat_quads: false,
+            hypsometric_tint: false,
+            color_ramp: ColorRamp::default(),
+            slopes: false,
+            splat_weights: false,
+            splat_rules: SplatRules::default(),
+            coast_feather: 0.0,
+            lod_steps: vec![8, 4, 2],
+        }
+    }
+
+    /// Emit per-vertex tangents (x, y, z, handedness) for normal mapping
+    #[wasm_bindgen]
+    pub fn set_tangents(&mut self, enabled: bool) {
+        self.tangents = enabled;
+    }
+
+    /// Hang a vertical skirt of the given depth (world units) below the tile perimeter
+    /// Hides T-junction cracks against neighbors at a different LOD; 0 disables skirts
+    #[wasm_bindgen]
+    pub fn set_skirt_depth(&mut self, depth: f32) {
+        self.skirt_depth = depth.max(0.0);
+    }
+
+    /// Use 16-bit indices whenever the mesh has at most 65536 vertices
+    /// Halves index memory and allows gl.UNSIGNED_SHORT draws; larger meshes stay 32-bit
+    #[wasm_bindgen]
+    pub fn set_u16_indices(&mut self, enabled: bool) {
+        self.u16_indices = enabled;
+    }
+
+    /// Also emit a single interleaved buffer (position, normal, uv per vertex)
+    /// so a tile can be uploaded as one GPU buffer; see vertex_stride and the offsets
+    #[wasm_bindgen]
+    pub fn set_interleaved(&mut self, enabled: bool) {
+        self.interleaved = enabled;
+    }
+
+    /// Also emit positions quantized to u16 over the mesh bounding box (Cesium-style)
+    /// Upload them as normalized shorts and rebuild with the offset / scale transform;
+    /// the precision is 1/65535 of the tile extent and of the elevation range
+    #[wasm_bindgen]
+    pub fn set_quantized_positions(&mut self, enabled: bool) {
+        self.quantized_positions = enabled;
+    }
+
+    /// Emit per-vertex geomorph targets: the position each vertex would have at the
+    /// next-coarser uniform grid LOD, for blending between levels in the shader
+    /// Only generate() has a coarser level; other modes emit their own positions
+    #[wasm_bindgen]
+    pub fn set_morph_targets(&mut self, enabled: bool) {
+        self.morph_targets = enabled;
+    }
+
+    /// Emit non-indexed triangles with per-face normals for a low-poly look
+    /// Every triangle gets its own three vertices and the index buffer is empty
+    /// (draw with gl.drawArrays); generate_lod_chain keeps its shared indexed pool
+    #[wasm_bindgen]
+    pub fn set_flat_shading(&mut self, enabled: bool) {
+        self.flat_shading = enabled;
+    }
+
+    /// Also emit a line index buffer with every unique triangle edge once
+    /// for wireframe overlays drawn with gl.LINES; a LOD chain's pool gets the
+    /// edges of its finest level. Edge indices are always 32-bit
+    #[wasm_bindgen]
+    pub fn set_edge_indices(&mut self, enabled: bool) {
+        self.edge_indices = enabled;
+    }
+
+    /// Also emit the regular grid meshes (generate, generate_sized,
+    /// generate_with_borders, generate_globe) as primitive-restart triangle
+    /// strips, one per grid row plus one per skirt quad, for about a third of
+    /// the triangle list's indices. Other meshes, flat quads and flat-shaded
+    /// meshes keep only the triangle list. Strip indices are always 32-bit
+    #[wasm_bindgen]
+    pub fn set_triangle_strips(&mut self, enabled: bool) {
+        self.triangle_strips = enabled;
+    }
+
+    /// Also emit the vertices and indices in the meshoptimizer codecs
+    /// (EXT_meshopt_compression), for smaller copies out of a worker. The vertex
+    /// stream is the 32-byte position, normal, uv layout of the interleaved buffer;
+    /// decode with MeshoptDecoder.decodeVertexBuffer(target, count, 32, source) and
+    /// decodeIndexBuffer(target, index_count, 2 or 4, source). A LOD chain's pool
+    /// encodes its finest level; flat-shaded meshes have no index stream
+    #[wasm_bindgen]
+    pub fn set_meshopt_compression(&mut self, enabled: bool) {
+        self.meshopt = enabled;
+    }
+
+    /// Mesh constant-elevation heightmaps (open sea) as one quad of 2 triangles,
+    /// with skirts and normals as usual, instead of the LOD grid (default off)
+    /// Applies to generate(), generate_sized() and generate_stitched(); such
+    /// meshes report is_flat(). The quad has no edge vertices, so coarser
+    /// neighbors in generate_stitched() meet it with T-junctions, as skirts hide
+    #[wasm_bindgen]
+    pub fn set_flat_tile_quads(&mut self, enabled: bool) {
+        self.flat_quads = enabled;
+    }
+
+    /// Ramp land down to sea level over this many pixels where it meets sea
+    /// (no-data, stored as 0 m), instead of a vertical wall at the coastline;
+    /// 0 disables it (default). Applies to every mesh and the normal map. Sea
+    /// across a tile border is not seen, so the edge pixels may differ slightly
+    /// from the neighbor's; skirts hide the gap
+    #[wasm_bindgen]
+    pub fn set_coast_feather(&mut self, distance: f32) {
+        self.coast_feather = distance.max(0.0);
+    }
+
+    /// Emit per-vertex RGB colors by elevation from the color ramp (by default
+    /// green lowlands through brown to grey rock and snow above 3500 m), for
+    /// untextured terrain or PLY export
+    #[wasm_bindgen]
+    pub fn set_hypsometric_tint(&mut self, enabled: bool) {
+        self.hypsometric_tint = enabled;
+    }
+
+    /// Replace the hypsometric color ramp
+    /// stops: flat (elevation in meters, r, g, b) quadruples by increasing
+    /// elevation, channels 0-255; colors are interpolated linearly between
+    /// stops and held beyond the first and last
+    #[wasm_bindgen]
+    pub fn set_color_ramp(&mut self, stops: &[f32]) -> Result<(), Error> {
+        self.color_ramp = ColorRamp::from_stops(stops)?;
+        Ok(())
+    }
+
+    /// Emit a per-vertex slope angle in degrees (0 = level, 90 = vertical), from
+    /// the vertex normals, for blending rock, grass and snow by steepness
+    #[wasm_bindgen]
+    pub fn set_slope_attribute(&mut self, enabled: bool) {
+        self.slopes = enabled;
+    }
+
+    /// Emit four texture splat weights per vertex (snow, rock, forest, grass,
+    /// summing to 1) from elevation and slope, for a vec4 splat-map attribute
+    #[wasm_bindgen]
+    pub fn set_splat_weights(&mut self, enabled: bool) {
+        self.splat_weights = enabled;
+    }
+
+    /// Configure the splat weights (default 2500 m, 3000 m, 40°, 200 m, 10°)
+    /// tree_line: forest below, alpine grass above (meters)
+    /// snow_line: snow above (meters)
+    /// rock_slope: rock on slopes steeper than this (degrees)
+    /// blend_height, blend_slope: width of the transition bands around the
+    /// lines (meters) and around rock_slope (degrees); 0 gives hard edges
+    #[wasm_bindgen]
+    pub fn set_splat_thresholds(
+        &mut self,
+        tree_line: f32,
+        snow_line: f32,
+        rock_slope: f32,
+        blend_height: f32,
+        blend_slope: f32,
+    ) -> Result<(), Error> {
+        self.splat_rules =
+            SplatRules::new(tree_line, snow_line, rock_slope, blend_height, blend_slope)?;
+        Ok(())
+    }
+
+    /// Take vertex normals from the full-resolution heightmap (Sobel gradient at
+    /// each vertex) instead of averaging the mesh's face normals: smooth lighting
+    /// at coarse LODs and less work. Applies to the meshes built from a
+    /// heightmap; flat shading still uses face normals
+    #[wasm_bindgen]
+    pub fn set_heightmap_normals(&mut self, enabled: bool) {
+        self.heightmap_normals = enabled;
+    }
+
+    /// Reorder triangles for GPU vertex cache reuse (Forsyth's algorithm)
+    /// Costs some generation time but saves vertex shader runs on every draw;
+    /// every level of a LOD chain is reordered. Has no effect with flat shading
+    #[wasm_bindgen]
+    pub fn set_vertex_cache_optimization(&mut self, enabled: bool) {
+        self.vertex_cache = enabled;
+    }
+
+    /// Measure generate(), generate_sized(), generate_adaptive() and
+    /// generate_tin() meshes against the heightmap they were given (before
+    /// coastal feathering), for MeshData::max_deviation and mean_deviation.
+    /// Costs a pass over every pixel per mesh (default off)
+    #[wasm_bindgen]
+    pub fn set_fidelity_report(&mut self, enabled: bool) {
+        self.fidelity_report = enabled;
+    }
+
+    /// Replace the LOD table used by generate(), generate_sized() and generate_stitched()
+    /// steps: grid sample spacing in pixels per LOD level, coarsest first (default 8, 4, 2);
+    /// each a power of two from 1 (full 256x256 resolution) to 256
+    #[wasm_bindgen]
+    pub fn set_lod_steps(&mut self, steps: &[u32]) -> Result<(), Error> {
+        if steps.is_empty() {
+            return Err(Error::InvalidArgument(
+                "LOD table must have at least one level".into(),
+            ));
+        }
+        for (level, &step) in steps.iter().enumerate() {
+            if !step.is_power_of_two() || step > 256 {
+                return Err(Error::InvalidArgument(format!(
+                    "Invalid step {} for LOD level {}: expected a power of two from 1 to 256",
+                    step, level
+                )));
+            }
+            if level > 0 && step >= steps[level - 1] {
+                return Err(Error::InvalidArgument(
+                    "LOD steps must decrease from the coarsest to the finest level".into(),
+                ));
+            }
+        }
+        self.lod_steps = steps.iter().map(|&step| step as usize).collect();
+        Ok(())
+    }
+
+    /// Geometric error of each LOD level for a tile: the largest vertical
+    /// deviation (meters) of the level's grid surface from the full-resolution
+    /// heightmap, coarsest level first; feed it to select_lod()
+    /// elevations: 256x256 heightmap (65536 values)
+    #[wasm_bindgen]
+    pub fn lod_errors(&self, elevations: &[f32]) -> Result<Vec<f32>, Error> {
+        if elevations.len() != 65536 {
+            return Err(tile_length_error(elevations.len()));
+        }
+        let elevations = &*self.feathered(elevations, 256, 256);
+        let heightmap = Heightfield::new(elevations, 256, 256).unwrap();
+        Ok(self
+            .lod_steps
+            .iter()
+            .map(|&step| grid_error(&heightmap, step))
+            .collect())
+    }
+
+    /// The coarsest LOD level whose geometric error, projected to the screen,
+    /// stays within max_pixel_error (the finest level when none does)
+    /// lod_errors: geometric error per level in world units, coarsest first
+    /// (e.g. from lod_errors())
+    /// distance: camera distance to the tile in world units
+    /// fov_deg: vertical field of view in degrees
+    /// viewport_height: viewport height in pixels
+    /// max_pixel_error: tolerated screen-space error in pixels
+    #[wasm_bindgen]
+    pub fn select_lod(
+        lod_errors: &[f32],
+        distance: f32,
+        fov_deg: f32,
+        viewport_height: f32,
+        max_pixel_error: f32,
+    ) -> Result<u8, Error> {
+        if lod_errors.is_empty() {
+            return Err(Error::InvalidArgument("No LOD errors given".into()));
+        }
+        check_positive("fov_deg", fov_deg as f64)?;
+        check_positive("viewport_height", viewport_height as f64)?;
+        let finest = (lod_errors.len() - 1) as u8;
+        if distance <= 0.0 {
+            return Ok(finest);
+        }
+        // Pixels per world unit at the distance
+        let pixels_per_unit =
+            viewport_height / (2.0 * distance * (fov_deg.to_radians() / 2.0).tan());
+        Ok(lod_errors
+            .iter()
+            .position(|&error| error * pixels_per_unit <= max_pixel_error)
+            .map_or(finest, |level| level as u8))
+    }
+
+    /// Generate terrain mesh from elevation data
+    /// elevations: 256x256 heightmap (65536 values)
+    /// tile_size: size of tile in world units
+    /// lod_level: index into the LOD table (default 0=far, 1=mid, 2=near)
+    #[wasm_bindgen]
+    pub fn generate(
+        &self,
+        elevations: &[f32],
+        tile_size: f32,
+        lod_level: u8,
+    ) -> Result<MeshData, Error> {
+        check_positive("tile_size", tile_size as f64)?;
+        if elevations.len() != 65536 {
+            return Err(tile_length_error(elevations.len()));
+        }
+        self.generate_sized(elevations, 256, 256, tile_size, lod_level)
+    }
+
+    /// Generate terrain mesh for a tile relative to its world position
+    /// The vertices are those of generate(), and MeshData::origin holds the
+    /// tile's position in double precision (CoordinateTransform::tile_origin),
+    /// so far-off tiles at high zoom can be placed at origin - camera position
+    /// without the f32 jitter of tile_x * tile_size
+    /// elevations: 256x256 heightmap (65536 values)
+    /// tile_x, tile_y: tile indices
+    /// tile_size: size of tile in world units
+    /// lod_level: index into the LOD table (default 0=far, 1=mid, 2=near)
+    #[wasm_bindgen]
+    pub fn generate_tile(
+        &self,
+        elevations: &[f32],
+        tile_x: u32,
+        tile_y: u32,
+        tile_size: f32,
+        lod_level: u8,
+    ) -> Result<MeshData, Error> {
+        let mut mesh = self
+            .generate(elevations, tile_size, lod_level)?
+            .into_buffers();
+        mesh.origin = tile_origin(tile_x, tile_y, tile_size as f64);
+        Ok(MeshData::from(mesh))
+    }
+
+    /// Largest vertex count of a generate_into() mesh at a LOD level
+    #[wasm_bindgen]
+    pub fn max_vertex_count(&self, lod_level: u8) -> Result<u32, Error> {
+        let columns = grid_sample_count(256, self.lod_step(lod_level)?);
+        Ok((columns * columns) as u32)
+    }
+
+    /// Largest index count of a generate_into() mesh at a LOD level
+    #[wasm_bindgen]
+    pub fn max_index_count(&self, lod_level: u8) -> Result<u32, Error> {
+        let cells = grid_sample_count(256, self.lod_step(lod_level)?) - 1;
+        Ok((cells * cells * 6) as u32)
+    }
+
+    /// Generate the grid of generate() into preallocated buffers without
+    /// allocating: vertices, 32-bit indices and face-averaged normals only
+    /// (skirts, heightmap normals, flat quads and the optional buffers do
+    /// not apply; coastal feathering does, in the buffers' scratch rasters).
+    /// The buffers' memory stays in place, so views on it can be created once
+    /// and reused for every tile
+    /// elevations: 256x256 heightmap (65536 values)
+    /// tile_size: size of tile in world units
+    /// lod_level: index into the LOD table (default 0=far, 1=mid, 2=near)
+    /// out: buffers sized for at least max_vertex_count() and max_index_count()
+    #[wasm_bindgen]
+    pub fn generate_into(
+        &self,
+        elevations: &[f32],
+        tile_size: f32,
+        lod_level: u8,
+        out: &mut StagingBuffers,
+    ) -> Result<(), Error> {
+        check_positive("tile_size", tile_size as f64)?;
+        if elevations.len() != 65536 {
+            return Err(tile_length_error(elevations.len()));
+        }
+        let step = self.lod_step(lod_level)?;
+        let columns = grid_sample_count(256, step);
+        let (vertex_count, index_count) = (columns * columns, (columns - 1).pow(2) * 6);
+        if vertex_count > out.vertex_capacity() || index_count > out.index_capacity() {
+            return Err(Error::InvalidArgument(format!(
+                "Staging buffers too small: LOD {} needs {} vertices and {} indices",
+                lod_level, vertex_count, index_count
+            )));
+        }
+
+        // As grid_geometry(), written in place
+        let elevations = if self.coast_feather > 0.0 {
+            let heightmap = Heightfield::new(elevations, 256, 256).unwrap();
+            let (feathered, nearest) = (&mut out.feathered, &mut out.sea_distances);
+            heightmap.feather_coast_into(self.coast_feather, feathered, nearest);
+            &out.feathered
+        } else {
+            elevations
+        };
+        let pixel_size = tile_size / 256.0;
+        let sample = |k: usize| (k * step).min(255);
+        let vertices = &mut out.vertices[..vertex_count * 3];
+        for y in 0..columns {
+            for x in 0..columns {
+                let (px, py) = (sample(x), sample(y));
+                let vertex = &mut vertices[(y * columns + x) * 3..][..3];
+                vertex[0] = px as f32 * pixel_size - tile_size / 2.0;
+                vertex[1] = elevations[py * 256 + px];
+                vertex[2] = py as f32 * pixel_size - tile_size / 2.0;
+            }
+        }
+        let indices = &mut out.indices[..index_count];
+        for y in 0..columns - 1 {
+            for x in 0..columns - 1 {
+                let top_left = (y * columns + x) as u32;
+                let bottom_left = top_left + columns as u32;
+                let cell = &mut indices[(y * (columns - 1) + x) * 6..][..6];
+                cell.copy_from_slice(&[
+                    top_left,
+                    bottom_left,
+                    top_left + 1,
+                    top_left + 1,
+                    bottom_left,
+                    bottom_left + 1,
+                ]);
+            }
+        }
+        let normals = &mut out.normals[..vertex_count * 3];
+        normals.fill(0.0);
+        write_normals(vertices, indices, normals);
+        out.vertex_count = vertex_count;
+        out.index_count = index_count;
+        Ok(())
+    }
+
+    /// Generate a uniform grid mesh from a heightmap of any size
+    /// (e.g. 512px tiles or several tiles mosaicked into one raster)
+    /// elevations: width x height heightmap, at least the LOD step on each side;
+    /// the last row and column are always sampled, whatever the step
+    /// tile_size: world size of the heightmap's width; the depth follows the aspect ratio
+    /// lod_level: index into the LOD table (default 0=far, 1=mid, 2=near)
+    #[wasm_bindgen]
+    pub fn generate_sized(
+        &self,
+        elevations: &[f32],
+        width: u32,
+        height: u32,
+        tile_size: f32,
+        lod_level: u8,
+    ) -> Result<MeshData, Error> {
+        check_positive("tile_size", tile_size as f64)?;
+        Heightfield::from_slice(elevations, width, height)?;
+        if width < 2 || height < 2 {
+            return Err(Error::InvalidArgument(
+                "Heightmap must be at least 2x2".into(),
+            ));
+        }
+
+        let step = self.lod_step(lod_level)?;
+        let (width, height) = (width as usize, height as usize);
+        if width < step || height < step {
+            return Err(Error::InvalidArgument(format!(
+                "Heightmap of {}x{} is smaller than the LOD {} step of {} pixels",
+                width, height, lod_level, step
+            )));
+        }
+        let source = Heightfield::new(elevations, width, height).unwrap();
+        let elevations = &*self.feathered(elevations, width, height);
+        if self.flat_quads && is_constant(elevations) {
+            let mesh = self.flat_quad(elevations, width, height, tile_size);
+            return Ok(self.report_fidelity(mesh, &source, tile_size));
+        }
+        let (vertices, indices) = grid_geometry(elevations, width, height, tile_size, step);
+        let columns = grid_sample_count(width, step);
+        let grid = (columns, grid_sample_count(height, step));
+        let extent = (tile_size, tile_size * height as f32 / width as f32);
+        let heightmap = Heightfield::new(elevations, width, height);
+        let mesh = self.build_grid_mesh(vertices, indices, extent, Some(grid), heightmap.as_ref());
+        Ok(self.report_fidelity(mesh, &source, tile_size))
+    }
+
+    /// Generate a grid mesh of only a rectangular window of the heightmap
+    /// Vertices keep their positions in the full tile, and interior samples fall on
+    /// the same pixels generate() uses at this LOD, so the region can replace that
+    /// part of a full-tile mesh (partial updates) or clip it to the visible extent.
+    /// The window's own edges are always sampled.
+    /// elevations: 256x256 heightmap (65536 values)
+    /// x0, y0, width, height: window in heightmap pixels (at least 2x2)
+    /// tile_size: size of tile in world units
+    /// lod_level: index into the LOD table (default 0=far, 1=mid, 2=near)
+    #[wasm_bindgen]
+    #[allow(clippy::too_many_arguments)]
+    pub fn generate_region(
+        &self,
+        elevations: &[f32],
+        x0: u32,
+        y0: u32,
+        width: u32,
+        height: u32,
+        tile_size: f32,
+        lod_level: u8,
+    ) -> Result<MeshData, Error> {
+        check_positive("tile_size", tile_size as f64)?;
+        if elevations.len() != 65536 {
+            return Err(tile_length_error(elevations.len()));
+        }
+        // Widened so windows near u32::MAX cannot wrap around
+        let inside = x0 as u64 + width as u64 <= 256 && y0 as u64 + height as u64 <= 256;
+        if width < 2 || height < 2 || !inside {
+            return Err(Error::InvalidArgument(format!(
+                "Invalid region: {}x{} at ({}, {}), expected at least 2x2 within 256x256",
+                width, height, x0, y0
+            )));
+        }
+
+        let step = self.lod_step(lod_level)?;
+        let elevations = &*self.feathered(elevations, 256, 256);
+        let samples = |start: u32, length: u32| -> Vec<usize> {
+            let (first, last) = (start as usize, (start + length - 1) as usize);
+            let mut samples = vec![first];
+            samples.extend(
+                (first / step + 1..)
+                    .map(|k| k * step)
+                    .take_while(|&p| p < last),
+            );
+            samples.push(last);
+            samples
+        };
+        let (vertices, indices) = sampled_grid_geometry(
+            elevations,
+            256,
+            256,
+            tile_size,
+            &samples(x0, width),
+            &samples(y0, height),
+        );
+        let heightmap = Heightfield::new(elevations, 256, 256);
+        Ok(self.build_mesh(vertices, indices, tile_size, heightmap.as_ref()))
+    }
+
+    /// Apply changed heights inside a window of the heightmap to a generate() mesh
+    /// Only the grid vertices sampling a pixel of the window move; their normals,
+    /// their neighbors' normals and the skirts below them follow, and the derived
+    /// buffers (tangents, morph targets, colors, slopes, splat weights, bounds and
+    /// the packed buffers) are refreshed with this generator's options. Normals are
+    /// face-averaged even with heightmap normals, and coastal feathering is not
+    /// applied to the new values. Flat quads and flat-shaded meshes must be
+    /// regenerated instead.
+    /// mesh: a generate() mesh at lod_level
+    /// x0, y0, width, height: window in heightmap pixels
+    /// new_values: width x height elevations for the window, row by row
+    /// lod_level: the LOD level the mesh was generated at
+    /// Returns the number of grid vertices that moved
+    #[wasm_bindgen]
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_region(
+        &self,
+        mesh: &mut MeshData,
+        x0: u32,
+        y0: u32,
+        width: u32,
+        height: u32,
+        new_values: &[f32],
+        lod_level: u8,
+    ) -> Result<usize, Error> {
+        let step = self.lod_step(lod_level)?;
+        // Checked before narrowing to usize, which is 32 bits on wasm32
+        let inside = x0 as u64 + width as u64 <= 256 && y0 as u64 + height as u64 <= 256;
+        if width == 0 || height == 0 || !inside {
+            return Err(Error::InvalidArgument(
+                "Region must be non-empty and inside the 256x256 tile".into(),
+            ));
+        }
+        let (x0, y0, width, height) = (x0 as usize, y0 as usize, width as usize, height as usize);
+        if new_values.len() != width * height {
+            return Err(Error::InvalidArgument(format!(
+                "Invalid region length: {}, expected {} ({}x{})",
+                new_values.len(),
+                width * height,
+                width,
+                height
+            )));
+        }
+        let samples = grid_samples(256, step);
+        let columns = samples.len();
+        let mesh = &mut mesh.buffers;
+        let indexed = !mesh.indices.is_empty() || !mesh.indices_u16.is_empty();
+        if mesh.flat || !indexed || mesh.vertices.len() / 3 < columns * columns {
+            return Err(Error::InvalidArgument(
+                "Mesh is not an indexed generate() grid at this LOD level".into(),
+            ));
+        }
+
+        // Grid vertices sampling a changed pixel, with their previous height
+        let mut changed = Vec::new();
+        for (row, &y) in samples.iter().enumerate() {
+            if !(y0..y0 + height).contains(&y) {
+                continue;
+            }
+            for (column, &x) in samples.iter().enumerate() {
+                if !(x0..x0 + width).contains(&x) {
+                    continue;
+                }
+                let vertex = row * columns + column;
+                changed.push((vertex, mesh.vertices[vertex * 3 + 1]));
+                mesh.vertices[vertex * 3 + 1] = new_values[(y - y0) * width + x - x0];
+            }
+        }
+        if changed.is_empty() {
+            return Ok(0);
+        }
+        refresh_grid_vertices(mesh, columns, &changed);
+        // Measured against the previous heightmap
+        mesh.max_deviation = None;
+        mesh.mean_deviation = None;
+
+        self.add_surface_attributes(mesh);
+        let u16_indices = !mesh.indices_u16.is_empty();
+        mesh.indices = mesh.triangle_corners();
+        mesh.indices_u16 = Vec::new();
+        self.pack_buffers(mesh, u16_indices);
+        Ok(changed.len())
+    }
+
+    /// Generate a uniform grid mesh of only the area inside a per-pixel mask
+    /// (e.g. one national park); a triangle is kept when the mask is set at all
+    /// three of its grid vertices, so parts narrower than the LOD spacing drop out.
+    /// Skirts, when enabled, also hang from the mask outline.
+    /// elevations: 256x256 heightmap (65536 values)
+    /// mask: 256x256 bytes, nonzero inside the region
+    /// tile_size: size of tile in world units
+    /// lod_level: index into the LOD table (default 0=far, 1=mid, 2=near)
+    #[wasm_bindgen]
+    pub fn generate_masked(
+        &self,
+        elevations: &[f32],
+        mask: &[u8],
+        tile_size: f32,
+        lod_level: u8,
+    ) -> Result<MeshData, Error> {
+        check_positive("tile_size", tile_size as f64)?;
+        if elevations.len() != 65536 {
+            return Err(tile_length_error(elevations.len()));
+        }
+        if mask.len() != 65536 {
+            return Err(Error::InvalidArgument(format!(
+                "Invalid mask length: {}, expected 65536",
+                mask.len()
+            )));
+        }
+
+        let step = self.lod_step(lod_level)?;
+        let elevations = &*self.feathered(elevations, 256, 256);
+        let (vertices, indices) = grid_geometry(elevations, 256, 256, tile_size, step);
+        let grid_size = grid_sample_count(256, step);
+        let pixel = |k: usize| (k * step).min(255);
+        let inside: Vec<bool> = (0..grid_size * grid_size)
+            .map(|v| mask[pixel(v / grid_size) * 256 + pixel(v % grid_size)] != 0)
+            .collect();
+
+        let kept: Vec<u32> = indices
+            .chunks_exact(3)
+            .filter(|triangle| triangle.iter().all(|&i| inside[i as usize]))
+            .flatten()
+            .copied()
+            .collect();
+        let (vertices, indices) = compact_vertices(vertices, kept);
+        let heightmap = Heightfield::new(elevations, 256, 256);
+        Ok(self.build_mesh(vertices, indices, tile_size, heightmap.as_ref()))
+    }
+
+    /// Generate a uniform grid mesh clipped to a polygon, or with a polygon
+    /// cut out of it (e.g. a prefecture boundary, or a hole where a dam or
+    /// stadium model goes): generate_masked() with polygon_mask()
+    /// elevations: 256x256 heightmap (65536 values)
+    /// polygon: rings as in polygon_mask()
+    /// hole: cut the polygon out instead of keeping only its inside
+    /// tile_size: size of tile in world units
+    /// lod_level: index into the LOD table (default 0=far, 1=mid, 2=near)
+    #[wasm_bindgen]
+    pub fn generate_clipped(
+        &self,
+        elevations: &[f32],
+        polygon: &[f32],
+        hole: bool,
+        tile_size: f32,
+        lod_level: u8,
+    ) -> Result<MeshData, Error> {
+        let mut mask = MeshGenerator::polygon_mask(polygon)?;
+        if hole {
+            mask.iter_mut().for_each(|inside| *inside = 255 - *inside);
+        }
+        self.generate_masked(elevations, &mask, tile_size, lod_level)
+    }
+
+    /// Rasterize a polygon into a 256x256 mask for generate_masked()
+    /// (255 inside, 0 outside, by the even-odd rule so inner rings are holes)
+    /// polygon: x, y pairs in heightmap pixels, where the vertex of pixel x sits
+    /// (x = 0 is the western edge's vertices, x = 255 the eastern); points may lie
+    /// outside the tile. Rings are closed implicitly and separated by a NaN pair
+    #[wasm_bindgen]
+    pub fn polygon_mask(polygon: &[f32]) -> Result<Vec<u8>, Error> {
+        if !polygon.len().is_multiple_of(2) {
+            return Err(Error::InvalidArgument(format!(
+                "Invalid polygon length: {}, expected x, y pairs",
+                polygon.len()
+            )));
+        }
+        let points: Vec<[f32; 2]> = polygon.chunks_exact(2).map(|p| [p[0], p[1]]).collect();
+        let rings: Vec<&[[f32; 2]]> = points
+            .split(|p| p[0].is_nan() && p[1].is_nan())
+            .filter(|ring| !ring.is_empty())
+            .collect();
+        let invalid = |ring: &&[[f32; 2]]| {
+            ring.len() < 3 || ring.iter().flatten().any(|value| !value.is_finite())
+        };
+        if rings.iter().any(invalid) {
+            return Err(Error::InvalidArgument(
+                "Polygon rings need at least 3 finite points".into(),
+            ));
+        }
+
+        let mut mask = vec![0u8; 65536];
+        let mut crossings = Vec::new();
+        for y in 0..256 {
+            let row = y as f32;
+            crossings.clear();
+            for ring in &rings {
+                for (i, a) in ring.iter().enumerate() {
+                    let b = ring[(i + 1) % ring.len()];
+                    if (a[1] <= row) != (b[1] <= row) {
+                        crossings.push(a[0] + (row - a[1]) / (b[1] - a[1]) * (b[0] - a[0]));
+                    }
+                }
+            }
+            crossings.sort_unstable_by(f32::total_cmp);
+            for span in crossings.chunks_exact(2) {
+                let start = span[0].ceil().clamp(0.0, 256.0) as usize;
+                let end = span[1].ceil().clamp(0.0, 256.0) as usize;
+                mask[y * 256 + start..y * 256 + end].fill(255);
+            }
+        }
+        Ok(mask)
+    }
+
+    /// Generate a uniform grid mesh whose edges match coarser neighbors exactly
+    /// Edge vertices that a coarser neighbor does not have are collapsed onto the
+    /// neighbor's vertices, removing T-junctions so the terrain is watertight
+    /// without skirts. Pass the tile's own lod_level for edges with no coarser neighbor.
+    /// neighbor_lods: LOD level of the north, east, south and west neighbors
+    #[wasm_bindgen]
+    pub fn generate_stitched(
+        &self,
+        elevations: &[f32],
+        tile_size: f32,
+        lod_level: u8,
+        neighbor_lods: &[u8],
+    ) -> Result<MeshData, Error> {
+        check_positive("tile_size", tile_size as f64)?;
+        if elevations.len() != 65536 {
+            return Err(tile_length_error(elevations.len()));
+        }
+        if neighbor_lods.len() != 4 {
+            return Err(Error::InvalidArgument(format!(
+                "Invalid neighbor LOD count: {}, expected 4 (N, E, S, W)",
+                neighbor_lods.len()
+            )));
+        }
+
+        let step = self.lod_step(lod_level)?;
+
+        // Ratio between the neighbor's sample spacing and ours along each edge;
+        // neighbors at the same or finer detail leave the edge untouched
+        let mut ratios = [1; 4];
+        for (ratio, &neighbor) in ratios.iter_mut().zip(neighbor_lods) {
+            *ratio = (self.lod_step(neighbor)? / step).max(1);
+        }
+        let elevations = &*self.feathered(elevations, 256, 256);
+        if self.flat_quads && is_constant(elevations) {
+            return Ok(self.flat_quad(elevations, 256, 256, tile_size));
+        }
+        let (vertices, indices) = grid_geometry(elevations, 256, 256, tile_size, step);
+        let grid_size = grid_sample_count(256, step);
+        let [north, east, south, west] = ratios;
+
+        let last = grid_size - 1;
+        let mut remap: Vec<u32> = (0..(grid_size * grid_size) as u32).collect();
+        for i in 0..grid_size {
+            remap[i] = (i / north * north) as u32;
+            remap[last * grid_size + i] = (last * grid_size + i / south * south) as u32;
+        }
+        for i in 1..last {
+            remap[i * grid_size] = (i / west * west * grid_size) as u32;
+            remap[i * grid_size + last] = (i / east * east * grid_size + last) as u32;
+        }
+
+        // Drop the triangles that collapse to zero area
+        let mut stitched = Vec::with_capacity(indices.len());
+        for triangle in indices.chunks_exact(3) {
+            let (a, b, c) = (
+                remap[triangle[0] as usize],
+                remap[triangle[1] as usize],
+                remap[triangle[2] as usize],
+            );
+            if a != b && b != c && a != c {
+                stitched.extend_from_slice(&[a, b, c]);
+            }
+        }
+
+        let (vertices, indices) = compact_vertices(vertices, stitched);
+        let heightmap = Heightfield::new(elevations, 256, 256);
+        Ok(self.build_mesh(vertices, indices, tile_size, heightmap.as_ref()))
+    }
+
+    /// Generate several uniform grid LODs over one shared vertex pool
+    /// Level k samples every 2^(levels - k) pixels, so levels = 3 reproduces LOD 0-2
+    /// of generate(); skirts, tangents, interleaving and quantization apply to the
+    /// pool, while indices are always 32-bit
+    /// elevations: 256x256 heightmap (65536 values)
+    /// tile_size: size of tile in world units
+    /// levels: number of LOD levels (1-7)
+    #[wasm_bindgen]
+    pub fn generate_lod_chain(
+        &self,
+        elevations: &[f32],
+        tile_size: f32,
+        levels: u8,
+    ) -> Result<LodChain, Error> {
+        check_positive("tile_size", tile_size as f64)?;
+        if elevations.len() != 65536 {
+            return Err(tile_length_error(elevations.len()));
+        }
+        if !(1..=7).contains(&levels) {
+            return Err(Error::InvalidArgument(
+                "Invalid LOD level count (1-7)".into(),
+            ));
+        }
+
+        // The finest grid holds every vertex of the coarser grids
+        const FINEST_STEP: usize = 2;
+        let elevations = &*self.feathered(elevations, 256, 256);
+        let (vertices, finest) = grid_geometry(elevations, 256, 256, tile_size, FINEST_STEP);
+        let pool_size = grid_sample_count(256, FINEST_STEP);
+
+        let mut level_indices = Vec::with_capacity(levels as usize);
+        for level in 0..levels as usize - 1 {
+            let ratio = 1 << (levels as usize - 1 - level);
+            let grid_size = (pool_size - 1) / ratio + 1;
+            let pool_index = |x: usize, y: usize| (y * ratio * pool_size + x * ratio) as u32;
+            let mut indices = Vec::with_capacity((grid_size - 1) * (grid_size - 1) * 6);
+            for y in 0..(grid_size - 1) {
+                for x in 0..(grid_size - 1) {
+                    let (idx0, idx1) = (pool_index(x, y), pool_index(x + 1, y));
+                    let (idx2, idx3) = (pool_index(x, y + 1), pool_index(x + 1, y + 1));
+                    indices.extend_from_slice(&[idx0, idx2, idx1, idx1, idx2, idx3]);
+                }
+            }
+            level_indices.push(indices);
+        }
+        level_indices.push(finest.clone());
+
+        // Normals come from the finest surface and are shared by every level
+        let extent = (tile_size, tile_size);
+        let heightmap = Heightfield::new(elevations, 256, 256);
+        let mut pool = self.build_mesh_with(vertices, finest, extent, None, heightmap.as_ref());
+        if self.skirt_depth > 0.0 {
+            let mut skirt_vertex = HashMap::new();
+            for indices in &mut level_indices {
+                add_skirts_to(&mut pool, indices, &mut skirt_vertex, self.skirt_depth);
+            }
+            pool.indices = level_indices[level_indices.len() - 1].clone();
+        }
+        if self.vertex_cache {
+            let vertex_count = pool.vertices.len() / 3;
+            for indices in &mut level_indices {
+                *indices = optimize_vertex_cache(indices, vertex_count);
+            }
+            pool.indices = level_indices[level_indices.len() - 1].clone();
+        }
+        self.finish_mesh(&mut pool, false);
+
+        Ok(LodChain {
+            pool,
+            levels: level_indices,
+        })
+    }
+
+    /// Generate a grid mesh whose edge normals account for the neighboring tiles
+    /// Normals computed from one tile alone bend at its edges, which shows as a
+    /// lighting seam; here the edge vertices take the Sobel normal of the
+    /// heightmap extended by one pixel of each neighbor, so both sides of a
+    /// border match (with either normal mode).
+    /// elevations: 256x256 heightmap (65536 values)
+    /// borders: 1024 neighbor pixels, 256 each: the row north of the tile
+    /// (west to east), the column east of it (north to south), the row south
+    /// and the column west; NaN where a neighbor is missing (see Mosaic::tile_borders)
+    /// tile_size: size of tile in world units
+    /// lod_level: index into the LOD table (default 0=far, 1=mid, 2=near)
+    #[wasm_bindgen]
+    pub fn generate_with_borders(
+        &self,
+        elevations: &[f32],
+        borders: &[f32],
+        tile_size: f32,
+        lod_level: u8,
+    ) -> Result<MeshData, Error> {
+        check_positive("tile_size", tile_size as f64)?;
+        if elevations.len() != 65536 {
+            return Err(tile_length_error(elevations.len()));
+        }
+        if borders.len() != 1024 {
+            return Err(Error::InvalidArgument(format!(
+                "Invalid border length: {}, expected 1024 (N, E, S, W rows of 256)",
+                borders.len()
+            )));
+        }
+
+        let step = self.lod_step(lod_level)?;
+        let elevations = &*self.feathered(elevations, 256, 256);
+        let padded = pad_with_borders(elevations, borders);
+        let padded = Heightfield::new(&padded, 258, 258).unwrap();
+        let (vertices, indices) = grid_geometry(elevations, 256, 256, tile_size, step);
+        let grid = (grid_sample_count(256, step), grid_sample_count(256, step));
+        let extent = (tile_size, tile_size);
+        let heightmap = Heightfield::new(elevations, 256, 256);
+        let mut mesh =
+            self.build_mesh_with(vertices, indices, extent, Some(grid), heightmap.as_ref());
+
+        let pixel_size = tile_size / 256.0;
+        for (vertex, normal) in mesh
+            .vertices
+            .chunks_exact(3)
+            .zip(mesh.normals.chunks_exact_mut(3))
+        {
+            let px = ((vertex[0] + tile_size / 2.0) / pixel_size).round() as usize;
+            let py = ((vertex[2] + tile_size / 2.0) / pixel_size).round() as usize;
+            if px == 0 || py == 0 || px == 255 || py == 255 {
+                let (dzdx, dzdy) = padded.gradient(px + 1, py + 1, pixel_size);
+                let n = Vec3::new(-dzdx, 1.0, -dzdy).normalize();
+                normal.copy_from_slice(&[n.x, n.y, n.z]);
+            }
+        }
+        if self.tangents {
+            mesh.tangents =
+                compute_tangents(&mesh.vertices, &mesh.indices, &mesh.normals, &mesh.uvs);
+        }
+        Ok(self.finish_grid_mesh(mesh))
+    }
+
+    /// Generate a grid mesh on the WGS84 ellipsoid for globe views
+    /// Vertices are ECEF positions in meters relative to the tile center at
+    /// height 0 (MeshData::origin), so neighboring tiles meet on a curved
+    /// earth; skirts hang toward the earth's center. Normals and tangents are
+    /// in ECEF; uvs, colors, slopes and splat weights are as for generate()
+    /// elevations: 256x256 heightmap (65536 values)
+    /// tile_x, tile_y, zoom: the tile's Web Mercator (GSI) address
+    /// lod_level: index into the LOD table (default 0=far, 1=mid, 2=near)
+    #[wasm_bindgen]
+    pub fn generate_globe(
+        &self,
+        elevations: &[f32],
+        tile_x: u32,
+        tile_y: u32,
+        zoom: u8,
+        lod_level: u8,
+    ) -> Result<MeshData, Error> {
+        check_zoom(zoom)?;
+        check_tile_index("tile_x", tile_x, zoom)?;
+        check_tile_index("tile_y", tile_y, zoom)?;
+        if elevations.len() != 65536 {
+            return Err(tile_length_error(elevations.len()));
+        }
+
+        let step = self.lod_step(lod_level)?;
+        let elevations = &*self.feathered(elevations, 256, 256);
+        let globe = GlobeTile::new(tile_x, tile_y, zoom);
+        let tile_size = globe.ground_size() as f32;
+        let (vertices, indices) = grid_geometry(elevations, 256, 256, tile_size, step);
+        let grid = (grid_sample_count(256, step), grid_sample_count(256, step));
+        let extent = (tile_size, tile_size);
+        let heightmap = Heightfield::new(elevations, 256, 256);
+        let mut mesh =
+            self.build_mesh_with(vertices, indices, extent, Some(grid), heightmap.as_ref());
+
+        self.arrange_grid_mesh(&mut mesh);
+        self.add_surface_attributes(&mut mesh);
+        globe.project(&mut mesh);
+        self.pack_buffers(&mut mesh, self.u16_indices);
+        Ok(MeshData::from(mesh))
+    }
+
+    /// Generate an adaptive (RTIN) terrain mesh from elevation data
+    /// Flat areas get large triangles while rugged areas keep detail; the
+    /// vertical error of the surface never exceeds max_error (meters)
+    /// elevations: 256x256 heightmap (65536 values)
+    /// tile_size: size of tile in world units
+    #[wasm_bindgen]
+    pub fn generate_adaptive(&self, elevations: &[f32], tile_size: f32) -> Result<MeshData, Error> {
+        check_positive("tile_size", tile_size as f64)?;
+        if elevations.len() != 65536 {
+            return Err(tile_length_error(elevations.len()));
+        }
+
+        // RTIN needs a (2^k + 1) grid; resample the 256x256 pixels onto a 257x257
+        // grid spanning the same extent as the uniform grid (pixel 0 to pixel 255)
+        const GRID_SIZE: usize = 257;
+        let grid_to_pixel = 255.0 / (GRID_SIZE - 1) as f32;
+        let source = Heightfield::new(elevations, 256, 256).unwrap();
+        let elevations = &*self.feathered(elevations, 256, 256);
+        let heightmap = Heightfield::new(elevations, 256, 256).unwrap();
+        let mut heights = Vec::with_capacity(GRID_SIZE * GRID_SIZE);
+        for gy in 0..GRID_SIZE {
+            for gx in 0..GRID_SIZE {
+                let (px, py) = (gx as f32 * grid_to_pixel, gy as f32 * grid_to_pixel);
+                heights.push(heightmap.sample(px, py));
+            }
+        }
+
+        let rtin = Rtin::new(GRID_SIZE);
+        let errors = rtin.errors(&heights);
+        let selected = rtin.mesh(&errors, self.max_error);
+
+        let pixel_size = tile_size / 256.0;
+        let mut vertices = Vec::with_capacity(selected.vertices.len() * 3);
+        for &(gx, gy) in &selected.vertices {
+            let (gx, gy) = (gx as usize, gy as usize);
+            vertices.push(gx as f32 * grid_to_pixel * pixel_size - tile_size / 2.0);
+            vertices.push(heights[gy * GRID_SIZE + gx]);
+            vertices.push(gy as f32 * grid_to_pixel * pixel_size - tile_size / 2.0);
+        }
+
+        let indices = selected.triangles;
+        let mesh = self.build_mesh(vertices, indices, tile_size, Some(&heightmap));
+        Ok(self.report_fidelity(mesh, &source, tile_size))
+    }
+
+    /// Generate a Delaunay TIN by greedy point insertion
+    /// Points are inserted where the surface error is largest until the error
+    /// falls to max_error or the triangle budget is reached
+    /// elevations: 256x256 heightmap (65536 values)
+    /// tile_size: size of tile in world units
+    /// max_triangles: triangle budget per tile (0 = limited by max_error only)
+    #[wasm_bindgen]
+    pub fn generate_tin(
+        &self,
+        elevations: &[f32],
+        tile_size: f32,
+        max_triangles: u32,
+    ) -> Result<MeshData, Error> {
+        check_positive("tile_size", tile_size as f64)?;
+        Heightfield::new(elevations, 256, 256)
+            .ok_or_else(|| tile_length_error(elevations.len()))?;
+        if max_triangles != 0 && max_triangles < 2 {
+            return Err(Error::InvalidArgument(
+                "Triangle budget must be at least 2".into(),
+            ));
+        }
+
+        let source = Heightfield::new(elevations, 256, 256).unwrap();
+        let elevations = &*self.feathered(elevations, 256, 256);
+        let heightmap = Heightfield::new(elevations, 256, 256).unwrap();
+        let mut tin = DelaunayTin::new(heightmap);
+        tin.run(self.max_error, max_triangles as usize);
+
+        let pixel_size = tile_size / 256.0;
+        let mut vertices = Vec::with_capacity(tin.coords.len() * 3);
+        for &(x, y) in &tin.coords {
+            vertices.push(x as f32 * pixel_size - tile_size / 2.0);
+            vertices.push(elevations[y as usize * 256 + x as usize]);
+            vertices.push(y as f32 * pixel_size - tile_size / 2.0);
+        }
+
+        let indices: Vec<u32> = tin.triangles.iter().map(|&i| i as u32).collect();
+        let mesh = self.build_mesh(vertices, indices, tile_size, Some(&heightmap));
+        Ok(self.report_fidelity(mesh, &source, tile_size))
+    }
+
+    /// Generate a mesh draping a rectangle over the terrain, for decals such as
+    /// a crosshair, a selection box or a photo footprint; uvs run 0..1 across it
+    /// Triangles follow the heightmap within max_error (finer on rugged ground),
+    /// and the parts beyond the tile are clipped off, so the mesh may be empty.
+    /// elevations: 256x256 heightmap (65536 values)
+    /// tile_size: size of tile in world units (as for the terrain mesh)
+    /// center_x, center_z: decal center in the tile's world coordinates
+    /// width, depth: decal size in world units along its u and v axes
+    /// rotation: radians counter-clockwise seen from above (0 = u east, v south)
+    /// lift: height added to every vertex against z-fighting (world units)
+    #[wasm_bindgen]
+    #[allow(clippy::too_many_arguments)]
+    pub fn generate_decal(
+        &self,
+        elevations: &[f32],
+        tile_size: f32,
+        center_x: f32,
+        center_z: f32,
+        width: f32,
+        depth: f32,
+        rotation: f32,
+        lift: f32,
+    ) -> Result<MeshData, Error> {
+        check_positive("tile_size", tile_size as f64)?;
+        Heightfield::new(elevations, 256, 256)
+            .ok_or_else(|| tile_length_error(elevations.len()))?;
+        if !(width > 0.0 && depth > 0.0 && width.is_finite() && depth.is_finite()) {
+            return Err(Error::InvalidArgument(format!(
+                "Invalid decal size: {}x{}, expected positive numbers",
+                width, depth
+            )));
+        }
+        if ![center_x, center_z, rotation, lift]
+            .iter()
+            .all(|v| v.is_finite())
+        {
+            return Err(Error::InvalidArgument(
+                "Decal center, rotation and lift must be finite".into(),
+            ));
+        }
+
+        let elevations = &*self.feathered(elevations, 256, 256);
+        let heightmap = Heightfield::new(elevations, 256, 256).unwrap();
+        let decal = Decal {
+            center: (center_x, center_z),
+            size: (width, depth),
+            rotation,
+            lift,
+        };
+        let geometry = drape_decal(&heightmap, tile_size, &decal, self.max_error);
+
+        let extent = (tile_size, tile_size);
+        let mut mesh = self.build_mesh_with(
+            geometry.vertices,
+            geometry.indices,
+            extent,
+            None,
+            Some(&heightmap),
+        );
+        mesh.uvs = geometry.uvs;
+        if self.tangents {
+            mesh.tangents =
+                compute_tangents(&mesh.vertices, &mesh.indices, &mesh.normals, &mesh.uvs);
+        }
+        self.finish_mesh(&mut mesh, self.u16_indices);
+        Ok(MeshData::from(mesh))
+    }
+
+    /// Height of the heightmap surface at a point in mesh coordinates (the
+    /// generate_sized placement for tile_size), interpolated over the
+    /// full-resolution grid triangles with coastal feathering applied, or
+    /// undefined outside the heightmap; the exact surface a LOD mesh approximates
+    /// elevations: 256x256 heightmap (65536 values)
+    /// tile_size: size of tile in world units (as for the terrain mesh)
+    #[wasm_bindgen]
+    pub fn elevation_at(
+        &self,
+        elevations: &[f32],
+        tile_size: f32,
+        x: f32,
+        z: f32,
+    ) -> Result<Option<f32>, Error> {
+        check_positive("tile_size", tile_size as f64)?;
+        Heightfield::new(elevations, 256, 256)
+            .ok_or_else(|| tile_length_error(elevations.len()))?;
+        let elevations = &*self.feathered(elevations, 256, 256);
+        let heightmap = Heightfield::new(elevations, 256, 256).unwrap();
+
+        let pixel_size = tile_size / 256.0;
+        let px = (x + tile_size / 2.0) / pixel_size;
+        let py = (z + tile_size / 2.0) / pixel_size;
+        if !(0.0..=255.0).contains(&px) || !(0.0..=255.0).contains(&py) {
+            return Ok(None);
+        }
+        Ok(Some(heightmap.sample_triangulated(px, py)))
+    }
+
+    /// Generate a flat water plane at sea_level covering the terrain at or below it
+    /// The shoreline follows the full-resolution heightmap, so coastal tiles can
+    /// draw the sea as a second mesh; the mesh is empty when all ground is above
+    /// sea_level.
+    /// elevations: 256x256 heightmap (65536 values)
+    /// sea_level: water height (GSI tiles store the sea as 0, so use a little above 0)
+    /// tile_size: size of tile in world units (as for the terrain mesh)
+    #[wasm_bindgen]
+    pub fn generate_water(
+        &self,
+        elevations: &[f32],
+        sea_level: f32,
+        tile_size: f32,
+    ) -> Result<MeshData, Error> {
+        check_positive("tile_size", tile_size as f64)?;
+        let heightmap = Heightfield::new(elevations, 256, 256)
+            .ok_or_else(|| tile_length_error(elevations.len()))?;
+        let (vertices, indices) = water_geometry(&heightmap, tile_size, sea_level);
+
+        let extent = (tile_size, tile_size);
+        let mut mesh = self.build_mesh_with(vertices, indices, extent, None, None);
+        self.finish_mesh(&mut mesh, self.u16_indices);
+        Ok(MeshData::from(mesh))
+    }
+
+    /// Merge several meshes into one vertex and index buffer for a single draw call
+    /// (e.g. distant tiles). Each mesh is translated by its offset; tangents,
+    /// colors, slopes, splat weights and morph targets are kept when every mesh
+    /// has them, and the packed buffers (interleaved, quantized, meshopt, edges,
+    /// 16-bit indices) follow this generator's options. The meshes are consumed.
+    /// offsets: x, y, z per mesh (e.g. origin - batch origin for generate_tile meshes)
+    #[wasm_bindgen]
+    pub fn merge(&self, meshes: Vec<MeshData>, offsets: &[f32]) -> Result<MeshData, Error> {
+        if offsets.len() != meshes.len() * 3 {
+            return Err(Error::InvalidArgument(format!(
+                "Invalid offsets length: {}, expected 3 per mesh ({})",
+                offsets.len(),
+                meshes.len() * 3
+            )));
+        }
+        let offsets: Vec<Vec3> = offsets.chunks_exact(3).map(Vec3::from_slice).collect();
+        let mut mesh = merge_meshes(&meshes, &offsets);
+        self.pack_buffers(&mut mesh, self.u16_indices);
+        Ok(MeshData::from(mesh))
+    }
+
+    /// Weld vertices closer than tolerance into one, e.g. the duplicated edge
+    /// vertices of neighboring tiles after merge(), so the mesh is watertight
+    /// for export. A welded vertex takes the averaged normals and tangents of
+    /// its copies (shading the shared edge from both sides) and the other
+    /// attributes, uvs included, of the first copy; triangles that collapse are
+    /// dropped. Merge tiles generated without skirts, or the skirts stay behind
+    /// as walls inside the mesh. The packed buffers follow this generator's options
+    /// tolerance: largest distance between welded vertices, in world units
+    /// (0 welds only identical positions)
+    #[wasm_bindgen]
+    pub fn weld(&self, mesh: &MeshData, tolerance: f32) -> MeshData {
+        let mut welded = weld_vertices(mesh, tolerance.max(0.0));
+        self.pack_buffers(&mut welded, self.u16_indices);
+        MeshData::from(welded)
+    }
+
+    /// Generate a 256x256 RGBA normal map from the full-resolution heightmap
+    /// Gives per-pixel lighting detail independent of the mesh LOD. Normals are in
+    /// world space (x east, y up, z south) encoded as rgb = n * 0.5 + 0.5, alpha 255;
+    /// row 0 is the tile's northern edge, matching the mesh uvs
+    /// elevations: 256x256 heightmap (65536 values)
+    /// tile_size: size of tile in world units
+    #[wasm_bindgen]
+    pub fn generate_normal_map(
+        &self,
+        elevations: &[f32],
+        tile_size: f32,
+    ) -> Result<Vec<u8>, Error> {
+        check_positive("tile_size", tile_size as f64)?;
+        Heightfield::new(elevations, 256, 256)
+            .ok_or_else(|| tile_length_error(elevations.len()))?;
+        let elevations = &*self.feathered(elevations, 256, 256);
+        let heightmap = Heightfield::new(elevations, 256, 256).unwrap();
+
+        let pixel_size = tile_size / 256.0;
+        let encode = |value: f32| ((value * 0.5 + 0.5) * 255.0).round().clamp(0.0, 255.0) as u8;
+        let mut pixels = Vec::with_capacity(256 * 256 * 4);
+        for y in 0..256 {
+            for x in 0..256 {
+                let (dzdx, dzdy) = heightmap.gradient(x, y, pixel_size);
+                let normal = Vec3::new(-dzdx, 1.0, -dzdy).normalize_or_zero();
+                pixels.extend_from_slice(&[
+                    encode(normal.x),
+                    encode(normal.y),
+                    encode(normal.z),
+                    255,
+                ]);
+            }
+        }
+        Ok(pixels)
+    }
+}
+
+impl MeshGenerator {
+    /// Hash of every option, so equal hashes mean equal output for equal input
+    pub(crate) fn config_hash(&self) -> u64 {
+        let mut hasher = Fnv1a::new();
+        hasher.write_f32s(&[self.max_error, self.skirt_depth, self.coast_feather]);
+        hasher.write(&[
+            self.tangents as u8,
+            self.u16_indices as u8,
+            self.interleaved as u8,
+            self.quantized_positions as u8,
+            self.morph_targets as u8,
+            self.flat_shading as u8,
+            self.heightmap_normals as u8,
+            self.edge_indices as u8,
+            self.triangle_strips as u8,
+            self.meshopt as u8,
+            self.vertex_cache as u8,
+            self.fidelity_report as u8,
+            self.flat_quads as u8,
+            self.hypsometric_tint as u8,
+            self.slopes as u8,
+            self.splat_weights as u8,
+        ]);
+        for &step in &self.lod_steps {
+            hasher.write(&(step as u32).to_le_bytes());
+        }
+        hasher.write_f32s(&self.color_ramp.to_stops());
+        hasher.write_f32s(&self.splat_rules.to_values());
+        hasher.finish()
+    }
+
+    /// Record the mesh's deviation from the source heightmap, when enabled
+    fn report_fidelity(&self, mesh: MeshData, source: &Heightfield, tile_size: f32) -> MeshData {
+        if !self.fidelity_report {
+            return mesh;
+        }
+        let mut mesh = mesh.into_buffers();
+        let error = surface_error(source, &mesh.vertices, &mesh.triangle_corners(), tile_size);
+        mesh.max_deviation = Some(error.max);
+        mesh.mean_deviation = Some(error.mean());
+        MeshData::from(mesh)
+    }
+
+    /// Number of levels in the LOD table
+    pub(crate) fn lod_level_count(&self) -> usize {
+        self.lod_steps.len()
+    }
+
+    /// The heightmap with coastal feathering applied, when enabled
+    fn feathered<'a>(&self, elevations: &'a [f32], width: usize, height: usize) -> Cow<'a, [f32]> {
+        if self.coast_feather > 0.0 {
+            let heightmap = Heightfield::new(elevations, width, height).unwrap();
+            Cow::Owned(heightmap.feather_coast(self.coast_feather))
+        } else {
+            Cow::Borrowed(elevations)
+        }
+    }
+
+    /// Grid sample spacing in pixels for a LOD level
+    pub(crate) fn lod_step(&self, lod_level: u8) -> Result<usize, Error> {
+        self.lod_steps
+            .get(lod_level as usize)
+            .copied()
+            .ok_or_else(|| {
+                Error::InvalidArgument(format!(
+                    "Invalid LOD level (0-{})",
+                    self.lod_steps.len() - 1
+                ))
+            })
+    }
+
+    /// Derive per-vertex attributes shared by every triangulation mode
+    fn build_mesh(
+        &self,
+        vertices: Vec<f32>,
+        indices: Vec<u32>,
+        tile_size: f32,
+        heightmap: Option<&Heightfield>,
+    ) -> MeshData {
+        self.build_grid_mesh(vertices, indices, (tile_size, tile_size), None, heightmap)
+    }
+
+    /// Two triangles over the corner pixels of a constant heightmap
+    fn flat_quad(&self, elevations: &[f32], width: usize, height: usize, tile_size: f32) -> MeshData {
+        let (vertices, indices) = sampled_grid_geometry(
+            elevations,
+            width,
+            height,
+            tile_size,
+            &[0, width - 1],
+            &[0, height - 1],
+        );
+        let extent = (tile_size, tile_size * height as f32 / width as f32);
+        let mut mesh = self
+            .build_grid_mesh(vertices, indices, extent, None, None)
+            .into_buffers();
+        mesh.flat = true;
+        MeshData::from(mesh)
+    }
+
+    /// build_mesh() for meshes that may be a uniform grid
+    /// extent: world size of the mesh along x and z
+    fn build_grid_mesh(
+        &self,
+        vertices: Vec<f32>,
+        indices: Vec<u32>,
+        extent: (f32, f32),
+        grid: Option<(usize, usize)>,
+        heightmap: Option<&Heightfield>,
+    ) -> MeshData {
+        let mesh = self.build_mesh_with(vertices, indices, extent, grid, heightmap);
+        self.finish_grid_mesh(mesh)
+    }
+
+    /// Skirts, flat shading or vertex cache order, and the optional buffers
+    fn finish_grid_mesh(&self, mut mesh: MeshBuffers) -> MeshData {
+        self.arrange_grid_mesh(&mut mesh);
+        self.finish_mesh(&mut mesh, self.u16_indices);
+        MeshData::from(mesh)
+    }
+
+    /// Skirts, then flat shading or vertex cache order
+    fn arrange_grid_mesh(&self, mesh: &mut MeshBuffers) {
+        if self.skirt_depth > 0.0 {
+            let surface = mesh.indices.len();
+            add_skirts(mesh, self.skirt_depth);
+            if !mesh.strip_indices.is_empty() {
+                // add_skirts() emits each quad as [a, a', b', a, b', b]
+                for quad in mesh.indices[surface..].chunks_exact(6) {
+                    let strip = [quad[1], quad[2], quad[0], quad[5], STRIP_RESTART];
+                    mesh.strip_indices.extend_from_slice(&strip);
+                }
+            }
+        }
+        if self.flat_shading {
+            flatten(mesh, self.tangents);
+            mesh.strip_indices = Vec::new();
+        } else if self.vertex_cache {
+            mesh.indices = optimize_vertex_cache(&mesh.indices, mesh.vertices.len() / 3);
+        }
+    }
+
+    /// Surface attributes (normals, uvs, tangents, morph targets)
+    /// grid: vertices per row and column when the vertices form a uniform grid
+    /// heightmap: the raster the vertices were placed on, for heightmap normals
+    fn build_mesh_with(
+        &self,
+        vertices: Vec<f32>,
+        indices: Vec<u32>,
+        extent: (f32, f32),
+        grid: Option<(usize, usize)>,
+        heightmap: Option<&Heightfield>,
+    ) -> MeshBuffers {
+        let normals = match heightmap {
+            Some(heightmap) if self.heightmap_normals => {
+                heightmap_normals(heightmap, &vertices, extent)
+            }
+            _ => compute_normals(&vertices, &indices),
+        };
+        let uvs = compute_uvs(&vertices, extent);
+        let tangents = if self.tangents {
+            compute_tangents(&vertices, &indices, &normals, &uvs)
+        } else {
+            Vec::new()
+        };
+        let morph_targets = match (self.morph_targets, grid) {
+            (false, _) => Vec::new(),
+            (true, Some((columns, rows))) => grid_morph_targets(&vertices, columns, rows),
+            (true, None) => vertices.clone(),
+        };
+        let strip_indices = match grid {
+            Some((columns, rows)) if self.triangle_strips => grid_strips(columns, rows),
+            _ => Vec::new(),
+        };
+
+        MeshBuffers {
+            vertices,
+            indices,
+            indices_u16: Vec::new(),
+            normals,
+            uvs,
+            tangents,
+            colors: Vec::new(),
+            slopes: Vec::new(),
+            splat_weights: Vec::new(),
+            interleaved: Vec::new(),
+            quantized_positions: Vec::new(),
+            quantization_offset: [0.0; 3],
+            quantization_scale: [1.0; 3],
+            morph_targets,
+            edge_indices: Vec::new(),
+            strip_indices,
+            meshopt_vertices: Vec::new(),
+            meshopt_indices: Vec::new(),
+            bounds_min: [0.0; 3],
+            bounds_max: [0.0; 3],
+            bounding_sphere: [0.0; 4],
+            flat: false,
+            origin: [0.0; 3],
+            max_deviation: None,
+            mean_deviation: None,
+        }
+    }
+
+    /// Output-format conversions applied once the vertex pool is complete
+    fn finish_mesh(&self, mesh: &mut MeshBuffers, u16_indices: bool) {
+        self.add_surface_attributes(mesh);
+        self.pack_buffers(mesh, u16_indices);
+    }
+
+    /// Optional attributes read from the flat mesh (elevation is y, up is +y)
+    fn add_surface_attributes(&self, mesh: &mut MeshBuffers) {
+        if self.hypsometric_tint {
+            mesh.colors = self.color_ramp.vertex_colors(&mesh.vertices);
+        }
+        if self.slopes {
+            mesh.slopes = mesh.normals.chunks_exact(3).map(slope_degrees).collect();
+        }
+        if self.splat_weights {
+            mesh.splat_weights = self
+                .splat_rules
+                .vertex_weights(&mesh.vertices, &mesh.normals);
+        }
+    }
+
+    /// Bounds and the optional packed and encoded buffers
+    fn pack_buffers(&self, mesh: &mut MeshBuffers, u16_indices: bool) {
+        compute_bounds(mesh);
+        if self.interleaved {
+            mesh.interleaved = interleave(&mesh.vertices, &mesh.normals, &mesh.uvs);
+        }
+        if self.quantized_positions {
+            quantize_positions(mesh);
+        }
+        if self.meshopt {
+            let interleaved = if self.interleaved {
+                None
+            } else {
+                Some(interleave(&mesh.vertices, &mesh.normals, &mesh.uvs))
+            };
+            let floats = interleaved.as_deref().unwrap_or(&mesh.interleaved);
+            let bytes: Vec<u8> = floats.iter().flat_map(|v| v.to_le_bytes()).collect();
+            mesh.meshopt_vertices = encode_vertex_buffer(&bytes, INTERLEAVED_FLOATS * 4);
+            if !mesh.indices.is_empty() {
+                mesh.meshopt_indices = encode_index_buffer(&mesh.indices);
+            }
+        }
+        if self.edge_indices {
+            mesh.edge_indices = if mesh.indices.is_empty() {
+                // Non-indexed (flat-shaded) triangles use consecutive vertices
+                let corners: Vec<u32> = (0..(mesh.vertices.len() / 3) as u32).collect();
+                unique_edges(&corners)
+            } else {
+                unique_edges(&mesh.indices)
+            };
+        }
+        if u16_indices && mesh.vertices.len() / 3 <= 65536 {
+            mesh.indices_u16 = mesh.indices.iter().map(|&i| i as u16).collect();
+            mesh.indices = Vec::new();
+        }
+    }
+}
+
+/// Whether every elevation is the same (no-data NaNs never count as flat)
+fn is_constant(elevations: &[f32]) -> bool {
+    match elevations.first() {
+        Some(&first) => elevations.iter().all(|&value| value == first),
+        None => false,
+    }
+}
+
+/// Uniform grid vertices and indices sampling every `step` pixels
+fn grid_geometry(
+    elevations: &[f32],
+    width: usize,
+    height: usize,
+    tile_size: f32,
+    step: usize,
+) -> (Vec<f32>, Vec<u32>) {
+    // Note: the grid includes the edge vertices to ensure full tile coverage
+    // to prevent gaps between adjacent tiles
+    let columns = grid_samples(width, step);
+    let rows = grid_samples(height, step);
+    sampled_grid_geometry(elevations, width, height, tile_size, &columns, &rows)
+}
+
+/// Pixels a grid samples along `size` pixels: every `step`-th from 0, then
+/// the last pixel, which is never repeated
+pub(crate) fn grid_samples(size: usize, step: usize) -> Vec<usize> {
+    (0..grid_sample_count(size, step))
+        .map(|k| (k * step).min(size - 1))
+        .collect()
+}
+
+/// Length of grid_samples(size, step)
+pub(crate) fn grid_sample_count(size: usize, step: usize) -> usize {
+    (size - 1).div_ceil(step) + 1
+}
+
+/// Grid through the given pixel columns and rows of a width x height heightmap,
+/// placed like grid_geometry() places the full heightmap
+fn sampled_grid_geometry(
+    elevations: &[f32],
+    width: usize,
+    height: usize,
+    tile_size: f32,
+    sample_columns: &[usize],
+    sample_rows: &[usize],
+) -> (Vec<f32>, Vec<u32>) {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+
+    let columns = sample_columns.len();
+    let rows = sample_rows.len();
+    let pixel_size = tile_size / width as f32;
+    let depth = pixel_size * height as f32;
+
+    // Generate vertices
+    for &sample_y in sample_rows {
+        for &sample_x in sample_columns {
+            let px = sample_x as f32;
+            let py = sample_y as f32;
+
+            let world_x = px * pixel_size - tile_size / 2.0;
+            let world_z = py * pixel_size - depth / 2.0;
+
+            let elevation_idx = sample_y * width + sample_x;
+            let world_y = elevations[elevation_idx];
+
+            vertices.push(world_x);
+            vertices.push(world_y);
+            vertices.push(world_z);
+        }
+    }
+
+    // Generate indices (simple triangle strip)
+    // IMPORTANT: Winding order must be counter-clockwise when viewed from above
+    // to ensure normals point outward (upward for terrain)
+    for y in 0..(rows - 1) {
+        for x in 0..(columns - 1) {
+            let idx0 = y * columns + x;
+            let idx1 = y * columns + (x + 1);
+            let idx2 = (y + 1) * columns + x;
+            let idx3 = (y + 1) * columns + (x + 1);
+
+            // First triangle (counter-clockwise: 0, 2, 1)
+            indices.push(idx0 as u32);
+            indices.push(idx2 as u32);
+            indices.push(idx1 as u32);
+
+            // Second triangle (counter-clockwise: 1, 2, 3)
+            indices.push(idx1 as u32);
+            indices.push(idx2 as u32);
+            indices.push(idx3 as u32);
+        }
+    }
+
+    (vertices, indices)
+}
+
+/// A 256x256 heightmap inside a one-pixel frame of neighbor pixels (N, E, S, W
+/// borders of 256); missing (NaN) border pixels repeat the tile's edge and the
+/// corners extrapolate from the two borders meeting there
+fn pad_with_borders(elevations: &[f32], borders: &[f32]) -> Vec<f32> {
+    let mut padded = vec![0.0; 258 * 258];
+    for y in 0..256 {
+        padded[(y + 1) * 258 + 1..(y + 1) * 258 + 257]
+            .copy_from_slice(&elevations[y * 256..(y + 1) * 256]);
+    }
+    let or_edge = |value: f32, edge: f32| if value.is_nan() { edge } else { value };
+    for i in 0..256 {
+        padded[i + 1] = or_edge(borders[i], elevations[i]);
+        padded[(i + 1) * 258 + 257] = or_edge(borders[256 + i], elevations[i * 256 + 255]);
+        padded[257 * 258 + i + 1] = or_edge(borders[512 + i], elevations[255 * 256 + i]);
+        padded[(i + 1) * 258] = or_edge(borders[768 + i], elevations[i * 256]);
+    }
+    for (x, y, inner_x, inner_y) in [
+        (0, 0, 1, 1),
+        (257, 0, 256, 1),
+        (0, 257, 1, 256),
+        (257, 257, 256, 256),
+    ] {
+        padded[y * 258 + x] =
+            padded[y * 258 + inner_x] + padded[inner_y * 258 + x] - padded[inner_y * 258 + inner_x];
+    }
+    padded
+}
+
+/// Positions of a uniform grid's vertices on the grid with twice the spacing
+/// Vertices the coarser grid lacks move onto its edges: odd rows and columns to
+/// the midpoint of their coarse edge, cell centers onto the coarse diagonal that
+/// grid_geometry's triangulation uses (from the cell's top-right to bottom-left)
+/// A trailing odd row or column (even vertex count) has no coarse edge and keeps its heights
+fn grid_morph_targets(vertices: &[f32], columns: usize, rows: usize) -> Vec<f32> {
+    let height = |x: usize, y: usize| vertices[(y * columns + x) * 3 + 1];
+    let mut targets = vertices.to_vec();
+    for y in 0..rows {
+        for x in 0..columns {
+            if (x % 2 == 1 && x + 1 == columns) || (y % 2 == 1 && y + 1 == rows) {
+                continue;
+            }
+            let coarse_y = match (x % 2, y % 2) {
+                (0, 0) => continue,
+                (1, 0) => (height(x - 1, y) + height(x + 1, y)) / 2.0,
+                (0, 1) => (height(x, y - 1) + height(x, y + 1)) / 2.0,
+                _ => (height(x + 1, y - 1) + height(x - 1, y + 1)) / 2.0,
+            };
+            targets[(y * columns + x) * 3 + 1] = coarse_y;
+        }
+    }
+    targets
+}
+
+/// Follow moved vertices of a columns x columns grid (the first vertices of
+/// the mesh, skirts after them): normals of the triangles around them, skirt
+/// vertices hanging below them, tangents and morph targets
+/// changed: moved grid vertices and their previous heights
+fn refresh_grid_vertices(mesh: &mut MeshBuffers, columns: usize, changed: &[(usize, f32)]) {
+    let grid = columns * columns;
+    let corners = mesh.triangle_corners();
+    let surface: Vec<u32> = corners
+        .chunks_exact(3)
+        .filter(|triangle| triangle.iter().all(|&i| (i as usize) < grid))
+        .flatten()
+        .copied()
+        .collect();
+    let position = |vertices: &[f32], i: u32| Vec3::from_slice(&vertices[i as usize * 3..][..3]);
+
+    // Vertices sharing a triangle with a moved vertex need new normals
+    let mut moved = vec![false; grid];
+    changed.iter().for_each(|&(vertex, _)| moved[vertex] = true);
+    let mut affected = vec![false; grid];
+    for triangle in surface.chunks_exact(3) {
+        if triangle.iter().any(|&i| moved[i as usize]) {
+            triangle.iter().for_each(|&i| affected[i as usize] = true);
+        }
+    }
+    let mut sums = HashMap::new();
+    for triangle in surface.chunks_exact(3) {
+        if !triangle.iter().any(|&i| affected[i as usize]) {
+            continue;
+        }
+        let [a, b, c] =
+            [triangle[0], triangle[1], triangle[2]].map(|i| position(&mesh.vertices, i));
+        // As compute_normals(): unit face normals, summed
+        let normal = (b - a).cross(c - a).normalize_or_zero();
+        for &i in triangle.iter().filter(|&&i| affected[i as usize]) {
+            *sums.entry(i as usize).or_insert(Vec3::ZERO) += normal;
+        }
+    }
+    for (vertex, sum) in sums {
+        let normal = sum.normalize_or_zero();
+        mesh.normals[vertex * 3..vertex * 3 + 3].copy_from_slice(&normal.to_array());
+    }
+
+    if !mesh.tangents.is_empty() {
+        let tangents = compute_tangents(
+            &mesh.vertices[..grid * 3],
+            &surface,
+            &mesh.normals[..grid * 3],
+            &mesh.uvs[..grid * 2],
+        );
+        mesh.tangents[..grid * 4].copy_from_slice(&tangents);
+    }
+    if !mesh.morph_targets.is_empty() {
+        let targets = grid_morph_targets(&mesh.vertices[..grid * 3], columns, columns);
+        mesh.morph_targets[..grid * 3].copy_from_slice(&targets);
+    }
+
+    // Skirt vertices copy the grid vertex above them, minus the skirt depth
+    let previous: HashMap<usize, f32> = changed.iter().copied().collect();
+    let key =
+        |vertices: &[f32], i: usize| [vertices[i * 3].to_bits(), vertices[i * 3 + 2].to_bits()];
+    let edge: HashMap<[u32; 2], usize> = (0..grid)
+        .filter(|&i| {
+            let (x, y) = (i % columns, i / columns);
+            x == 0 || y == 0 || x + 1 == columns || y + 1 == columns
+        })
+        .map(|i| (key(&mesh.vertices, i), i))
+        .collect();
+    for skirt in grid..mesh.vertices.len() / 3 {
+        let Some(&top) = edge.get(&key(&mesh.vertices, skirt)) else {
+            continue;
+        };
+        if let Some(&old_height) = previous.get(&top) {
+            let depth = old_height - mesh.vertices[skirt * 3 + 1];
+            mesh.vertices[skirt * 3 + 1] = mesh.vertices[top * 3 + 1] - depth;
+        }
+        let depth = mesh.vertices[top * 3 + 1] - mesh.vertices[skirt * 3 + 1];
+        mesh.normals.copy_within(top * 3..top * 3 + 3, skirt * 3);
+        if !mesh.tangents.is_empty() {
+            mesh.tangents.copy_within(top * 4..top * 4 + 4, skirt * 4);
+        }
+        if !mesh.morph_targets.is_empty() {
+            mesh.morph_targets
+                .copy_within(top * 3..top * 3 + 3, skirt * 3);
+            mesh.morph_targets[skirt * 3 + 1] -= depth;
+        }
+    }
+}
+
+/// Drop vertices no triangle references and renumber the indices
+fn compact_vertices(vertices: Vec<f32>, indices: Vec<u32>) -> (Vec<f32>, Vec<u32>) {
+    let mut new_index = vec![u32::MAX; vertices.len() / 3];
+    let mut compacted = Vec::with_capacity(vertices.len());
+    let indices = indices
+        .into_iter()
+        .map(|i| {
+            let slot = &mut new_index[i as usize];
+            if *slot == u32::MAX {
+                *slot = (compacted.len() / 3) as u32;
+                compacted.extend_from_slice(&vertices[i as usize * 3..i as usize * 3 + 3]);
+            }
+            *slot
+        })
+        .collect();
+    (compacted, indices)
+}
+
+/// Texture coordinates spanning 0..1 across the tile
+/// u increases east and v increases south, so v = 0 is the top row of an imagery tile
+fn compute_uvs(vertices: &[f32], (width, depth): (f32, f32)) -> Vec<f32> {
+    let mut uvs = Vec::with_capacity(vertices.len() / 3 * 2);
+    for vertex in vertices.chunks_exact(3) {
+        uvs.push((vertex[0] + width / 2.0) / width);
+        uvs.push((vertex[2] + depth / 2.0) / depth);
+    }
+    uvs
+}
+
+/// Bounding box and a sphere around the box center enclosing every vertex
+fn compute_bounds(mesh: &mut MeshBuffers) {
+    if mesh.vertices.is_empty() {
+        return;
+    }
+    let mut min = Vec3::splat(f32::INFINITY);
+    let mut max = Vec3::splat(f32::NEG_INFINITY);
+    for vertex in mesh.vertices.chunks_exact(3) {
+        let position = Vec3::from_slice(vertex);
+        min = min.min(position);
+        max = max.max(position);
+    }
+    let center = (min + max) / 2.0;
+    let radius = mesh
+        .vertices
+        .chunks_exact(3)
+        .map(|vertex| Vec3::from_slice(vertex).distance_squared(center))
+        .fold(0.0f32, f32::max)
+        .sqrt();
+
+    mesh.bounds_min = min.to_array();
+    mesh.bounds_max = max.to_array();
+    mesh.bounding_sphere = [center.x, center.y, center.z, radius];
+}
+
+/// Concatenate translated meshes, indexing every triangle (an attribute
+/// survives only if no mesh lacks it)
+fn merge_meshes(meshes: &[MeshData], offsets: &[Vec3]) -> MeshBuffers {
+    let every = |has: fn(&MeshBuffers) -> bool| meshes.iter().all(|mesh| has(mesh));
+    let tangents = every(|mesh| !mesh.tangents.is_empty());
+    let colors = every(|mesh| !mesh.colors.is_empty());
+    let slopes = every(|mesh| !mesh.slopes.is_empty());
+    let splat_weights = every(|mesh| !mesh.splat_weights.is_empty());
+    let morph_targets = every(|mesh| !mesh.morph_targets.is_empty());
+
+    let mut merged = MeshBuffers::default();
+    for (mesh, offset) in meshes.iter().zip(offsets) {
+        let base = (merged.vertices.len() / 3) as u32;
+        let translate = |positions: &[f32], into: &mut Vec<f32>| {
+            for position in positions.chunks_exact(3) {
+                into.extend_from_slice(&(Vec3::from_slice(position) + *offset).to_array());
+            }
+        };
+        translate(&mesh.vertices, &mut merged.vertices);
+        merged
+            .indices
+            .extend(mesh.triangle_corners().iter().map(|&i| base + i));
+        merged.normals.extend_from_slice(&mesh.normals);
+        merged.uvs.extend_from_slice(&mesh.uvs);
+        if tangents {
+            merged.tangents.extend_from_slice(&mesh.tangents);
+        }
+        if colors {
+            merged.colors.extend_from_slice(&mesh.colors);
+        }
+        if slopes {
+            merged.slopes.extend_from_slice(&mesh.slopes);
+        }
+        if splat_weights {
+            merged.splat_weights.extend_from_slice(&mesh.splat_weights);
+        }
+        if morph_targets {
+            translate(&mesh.morph_targets, &mut merged.morph_targets);
+        }
+    }
+    merged
+}
+
+/// Largest vertical deviation from the heightmap of the grid sampling every
+/// `step` pixels (grid_geometry() placement), over every pixel with data
+fn grid_error(heightmap: &Heightfield, step: usize) -> f32 {
+    let (width, height) = (heightmap.width, heightmap.height);
+    let columns = grid_samples(width, step);
+    let rows = grid_samples(height, step);
+    let mut samples = Vec::with_capacity(columns.len() * rows.len());
+    for &y in &rows {
+        for &x in &columns {
+            samples.push(heightmap.data[y * width + x]);
+        }
+    }
+    let grid = Heightfield::new(&samples, columns.len(), rows.len()).unwrap();
+
+    // Cell of the grid holding a pixel, and its fraction across the cell;
+    // the last cell may be narrower than step
+    let locate = |pixel: usize, samples: &[usize]| {
+        let cell = (pixel / step).min(samples.len() - 2);
+        let span = (samples[cell + 1] - samples[cell]) as f32;
+        cell as f32 + (pixel - samples[cell]) as f32 / span
+    };
+    let mut error = 0.0f32;
+    for y in 0..height {
+        let row = locate(y, &rows);
+        for x in 0..width {
+            let deviation = (grid.sample_triangulated(locate(x, &columns), row)
+                - heightmap.data[y * width + x])
+                .abs();
+            if deviation > error {
+                error = deviation;
+            }
+        }
+    }
+    error
+}
+
+/// Merge vertices within tolerance of the first vertex of their cluster,
+/// found through a hash grid of tolerance-sized cells
+fn weld_vertices(mesh: &MeshBuffers, tolerance: f32) -> MeshBuffers {
+    let vertex_count = mesh.vertices.len() / 3;
+    let position = |i: usize| Vec3::from_slice(&mesh.vertices[i * 3..i * 3 + 3]);
+    let cell_size = if tolerance > 0.0 { tolerance } else { 1.0 };
+    let cell = |p: Vec3| (p / cell_size).floor().as_ivec3().to_array();
+
+    let mut cells: HashMap<[i32; 3], Vec<u32>> = HashMap::new();
+    let mut remap = vec![0u32; vertex_count];
+    let mut kept = Vec::new();
+    for (i, slot) in remap.iter_mut().enumerate() {
+        let p = position(i);
+        let [x, y, z] = cell(p);
+        let mut found = None;
+        'search: for dx in -1..=1 {
+            for dy in -1..=1 {
+                for dz in -1..=1 {
+                    let Some(candidates) = cells.get(&[x + dx, y + dy, z + dz]) else {
+                        continue;
+                    };
+                    for &k in candidates {
+                        if position(kept[k as usize]).distance(p) <= tolerance {
+                            found = Some(k);
+                            break 'search;
+                        }
+                    }
+                }
+            }
+        }
+        *slot = found.unwrap_or_else(|| {
+            let k = kept.len() as u32;
+            kept.push(i);
+            cells.entry([x, y, z]).or_default().push(k);
+            k
+        });
+    }
+
+    let mut welded = MeshBuffers {
+        origin: mesh.origin,
+        ..MeshBuffers::default()
+    };
+    let copy = |from: &[f32], width: usize, into: &mut Vec<f32>| {
+        if !from.is_empty() {
+            into.extend(kept.iter().flat_map(|&i| &from[i * width..][..width]));
+        }
+    };
+    copy(&mesh.vertices, 3, &mut welded.vertices);
+    copy(&mesh.uvs, 2, &mut welded.uvs);
+    copy(&mesh.slopes, 1, &mut welded.slopes);
+    copy(&mesh.splat_weights, 4, &mut welded.splat_weights);
+    copy(&mesh.morph_targets, 3, &mut welded.morph_targets);
+    if !mesh.colors.is_empty() {
+        welded.colors = kept
+            .iter()
+            .flat_map(|&i| &mesh.colors[i * 3..i * 3 + 3])
+            .copied()
+            .collect();
+    }
+
+    // Sum every copy's normal and tangent into its welded vertex
+    let mut normals = vec![Vec3::ZERO; kept.len()];
+    let mut tangents = vec![Vec3::ZERO; kept.len()];
+    for (i, &k) in remap.iter().enumerate() {
+        normals[k as usize] += Vec3::from_slice(&mesh.normals[i * 3..i * 3 + 3]);
+        if !mesh.tangents.is_empty() {
+            tangents[k as usize] += Vec3::from_slice(&mesh.tangents[i * 4..i * 4 + 3]);
+        }
+    }
+    welded.normals = normals
+        .iter()
+        .flat_map(|n| n.normalize_or_zero().to_array())
+        .collect();
+    if !mesh.tangents.is_empty() {
+        for (tangent, &i) in tangents.iter().zip(&kept) {
+            let [x, y, z] = tangent.normalize_or_zero().to_array();
+            let handedness = mesh.tangents[i * 4 + 3];
+            welded.tangents.extend_from_slice(&[x, y, z, handedness]);
+        }
+    }
+
+    for triangle in mesh.triangle_corners().chunks_exact(3) {
+        let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|i| remap[i as usize]);
+        if a != b && b != c && a != c {
+            welded.indices.extend_from_slice(&[a, b, c]);
+        }
+    }
+    welded
+}
+
+/// Primitive restart index ending each triangle strip
+const STRIP_RESTART: u32 = u32::MAX;
+
+/// One strip per row of grid cells, alternating top and bottom vertices so
+/// the triangles, diagonals and winding match sampled_grid_geometry()'s list
+fn grid_strips(columns: usize, rows: usize) -> Vec<u32> {
+    let mut strips = Vec::with_capacity((rows - 1) * (columns * 2 + 1));
+    for y in 0..rows - 1 {
+        for x in 0..columns {
+            strips.push((y * columns + x) as u32);
+            strips.push(((y + 1) * columns + x) as u32);
+        }
+        strips.push(STRIP_RESTART);
+    }
+    strips
+}
+
+/// Every undirected triangle edge once, as index pairs in first-seen order
+fn unique_edges(indices: &[u32]) -> Vec<u32> {
+    let mut seen = HashSet::new();
+    let mut edges = Vec::new();
+    for triangle in indices.chunks_exact(3) {
+        for (a, b) in [
+            (triangle[0], triangle[1]),
+            (triangle[1], triangle[2]),
+            (triangle[2], triangle[0]),
+        ] {
+            if seen.insert((a.min(b), a.max(b))) {
+                edges.extend_from_slice(&[a, b]);
+            }
+        }
+    }
+    edges
+}
+
+/// Unshare vertices so every triangle has its own corners and face normal
+/// Leaves the mesh non-indexed
+fn flatten(mesh: &mut MeshBuffers, tangents: bool) {
+    let indices = std::mem::take(&mut mesh.indices);
+    let gather = |values: &[f32], components: usize| -> Vec<f32> {
+        if values.is_empty() {
+            return Vec::new();
+        }
+        indices
+            .iter()
+            .flat_map(|&i| {
+                let start = i as usize * components;
+                values[start..start + components].iter().copied()
+            })
+            .collect()
+    };
+    mesh.vertices = gather(&mesh.vertices, 3);
+    mesh.uvs = gather(&mesh.uvs, 2);
+    mesh.morph_targets = gather(&mesh.morph_targets, 3);
+
+    // With no shared vertices the accumulated normal is the face normal
+    let corners: Vec<u32> = (0..indices.len() as u32).collect();
+    mesh.normals = compute_normals(&mesh.vertices, &corners);
+    mesh.tangents = if tangents {
+        compute_tangents(&mesh.vertices, &corners, &mesh.normals, &mesh.uvs)
+    } else {
+        Vec::new()
+    };
+}
+
+/// Vertex normals from the full-resolution heightmap's Sobel gradient at each
+/// vertex, interpolated bilinearly between pixels
+fn heightmap_normals(heightmap: &Heightfield, vertices: &[f32], extent: (f32, f32)) -> Vec<f32> {
+    let pixel_size = extent.0 / heightmap.width as f32;
+    let (max_x, max_y) = (heightmap.width - 1, heightmap.height - 1);
+    let mut normals = Vec::with_capacity(vertices.len());
+    for vertex in vertices.chunks_exact(3) {
+        let px = ((vertex[0] + extent.0 / 2.0) / pixel_size).clamp(0.0, max_x as f32);
+        let py = ((vertex[2] + extent.1 / 2.0) / pixel_size).clamp(0.0, max_y as f32);
+        let (x0, y0) = (px.floor() as usize, py.floor() as usize);
+        let (tx, ty) = (px - x0 as f32, py - y0 as f32);
+        let (x1, y1) = ((x0 + 1).min(max_x), (y0 + 1).min(max_y));
+
+        let mut gradient = (0.0, 0.0);
+        for (x, y, weight) in [
+            (x0, y0, (1.0 - tx) * (1.0 - ty)),
+            (x1, y0, tx * (1.0 - ty)),
+            (x0, y1, (1.0 - tx) * ty),
+            (x1, y1, tx * ty),
+        ] {
+            if weight > 0.0 {
+                let (dzdx, dzdy) = heightmap.gradient(x, y, pixel_size);
+                gradient.0 += dzdx * weight;
+                gradient.1 += dzdy * weight;
+            }
+        }
+        let normal = Vec3::new(-gradient.0, 1.0, -gradient.1).normalize();
+        normals.extend_from_slice(&[normal.x, normal.y, normal.z]);
+    }
+    normals
+}
+
+/// Calculate smooth vertex normals by accumulating face normals
+fn compute_normals(vertices: &[f32], indices: &[u32]) -> Vec<f32> {
+    let mut normals = vec![0.0; vertices.len()];
+    write_normals(vertices, indices, &mut normals);
+    normals
+}
+
+/// compute_normals() into a zeroed buffer of vertices.len() values
+fn write_normals(vertices: &[f32], indices: &[u32], normals: &mut [f32]) {
+    for i in (0..indices.len()).step_by(3) {
+        let idx0 = indices[i] as usize;
+        let idx1 = indices[i + 1] as usize;
+        let idx2 = indices[i + 2] as usize;
+
+        let v0 = Vec3::new(
+            vertices[idx0 * 3],
+            vertices[idx0 * 3 + 1],
+            vertices[idx0 * 3 + 2],
+        );
+        let v1 = Vec3::new(
+            vertices[idx1 * 3],
+            vertices[idx1 * 3 + 1],
+            vertices[idx1 * 3 + 2],
+        );
+        let v2 = Vec3::new(
+            vertices[idx2 * 3],
+            vertices[idx2 * 3 + 1],
+            vertices[idx2 * 3 + 2],
+        );
+
+        let edge1 = v1 - v0;
+        let edge2 = v2 - v0;
+        // Zero for degenerate (zero-area) triangles so they add nothing
+        let normal = edge1.cross(edge2).normalize_or_zero();
+
+        // Accumulate normal to all three vertices
+        for &idx in &[idx0, idx1, idx2] {
+            normals[idx * 3] += normal.x;
+            normals[idx * 3 + 1] += normal.y;
+            normals[idx * 3 + 2] += normal.z;
+        }
+    }
+
+    // Normalize vertex normals
+    for i in (0..normals.len()).step_by(3) {
+        let normal = Vec3::new(normals[i], normals[i + 1], normals[i + 2]);
+        let normalized = normal.normalize_or_zero();
+        normals[i] = normalized.x;
+        normals[i + 1] = normalized.y;
+        normals[i + 2] = normalized.z;
+    }
+}
+
+/// Per-vertex tangents from UV gradients (Lengyel's method)
+/// w holds the bitangent handedness so the shader can rebuild it as cross(n, t) * w
+fn compute_tangents(vertices: &[f32], indices: &[u32], normals: &[f32], uvs: &[f32]) -> Vec<f32> {
+    let vertex_count = vertices.len() / 3;
+    let mut tan1 = vec![Vec3::ZERO; vertex_count];
+    let mut tan2 = vec![Vec3::ZERO; vertex_count];
+
+    let position = |i: usize| Vec3::new(vertices[i * 3], vertices[i * 3 + 1], vertices[i * 3 + 2]);
+
+    for triangle in indices.chunks_exact(3) {
+        let (i0, i1, i2) = (
+            triangle[0] as usize,
+            triangle[1] as usize,
+            triangle[2] as usize,
+        );
+        let e1 = position(i1) - position(i0);
+        let e2 = position(i2) - position(i0);
+        let (du1, dv1) = (uvs[i1 * 2] - uvs[i0 * 2], uvs[i1 * 2 + 1] - uvs[i0 * 2 + 1]);
+        let (du2, dv2) = (uvs[i2 * 2] - uvs[i0 * 2], uvs[i2 * 2 + 1] - uvs[i0 * 2 + 1]);
+
+        let det = du1 * dv2 - du2 * dv1;
+        if det.abs() < f32::EPSILON {
+            continue;
+        }
+        let r = 1.0 / det;
+        let sdir = (e1 * dv2 - e2 * dv1) * r;
+        let tdir = (e2 * du1 - e1 * du2) * r;
+
+        for &i in &[i0, i1, i2] {
+            tan1[i] += sdir;
+            tan2[i] += tdir;
+        }
+    }
+
+    let mut tangents = Vec::with_capacity(vertex_count * 4);
+    for i in 0..vertex_count {
+        let n = Vec3::new(normals[i * 3], normals[i * 3 + 1], normals[i * 3 + 2]);
+        // Gram-Schmidt orthogonalize against the normal
+        let t = (tan1[i] - n * n.dot(tan1[i])).normalize_or_zero();
+        let w = if n.cross(t).dot(tan2[i]) < 0.0 {
+            -1.0
+        } else {
+            1.0
+        };
+        tangents.extend_from_slice(&[t.x, t.y, t.z, w]);
+    }
+    tangents
+}
+
+/// Pack position, normal and uv of each vertex into one contiguous buffer
+fn interleave(vertices: &[f32], normals: &[f32], uvs: &[f32]) -> Vec<f32> {
+    let mut buffer = Vec::with_capacity(vertices.len() / 3 * INTERLEAVED_FLOATS);
+    for ((position, normal), uv) in vertices
+        .chunks_exact(3)
+        .zip(normals.chunks_exact(3))
+        .zip(uvs.chunks_exact(2))
+    {
+        buffer.extend_from_slice(position);
+        buffer.extend_from_slice(normal);
+        buffer.extend_from_slice(uv);
+    }
+    buffer
+}
+
+/// Quantize positions to the full u16 range over the mesh bounding box
+fn quantize_positions(mesh: &mut MeshBuffers) {
+    if mesh.vertices.is_empty() {
+        return;
+    }
+
+    let mut min = [f32::INFINITY; 3];
+    let mut max = [f32::NEG_INFINITY; 3];
+    for vertex in mesh.vertices.chunks_exact(3) {
+        for axis in 0..3 {
+            min[axis] = min[axis].min(vertex[axis]);
+            max[axis] = max[axis].max(vertex[axis]);
+        }
+    }
+
+    let mut scale = [1.0f32; 3];
+    for axis in 0..3 {
+        let extent = max[axis] - min[axis];
+        // A flat axis (e.g. level terrain) keeps scale 1 so every value maps to 0
+        if extent > 0.0 {
+            scale[axis] = extent / 65535.0;
+        }
+    }
+
+    mesh.quantized_positions = mesh
+        .vertices
+        .chunks_exact(3)
+        .flat_map(|vertex| {
+            (0..3).map(move |axis| {
+                ((vertex[axis] - min[axis]) / scale[axis])
+                    .round()
+                    .clamp(0.0, 65535.0) as u16
+            })
+        })
+        .collect();
+    mesh.quantization_offset = min;
+    mesh.quantization_scale = scale;
+}
+
+/// Extrude every boundary edge of the surface downward by `depth`
+fn add_skirts(mesh: &mut MeshBuffers, depth: f32) {
+    let mut indices = std::mem::take(&mut mesh.indices);
+    add_skirts_to(mesh, &mut indices, &mut HashMap::new(), depth);
+    mesh.indices = indices;
+}
+
+/// Extrude the boundary edges of one index buffer over the mesh's vertex pool
+/// Skirt vertices copy the attributes of the surface vertex above them so the
+/// skirt shades like the terrain edge it hangs from; `skirt_vertex` maps surface
+/// vertices to their skirt copies so several index buffers can share them
+fn add_skirts_to(
+    mesh: &mut MeshBuffers,
+    indices: &mut Vec<u32>,
+    skirt_vertex: &mut HashMap<u32, u32>,
+    depth: f32,
+) {
+    // Boundary edges belong to exactly one triangle; keep them in winding order
+    let mut edges: HashMap<(u32, u32), (u32, u32, u32)> = HashMap::new();
+    for triangle in indices.chunks_exact(3) {
+        for k in 0..3 {
+            let (a, b) = (triangle[k], triangle[(k + 1) % 3]);
+            let key = (a.min(b), a.max(b));
+            edges.entry(key).or_insert((a, b, 0)).2 += 1;
+        }
+    }
+    let mut boundary: Vec<(u32, u32)> = edges
+        .into_values()
+        .filter(|&(_, _, count)| count == 1)
+        .map(|(a, b, _)| (a, b))
+        .collect();
+    boundary.sort_unstable();
+
+    let mut bottom = |mesh: &mut MeshBuffers, top: u32| -> u32 {
+        *skirt_vertex.entry(top).or_insert_with(|| {
+            let index = (mesh.vertices.len() / 3) as u32;
+            let t = top as usize;
+            mesh.vertices.extend_from_slice(&[
+                mesh.vertices[t * 3],
+                mesh.vertices[t * 3 + 1] - depth,
+                mesh.vertices[t * 3 + 2],
+            ]);
+            mesh.normals.extend_from_within(t * 3..t * 3 + 3);
+            mesh.uvs.extend_from_within(t * 2..t * 2 + 2);
+            if !mesh.tangents.is_empty() {
+                mesh.tangents.extend_from_within(t * 4..t * 4 + 4);
+            }
+            if !mesh.morph_targets.is_empty() {
+                let target = &mesh.morph_targets[t * 3..t * 3 + 3];
+                let skirt_target = [target[0], target[1] - depth, target[2]];
+                mesh.morph_targets.extend_from_slice(&skirt_target);
+            }
+            index
+        })
+    };
+
+    for (a, b) in boundary {
+        let a_bottom = bottom(mesh, a);
+        let b_bottom = bottom(mesh, b);
+        // Wound so the skirt faces outward, away from the tile
+        indices.extend_from_slice(&[a, a_bottom, b_bottom, a, b_bottom, b]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Smooth synthetic tile: a ridge rising to the east with a bump
+    fn test_tile() -> Vec<f32> {
+        (0..65536)
+            .map(|i| {
+                let (x, y) = ((i % 256) as f32, (i / 256) as f32);
+                x * 4.0 + 200.0 * (-((x - 128.0).powi(2) + (y - 96.0).powi(2)) / 2000.0).exp()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn weld_keeps_vertex_colors() {
+        let mut generator = MeshGenerator::new(1.0);
+        generator.set_hypsometric_tint(true);
+        let mesh = generator.generate(&test_tile(), 1000.0, 2).unwrap();
+        let welded = generator.weld(&mesh, 0.0);
+
+        assert_eq!(welded.colors.len(), welded.vertices.len());
+        let original: HashMap<[u32; 3], &[u8]> = mesh
+            .vertices
+            .chunks_exact(3)
+            .zip(mesh.colors.chunks_exact(3))
+            .map(|(p, color)| ([p[0], p[1], p[2]].map(f32::to_bits), color))
+            .collect();
+        let colors = welded.colors.chunks_exact(3);
+        for (p, color) in welded.vertices.chunks_exact(3).zip(colors) {
+            assert_eq!(original[&[p[0], p[1], p[2]].map(f32::to_bits)], color);
+        }
+    }
+
+    #[test]
+    fn full_resolution_normals_are_finite() {
+        let mut generator = MeshGenerator::new(1.0);
+        generator.set_lod_steps(&[4, 1]).unwrap();
+        let tile = test_tile();
+        let mut mesh = generator.generate(&tile, 1000.0, 1).unwrap();
+        assert!(mesh.normals.iter().all(|n| n.is_finite()));
+
+        let raised: Vec<f32> = tile[..16 * 256].iter().map(|h| h + 10.0).collect();
+        generator
+            .update_region(&mut mesh, 0, 0, 256, 16, &raised, 1)
+            .unwrap();
+        assert!(mesh.normals.iter().all(|n| n.is_finite()));
+    }
+
+    #[test]
+    fn regions_past_u32_max_are_rejected() {
+        let generator = MeshGenerator::new(1.0);
+        let tile = test_tile();
+        let result = generator.generate_region(&tile, u32::MAX, 0, 2, 2, 1000.0, 0);
+        assert!(matches!(result, Err(Error::InvalidArgument(_))));
+
+        let mut mesh = generator.generate(&tile, 1000.0, 0).unwrap();
+        let result = generator.update_region(&mut mesh, 0, u32::MAX, 1, 2, &[0.0; 2], 0);
+        assert!(matches!(result, Err(Error::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn constant_tiles_mesh_as_a_grid_by_default() {
+        let mut generator = MeshGenerator::new(1.0);
+        let sea = vec![0.0; 65536];
+        let grid = generator.generate(&sea, 1000.0, 0).unwrap();
+        assert!(!grid.is_flat());
+        let hills = generator.generate(&test_tile(), 1000.0, 0).unwrap();
+        assert_eq!(grid.vertices.len(), hills.vertices.len());
+
+        generator.set_flat_tile_quads(true);
+        assert!(generator.generate(&sea, 1000.0, 0).unwrap().is_flat());
+    }
+
+    #[test]
+    fn generate_into_feathers_like_generate() {
+        let mut generator = MeshGenerator::new(1.0);
+        generator.set_coast_feather(8.0);
+        // Land in the east, sea (0 m) in the west
+        let tile: Vec<f32> = test_tile()
+            .iter()
+            .enumerate()
+            .map(|(i, &h)| if i % 256 < 100 { 0.0 } else { h })
+            .collect();
+        let mesh = generator.generate(&tile, 1000.0, 1).unwrap();
+
+        let mut out = StagingBuffers::new(
+            generator.max_vertex_count(1).unwrap(),
+            generator.max_index_count(1).unwrap(),
+        );
+        generator.generate_into(&tile, 1000.0, 1, &mut out).unwrap();
+        assert_eq!(out.vertex_count, mesh.vertices.len() / 3);
+        assert_eq!(&out.vertices[..mesh.vertices.len()], &mesh.vertices[..]);
     }
 }
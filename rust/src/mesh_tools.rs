@@ -0,0 +1,169 @@
+use glam::Vec3;
+use wasm_bindgen::prelude::*;
+
+use crate::mesh_generator::{MeshBuffers, MeshData};
+
+/// Problems found in a mesh, each listed by triangle or vertex number
+/// Triangle n is indices 3n..3n+3 (vertices 3n..3n+3 for non-indexed meshes)
+#[wasm_bindgen]
+pub struct MeshValidation {
+    triangle_count: usize,
+    vertex_count: usize,
+    /// Triangles referencing a vertex past the end of the vertex buffer
+    out_of_range: Vec<u32>,
+    /// Triangles using the same vertex twice
+    duplicate_indices: Vec<u32>,
+    /// Triangles with distinct vertices but no area (collinear or coincident)
+    degenerate: Vec<u32>,
+    /// Vertices whose position has a NaN or infinite component
+    invalid_positions: Vec<u32>,
+    /// Vertices whose normal is NaN, infinite or zero length
+    invalid_normals: Vec<u32>,
+    /// Vertices no triangle uses
+    unreferenced: Vec<u32>,
+}
+
+#[wasm_bindgen]
+impl MeshValidation {
+    /// True when no problem of any kind was found
+    #[wasm_bindgen]
+    pub fn is_valid(&self) -> bool {
+        self.out_of_range.is_empty()
+            && self.duplicate_indices.is_empty()
+            && self.degenerate.is_empty()
+            && self.invalid_positions.is_empty()
+            && self.invalid_normals.is_empty()
+            && self.unreferenced.is_empty()
+    }
+
+    #[wasm_bindgen]
+    pub fn triangle_count(&self) -> usize {
+        self.triangle_count
+    }
+
+    #[wasm_bindgen]
+    pub fn vertex_count(&self) -> usize {
+        self.vertex_count
+    }
+
+    /// Get triangles with an index past the end of the vertex buffer
+    #[wasm_bindgen]
+    pub fn get_out_of_range_triangles(&self) -> Vec<u32> {
+        self.out_of_range.clone()
+    }
+
+    /// Get triangles that repeat a vertex index
+    #[wasm_bindgen]
+    pub fn get_duplicate_index_triangles(&self) -> Vec<u32> {
+        self.duplicate_indices.clone()
+    }
+
+    /// Get zero-area triangles (distinct indices, collinear or coincident positions)
+    #[wasm_bindgen]
+    pub fn get_degenerate_triangles(&self) -> Vec<u32> {
+        self.degenerate.clone()
+    }
+
+    /// Get vertices with a NaN or infinite position
+    #[wasm_bindgen]
+    pub fn get_invalid_positions(&self) -> Vec<u32> {
+        self.invalid_positions.clone()
+    }
+
+    /// Get vertices with a NaN, infinite or zero-length normal
+    /// (typically no-data regions or degenerate triangles; rendered black)
+    #[wasm_bindgen]
+    pub fn get_invalid_normals(&self) -> Vec<u32> {
+        self.invalid_normals.clone()
+    }
+
+    /// Get vertices not referenced by any triangle
+    #[wasm_bindgen]
+    pub fn get_unreferenced_vertices(&self) -> Vec<u32> {
+        self.unreferenced.clone()
+    }
+}
+
+/// Diagnostics for generated meshes
+#[wasm_bindgen]
+pub struct MeshTools;
+
+#[wasm_bindgen]
+impl MeshTools {
+    /// Check a mesh for degenerate triangles, bad indices, NaN or zero normals
+    /// and unused vertices
+    #[wasm_bindgen]
+    pub fn validate(mesh: &MeshData) -> MeshValidation {
+        MeshTools::validate_buffers(mesh)
+    }
+}
+
+impl MeshTools {
+    /// validate() for plain mesh buffers
+    pub fn validate_buffers(mesh: &MeshBuffers) -> MeshValidation {
+        let vertex_count = mesh.vertices.len() / 3;
+        let indices: Vec<u32> = if !mesh.indices_u16.is_empty() {
+            mesh.indices_u16.iter().map(|&i| i as u32).collect()
+        } else if !mesh.indices.is_empty() {
+            mesh.indices.clone()
+        } else {
+            // Non-indexed (flat-shaded) triangles use consecutive vertices
+            (0..vertex_count as u32).collect()
+        };
+
+        let mut report = MeshValidation {
+            triangle_count: indices.len() / 3,
+            vertex_count,
+            out_of_range: Vec::new(),
+            duplicate_indices: Vec::new(),
+            degenerate: Vec::new(),
+            invalid_positions: Vec::new(),
+            invalid_normals: Vec::new(),
+            unreferenced: Vec::new(),
+        };
+
+        let position = |index: u32| {
+            let i = index as usize * 3;
+            Vec3::new(mesh.vertices[i], mesh.vertices[i + 1], mesh.vertices[i + 2])
+        };
+        let mut referenced = vec![false; vertex_count];
+        for (triangle, corners) in indices.chunks_exact(3).enumerate() {
+            let triangle = triangle as u32;
+            if corners.iter().any(|&i| i as usize >= vertex_count) {
+                report.out_of_range.push(triangle);
+                continue;
+            }
+            for &i in corners {
+                referenced[i as usize] = true;
+            }
+            let (a, b, c) = (corners[0], corners[1], corners[2]);
+            if a == b || b == c || a == c {
+                report.duplicate_indices.push(triangle);
+                continue;
+            }
+
+            let (edge1, edge2) = (position(b) - position(a), position(c) - position(a));
+            let area = edge1.cross(edge2).length();
+            // Relative test, so large and small tiles are judged alike
+            if area <= f32::EPSILON * edge1.length() * edge2.length() {
+                report.degenerate.push(triangle);
+            }
+        }
+
+        for (v, &used) in referenced.iter().enumerate() {
+            if !position(v as u32).is_finite() {
+                report.invalid_positions.push(v as u32);
+            }
+            if let Some(normal) = mesh.normals.get(v * 3..v * 3 + 3) {
+                let length = Vec3::from_slice(normal).length();
+                if !length.is_finite() || length < 1e-6 {
+                    report.invalid_normals.push(v as u32);
+                }
+            }
+            if !used {
+                report.unreferenced.push(v as u32);
+            }
+        }
+        report
+    }
+}
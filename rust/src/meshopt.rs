@@ -0,0 +1,529 @@
+//! Encoders for the meshoptimizer buffer format (EXT_meshopt_compression),
+//! vertex codec version 0 and index codec version 1. Streams decode with
+//! meshopt_decodeVertexBuffer / meshopt_decodeIndexBuffer or the JavaScript
+//! MeshoptDecoder.
+
+const VERTEX_HEADER: u8 = 0xa0;
+const INDEX_HEADER: u8 = 0xe1;
+
+/// Values per byte group and the most vertices per block
+const BYTE_GROUP_SIZE: usize = 16;
+const VERTEX_BLOCK_MAX_SIZE: usize = 256;
+const VERTEX_BLOCK_SIZE_BYTES: usize = 8192;
+/// The first vertex is stored at the end, padded to at least this size
+const TAIL_MIN_SIZE: usize = 32;
+
+/// Encode vertex_size-byte vertices (a multiple of 4, at most 256)
+pub(crate) fn encode_vertex_buffer(data: &[u8], vertex_size: usize) -> Vec<u8> {
+    debug_assert!(vertex_size > 0 && vertex_size <= 256 && vertex_size.is_multiple_of(4));
+    let vertex_count = data.len() / vertex_size;
+    let mut output = vec![VERTEX_HEADER];
+
+    let first_vertex = if vertex_count > 0 {
+        data[..vertex_size].to_vec()
+    } else {
+        vec![0; vertex_size]
+    };
+    let mut last_vertex = first_vertex.clone();
+
+    let block_size = ((VERTEX_BLOCK_SIZE_BYTES / vertex_size) & !(BYTE_GROUP_SIZE - 1))
+        .min(VERTEX_BLOCK_MAX_SIZE);
+    let mut deltas = [0u8; VERTEX_BLOCK_MAX_SIZE];
+    for block in data[..vertex_count * vertex_size].chunks(block_size * vertex_size) {
+        let count = block.len() / vertex_size;
+        // Each byte lane of the vertex is delta coded against the previous vertex
+        for (k, last) in last_vertex.iter_mut().enumerate() {
+            deltas.fill(0);
+            let mut previous = *last;
+            for (i, delta) in deltas.iter_mut().take(count).enumerate() {
+                let value = block[i * vertex_size + k];
+                let difference = value.wrapping_sub(previous);
+                *delta = (difference << 1) ^ ((difference as i8 >> 7) as u8);
+                previous = value;
+            }
+            *last = previous;
+            let padded = count.div_ceil(BYTE_GROUP_SIZE) * BYTE_GROUP_SIZE;
+            encode_bytes(&mut output, &deltas[..padded]);
+        }
+    }
+
+    output.resize(output.len() + TAIL_MIN_SIZE.saturating_sub(vertex_size), 0);
+    output.extend_from_slice(&first_vertex);
+    output
+}
+
+/// Byte groups of one lane, each packed at 0, 2, 4 or 8 bits per value,
+/// preceded by a 2-bit mode per group
+fn encode_bytes(output: &mut Vec<u8>, lane: &[u8]) {
+    let groups = lane.len() / BYTE_GROUP_SIZE;
+    let header_start = output.len();
+    output.resize(header_start + groups.div_ceil(4), 0);
+
+    for (g, group) in lane.chunks_exact(BYTE_GROUP_SIZE).enumerate() {
+        let (mut best_mode, mut best_size) = (3, BYTE_GROUP_SIZE);
+        for mode in 0..3 {
+            if let Some(size) = group_size(group, mode) {
+                if size < best_size {
+                    best_mode = mode;
+                    best_size = size;
+                }
+            }
+        }
+        output[header_start + g / 4] |= (best_mode as u8) << ((g % 4) * 2);
+
+        match best_mode {
+            0 => {}
+            3 => output.extend_from_slice(group),
+            _ => {
+                let bits = 1 << best_mode;
+                let sentinel = (1u8 << bits) - 1;
+                for values in group.chunks_exact(8 / bits) {
+                    let mut byte = 0u8;
+                    for &value in values {
+                        byte = (byte << bits) | value.min(sentinel);
+                    }
+                    output.push(byte);
+                }
+                // Values that do not fit follow as whole bytes
+                output.extend(group.iter().filter(|&&value| value >= sentinel));
+            }
+        }
+    }
+}
+
+/// Encoded size of a byte group in a mode (0 = all zero, 1 = 2 bits, 2 = 4 bits)
+fn group_size(group: &[u8], mode: usize) -> Option<usize> {
+    if mode == 0 {
+        return group.iter().all(|&value| value == 0).then_some(0);
+    }
+    let bits = 1 << mode;
+    let sentinel = (1u8 << bits) - 1;
+    let overflow = group.iter().filter(|&&value| value >= sentinel).count();
+    Some(BYTE_GROUP_SIZE * bits / 8 + overflow)
+}
+
+/// Triangle corner orders for rotations 0-2
+const TRIANGLE_ORDER: [[usize; 3]; 3] = [[0, 1, 2], [1, 2, 0], [2, 0, 1]];
+
+/// Byte codes for common (feb, fec) pairs; stored after the data as the stream's table
+const CODE_AUX_TABLE: [u8; 16] = [
+    0x00, 0x76, 0x87, 0x56, 0x67, 0x78, 0xa9, 0x86, 0x65, 0x89, 0x68, 0x98, 0x01, 0x69, 0, 0,
+];
+
+/// Recently seen edges and vertices, mirrored by the decoder
+struct Fifos {
+    edges: [[u32; 2]; 16],
+    edge_offset: usize,
+    vertices: [u32; 16],
+    vertex_offset: usize,
+}
+
+impl Fifos {
+    /// Age of a recent edge matching a triangle edge, and the rotation that
+    /// brings that edge to the front
+    fn find_edge(&self, [a, b, c]: [u32; 3]) -> Option<(usize, usize)> {
+        (0..16).find_map(|i| {
+            let [e0, e1] = self.edges[(self.edge_offset + 15 - i) & 15];
+            if e0 == a && e1 == b {
+                Some((i, 0))
+            } else if e0 == b && e1 == c {
+                Some((i, 1))
+            } else if e0 == c && e1 == a {
+                Some((i, 2))
+            } else {
+                None
+            }
+        })
+    }
+
+    fn find_vertex(&self, v: u32) -> Option<usize> {
+        (0..16).find(|&i| self.vertices[(self.vertex_offset + 15 - i) & 15] == v)
+    }
+
+    fn push_edge(&mut self, a: u32, b: u32) {
+        self.edges[self.edge_offset] = [a, b];
+        self.edge_offset = (self.edge_offset + 1) & 15;
+    }
+
+    fn push_vertex(&mut self, v: u32) {
+        self.vertices[self.vertex_offset] = v;
+        self.vertex_offset = (self.vertex_offset + 1) & 15;
+    }
+}
+
+/// Zigzag delta of an index against the last explicitly coded one, as a varint
+fn encode_index(data: &mut Vec<u8>, index: u32, last: u32) {
+    let delta = index.wrapping_sub(last);
+    let mut value = (delta << 1) ^ ((delta as i32 >> 31) as u32);
+    loop {
+        data.push((value & 127) as u8 | if value > 127 { 128 } else { 0 });
+        value >>= 7;
+        if value == 0 {
+            break;
+        }
+    }
+}
+
+/// Encode a triangle list
+/// Triangles may be rotated, but keep their winding and order
+pub(crate) fn encode_index_buffer(indices: &[u32]) -> Vec<u8> {
+    let triangle_count = indices.len() / 3;
+    let mut codes = Vec::with_capacity(triangle_count);
+    let mut data = Vec::new();
+
+    let mut fifos = Fifos {
+        edges: [[u32::MAX; 2]; 16],
+        edge_offset: 0,
+        vertices: [u32::MAX; 16],
+        vertex_offset: 0,
+    };
+    let (mut next, mut last) = (0u32, 0u32);
+
+    for triangle in indices.chunks_exact(3) {
+        let triangle = [triangle[0], triangle[1], triangle[2]];
+
+        if let Some((fe, rotation)) = fifos.find_edge(triangle).filter(|&(fe, _)| fe < 15) {
+            // Two corners continue a recent edge; only the third needs coding
+            let order = TRIANGLE_ORDER[rotation];
+            let (a, b, c) = (triangle[order[0]], triangle[order[1]], triangle[order[2]]);
+            let mut fec = match fifos.find_vertex(c) {
+                Some(fc) if (1..13).contains(&fc) => fc,
+                _ if c == next => {
+                    next += 1;
+                    0
+                }
+                _ => 15,
+            };
+            if fec == 15 {
+                // Strip-like runs of last - 1 and last + 1
+                if c.wrapping_add(1) == last {
+                    fec = 13;
+                    last = c;
+                } else if c == last.wrapping_add(1) {
+                    fec = 14;
+                    last = c;
+                }
+            }
+            codes.push(((fe << 4) | fec) as u8);
+            if fec == 15 {
+                encode_index(&mut data, c, last);
+                last = c;
+            }
+            if fec == 0 || fec >= 13 {
+                fifos.push_vertex(c);
+            }
+            fifos.push_edge(c, b);
+            fifos.push_edge(a, c);
+        } else {
+            let rotation = if triangle[1] == next {
+                1
+            } else if triangle[2] == next {
+                2
+            } else {
+                0
+            };
+            let order = TRIANGLE_ORDER[rotation];
+            let (a, b, c) = (triangle[order[0]], triangle[order[1]], triangle[order[2]]);
+
+            // 0, 1, 2 after other vertices restarts the index sequence
+            let reset = a == 0 && b == 1 && c == 2 && next > 0;
+            if reset {
+                next = 0;
+                fifos.vertices = [u32::MAX; 16];
+            }
+
+            let (fb, fc) = (fifos.find_vertex(b), fifos.find_vertex(c));
+            let mut code = |v: u32, fifo: Option<usize>| match fifo {
+                Some(f) if f < 14 => f + 1,
+                _ if v == next => {
+                    next += 1;
+                    0
+                }
+                _ => 15,
+            };
+            let fea = code(a, None);
+            let feb = code(b, fb);
+            let fec = code(c, fc);
+
+            let code_aux = ((feb << 4) | fec) as u8;
+            match CODE_AUX_TABLE[..14]
+                .iter()
+                .position(|&entry| entry == code_aux)
+            {
+                Some(table_index) if fea == 0 && !reset => {
+                    codes.push(0xf0 | table_index as u8);
+                }
+                _ => {
+                    codes.push(0xf0 | 14 | fea as u8);
+                    data.push(code_aux);
+                }
+            }
+
+            for (v, fe) in [(a, fea), (b, feb), (c, fec)] {
+                if fe == 15 {
+                    encode_index(&mut data, v, last);
+                    last = v;
+                }
+            }
+            for (v, fe) in [(a, fea), (b, feb), (c, fec)] {
+                if fe == 0 || fe == 15 {
+                    fifos.push_vertex(v);
+                }
+            }
+            fifos.push_edge(b, a);
+            fifos.push_edge(c, b);
+            fifos.push_edge(a, c);
+        }
+    }
+
+    let mut output = Vec::with_capacity(1 + codes.len() + data.len() + 16);
+    output.push(INDEX_HEADER);
+    output.extend_from_slice(&codes);
+    output.extend_from_slice(&data);
+    output.extend_from_slice(&CODE_AUX_TABLE);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reference vertex decoder (meshopt_decodeVertexBuffer, version 0)
+    fn decode_vertex_buffer(buffer: &[u8], vertex_count: usize, vertex_size: usize) -> Vec<u8> {
+        assert_eq!(buffer[0], VERTEX_HEADER);
+        let mut last_vertex = buffer[buffer.len() - vertex_size..].to_vec();
+        let block_size = ((VERTEX_BLOCK_SIZE_BYTES / vertex_size) & !(BYTE_GROUP_SIZE - 1))
+            .min(VERTEX_BLOCK_MAX_SIZE);
+        let mut output = vec![0; vertex_count * vertex_size];
+        let mut position = 1;
+        for block_start in (0..vertex_count).step_by(block_size) {
+            let count = block_size.min(vertex_count - block_start);
+            let groups = count.div_ceil(BYTE_GROUP_SIZE);
+            for (k, last) in last_vertex.iter_mut().enumerate() {
+                let header = position;
+                position += groups.div_ceil(4);
+                let mut deltas = Vec::new();
+                for g in 0..groups {
+                    let mode = (buffer[header + g / 4] >> ((g % 4) * 2)) & 3;
+                    match mode {
+                        0 => deltas.extend([0; BYTE_GROUP_SIZE]),
+                        3 => {
+                            deltas.extend(&buffer[position..position + BYTE_GROUP_SIZE]);
+                            position += BYTE_GROUP_SIZE;
+                        }
+                        _ => {
+                            let bits = 1 << mode;
+                            let sentinel = (1u8 << bits) - 1;
+                            let mut overflow = position + BYTE_GROUP_SIZE * bits / 8;
+                            for i in 0..BYTE_GROUP_SIZE {
+                                let byte = buffer[position + i * bits / 8];
+                                let shift = 8 - bits - (i * bits) % 8;
+                                let mut value = (byte >> shift) & sentinel;
+                                if value == sentinel {
+                                    value = buffer[overflow];
+                                    overflow += 1;
+                                }
+                                deltas.push(value);
+                            }
+                            position = overflow;
+                        }
+                    }
+                }
+                for (i, &delta) in deltas.iter().take(count).enumerate() {
+                    *last = last.wrapping_add((delta >> 1) ^ (delta & 1).wrapping_neg());
+                    output[(block_start + i) * vertex_size + k] = *last;
+                }
+            }
+        }
+        assert_eq!(
+            buffer.len() - position,
+            TAIL_MIN_SIZE.max(vertex_size),
+            "tail"
+        );
+        output
+    }
+
+    /// Reference index decoder state (meshopt_decodeIndexBuffer, version 1)
+    struct IndexDecoder<'a> {
+        buffer: &'a [u8],
+        data: usize,
+        edges: [[u32; 2]; 16],
+        edge_offset: usize,
+        vertices: [u32; 16],
+        vertex_offset: usize,
+        next: u32,
+        last: u32,
+    }
+
+    impl IndexDecoder<'_> {
+        fn byte(&mut self) -> u8 {
+            self.data += 1;
+            self.buffer[self.data - 1]
+        }
+
+        /// Varint zigzag delta against the last explicit index
+        fn index(&mut self) -> u32 {
+            let (mut value, mut shift) = (0u32, 0);
+            loop {
+                let byte = self.byte();
+                value |= ((byte & 127) as u32) << shift;
+                shift += 7;
+                if byte < 128 {
+                    break;
+                }
+            }
+            self.last = self
+                .last
+                .wrapping_add((value >> 1) ^ (value & 1).wrapping_neg());
+            self.last
+        }
+
+        /// Vertex fifo entry at an age, or the next new index for age 0
+        fn vertex(&mut self, age: usize, base: usize) -> u32 {
+            if age == 0 {
+                self.next += 1;
+                self.next - 1
+            } else {
+                self.vertices[(self.vertex_offset + 16 - base - age) & 15]
+            }
+        }
+
+        fn push_edge(&mut self, a: u32, b: u32) {
+            self.edges[self.edge_offset] = [a, b];
+            self.edge_offset = (self.edge_offset + 1) & 15;
+        }
+
+        fn push_vertex(&mut self, v: u32, advance: bool) {
+            self.vertices[self.vertex_offset] = v;
+            self.vertex_offset = (self.vertex_offset + advance as usize) & 15;
+        }
+    }
+
+    fn decode_index_buffer(buffer: &[u8], index_count: usize) -> Vec<u32> {
+        assert_eq!(buffer[0], INDEX_HEADER);
+        let table = &buffer[buffer.len() - 16..];
+        let codes = &buffer[1..1 + index_count / 3];
+        let mut decoder = IndexDecoder {
+            buffer,
+            data: 1 + codes.len(),
+            edges: [[u32::MAX; 2]; 16],
+            edge_offset: 0,
+            vertices: [u32::MAX; 16],
+            vertex_offset: 0,
+            next: 0,
+            last: 0,
+        };
+        let mut output = Vec::with_capacity(index_count);
+
+        for &code in codes {
+            let (a, b, c);
+            if code < 0xf0 {
+                let fe = (code >> 4) as usize;
+                [a, b] = decoder.edges[(decoder.edge_offset + 15 - fe) & 15];
+                let fec = (code & 15) as usize;
+                c = match fec {
+                    13 => decoder.last.wrapping_sub(1),
+                    14 => decoder.last.wrapping_add(1),
+                    15 => decoder.index(),
+                    _ => decoder.vertex(fec, 1),
+                };
+                if fec >= 13 {
+                    decoder.last = c;
+                }
+                decoder.push_vertex(c, fec == 0 || fec >= 13);
+                decoder.push_edge(c, b);
+                decoder.push_edge(a, c);
+            } else {
+                let explicit = code >= 0xfe;
+                let aux = if explicit {
+                    decoder.byte()
+                } else {
+                    table[(code & 15) as usize]
+                };
+                if explicit && aux == 0 {
+                    decoder.next = 0;
+                }
+                let fea = if code == 0xff { 15 } else { 0 };
+                let (feb, fec) = ((aux >> 4) as usize, (aux & 15) as usize);
+                let mut corners = [fea, feb, fec].map(|fe| match fe {
+                    15 => 0,
+                    _ => decoder.vertex(fe, 0),
+                });
+                for (corner, fe) in corners.iter_mut().zip([fea, feb, fec]) {
+                    if fe == 15 {
+                        *corner = decoder.index();
+                    }
+                }
+                [a, b, c] = corners;
+                decoder.push_vertex(a, true);
+                decoder.push_vertex(b, feb == 0 || feb == 15);
+                decoder.push_vertex(c, fec == 0 || fec == 15);
+                decoder.push_edge(b, a);
+                decoder.push_edge(c, b);
+                decoder.push_edge(a, c);
+            }
+            output.extend([a, b, c]);
+        }
+        assert_eq!(decoder.data, buffer.len() - 16);
+        output
+    }
+
+    /// Triangles rotated to start at their smallest index, keeping winding
+    fn canonical(indices: &[u32]) -> Vec<[u32; 3]> {
+        indices
+            .chunks_exact(3)
+            .map(|t| {
+                let first = (0..3).min_by_key(|&i| t[i]).unwrap();
+                [t[first], t[(first + 1) % 3], t[(first + 2) % 3]]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn vertex_buffers_round_trip() {
+        let mut seed = 12345u32;
+        let mut random = || {
+            seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+            (seed >> 24) as u8
+        };
+        for (vertex_count, vertex_size) in [(0, 12), (1, 4), (300, 12), (1000, 8), (40, 64)] {
+            let data: Vec<u8> = (0..vertex_count * vertex_size)
+                .map(|i| match i % 4 {
+                    // Smooth, noisy and constant lanes exercise every group mode
+                    0 => (i / vertex_size) as u8,
+                    1 => random(),
+                    2 => (i / vertex_size / 7) as u8 ^ (random() & 3),
+                    _ => 0,
+                })
+                .collect();
+            let encoded = encode_vertex_buffer(&data, vertex_size);
+            assert_eq!(
+                decode_vertex_buffer(&encoded, vertex_count, vertex_size),
+                data
+            );
+        }
+    }
+
+    #[test]
+    fn index_buffers_round_trip() {
+        // A grid, then triangles reaching the other codes
+        let mut indices = Vec::new();
+        let columns = 20u32;
+        for y in 0..6 {
+            for x in 0..columns - 1 {
+                let (i, j) = (y * columns + x, (y + 1) * columns + x);
+                indices.extend([i, j, i + 1, i + 1, j, j + 1]);
+            }
+        }
+        // Restarting at 0, 1, 2 once the grid's edges have left the fifo
+        indices.extend([0, 1, 2, 2, 1, 3, 4, 2, 3, 100_000, 4, 3]);
+        // Ascending and descending runs coded as last + 1 and last - 1
+        indices.extend([1000, 1001, 1002, 1002, 1001, 1003]);
+        indices.extend([2000, 1999, 1998, 1998, 1999, 1997]);
+        indices.extend([500, 3, 77, 77, 3, 1000, 2, 1, 0, 40, 41, 39]);
+
+        let encoded = encode_index_buffer(&indices);
+        let decoded = decode_index_buffer(&encoded, indices.len());
+        assert_eq!(canonical(&decoded), canonical(&indices));
+    }
+}
@@ -0,0 +1,223 @@
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+use wasm_bindgen::prelude::*;
+
+use crate::coordinate_transform::CoordinateTransform;
+use crate::elevation_tile::ElevationTile;
+use crate::error::{tile_length_error, Error};
+use crate::tile_cache::Fnv1a;
+use crate::validation::{check_tile_index, check_zoom};
+
+/// Pixels per tile edge
+const TILE_DIM: i64 = 256;
+
+/// WGS84 equatorial radius in meters (Web Mercator sphere)
+const EARTH_RADIUS: f64 = 6378137.0;
+
+/// Set of 256x256 elevation tiles at one zoom level, sampled as one seamless raster
+/// Pixel (i, j) of tile (x, y) sits at global pixel (x * 256 + i, y * 256 + j),
+/// matching the vertex placement of MeshGenerator
+#[wasm_bindgen]
+pub struct Mosaic {
+    zoom: u8,
+    tiles: HashMap<(u32, u32), Vec<f32>>,
+}
+
+#[wasm_bindgen]
+impl Mosaic {
+    #[wasm_bindgen(constructor)]
+    pub fn new(zoom: u8) -> Mosaic {
+        Mosaic {
+            zoom,
+            tiles: HashMap::new(),
+        }
+    }
+
+    /// Zoom level of every tile in the mosaic
+    #[wasm_bindgen]
+    pub fn zoom(&self) -> u8 {
+        self.zoom
+    }
+
+    /// Add or replace a tile
+    /// elevations: 256x256 heightmap (65536 values)
+    #[wasm_bindgen]
+    pub fn set_tile(&mut self, tile_x: u32, tile_y: u32, elevations: &[f32]) -> Result<(), Error> {
+        check_zoom(self.zoom)?;
+        check_tile_index("tile_x", tile_x, self.zoom)?;
+        check_tile_index("tile_y", tile_y, self.zoom)?;
+        if elevations.len() != 65536 {
+            return Err(tile_length_error(elevations.len()));
+        }
+        self.tiles.insert((tile_x, tile_y), elevations.to_vec());
+        Ok(())
+    }
+
+    /// Remove a tile; returns false if it was not loaded
+    #[wasm_bindgen]
+    pub fn remove_tile(&mut self, tile_x: u32, tile_y: u32) -> bool {
+        self.tiles.remove(&(tile_x, tile_y)).is_some()
+    }
+
+    #[wasm_bindgen]
+    pub fn has_tile(&self, tile_x: u32, tile_y: u32) -> bool {
+        self.tiles.contains_key(&(tile_x, tile_y))
+    }
+
+    #[wasm_bindgen]
+    pub fn tile_count(&self) -> usize {
+        self.tiles.len()
+    }
+
+    #[wasm_bindgen]
+    pub fn clear(&mut self) {
+        self.tiles.clear();
+    }
+
+    /// Bilinear elevation at a lat/lon, or undefined when its tile is not loaded
+    /// (or the lat/lon is not finite)
+    #[wasm_bindgen]
+    pub fn elevation_at(&self, lat: f64, lon: f64) -> Option<f32> {
+        let (px, py) = self.latlon_to_pixel(lat, lon)?;
+        self.sample_pixel(px, py)
+    }
+
+    /// Along-the-ground distance in kilometers between two points: the terrain
+    /// profile sampled about a pixel apart on the straight line between them,
+    /// each step counting its climb. Undefined when the line leaves the
+    /// loaded tiles
+    #[wasm_bindgen]
+    pub fn surface_distance_km(&self, lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> Option<f64> {
+        let ground = CoordinateTransform::haversine_km(lat1, lon1, lat2, lon2) * 1000.0;
+        let spacing = self.meters_per_pixel((lat1 + lat2) / 2.0).max(f64::EPSILON);
+        let steps = ((ground / spacing).ceil() as usize).max(1);
+        let step_length = ground / steps as f64;
+
+        let mut previous = self.elevation_at(lat1, lon1)? as f64;
+        let mut distance = 0.0;
+        for step in 1..=steps {
+            let t = step as f64 / steps as f64;
+            let elevation =
+                self.elevation_at(lat1 + (lat2 - lat1) * t, lon1 + (lon2 - lon1) * t)? as f64;
+            distance += step_length.hypot(elevation - previous);
+            previous = elevation;
+        }
+        Some(distance / 1000.0)
+    }
+
+    /// The pixels framing a tile for MeshGenerator::generate_with_borders:
+    /// the row north of it, the column east, the row south and the column west,
+    /// 256 each; NaN where the neighbor is not loaded
+    #[wasm_bindgen]
+    pub fn tile_borders(&self, tile_x: u32, tile_y: u32) -> Vec<f32> {
+        let (x0, y0) = (tile_x as i64 * TILE_DIM, tile_y as i64 * TILE_DIM);
+        let pixel = |gx: i64, gy: i64| self.pixel(gx, gy).unwrap_or(f32::NAN);
+        let mut borders = Vec::with_capacity(4 * TILE_DIM as usize);
+        borders.extend((0..TILE_DIM).map(|i| pixel(x0 + i, y0 - 1)));
+        borders.extend((0..TILE_DIM).map(|i| pixel(x0 + TILE_DIM, y0 + i)));
+        borders.extend((0..TILE_DIM).map(|i| pixel(x0 + i, y0 + TILE_DIM)));
+        borders.extend((0..TILE_DIM).map(|i| pixel(x0 - 1, y0 + i)));
+        borders
+    }
+}
+
+impl Mosaic {
+    /// Hash of the zoom and every loaded tile, independent of load order
+    pub(crate) fn content_hash(&self) -> u64 {
+        let mut keys: Vec<&(u32, u32)> = self.tiles.keys().collect();
+        keys.sort_unstable();
+        let mut hasher = Fnv1a::new();
+        hasher.write(&[self.zoom]);
+        for key in keys {
+            hasher.write(&key.0.to_le_bytes());
+            hasher.write(&key.1.to_le_bytes());
+            hasher.write_f32s(&self.tiles[key]);
+        }
+        hasher.finish()
+    }
+
+    /// Add or replace a tile without copying its heights
+    pub fn insert_tile(&mut self, tile_x: u32, tile_y: u32, tile: ElevationTile) {
+        self.tiles.insert((tile_x, tile_y), tile.into_vec());
+    }
+
+    /// Heights of a loaded tile (65536 values)
+    pub fn tile(&self, tile_x: u32, tile_y: u32) -> Option<&[f32]> {
+        self.tiles.get(&(tile_x, tile_y)).map(Vec::as_slice)
+    }
+
+    /// Fractional global pixel coordinates of a lat/lon at the mosaic zoom,
+    /// None unless both are finite (the poles map to infinity)
+    pub(crate) fn latlon_to_pixel(&self, lat: f64, lon: f64) -> Option<(f64, f64)> {
+        let scale = (1u64 << self.zoom) as f64 * TILE_DIM as f64;
+        let lat_rad = lat.to_radians();
+        let x = (lon + 180.0) / 360.0 * scale;
+        let y = (1.0 - (lat_rad.tan() + 1.0 / lat_rad.cos()).ln() / PI) / 2.0 * scale;
+        (x.is_finite() && y.is_finite()).then_some((x, y))
+    }
+
+    /// Lat/lon of fractional global pixel coordinates (inverse of latlon_to_pixel)
+    pub(crate) fn pixel_to_latlon(&self, px: f64, py: f64) -> (f64, f64) {
+        let scale = (1u64 << self.zoom) as f64 * TILE_DIM as f64;
+        let lon = px / scale * 360.0 - 180.0;
+        let lat = (PI * (1.0 - 2.0 * py / scale)).sinh().atan().to_degrees();
+        (lat, lon)
+    }
+
+    /// Ground distance covered by one pixel at a latitude, in meters
+    pub(crate) fn meters_per_pixel(&self, lat: f64) -> f64 {
+        let scale = (1u64 << self.zoom) as f64 * TILE_DIM as f64;
+        2.0 * PI * EARTH_RADIUS * lat.to_radians().cos() / scale
+    }
+
+    /// Elevation of a single global pixel, if its tile is loaded
+    pub(crate) fn pixel(&self, gx: i64, gy: i64) -> Option<f32> {
+        if gx < 0 || gy < 0 {
+            return None;
+        }
+        let key = (
+            u32::try_from(gx / TILE_DIM).ok()?,
+            u32::try_from(gy / TILE_DIM).ok()?,
+        );
+        let tile = self.tiles.get(&key)?;
+        Some(tile[((gy % TILE_DIM) * TILE_DIM + gx % TILE_DIM) as usize])
+    }
+
+    /// Bilinear sample at fractional global pixel coordinates
+    /// Neighbors in missing tiles fall back to the nearest loaded pixel
+    pub(crate) fn sample_pixel(&self, px: f64, py: f64) -> Option<f32> {
+        if !px.is_finite() || !py.is_finite() {
+            return None;
+        }
+        let (x0, y0) = (px.floor() as i64, py.floor() as i64);
+        let (x1, y1) = (x0.checked_add(1)?, y0.checked_add(1)?);
+        let (tx, ty) = ((px - x0 as f64) as f32, (py - y0 as f64) as f32);
+        let base = self.pixel(x0, y0)?;
+        let right = self.pixel(x1, y0).unwrap_or(base);
+        let below = self.pixel(x0, y1).unwrap_or(base);
+        let diagonal = self.pixel(x1, y1).unwrap_or(below);
+
+        let top = base * (1.0 - tx) + right * tx;
+        let bottom = below * (1.0 - tx) + diagonal * tx;
+        Some(top * (1.0 - ty) + bottom * ty)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn non_finite_and_far_points_have_no_elevation() {
+        let mut mosaic = Mosaic::new(0);
+        mosaic.set_tile(0, 0, &[5.0; 65536]).unwrap();
+        assert_eq!(mosaic.elevation_at(35.0, 139.0), Some(5.0));
+
+        assert_eq!(mosaic.elevation_at(f64::NAN, 139.0), None);
+        assert_eq!(mosaic.elevation_at(35.0, f64::INFINITY), None);
+        assert_eq!(mosaic.elevation_at(90.0, 139.0), None);
+        assert_eq!(mosaic.elevation_at(35.0, 1e300), None);
+        assert_eq!(mosaic.surface_distance_km(f64::NAN, 0.0, 35.0, 139.0), None);
+    }
+}
@@ -0,0 +1,88 @@
+use std::collections::BTreeMap;
+
+use wasm_bindgen::prelude::*;
+
+use crate::mesh_generator::{MeshData, MeshGenerator};
+use crate::mosaic::Mosaic;
+use crate::tile_cache::{format_hash, Fnv1a};
+
+/// Digest of a terrain pipeline run: the loaded mosaic, the MeshGenerator
+/// options and the meshes generated from them
+/// Everything is hashed by value in a fixed order (meshes by tile and LOD, not
+/// by the order they were added), so equal inputs give the same state_hash()
+/// in every browser and build, for end-to-end tests and bug reports.
+#[wasm_bindgen]
+pub struct Pipeline {
+    mosaic: Option<u64>,
+    config: Option<u64>,
+    meshes: BTreeMap<(u32, u32, u8), u64>,
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Pipeline::new()
+    }
+}
+
+#[wasm_bindgen]
+impl Pipeline {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Pipeline {
+        Pipeline {
+            mosaic: None,
+            config: None,
+            meshes: BTreeMap::new(),
+        }
+    }
+
+    /// Record the mosaic's current tiles (call again after loading more)
+    #[wasm_bindgen]
+    pub fn set_mosaic(&mut self, mosaic: &Mosaic) {
+        self.mosaic = Some(mosaic.content_hash());
+    }
+
+    /// Record the generator options
+    #[wasm_bindgen]
+    pub fn set_generator(&mut self, generator: &MeshGenerator) {
+        self.config = Some(generator.config_hash());
+    }
+
+    /// Record a generated mesh, replacing any earlier one for the same tile and LOD
+    #[wasm_bindgen]
+    pub fn add_mesh(&mut self, tile_x: u32, tile_y: u32, lod_level: u8, mesh: &MeshData) {
+        self.meshes
+            .insert((tile_x, tile_y, lod_level), mesh.content_hash());
+    }
+
+    #[wasm_bindgen]
+    pub fn mesh_count(&self) -> usize {
+        self.meshes.len()
+    }
+
+    #[wasm_bindgen]
+    pub fn clear_meshes(&mut self) {
+        self.meshes.clear();
+    }
+
+    /// Combined digest as 16 hex digits
+    #[wasm_bindgen]
+    pub fn state_hash(&self) -> String {
+        let mut hasher = Fnv1a::new();
+        for part in [self.mosaic, self.config] {
+            match part {
+                Some(hash) => {
+                    hasher.write(&[1]);
+                    hasher.write(&hash.to_le_bytes());
+                }
+                None => hasher.write(&[0]),
+            }
+        }
+        for (&(tile_x, tile_y, lod_level), hash) in &self.meshes {
+            hasher.write(&tile_x.to_le_bytes());
+            hasher.write(&tile_y.to_le_bytes());
+            hasher.write(&[lod_level]);
+            hasher.write(&hash.to_le_bytes());
+        }
+        format_hash(hasher.finish())
+    }
+}
@@ -0,0 +1,81 @@
+use glam::Vec3;
+use wasm_bindgen::prelude::*;
+
+use crate::mesh_generator::MeshBuffers;
+
+/// Nearest intersection of a ray with a mesh, in the mesh's coordinates
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct RayHit {
+    distance: f32,
+    point: Vec3,
+    normal: Vec3,
+    triangle: u32,
+}
+
+#[wasm_bindgen]
+impl RayHit {
+    /// Distance from the ray origin, in world units
+    #[wasm_bindgen]
+    pub fn distance(&self) -> f32 {
+        self.distance
+    }
+
+    /// Hit position as [x, y, z]
+    #[wasm_bindgen]
+    pub fn point(&self) -> Vec<f32> {
+        self.point.to_array().to_vec()
+    }
+
+    /// Unit face normal of the hit triangle as [x, y, z], on its front
+    /// (counter-clockwise) side
+    #[wasm_bindgen]
+    pub fn normal(&self) -> Vec<f32> {
+        self.normal.to_array().to_vec()
+    }
+
+    /// Index of the hit triangle (indices 3n..3n+3)
+    #[wasm_bindgen]
+    pub fn triangle(&self) -> u32 {
+        self.triangle
+    }
+}
+
+/// Möller-Trumbore test against every triangle, keeping the nearest hit in
+/// front of the origin; both faces count, so rays from below hit too
+pub(crate) fn raycast(mesh: &MeshBuffers, origin: Vec3, direction: Vec3) -> Option<RayHit> {
+    let direction = direction.try_normalize()?;
+    let position = |i: u32| Vec3::from_slice(&mesh.vertices[i as usize * 3..i as usize * 3 + 3]);
+
+    let mut nearest: Option<RayHit> = None;
+    for (n, triangle) in mesh.triangle_corners().chunks_exact(3).enumerate() {
+        let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(position);
+        let (edge1, edge2) = (b - a, c - a);
+        let p = direction.cross(edge2);
+        let determinant = edge1.dot(p);
+        if determinant.abs() <= f32::EPSILON * edge1.length_squared().max(edge2.length_squared()) {
+            continue;
+        }
+        let offset = origin - a;
+        let u = offset.dot(p) / determinant;
+        if !(0.0..=1.0).contains(&u) {
+            continue;
+        }
+        let q = offset.cross(edge1);
+        let v = direction.dot(q) / determinant;
+        if v < 0.0 || u + v > 1.0 {
+            continue;
+        }
+        let distance = edge2.dot(q) / determinant;
+        if distance < 0.0 || nearest.is_some_and(|hit| hit.distance <= distance) {
+            continue;
+        }
+        nearest = Some(RayHit {
+            distance,
+            point: origin + direction * distance,
+            normal: edge1.cross(edge2).normalize_or_zero(),
+            triangle: n as u32,
+        });
+    }
+    nearest
+}
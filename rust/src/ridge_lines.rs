@@ -0,0 +1,301 @@
+use wasm_bindgen::prelude::*;
+
+use crate::error::Error;
+use crate::heightfield::Heightfield;
+use crate::hydrology::{d8_receivers, flow_accumulation};
+
+/// Polyline in fractional pixel coordinates
+type Polyline = Vec<(f32, f32)>;
+
+/// Ridge polylines extracted from a heightmap, longest first
+#[wasm_bindgen]
+pub struct RidgeLines {
+    /// World positions (x, y, z) of all polyline points
+    points: Vec<f32>,
+    /// Start index (in points, not floats) of each polyline, plus a final end offset
+    offsets: Vec<u32>,
+    /// Horizontal length in meters
+    lengths: Vec<f32>,
+    /// Chord length / path length (1 = perfectly straight)
+    straightness: Vec<f32>,
+}
+
+#[wasm_bindgen]
+impl RidgeLines {
+    /// Number of polylines
+    #[wasm_bindgen]
+    pub fn count(&self) -> usize {
+        self.lengths.len()
+    }
+
+    /// Get all polyline points as a flat (x, y, z) array
+    #[wasm_bindgen]
+    pub fn get_points(&self) -> Vec<f32> {
+        self.points.clone()
+    }
+
+    /// Get polyline point offsets; polyline i spans points offsets[i]..offsets[i + 1]
+    #[wasm_bindgen]
+    pub fn get_offsets(&self) -> Vec<u32> {
+        self.offsets.clone()
+    }
+
+    /// Get polyline lengths in meters
+    #[wasm_bindgen]
+    pub fn get_lengths(&self) -> Vec<f32> {
+        self.lengths.clone()
+    }
+
+    /// Get polyline straightness (chord / length, 0..1)
+    #[wasm_bindgen]
+    pub fn get_straightness(&self) -> Vec<f32> {
+        self.straightness.clone()
+    }
+}
+
+#[wasm_bindgen]
+pub struct RidgeExtractor {
+    min_accumulation: f32,
+    min_length: f32,
+    join_distance: f32,
+    smoothing_iterations: u32,
+}
+
+#[wasm_bindgen]
+impl RidgeExtractor {
+    /// min_accumulation: cells that must drain "uphill" through a cell for it to count as ridge
+    /// min_length: shortest polyline to keep, in meters
+    #[wasm_bindgen(constructor)]
+    pub fn new(min_accumulation: f32, min_length: f32) -> RidgeExtractor {
+        RidgeExtractor {
+            min_accumulation,
+            min_length,
+            join_distance: 3.0,
+            smoothing_iterations: 3,
+        }
+    }
+
+    /// Maximum gap (in pixels) bridged when joining ridge pieces across peaks and saddles
+    #[wasm_bindgen]
+    pub fn set_join_distance(&mut self, pixels: f32) {
+        self.join_distance = pixels;
+    }
+
+    /// Number of Chaikin corner-cutting passes applied to each polyline
+    #[wasm_bindgen]
+    pub fn set_smoothing(&mut self, iterations: u32) {
+        self.smoothing_iterations = iterations;
+    }
+
+    /// Extract smooth ridge polylines suitable as curved label baselines
+    /// elevations: width x height heightmap
+    /// cell_size: ground distance between pixels in meters
+    /// tile_size: size of the heightmap in world units (positions match MeshGenerator)
+    #[wasm_bindgen]
+    pub fn extract(
+        &self,
+        elevations: &[f32],
+        width: u32,
+        height: u32,
+        cell_size: f32,
+        tile_size: f32,
+    ) -> Result<RidgeLines, Error> {
+        let heightmap = Heightfield::from_slice(elevations, width, height)?;
+        if cell_size <= 0.0 {
+            return Err(Error::InvalidArgument("Cell size must be positive".into()));
+        }
+        let (width, height) = (width as usize, height as usize);
+
+        // Ridges are the drainage network of the inverted terrain
+        let inverted: Vec<f32> = elevations.iter().map(|&h| -h).collect();
+        let receivers = d8_receivers(&inverted, width, height);
+        let accumulation = flow_accumulation(&inverted, &receivers);
+        let network: Vec<bool> = accumulation
+            .iter()
+            .map(|&a| a >= self.min_accumulation)
+            .collect();
+
+        let traced = trace_network(elevations, &receivers, &network, width);
+        let joined = join_polylines(traced, self.join_distance);
+
+        let pixel_size = tile_size / width as f32;
+        let mut ridges: Vec<(Polyline, f32, f32)> = joined
+            .into_iter()
+            .filter_map(|line| {
+                let line = chaikin(line, self.smoothing_iterations);
+                let length = polyline_length(&line) * cell_size;
+                if length < self.min_length || length <= 0.0 {
+                    return None;
+                }
+                let first = line[0];
+                let last = line[line.len() - 1];
+                let chord = ((last.0 - first.0).powi(2) + (last.1 - first.1).powi(2)).sqrt();
+                let straightness = chord * cell_size / length;
+                Some((line, length, straightness))
+            })
+            .collect();
+        ridges.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        let mut result = RidgeLines {
+            points: Vec::new(),
+            offsets: vec![0],
+            lengths: Vec::new(),
+            straightness: Vec::new(),
+        };
+        for (line, length, straightness) in ridges {
+            for &(x, y) in &line {
+                result.points.push(x * pixel_size - tile_size / 2.0);
+                result.points.push(heightmap.sample(x, y));
+                result.points.push(y * pixel_size - tile_size / 2.0);
+            }
+            result.offsets.push((result.points.len() / 3) as u32);
+            result.lengths.push(length);
+            result.straightness.push(straightness);
+        }
+
+        Ok(result)
+    }
+}
+
+/// Split the ridge network into polylines, longest upstream paths first
+fn trace_network(
+    elevations: &[f32],
+    receivers: &[isize],
+    network: &[bool],
+    width: usize,
+) -> Vec<Polyline> {
+    let cell_count = elevations.len();
+    let coords = |index: usize| ((index % width) as f32, (index / width) as f32);
+
+    // Path length from each network cell up to its summit; summits are processed first
+    let mut order: Vec<usize> = (0..cell_count).filter(|&i| network[i]).collect();
+    order.sort_by(|&a, &b| elevations[b].total_cmp(&elevations[a]));
+    let mut distance = vec![0.0f32; cell_count];
+    let mut has_upstream = vec![false; cell_count];
+    for &index in &order {
+        let receiver = receivers[index];
+        if receiver >= 0 && network[receiver as usize] {
+            let receiver = receiver as usize;
+            let (x0, y0) = coords(index);
+            let (x1, y1) = coords(receiver);
+            distance[index] = distance[receiver] + ((x1 - x0).powi(2) + (y1 - y0).powi(2)).sqrt();
+            has_upstream[receiver] = true;
+        }
+    }
+
+    let mut leaves: Vec<usize> = order
+        .iter()
+        .copied()
+        .filter(|&i| !has_upstream[i])
+        .collect();
+    leaves.sort_by(|&a, &b| distance[b].total_cmp(&distance[a]));
+
+    let mut used = vec![false; cell_count];
+    let mut polylines = Vec::new();
+    for leaf in leaves {
+        let mut line = vec![coords(leaf)];
+        used[leaf] = true;
+        let mut current = leaf;
+        while receivers[current] >= 0 && network[receivers[current] as usize] {
+            let next = receivers[current] as usize;
+            line.push(coords(next));
+            if used[next] {
+                break;
+            }
+            used[next] = true;
+            current = next;
+        }
+        if line.len() >= 2 {
+            polylines.push(line);
+        }
+    }
+    polylines
+}
+
+/// Greedily join polylines whose endpoints nearly touch and whose directions continue
+fn join_polylines(mut lines: Vec<Polyline>, max_gap: f32) -> Vec<Polyline> {
+    // Directions must stay within 60 degrees of each other across the join
+    const MIN_ALIGNMENT: f32 = 0.5;
+
+    loop {
+        let mut best: Option<(usize, usize, bool, bool, f32)> = None;
+        for i in 0..lines.len() {
+            for j in (i + 1)..lines.len() {
+                for &end_i in &[false, true] {
+                    for &end_j in &[false, true] {
+                        let (pi, di) = endpoint(&lines[i], end_i);
+                        let (pj, dj) = endpoint(&lines[j], end_j);
+                        let gap = ((pi.0 - pj.0).powi(2) + (pi.1 - pj.1).powi(2)).sqrt();
+                        if gap > max_gap {
+                            continue;
+                        }
+                        // Outward directions must point at each other
+                        let alignment = -(di.0 * dj.0 + di.1 * dj.1);
+                        if alignment < MIN_ALIGNMENT {
+                            continue;
+                        }
+                        if best.is_none_or(|b| gap < b.4) {
+                            best = Some((i, j, end_i, end_j, gap));
+                        }
+                    }
+                }
+            }
+        }
+
+        let Some((i, j, end_i, end_j, _)) = best else {
+            return lines;
+        };
+        let mut second = lines.swap_remove(j);
+        let first = &mut lines[i];
+        // Orient so the joined ends meet: first runs into its end, second runs out of its start
+        if !end_i {
+            first.reverse();
+        }
+        if end_j {
+            second.reverse();
+        }
+        if first.last() == second.first() {
+            second.remove(0);
+        }
+        first.extend(second);
+    }
+}
+
+/// Endpoint of a polyline and the unit direction pointing outward from it
+fn endpoint(line: &[(f32, f32)], at_end: bool) -> ((f32, f32), (f32, f32)) {
+    let n = line.len();
+    let reach = (n - 1).min(4);
+    let (tip, inner) = if at_end {
+        (line[n - 1], line[n - 1 - reach])
+    } else {
+        (line[0], line[reach])
+    };
+    let (dx, dy) = (tip.0 - inner.0, tip.1 - inner.1);
+    let length = (dx * dx + dy * dy).sqrt().max(f32::EPSILON);
+    (tip, (dx / length, dy / length))
+}
+
+/// Chaikin corner cutting, keeping the original endpoints
+fn chaikin(mut line: Polyline, iterations: u32) -> Polyline {
+    for _ in 0..iterations {
+        if line.len() < 3 {
+            break;
+        }
+        let mut smoothed = Vec::with_capacity(line.len() * 2);
+        smoothed.push(line[0]);
+        for pair in line.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            smoothed.push((0.75 * a.0 + 0.25 * b.0, 0.75 * a.1 + 0.25 * b.1));
+            smoothed.push((0.25 * a.0 + 0.75 * b.0, 0.25 * a.1 + 0.75 * b.1));
+        }
+        smoothed.push(line[line.len() - 1]);
+        line = smoothed;
+    }
+    line
+}
+
+fn polyline_length(line: &[(f32, f32)]) -> f32 {
+    line.windows(2)
+        .map(|pair| ((pair[1].0 - pair[0].0).powi(2) + (pair[1].1 - pair[0].1).powi(2)).sqrt())
+        .sum()
+}
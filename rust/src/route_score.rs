@@ -0,0 +1,269 @@
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::coordinate_transform::CoordinateTransform;
+use crate::error::Error;
+use crate::mosaic::Mosaic;
+
+/// Summary of a scored route (distances in meters, times in hours)
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct RouteScore {
+    pub distance_m: f64,
+    pub ascent_m: f64,
+    pub descent_m: f64,
+    pub min_elevation_m: f64,
+    pub max_elevation_m: f64,
+    pub tobler_hours: f64,
+    pub naismith_hours: f64,
+    pub max_sustained_grade: f64,
+    pub max_adjacent_slope_deg: f64,
+    pub exposed_distance_m: f64,
+    /// 1 (easy) to 5 (expert); the worst of the time, grade and exposure ratings
+    pub difficulty: u8,
+}
+
+/// Hiking time and difficulty estimate for a drawn or imported route
+#[wasm_bindgen]
+pub struct RouteScorer {
+    sustained_distance: f64,
+    exposure_slope: f64,
+}
+
+impl Default for RouteScorer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen]
+impl RouteScorer {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> RouteScorer {
+        RouteScorer {
+            sustained_distance: 200.0,
+            exposure_slope: 35.0,
+        }
+    }
+
+    /// Length in meters over which the maximum sustained grade is averaged
+    #[wasm_bindgen]
+    pub fn set_sustained_distance(&mut self, meters: f64) {
+        self.sustained_distance = meters.max(0.0);
+    }
+
+    /// Terrain slope in degrees beside the path above which the route counts as exposed
+    #[wasm_bindgen]
+    pub fn set_exposure_slope(&mut self, degrees: f64) {
+        self.exposure_slope = degrees;
+    }
+
+    /// Score a route over the loaded tiles as a RouteScore object
+    /// (distances in meters, times in hours); see score()
+    #[wasm_bindgen(js_name = score)]
+    pub fn score_js(
+        &self,
+        mosaic: &Mosaic,
+        lats: &[f64],
+        lons: &[f64],
+    ) -> Result<JsValue, JsValue> {
+        let score = self.score(mosaic, lats, lons)?;
+        serde_wasm_bindgen::to_value(&score).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+}
+
+impl RouteScorer {
+    /// Score a route over the loaded tiles
+    /// lats, lons: route vertices in degrees; segments are resampled at DEM resolution
+    pub fn score(&self, mosaic: &Mosaic, lats: &[f64], lons: &[f64]) -> Result<RouteScore, Error> {
+        if lats.len() != lons.len() {
+            return Err(Error::InvalidArgument(format!(
+                "Coordinate length mismatch: {} latitudes, {} longitudes",
+                lats.len(),
+                lons.len()
+            )));
+        }
+        if lats.len() < 2 {
+            return Err(Error::InvalidArgument(
+                "Route needs at least 2 points".into(),
+            ));
+        }
+
+        let samples = densify(mosaic, lats, lons);
+        let mut distances = Vec::with_capacity(samples.len());
+        let mut elevations = Vec::with_capacity(samples.len());
+        let mut adjacent_slopes = Vec::with_capacity(samples.len());
+        let mut distance = 0.0;
+        for (i, &(lat, lon)) in samples.iter().enumerate() {
+            if i > 0 {
+                let (prev_lat, prev_lon) = samples[i - 1];
+                distance += CoordinateTransform::haversine_km(prev_lat, prev_lon, lat, lon) * 1000.0;
+            }
+            let leaves = || {
+                Error::InvalidArgument(format!(
+                    "Route leaves the loaded tiles near {:.5}, {:.5}",
+                    lat, lon
+                ))
+            };
+            let (px, py) = mosaic.latlon_to_pixel(lat, lon).ok_or_else(leaves)?;
+            let elevation = mosaic.sample_pixel(px, py).ok_or_else(leaves)?;
+            distances.push(distance);
+            elevations.push(elevation as f64);
+            adjacent_slopes.push(adjacent_slope(mosaic, px, py, mosaic.meters_per_pixel(lat)));
+        }
+
+        let mut score = RouteScore {
+            distance_m: distance,
+            min_elevation_m: f64::INFINITY,
+            max_elevation_m: f64::NEG_INFINITY,
+            ..Default::default()
+        };
+        for i in 0..samples.len() {
+            score.min_elevation_m = score.min_elevation_m.min(elevations[i]);
+            score.max_elevation_m = score.max_elevation_m.max(elevations[i]);
+            score.max_adjacent_slope_deg = score.max_adjacent_slope_deg.max(adjacent_slopes[i]);
+            if i == 0 {
+                continue;
+            }
+
+            let run = distances[i] - distances[i - 1];
+            let rise = elevations[i] - elevations[i - 1];
+            if rise > 0.0 {
+                score.ascent_m += rise;
+            } else {
+                score.descent_m -= rise;
+            }
+            if run > 0.0 {
+                // Tobler's hiking function, km/h
+                let speed = 6.0 * (-3.5 * (rise / run + 0.05).abs()).exp();
+                score.tobler_hours += run / 1000.0 / speed;
+            }
+            if adjacent_slopes[i].max(adjacent_slopes[i - 1]) >= self.exposure_slope {
+                score.exposed_distance_m += run;
+            }
+        }
+        // Naismith's rule: 5 km/h plus one hour per 600 m of ascent
+        score.naismith_hours = distance / 5000.0 + score.ascent_m / 600.0;
+        score.max_sustained_grade =
+            max_sustained_grade(&distances, &elevations, self.sustained_distance);
+        score.difficulty = difficulty(&score);
+        Ok(score)
+    }
+}
+
+/// Route vertices with extra points inserted so samples are about one DEM pixel apart
+fn densify(mosaic: &Mosaic, lats: &[f64], lons: &[f64]) -> Vec<(f64, f64)> {
+    let mut samples = vec![(lats[0], lons[0])];
+    for i in 1..lats.len() {
+        let (lat0, lon0, lat1, lon1) = (lats[i - 1], lons[i - 1], lats[i], lons[i]);
+        let length = CoordinateTransform::haversine_km(lat0, lon0, lat1, lon1) * 1000.0;
+        let spacing = mosaic
+            .meters_per_pixel((lat0 + lat1) / 2.0)
+            .max(f64::EPSILON);
+        let steps = ((length / spacing).ceil() as usize).max(1);
+        for step in 1..=steps {
+            let t = step as f64 / steps as f64;
+            samples.push((lat0 + (lat1 - lat0) * t, lon0 + (lon1 - lon0) * t));
+        }
+    }
+    samples
+}
+
+/// Steepest terrain slope (degrees) over the cell under a point and its 8 neighbors
+fn adjacent_slope(mosaic: &Mosaic, px: f64, py: f64, cell_size: f64) -> f64 {
+    let (cx, cy) = (px.floor() as i64, py.floor() as i64);
+    let mut steepest: f64 = 0.0;
+    for y in cy - 1..=cy + 1 {
+        for x in cx - 1..=cx + 1 {
+            let Some(center) = mosaic.pixel(x, y) else {
+                continue;
+            };
+            let get = |dx: i64, dy: i64| mosaic.pixel(x + dx, y + dy).unwrap_or(center) as f64;
+            // Horn's 3x3 gradient
+            let dzdx = ((get(1, -1) + 2.0 * get(1, 0) + get(1, 1))
+                - (get(-1, -1) + 2.0 * get(-1, 0) + get(-1, 1)))
+                / (8.0 * cell_size);
+            let dzdy = ((get(-1, 1) + 2.0 * get(0, 1) + get(1, 1))
+                - (get(-1, -1) + 2.0 * get(0, -1) + get(1, -1)))
+                / (8.0 * cell_size);
+            steepest = steepest.max((dzdx * dzdx + dzdy * dzdy).sqrt().atan().to_degrees());
+        }
+    }
+    steepest
+}
+
+/// Largest absolute grade averaged over any stretch of at least `window` meters
+/// Routes shorter than the window are averaged end to end
+fn max_sustained_grade(distances: &[f64], elevations: &[f64], window: f64) -> f64 {
+    let last = distances.len() - 1;
+    let window = window.min(distances[last]);
+    if window <= 0.0 {
+        return 0.0;
+    }
+
+    let mut best: f64 = 0.0;
+    let mut end = 0;
+    for start in 0..last {
+        end = end.max(start + 1);
+        while end < last && distances[end] - distances[start] < window {
+            end += 1;
+        }
+        let run = distances[end] - distances[start];
+        if run < window {
+            break;
+        }
+        best = best.max((elevations[end] - elevations[start]).abs() / run);
+    }
+    best
+}
+
+/// Five-step rating from hiking time, sustained grade and exposure
+fn difficulty(score: &RouteScore) -> u8 {
+    let rate = |value: f64, limits: [f64; 4]| {
+        1 + limits.iter().filter(|&&limit| value >= limit).count() as u8
+    };
+    let time = rate(score.tobler_hours, [2.0, 4.0, 6.0, 8.0]);
+    let grade = rate(score.max_sustained_grade, [0.10, 0.20, 0.30, 0.40]);
+    let exposure = rate(score.max_adjacent_slope_deg, [25.0, 30.0, 35.0, 40.0]);
+    time.max(grade).max(exposure)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Zoom 12 mosaic holding one tile with a 2000 m cone at its center
+    fn cone_mosaic() -> Mosaic {
+        let cone: Vec<f32> = (0..256 * 256)
+            .map(|i| {
+                let (x, y) = ((i % 256) as f32 - 127.5, (i / 256) as f32 - 127.5);
+                (2000.0 - (x * x + y * y).sqrt() * 15.0).max(0.0)
+            })
+            .collect();
+        let mut mosaic = Mosaic::new(12);
+        mosaic.set_tile(3626, 1617, &cone).unwrap();
+        mosaic
+    }
+
+    #[test]
+    fn routes_over_a_cone_climb_and_descend() {
+        let mosaic = cone_mosaic();
+        let (x, y) = (3626.0 * 256.0, 1617.0 * 256.0);
+        let (west, east) = (
+            mosaic.pixel_to_latlon(x + 40.0, y + 128.0),
+            mosaic.pixel_to_latlon(x + 215.0, y + 128.0),
+        );
+        let score = RouteScorer::new()
+            .score(&mosaic, &[west.0, east.0], &[west.1, east.1])
+            .unwrap();
+        assert!(score.max_elevation_m > 1900.0);
+        assert!((score.ascent_m - score.descent_m).abs() < 100.0);
+        assert!(score.ascent_m > 1000.0);
+        assert!(score.distance_m > 0.0 && score.tobler_hours > 0.0);
+        assert!((1..=5).contains(&score.difficulty));
+
+        // Past the loaded tile the route cannot be scored
+        let beyond = mosaic.pixel_to_latlon(x + 300.0, y + 128.0);
+        let outside = RouteScorer::new().score(&mosaic, &[west.0, beyond.0], &[west.1, beyond.1]);
+        assert!(matches!(outside, Err(Error::InvalidArgument(_))));
+    }
+}
@@ -0,0 +1,175 @@
+use wasm_bindgen::prelude::*;
+
+use crate::corridor::{point_segment_distance, Corridor};
+use crate::error::Error;
+use crate::heightfield::Heightfield;
+use crate::least_cost::least_cost_path;
+use crate::mosaic::Mosaic;
+
+/// Re-routes a hand-drawn polyline onto walkable terrain near it
+/// The search is confined to a corridor around the drawn line, cells steeper
+/// than the slope limit are avoided, and the path minimizing Tobler walking
+/// time is returned
+#[wasm_bindgen]
+pub struct RouteSnapper {
+    corridor_width: f64,
+    max_slope: f32,
+}
+
+#[wasm_bindgen]
+impl RouteSnapper {
+    /// corridor_width: maximum distance in meters the snapped path may stray from the drawn line
+    /// max_slope: steepest terrain in degrees the path may cross (e.g. 35)
+    #[wasm_bindgen(constructor)]
+    pub fn new(corridor_width: f64, max_slope: f32) -> RouteSnapper {
+        RouteSnapper {
+            corridor_width: corridor_width.max(0.0),
+            max_slope,
+        }
+    }
+
+    /// Snap a drawn route, passing through each of its vertices in order
+    /// lats, lons: drawn route vertices in degrees
+    /// Returns a flat (lat, lon) array of the snapped path
+    #[wasm_bindgen]
+    pub fn snap(&self, mosaic: &Mosaic, lats: &[f64], lons: &[f64]) -> Result<Vec<f64>, Error> {
+        if lats.len() != lons.len() {
+            return Err(Error::InvalidArgument(format!(
+                "Coordinate length mismatch: {} latitudes, {} longitudes",
+                lats.len(),
+                lons.len()
+            )));
+        }
+        if lats.len() < 2 {
+            return Err(Error::InvalidArgument(
+                "Route needs at least 2 points".into(),
+            ));
+        }
+
+        let corridor = Corridor::new(mosaic, lats, lons, self.corridor_width)?;
+        let (width, height) = (corridor.width, corridor.height);
+        let cell_size = corridor.cell_size;
+        let elevations = &corridor.elevations;
+        let heightmap = Heightfield::new(elevations, width, height)
+            .ok_or_else(|| Error::InvalidArgument("Route corridor is empty".into()))?;
+
+        // Walkable cells: inside the corridor, on loaded tiles and not too steep
+        let max_gradient = self.max_slope.to_radians().tan();
+        let mut walkable = vec![false; width * height];
+        for y in 0..height {
+            for x in 0..width {
+                let index = y * width + x;
+                if !corridor.mask[index] {
+                    continue;
+                }
+                let (dzdx, dzdy) = heightmap.gradient(x, y, cell_size as f32);
+                // NaN gradients (missing tiles) fail the comparison
+                walkable[index] = (dzdx * dzdx + dzdy * dzdy).sqrt() <= max_gradient;
+            }
+        }
+        let waypoints: Vec<usize> = corridor
+            .route
+            .iter()
+            .map(|&point| corridor.cell_of(point))
+            .collect();
+        for &cell in &waypoints {
+            walkable[cell] = !elevations[cell].is_nan();
+        }
+
+        // One search per drawn segment, so the path visits every vertex (a loop
+        // route would otherwise collapse to its start)
+        let mut result = vec![lats[0], lons[0]];
+        for (i, pair) in waypoints.windows(2).enumerate() {
+            let path = least_cost_path(width, height, pair[0], pair[1], |from, to, cells| {
+                if !walkable[to] {
+                    return None;
+                }
+                let run = cells * cell_size as f32;
+                let grade = (elevations[to] - elevations[from]) / run;
+                // Tobler's hiking function gives the walking speed in km/h; cost is hours
+                let speed = 6.0 * (-3.5 * (grade + 0.05).abs()).exp();
+                Some(run / 1000.0 / speed)
+            })
+            .ok_or_else(|| {
+                Error::InvalidArgument(format!(
+                    "No walkable path within the corridor from vertex {} to {}; \
+                     widen it or raise the slope limit",
+                    i,
+                    i + 1
+                ))
+            })?;
+
+            let points: Vec<(f64, f64)> = path
+                .iter()
+                .map(|&index| ((index % width) as f64, (index / width) as f64))
+                .collect();
+            let simplified = simplify(&points, 0.5);
+            // The segment starts where the previous one ended and ends on the
+            // drawn vertex exactly
+            let interior = simplified.len().saturating_sub(2);
+            for &point in simplified.iter().skip(1).take(interior) {
+                let (lat, lon) = corridor.to_latlon(mosaic, point);
+                result.push(lat);
+                result.push(lon);
+            }
+            result.push(lats[i + 1]);
+            result.push(lons[i + 1]);
+        }
+        Ok(result)
+    }
+}
+
+/// Douglas-Peucker simplification, removing the staircase of the raster path
+fn simplify(points: &[(f64, f64)], tolerance: f64) -> Vec<(f64, f64)> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+    let mut stack = vec![(0, points.len() - 1)];
+    while let Some((first, last)) = stack.pop() {
+        let mut farthest = (0.0, first);
+        for i in first + 1..last {
+            let distance = point_segment_distance(points[i], points[first], points[last]);
+            if distance > farthest.0 {
+                farthest = (distance, i);
+            }
+        }
+        if farthest.0 > tolerance {
+            keep[farthest.1] = true;
+            stack.push((first, farthest.1));
+            stack.push((farthest.1, last));
+        }
+    }
+    points
+        .iter()
+        .zip(keep)
+        .filter(|&(_, kept)| kept)
+        .map(|(&point, _)| point)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loop_routes_visit_every_vertex() {
+        let mut mosaic = Mosaic::new(14);
+        mosaic.set_tile(14552, 6451, &[100.0; 65536]).unwrap();
+        let corner = (14552.0 * 256.0, 6451.0 * 256.0);
+        let vertex = |x: f64, y: f64| mosaic.pixel_to_latlon(corner.0 + x, corner.1 + y);
+        let route = [vertex(40.0, 40.0), vertex(200.0, 60.0), vertex(40.0, 40.0)];
+        let lats: Vec<f64> = route.iter().map(|p| p.0).collect();
+        let lons: Vec<f64> = route.iter().map(|p| p.1).collect();
+
+        let snapped = RouteSnapper::new(200.0, 35.0)
+            .snap(&mosaic, &lats, &lons)
+            .unwrap();
+        let points: Vec<&[f64]> = snapped.chunks_exact(2).collect();
+        assert_eq!(points[0], [lats[0], lons[0]]);
+        assert!(points.contains(&[lats[1], lons[1]].as_slice()));
+        assert_eq!(points[points.len() - 1], [lats[2], lons[2]]);
+    }
+}
@@ -0,0 +1,162 @@
+/// Right-Triangulated Irregular Network over a (2^k + 1) square grid
+/// (Martini-style: every triangle is split along its hypotenuse, so the
+/// hierarchy can be refined only where the terrain needs it)
+pub(crate) struct Rtin {
+    grid_size: usize,
+    /// Hypotenuse endpoints (ax, ay, bx, by) of every triangle in the hierarchy
+    coords: Vec<u16>,
+}
+
+/// Triangles and vertices selected from the hierarchy for a given error threshold
+pub(crate) struct RtinMesh {
+    /// Grid coordinates (x, y) of each vertex
+    pub vertices: Vec<(u16, u16)>,
+    /// Triangle list indexing into `vertices`
+    pub triangles: Vec<u32>,
+}
+
+impl Rtin {
+    /// grid_size must be 2^k + 1
+    pub fn new(grid_size: usize) -> Rtin {
+        let tile_size = grid_size - 1;
+        debug_assert!(tile_size.is_power_of_two());
+
+        let num_triangles = tile_size * tile_size * 2 - 2;
+        let mut coords = vec![0u16; num_triangles * 4];
+        let t = tile_size as u16;
+
+        for i in 0..num_triangles {
+            let mut id = i + 2;
+            let (mut ax, mut ay, mut bx, mut by, mut cx, mut cy);
+            if id & 1 == 1 {
+                // Bottom-left root
+                ax = 0;
+                ay = 0;
+                bx = t;
+                by = t;
+                cx = t;
+                cy = 0;
+            } else {
+                // Top-right root
+                ax = t;
+                ay = t;
+                bx = 0;
+                by = 0;
+                cx = 0;
+                cy = t;
+            }
+            id >>= 1;
+            while id > 1 {
+                let mx = (ax + bx) / 2;
+                let my = (ay + by) / 2;
+                if id & 1 == 1 {
+                    // Left half
+                    bx = ax;
+                    by = ay;
+                    ax = cx;
+                    ay = cy;
+                } else {
+                    // Right half
+                    ax = bx;
+                    ay = by;
+                    bx = cx;
+                    by = cy;
+                }
+                cx = mx;
+                cy = my;
+                id >>= 1;
+            }
+            coords[i * 4..i * 4 + 4].copy_from_slice(&[ax, ay, bx, by]);
+        }
+
+        Rtin { grid_size, coords }
+    }
+
+    /// Approximation error at every grid point, propagated up the hierarchy so a
+    /// parent's error is never smaller than its children's
+    pub fn errors(&self, heights: &[f32]) -> Vec<f32> {
+        let size = self.grid_size;
+        let tile_size = size - 1;
+        let num_triangles = self.coords.len() / 4;
+        let num_parent_triangles = num_triangles - tile_size * tile_size;
+        let mut errors = vec![0.0f32; size * size];
+
+        for i in (0..num_triangles).rev() {
+            let k = i * 4;
+            let ax = self.coords[k] as usize;
+            let ay = self.coords[k + 1] as usize;
+            let bx = self.coords[k + 2] as usize;
+            let by = self.coords[k + 3] as usize;
+            let mx = (ax + bx) / 2;
+            let my = (ay + by) / 2;
+            let cx = mx + my - ay;
+            let cy = my + ax - mx;
+
+            let interpolated = (heights[ay * size + ax] + heights[by * size + bx]) / 2.0;
+            let middle = my * size + mx;
+            let mut error = (interpolated - heights[middle]).abs().max(errors[middle]);
+
+            if i < num_parent_triangles {
+                let left = ((ay + cy) / 2) * size + (ax + cx) / 2;
+                let right = ((by + cy) / 2) * size + (bx + cx) / 2;
+                error = error.max(errors[left]).max(errors[right]);
+            }
+            errors[middle] = error;
+        }
+
+        errors
+    }
+
+    /// Select the coarsest triangulation whose error stays within max_error
+    pub fn mesh(&self, errors: &[f32], max_error: f32) -> RtinMesh {
+        let max = (self.grid_size - 1) as u16;
+        let mut builder = MeshBuilder {
+            size: self.grid_size,
+            errors,
+            max_error,
+            vertex_ids: vec![u32::MAX; self.grid_size * self.grid_size],
+            mesh: RtinMesh {
+                vertices: Vec::new(),
+                triangles: Vec::new(),
+            },
+        };
+        builder.process(0, 0, max, max, max, 0);
+        builder.process(max, max, 0, 0, 0, max);
+        builder.mesh
+    }
+}
+
+struct MeshBuilder<'a> {
+    size: usize,
+    errors: &'a [f32],
+    max_error: f32,
+    vertex_ids: Vec<u32>,
+    mesh: RtinMesh,
+}
+
+impl MeshBuilder<'_> {
+    fn process(&mut self, ax: u16, ay: u16, bx: u16, by: u16, cx: u16, cy: u16) {
+        let mx = (ax + bx) / 2;
+        let my = (ay + by) / 2;
+        let splittable = ax.abs_diff(cx) + ay.abs_diff(cy) > 1;
+
+        if splittable && self.errors[my as usize * self.size + mx as usize] > self.max_error {
+            self.process(cx, cy, ax, ay, mx, my);
+            self.process(bx, by, cx, cy, mx, my);
+        } else {
+            let a = self.vertex(ax, ay);
+            let b = self.vertex(bx, by);
+            let c = self.vertex(cx, cy);
+            self.mesh.triangles.extend_from_slice(&[a, b, c]);
+        }
+    }
+
+    fn vertex(&mut self, x: u16, y: u16) -> u32 {
+        let slot = y as usize * self.size + x as usize;
+        if self.vertex_ids[slot] == u32::MAX {
+            self.vertex_ids[slot] = self.mesh.vertices.len() as u32;
+            self.mesh.vertices.push((x, y));
+        }
+        self.vertex_ids[slot]
+    }
+}
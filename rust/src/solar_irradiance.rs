@@ -0,0 +1,263 @@
+use wasm_bindgen::prelude::*;
+
+use crate::error::Error;
+use crate::heightfield::Heightfield;
+
+/// Number of azimuth sectors in the precomputed horizon
+const HORIZON_SECTORS: usize = 16;
+
+/// Solar constant in W/m²
+const SOLAR_CONSTANT: f32 = 1367.0;
+
+/// Direct (beam) solar irradiance integrated over a range of days
+/// Uses a clear-sky beam model with terrain shadowing from a per-cell horizon
+/// and slope/aspect incidence. Diffuse and reflected radiation are not included.
+#[wasm_bindgen]
+pub struct SolarIrradiance {
+    latitude: f32,
+    start_day: u32,
+    end_day: u32,
+    day_step: u32,
+    time_step: f32,
+    transmittance: f32,
+    horizon_distance: f32,
+}
+
+#[wasm_bindgen]
+impl SolarIrradiance {
+    /// latitude: latitude of the heightmap in degrees (the raster is assumed north-up)
+    #[wasm_bindgen(constructor)]
+    pub fn new(latitude: f32) -> SolarIrradiance {
+        SolarIrradiance {
+            latitude,
+            start_day: 172,
+            end_day: 172,
+            day_step: 1,
+            time_step: 15.0,
+            transmittance: 0.7,
+            horizon_distance: 3000.0,
+        }
+    }
+
+    /// Day-of-year range to integrate over (1-366, inclusive)
+    /// A start later than the end wraps around the new year
+    #[wasm_bindgen]
+    pub fn set_days(&mut self, start_day: u32, end_day: u32) {
+        self.start_day = start_day.clamp(1, 366);
+        self.end_day = end_day.clamp(1, 366);
+    }
+
+    /// Sample every Nth day and weight it by N, trading accuracy for speed on long ranges
+    #[wasm_bindgen]
+    pub fn set_day_step(&mut self, days: u32) {
+        self.day_step = days.max(1);
+    }
+
+    /// Time step in minutes within each day
+    #[wasm_bindgen]
+    pub fn set_time_step(&mut self, minutes: f32) {
+        self.time_step = minutes.max(1.0);
+    }
+
+    /// Atmospheric transmittance for the beam at zenith (0..1, clear sky ~0.7)
+    #[wasm_bindgen]
+    pub fn set_transmittance(&mut self, transmittance: f32) {
+        self.transmittance = transmittance.clamp(0.0, 1.0);
+    }
+
+    /// Search radius in meters for terrain casting shadows
+    #[wasm_bindgen]
+    pub fn set_horizon_distance(&mut self, meters: f32) {
+        self.horizon_distance = meters.max(0.0);
+    }
+
+    /// Compute accumulated direct irradiance in kWh/m² per cell
+    /// elevations: width x height heightmap
+    /// cell_size: ground distance between pixels in meters
+    #[wasm_bindgen]
+    pub fn compute(
+        &self,
+        elevations: &[f32],
+        width: u32,
+        height: u32,
+        cell_size: f32,
+    ) -> Result<Vec<f32>, Error> {
+        let heightmap = Heightfield::from_slice(elevations, width, height)?;
+        if cell_size <= 0.0 {
+            return Err(Error::InvalidArgument("Cell size must be positive".into()));
+        }
+
+        let (width, height) = (width as usize, height as usize);
+        let horizon = self.horizons(&heightmap, cell_size);
+
+        // Unit surface normals in (east, north, up)
+        let mut normals = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let (dzdx, dzdy) = heightmap.gradient(x, y, cell_size);
+                let length = (dzdx * dzdx + dzdy * dzdy + 1.0).sqrt();
+                normals.push((-dzdx / length, dzdy / length, 1.0 / length));
+            }
+        }
+
+        let mut energy = vec![0.0f32; width * height];
+        let hours = self.time_step / 60.0;
+        for day in self.days() {
+            let weight = hours * self.day_step as f32;
+            let steps = (24.0 / hours).ceil() as u32;
+            for step in 0..steps {
+                let solar_time = (step as f32 + 0.5) * hours;
+                let Some((altitude, azimuth, beam)) = self.sun(day, solar_time) else {
+                    continue;
+                };
+                let sun = (
+                    altitude.cos() * azimuth.sin(),
+                    altitude.cos() * azimuth.cos(),
+                    altitude.sin(),
+                );
+
+                // Interpolate the horizon between the two nearest sectors
+                let sector = azimuth / std::f32::consts::TAU * HORIZON_SECTORS as f32;
+                let s0 = sector.floor() as usize % HORIZON_SECTORS;
+                let s1 = (s0 + 1) % HORIZON_SECTORS;
+                let t = sector.fract();
+
+                for (index, normal) in normals.iter().enumerate() {
+                    let cell_horizon = &horizon[index * HORIZON_SECTORS..];
+                    let limit = cell_horizon[s0] * (1.0 - t) + cell_horizon[s1] * t;
+                    if altitude <= limit {
+                        continue;
+                    }
+                    let incidence = normal.0 * sun.0 + normal.1 * sun.1 + normal.2 * sun.2;
+                    if incidence > 0.0 {
+                        energy[index] += beam * incidence * weight;
+                    }
+                }
+            }
+        }
+
+        // Wh/m² to kWh/m²
+        for value in &mut energy {
+            *value /= 1000.0;
+        }
+        Ok(energy)
+    }
+}
+
+impl SolarIrradiance {
+    /// Sampled days of year, following the configured range and step
+    fn days(&self) -> impl Iterator<Item = u32> {
+        // A range that names day 366 runs through a leap year's last day
+        let year = self.start_day.max(self.end_day).max(365);
+        let span = if self.end_day >= self.start_day {
+            self.end_day - self.start_day + 1
+        } else {
+            year + 1 - self.start_day + self.end_day
+        };
+        let start = self.start_day;
+        (0..span)
+            .step_by(self.day_step as usize)
+            .map(move |offset| (start - 1 + offset) % year + 1)
+    }
+
+    /// Sun altitude (radians), azimuth (radians clockwise from north) and
+    /// beam irradiance normal to the rays (W/m²), or None below the horizon
+    fn sun(&self, day: u32, solar_time: f32) -> Option<(f32, f32, f32)> {
+        let (altitude, azimuth) = sun_position(self.latitude, day, solar_time);
+        if altitude <= 0.0 {
+            return None;
+        }
+        Some((
+            altitude,
+            azimuth,
+            beam_irradiance(day, altitude, self.transmittance),
+        ))
+    }
+
+    /// Horizon elevation angle (radians) per cell and azimuth sector
+    fn horizons(&self, heightmap: &Heightfield, cell_size: f32) -> Vec<f32> {
+        let (width, height) = (heightmap.width, heightmap.height);
+        let max_steps = (self.horizon_distance / cell_size).floor() as usize;
+        let directions: Vec<(f32, f32)> = (0..HORIZON_SECTORS)
+            .map(|s| {
+                let azimuth = std::f32::consts::TAU * s as f32 / HORIZON_SECTORS as f32;
+                // Raster rows run south, so north is -y
+                (azimuth.sin(), -azimuth.cos())
+            })
+            .collect();
+
+        let mut horizon = vec![0.0f32; width * height * HORIZON_SECTORS];
+        for y in 0..height {
+            for x in 0..width {
+                let index = y * width + x;
+                let origin = heightmap.data[index];
+                for (sector, &(dx, dy)) in directions.iter().enumerate() {
+                    let mut best = 0.0f32;
+                    for step in 1..=max_steps {
+                        let px = x as f32 + dx * step as f32;
+                        let py = y as f32 + dy * step as f32;
+                        if px < 0.0
+                            || py < 0.0
+                            || px > (width - 1) as f32
+                            || py > (height - 1) as f32
+                        {
+                            break;
+                        }
+                        let rise = heightmap.sample(px, py) - origin;
+                        best = best.max(rise.atan2(step as f32 * cell_size));
+                    }
+                    horizon[index * HORIZON_SECTORS + sector] = best;
+                }
+            }
+        }
+        horizon
+    }
+}
+
+/// Sun altitude (radians, negative below the horizon) and azimuth (radians
+/// clockwise from north) at a latitude in degrees, day of year and local solar time in hours
+pub(crate) fn sun_position(latitude: f32, day: u32, solar_time: f32) -> (f32, f32) {
+    let declination =
+        23.45f32.to_radians() * (std::f32::consts::TAU * (284.0 + day as f32) / 365.0).sin();
+    let hour_angle = (15.0 * (solar_time - 12.0)).to_radians();
+    let latitude = latitude.to_radians();
+
+    let sin_altitude =
+        latitude.sin() * declination.sin() + latitude.cos() * declination.cos() * hour_angle.cos();
+    let altitude = sin_altitude.clamp(-1.0, 1.0).asin();
+
+    // Azimuth from the east and north components of the sun direction
+    let east = -declination.cos() * hour_angle.sin();
+    let north =
+        latitude.cos() * declination.sin() - latitude.sin() * declination.cos() * hour_angle.cos();
+    let azimuth = east.atan2(north).rem_euclid(std::f32::consts::TAU);
+    (altitude, azimuth)
+}
+
+/// Clear-sky beam irradiance normal to the rays (W/m²) for a sun above the horizon
+pub(crate) fn beam_irradiance(day: u32, altitude: f32, transmittance: f32) -> f32 {
+    // Extraterrestrial irradiance attenuated along a simple air-mass path
+    let day_angle = std::f32::consts::TAU * day as f32 / 365.0;
+    let extraterrestrial = SOLAR_CONSTANT * (1.0 + 0.033 * day_angle.cos());
+    let air_mass = 1.0 / altitude.sin();
+    extraterrestrial * transmittance.powf(air_mass)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn days(start_day: u32, end_day: u32) -> Vec<u32> {
+        let mut irradiance = SolarIrradiance::new(35.0);
+        irradiance.set_days(start_day, end_day);
+        irradiance.days().collect()
+    }
+
+    #[test]
+    fn day_ranges_keep_the_leap_day() {
+        assert_eq!(days(364, 366), [364, 365, 366]);
+        assert_eq!(days(365, 2), [365, 1, 2]);
+        assert_eq!(days(366, 2), [366, 1, 2]);
+        assert_eq!(days(1, 366).len(), 366);
+    }
+}
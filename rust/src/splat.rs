@@ -0,0 +1,100 @@
+use crate::error::Error;
+
+/// Thresholds for the four terrain splat weights (snow, rock, forest, grass)
+/// Rock takes the slopes steeper than rock_slope; the rest is forest below
+/// tree_line, alpine grass above it and snow above snow_line. Each threshold
+/// blends over a band of the given width centered on it.
+#[derive(Clone)]
+pub(crate) struct SplatRules {
+    tree_line: f32,
+    snow_line: f32,
+    rock_slope: f32,
+    blend_height: f32,
+    blend_slope: f32,
+}
+
+impl Default for SplatRules {
+    /// Honshu alpine zones: forest to 2500 m, snow above 3000 m, rock past 40°
+    fn default() -> SplatRules {
+        SplatRules {
+            tree_line: 2500.0,
+            snow_line: 3000.0,
+            rock_slope: 40.0,
+            blend_height: 200.0,
+            blend_slope: 10.0,
+        }
+    }
+}
+
+impl SplatRules {
+    pub fn new(
+        tree_line: f32,
+        snow_line: f32,
+        rock_slope: f32,
+        blend_height: f32,
+        blend_slope: f32,
+    ) -> Result<SplatRules, Error> {
+        let values = [tree_line, snow_line, rock_slope, blend_height, blend_slope];
+        if values.iter().any(|value| !value.is_finite()) || blend_height < 0.0 || blend_slope < 0.0
+        {
+            return Err(Error::InvalidArgument(
+                "Splat thresholds must be finite and blend widths non-negative".into(),
+            ));
+        }
+        Ok(SplatRules {
+            tree_line,
+            snow_line,
+            rock_slope,
+            blend_height,
+            blend_slope,
+        })
+    }
+
+    /// Thresholds in new() argument order, for hashing the configuration
+    pub fn to_values(&self) -> [f32; 5] {
+        [
+            self.tree_line,
+            self.snow_line,
+            self.rock_slope,
+            self.blend_height,
+            self.blend_slope,
+        ]
+    }
+
+    /// Weights (snow, rock, forest, grass) summing to 1
+    pub fn weights(&self, elevation: f32, slope: f32) -> [f32; 4] {
+        let rock = band(self.rock_slope, self.blend_slope, slope);
+        let snow = band(self.snow_line, self.blend_height, elevation);
+        let grass = band(self.tree_line, self.blend_height, elevation);
+        let ground = (1.0 - rock) * (1.0 - snow);
+        [
+            (1.0 - rock) * snow,
+            rock,
+            ground * (1.0 - grass),
+            ground * grass,
+        ]
+    }
+
+    /// Four weights per vertex from the y of packed xyz positions and the normals
+    pub fn vertex_weights(&self, vertices: &[f32], normals: &[f32]) -> Vec<f32> {
+        vertices
+            .chunks_exact(3)
+            .zip(normals.chunks_exact(3))
+            .flat_map(|(vertex, normal)| self.weights(vertex[1], slope_degrees(normal)))
+            .collect()
+    }
+}
+
+/// Angle of a unit normal from vertical, i.e. the surface slope in degrees
+pub(crate) fn slope_degrees(normal: &[f32]) -> f32 {
+    normal[1].clamp(-1.0, 1.0).acos().to_degrees()
+}
+
+/// 0 below the band around threshold, 1 above it, smoothstep across it
+fn band(threshold: f32, width: f32, value: f32) -> f32 {
+    if width == 0.0 {
+        return if value >= threshold { 1.0 } else { 0.0 };
+    }
+    let t = ((value - threshold) / width + 0.5).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
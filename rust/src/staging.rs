@@ -0,0 +1,78 @@
+use wasm_bindgen::prelude::*;
+
+/// Fixed-size vertex, normal and index buffers for MeshGenerator::generate_into()
+/// Allocated once; the buffers never move, so JavaScript can keep typed array
+/// views on wasm memory (or copy into persistently mapped GPU staging buffers)
+/// across tiles. Only the first vertex_count() vertices and index_count()
+/// indices belong to the last generated mesh.
+#[wasm_bindgen]
+pub struct StagingBuffers {
+    pub(crate) vertices: Vec<f32>,
+    pub(crate) normals: Vec<f32>,
+    pub(crate) indices: Vec<u32>,
+    /// 256x256 scratch rasters for coastal feathering: the feathered heights
+    /// and the distances to the sea
+    pub(crate) feathered: Vec<f32>,
+    pub(crate) sea_distances: Vec<f32>,
+    pub(crate) vertex_count: usize,
+    pub(crate) index_count: usize,
+}
+
+#[wasm_bindgen]
+impl StagingBuffers {
+    /// vertex_capacity, index_capacity: e.g. MeshGenerator::max_vertex_count()
+    /// and max_index_count() of the finest LOD level that will be generated
+    #[wasm_bindgen(constructor)]
+    pub fn new(vertex_capacity: u32, index_capacity: u32) -> StagingBuffers {
+        let (vertices, indices) = (vertex_capacity as usize, index_capacity as usize);
+        StagingBuffers {
+            vertices: vec![0.0; vertices * 3],
+            normals: vec![0.0; vertices * 3],
+            indices: vec![0; indices],
+            feathered: vec![0.0; 65536],
+            sea_distances: vec![0.0; 65536],
+            vertex_count: 0,
+            index_count: 0,
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn vertex_capacity(&self) -> usize {
+        self.vertices.len() / 3
+    }
+
+    #[wasm_bindgen]
+    pub fn index_capacity(&self) -> usize {
+        self.indices.len()
+    }
+
+    /// Vertices of the last generated mesh
+    #[wasm_bindgen]
+    pub fn vertex_count(&self) -> usize {
+        self.vertex_count
+    }
+
+    /// Indices of the last generated mesh
+    #[wasm_bindgen]
+    pub fn index_count(&self) -> usize {
+        self.index_count
+    }
+
+    /// Get pointer to the vertex buffer (xyz, vertex_capacity() vertices)
+    #[wasm_bindgen]
+    pub fn vertices_ptr(&self) -> *const f32 {
+        self.vertices.as_ptr()
+    }
+
+    /// Get pointer to the normal buffer (xyz, vertex_capacity() vertices)
+    #[wasm_bindgen]
+    pub fn normals_ptr(&self) -> *const f32 {
+        self.normals.as_ptr()
+    }
+
+    /// Get pointer to the 32-bit index buffer (index_capacity() indices)
+    #[wasm_bindgen]
+    pub fn indices_ptr(&self) -> *const u32 {
+        self.indices.as_ptr()
+    }
+}
@@ -0,0 +1,298 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::elevation_parser::ElevationParser;
+use crate::error::{tile_length_error, Error};
+use crate::mesh_generator::MeshGenerator;
+
+/// Scripted camera paths, expressed in tile units around tile (0, 0)
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CameraPath {
+    /// Circle of radius 4 tiles around the origin tile
+    Orbit = 0,
+    /// Straight line across 32 tiles
+    Flyover = 1,
+    /// Back-and-forth sweep covering an 8x8 tile area
+    Zigzag = 2,
+}
+
+/// Recorded tile supplied by the caller
+enum RecordedTile {
+    Png(Vec<u8>),
+    Elevations(Vec<f32>),
+}
+
+/// Result of StressTest::run and StressTest::run_custom
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct StressReport {
+    pub frames: u32,
+    pub tiles_generated: u32,
+    pub cache_hits: u32,
+    pub triangles_generated: u64,
+    pub total_ms: f64,
+    pub mean_frame_ms: f64,
+    pub p95_frame_ms: f64,
+    pub max_frame_ms: f64,
+    pub parse_ms: f64,
+    pub mesh_ms: f64,
+    pub peak_resident_mesh_bytes: u64,
+    pub wasm_memory_start_bytes: u64,
+    pub wasm_memory_end_bytes: u64,
+}
+
+#[wasm_bindgen]
+pub struct StressTest {
+    tiles: Vec<RecordedTile>,
+    tile_size: f32,
+    view_radius: i32,
+    max_error: f32,
+}
+
+#[wasm_bindgen]
+impl StressTest {
+    /// Create a load-test harness
+    /// tile_size: size of tile in world units (same as MeshGenerator::generate)
+    #[wasm_bindgen(constructor)]
+    pub fn new(tile_size: f32) -> StressTest {
+        StressTest {
+            tiles: Vec::new(),
+            tile_size,
+            view_radius: 3,
+            max_error: 2.0,
+        }
+    }
+
+    /// Number of tiles loaded around the camera in each direction
+    #[wasm_bindgen]
+    pub fn set_view_radius(&mut self, radius: u32) {
+        self.view_radius = radius as i32;
+    }
+
+    /// Register a recorded GSI PNG tile; it is decoded every time it streams in
+    #[wasm_bindgen]
+    pub fn add_png_tile(&mut self, data: &[u8]) {
+        self.tiles.push(RecordedTile::Png(data.to_vec()));
+    }
+
+    /// Register an already decoded 256x256 heightmap
+    #[wasm_bindgen]
+    pub fn add_tile(&mut self, elevations: &[f32]) -> Result<(), Error> {
+        if elevations.len() != 65536 {
+            return Err(tile_length_error(elevations.len()));
+        }
+        self.tiles
+            .push(RecordedTile::Elevations(elevations.to_vec()));
+        Ok(())
+    }
+
+    /// Drop all recorded tiles (synthetic terrain is used when none are registered)
+    #[wasm_bindgen]
+    pub fn clear_tiles(&mut self) {
+        self.tiles.clear();
+    }
+
+    /// Run one of the built-in camera paths as a StressReport object; see run()
+    #[wasm_bindgen(js_name = run)]
+    pub fn run_js(&self, path: CameraPath, frames: u32) -> Result<JsValue, JsValue> {
+        let report = self.run(path, frames)?;
+        serde_wasm_bindgen::to_value(&report).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Run a custom camera path as a StressReport object; see run_custom()
+    #[wasm_bindgen(js_name = run_custom)]
+    pub fn run_custom_js(&self, positions: &[f32]) -> Result<JsValue, JsValue> {
+        let report = self.run_custom(positions)?;
+        serde_wasm_bindgen::to_value(&report).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+}
+
+impl StressTest {
+    /// Run one of the built-in camera paths for the given number of frames
+    /// Returns a report with timing and memory statistics
+    pub fn run(&self, path: CameraPath, frames: u32) -> Result<StressReport, Error> {
+        let positions: Vec<(f32, f32)> = (0..frames)
+            .map(|i| {
+                let t = if frames > 1 {
+                    i as f32 / (frames - 1) as f32
+                } else {
+                    0.0
+                };
+                path_position(path, t)
+            })
+            .collect();
+        self.run_positions(&positions)
+    }
+
+    /// Run a custom camera path
+    /// positions: flat [x0, z0, x1, z1, ...] camera positions in tile units, one per frame
+    pub fn run_custom(&self, positions: &[f32]) -> Result<StressReport, Error> {
+        if !positions.len().is_multiple_of(2) {
+            return Err(Error::InvalidArgument(
+                "Camera positions must be pairs of (x, z) values".into(),
+            ));
+        }
+        let positions: Vec<(f32, f32)> = positions.chunks(2).map(|p| (p[0], p[1])).collect();
+        self.run_positions(&positions)
+    }
+
+    fn run_positions(&self, positions: &[(f32, f32)]) -> Result<StressReport, Error> {
+        let generator = MeshGenerator::new(self.max_error);
+        let mut report = StressReport {
+            wasm_memory_start_bytes: wasm_memory_bytes(),
+            ..Default::default()
+        };
+
+        // Resident tiles: (tile_x, tile_y) -> (lod, mesh bytes)
+        let mut resident: HashMap<(i32, i32), (u8, u64)> = HashMap::new();
+        let mut frame_times = Vec::with_capacity(positions.len());
+        let run_start = now_ms();
+
+        for &(cam_x, cam_z) in positions {
+            let frame_start = now_ms();
+            let center_x = cam_x.floor() as i32;
+            let center_y = cam_z.floor() as i32;
+            let mut visible = HashMap::new();
+
+            for ty in (center_y - self.view_radius)..=(center_y + self.view_radius) {
+                for tx in (center_x - self.view_radius)..=(center_x + self.view_radius) {
+                    let distance = (tx - center_x).abs().max((ty - center_y).abs());
+                    let lod = match distance {
+                        0..=1 => 2,
+                        2..=3 => 1,
+                        _ => 0,
+                    };
+
+                    if let Some(&(cached_lod, bytes)) = resident.get(&(tx, ty)) {
+                        if cached_lod == lod {
+                            report.cache_hits += 1;
+                            visible.insert((tx, ty), (lod, bytes));
+                            continue;
+                        }
+                    }
+
+                    let parse_start = now_ms();
+                    let elevations = self.load_tile(tx, ty)?;
+                    report.parse_ms += now_ms() - parse_start;
+
+                    let mesh_start = now_ms();
+                    let mesh = generator.generate(&elevations, self.tile_size, lod)?;
+                    report.mesh_ms += now_ms() - mesh_start;
+
+                    report.tiles_generated += 1;
+                    report.triangles_generated += (mesh.indices_len() / 3) as u64;
+                    let bytes = ((mesh.vertices_len()
+                        + mesh.normals_len()
+                        + mesh.uvs_len()
+                        + mesh.indices_len())
+                        * 4) as u64;
+                    visible.insert((tx, ty), (lod, bytes));
+                }
+            }
+
+            resident = visible;
+            let resident_bytes: u64 = resident.values().map(|&(_, bytes)| bytes).sum();
+            report.peak_resident_mesh_bytes = report.peak_resident_mesh_bytes.max(resident_bytes);
+            frame_times.push(now_ms() - frame_start);
+        }
+
+        report.frames = positions.len() as u32;
+        report.total_ms = now_ms() - run_start;
+        report.wasm_memory_end_bytes = wasm_memory_bytes();
+
+        if !frame_times.is_empty() {
+            report.mean_frame_ms = frame_times.iter().sum::<f64>() / frame_times.len() as f64;
+            frame_times.sort_by(|a, b| a.total_cmp(b));
+            let p95_index = ((frame_times.len() as f64 * 0.95).ceil() as usize).saturating_sub(1);
+            report.p95_frame_ms = frame_times[p95_index];
+            report.max_frame_ms = frame_times[frame_times.len() - 1];
+        }
+
+        Ok(report)
+    }
+
+    /// Fetch the heightmap for a tile, cycling through recorded tiles if any were registered
+    fn load_tile(&self, tile_x: i32, tile_y: i32) -> Result<Vec<f32>, Error> {
+        if self.tiles.is_empty() {
+            return Ok(synthetic_tile(tile_x, tile_y));
+        }
+
+        let index = (tile_x.wrapping_mul(73_856_093) ^ tile_y.wrapping_mul(19_349_663))
+            .unsigned_abs() as usize
+            % self.tiles.len();
+        match &self.tiles[index] {
+            RecordedTile::Png(data) => ElevationParser::parse_png(data),
+            RecordedTile::Elevations(elevations) => Ok(elevations.clone()),
+        }
+    }
+}
+
+/// Camera position along a built-in path, t in 0..1
+fn path_position(path: CameraPath, t: f32) -> (f32, f32) {
+    match path {
+        CameraPath::Orbit => {
+            let angle = t * std::f32::consts::TAU;
+            (4.0 * angle.cos() + 0.5, 4.0 * angle.sin() + 0.5)
+        }
+        CameraPath::Flyover => (-16.0 + 32.0 * t, 0.5),
+        CameraPath::Zigzag => {
+            let rows = 8.0;
+            let row = (t * rows).floor().min(rows - 1.0);
+            let along = t * rows - row;
+            let x = if row as i32 % 2 == 0 {
+                along
+            } else {
+                1.0 - along
+            };
+            (x * 8.0 - 4.0, row - 4.0 + 0.5)
+        }
+    }
+}
+
+/// Continuous procedural terrain so neighboring synthetic tiles line up
+fn synthetic_tile(tile_x: i32, tile_y: i32) -> Vec<f32> {
+    let mut elevations = Vec::with_capacity(65536);
+    for py in 0..256 {
+        for px in 0..256 {
+            let gx = (tile_x * 256 + px) as f32;
+            let gz = (tile_y * 256 + py) as f32;
+            let elevation = 1500.0
+                + 800.0 * (gx * 0.004).sin() * (gz * 0.003).cos()
+                + 200.0 * (gx * 0.03 + gz * 0.02).sin()
+                + 40.0 * (gx * 0.11).sin() * (gz * 0.13).sin();
+            elevations.push(elevation);
+        }
+    }
+    elevations
+}
+
+/// High-resolution timestamp (performance.now when available, Date.now otherwise)
+pub(crate) fn now_ms() -> f64 {
+    let performance = js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("performance"))
+        .ok()
+        .filter(|value| !value.is_undefined());
+    if let Some(performance) = performance {
+        if let Ok(now) = js_sys::Reflect::get(&performance, &JsValue::from_str("now")) {
+            if let Some(now) = now.dyn_ref::<js_sys::Function>() {
+                if let Some(ms) = now.call0(&performance).ok().and_then(|v| v.as_f64()) {
+                    return ms;
+                }
+            }
+        }
+    }
+    js_sys::Date::now()
+}
+
+/// Current size of the WASM linear memory
+fn wasm_memory_bytes() -> u64 {
+    #[cfg(target_arch = "wasm32")]
+    {
+        (core::arch::wasm32::memory_size(0) * 65536) as u64
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        0
+    }
+}
@@ -0,0 +1,122 @@
+use wasm_bindgen::prelude::*;
+
+use crate::error::Error;
+use crate::mosaic::Mosaic;
+
+/// Zoom level of the sample tiles
+const ZOOM: u8 = 12;
+
+/// Sample tile range: 3x3 tiles with the summit in the middle one
+const FIRST_TILE: (u32, u32) = (3625, 1616);
+const TILES_PER_SIDE: u32 = 3;
+
+/// Summit position and height of the modeled volcano (those of Mt. Fuji)
+const SUMMIT: (f64, f64) = (35.3606, 138.7274);
+const SUMMIT_HEIGHT: f64 = 3776.0;
+
+/// Elevations are quantized to this step in meters, like decoded DEM tiles
+const QUANTUM: f64 = 0.1;
+
+/// Built-in offline sample region of synthetic terrain (zoom 12, 3x3 tiles)
+///
+/// Not survey data: an idealized stratovolcano (concave flanks, a summit
+/// crater and radial gullies) computed from a handful of parameters, placed
+/// at Mt. Fuji's summit position with its height. Only those two match the
+/// real mountain; the slopes, footprint and surroundings do not. Good for
+/// demos and exercising the API offline, never for measurements.
+#[wasm_bindgen]
+pub struct SyntheticSampleData;
+
+#[wasm_bindgen]
+impl SyntheticSampleData {
+    #[wasm_bindgen]
+    pub fn zoom() -> u8 {
+        ZOOM
+    }
+
+    #[wasm_bindgen]
+    pub fn tile_count() -> usize {
+        (TILES_PER_SIDE * TILES_PER_SIDE) as usize
+    }
+
+    /// Get the tile coordinates as a flat [x0, y0, x1, y1, ...] array, row by row
+    #[wasm_bindgen]
+    pub fn tile_coords() -> Vec<u32> {
+        let mut coords = Vec::with_capacity(Self::tile_count() * 2);
+        for dy in 0..TILES_PER_SIDE {
+            for dx in 0..TILES_PER_SIDE {
+                coords.push(FIRST_TILE.0 + dx);
+                coords.push(FIRST_TILE.1 + dy);
+            }
+        }
+        coords
+    }
+
+    /// Load one sample tile as a 256x256 heightmap
+    /// index: position in tile_coords()
+    #[wasm_bindgen]
+    pub fn load_tile(index: usize) -> Result<Vec<f32>, Error> {
+        if index >= Self::tile_count() {
+            return Err(Error::InvalidArgument(format!(
+                "Invalid sample tile index: {}, expected 0-{}",
+                index,
+                Self::tile_count() - 1
+            )));
+        }
+        let tile_x = FIRST_TILE.0 + index as u32 % TILES_PER_SIDE;
+        let tile_y = FIRST_TILE.1 + index as u32 / TILES_PER_SIDE;
+        Ok(tile_elevations(tile_x, tile_y))
+    }
+
+    /// Load the whole sample region into a new mosaic
+    #[wasm_bindgen]
+    pub fn load_mosaic() -> Mosaic {
+        let mut mosaic = Mosaic::new(ZOOM);
+        let coords = Self::tile_coords();
+        for tile in coords.chunks_exact(2) {
+            let elevations = tile_elevations(tile[0], tile[1]);
+            mosaic
+                .set_tile(tile[0], tile[1], &elevations)
+                .expect("sample tiles are 256x256");
+        }
+        mosaic
+    }
+}
+
+fn tile_elevations(tile_x: u32, tile_y: u32) -> Vec<f32> {
+    let projection = Mosaic::new(ZOOM);
+    let mut elevations = Vec::with_capacity(65536);
+    for j in 0..256 {
+        for i in 0..256 {
+            let gx = (tile_x * 256 + i) as f64;
+            let gy = (tile_y * 256 + j) as f64;
+            let (lat, lon) = projection.pixel_to_latlon(gx, gy);
+            let elevation = (volcano_height(lat, lon) / QUANTUM).round() * QUANTUM;
+            elevations.push(elevation as f32);
+        }
+    }
+    elevations
+}
+
+/// Modeled ground height in meters at a lat/lon
+fn volcano_height(lat: f64, lon: f64) -> f64 {
+    // Local east/north offsets from the summit in meters
+    let north = (lat - SUMMIT.0).to_radians() * 6371000.0;
+    let east = (lon - SUMMIT.1).to_radians() * 6371000.0 * SUMMIT.0.to_radians().cos();
+    let r = (east * east + north * north).sqrt();
+    let azimuth = east.atan2(north);
+
+    // Concave stratovolcano flanks falling from the crater rim to the plains
+    let mut height = SUMMIT_HEIGHT / (1.0 + ((r - 400.0).max(0.0) / 6000.0).powf(1.6));
+
+    // Radial gullies, fading in below the summit and out toward the foot
+    let fade =
+        ((r - 800.0) / 2000.0).clamp(0.0, 1.0) * (1.0 - ((r - 9000.0) / 6000.0).clamp(0.0, 1.0));
+    height *= 1.0 - 0.04 * fade * (0.5 + 0.5 * (azimuth * 28.0).sin()).powi(3);
+
+    // Summit crater about 250 m deep and 800 m across
+    if r < 400.0 {
+        height -= 250.0 * (1.0 - (r / 400.0).powi(2));
+    }
+    height.max(0.0)
+}
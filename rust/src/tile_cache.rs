@@ -0,0 +1,346 @@
+use std::collections::HashMap;
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::Error;
+
+/// Magic bytes at the start of every exported cache entry
+const ENTRY_MAGIC: &[u8; 4] = b"PVTC";
+const ENTRY_VERSION: u8 = 1;
+
+/// magic (4) + version (1) + zoom (1) + reserved (2) + x (4) + y (4)
+/// + source hash (8) + payload hash (8) + value count (4)
+const ENTRY_HEADER_LEN: usize = 36;
+
+/// (zoom, tile_x, tile_y)
+type TileKey = (u8, u32, u32);
+
+struct CacheEntry {
+    elevations: Vec<f32>,
+    /// Tiles whose source bytes have this hash (e.g. all-sea tiles share one);
+    /// never empty
+    tiles: Vec<TileKey>,
+}
+
+/// Decoded tile cache keyed by the hash of the source bytes
+/// Tabs announce their hashes to each other (e.g. over a BroadcastChannel) and
+/// exchange exported entries instead of decoding the same PNG twice. Tiles
+/// with identical source bytes share one entry
+#[wasm_bindgen]
+pub struct TileCache {
+    entries: HashMap<u64, CacheEntry>,
+    by_tile: HashMap<TileKey, u64>,
+}
+
+impl Default for TileCache {
+    fn default() -> Self {
+        TileCache::new()
+    }
+}
+
+#[wasm_bindgen]
+impl TileCache {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> TileCache {
+        TileCache {
+            entries: HashMap::new(),
+            by_tile: HashMap::new(),
+        }
+    }
+
+    /// Content hash of raw tile bytes (e.g. the fetched PNG) as 16 hex digits
+    #[wasm_bindgen]
+    pub fn content_hash(data: &[u8]) -> String {
+        format_hash(fnv1a64(data))
+    }
+
+    /// Store a decoded tile under the hash of its source bytes
+    #[wasm_bindgen]
+    pub fn insert(
+        &mut self,
+        zoom: u8,
+        tile_x: u32,
+        tile_y: u32,
+        source_hash: &str,
+        elevations: &[f32],
+    ) -> Result<(), Error> {
+        let hash = parse_hash(source_hash).ok_or_else(|| {
+            Error::InvalidArgument(format!("Invalid content hash: {}", source_hash))
+        })?;
+        self.store(hash, (zoom, tile_x, tile_y), elevations.to_vec());
+        Ok(())
+    }
+
+    /// Look up decoded elevations by source hash
+    #[wasm_bindgen]
+    pub fn get_by_hash(&self, source_hash: &str) -> Option<Vec<f32>> {
+        let hash = parse_hash(source_hash)?;
+        self.entries
+            .get(&hash)
+            .map(|entry| entry.elevations.clone())
+    }
+
+    /// Look up decoded elevations by tile coordinates
+    #[wasm_bindgen]
+    pub fn get(&self, zoom: u8, tile_x: u32, tile_y: u32) -> Option<Vec<f32>> {
+        let hash = self.by_tile.get(&(zoom, tile_x, tile_y))?;
+        self.entries.get(hash).map(|entry| entry.elevations.clone())
+    }
+
+    #[wasm_bindgen]
+    pub fn contains_hash(&self, source_hash: &str) -> bool {
+        parse_hash(source_hash)
+            .map(|hash| self.entries.contains_key(&hash))
+            .unwrap_or(false)
+    }
+
+    /// Hashes of all cached entries, for announcing to other tabs
+    #[wasm_bindgen]
+    pub fn hashes(&self) -> Vec<String> {
+        self.entries.keys().map(|&hash| format_hash(hash)).collect()
+    }
+
+    /// Number of distinct source hashes cached
+    #[wasm_bindgen]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[wasm_bindgen]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Remove the entry for a source hash, and with it every tile that shares it
+    #[wasm_bindgen]
+    pub fn remove(&mut self, source_hash: &str) -> bool {
+        let Some(hash) = parse_hash(source_hash) else {
+            return false;
+        };
+        match self.entries.remove(&hash) {
+            Some(entry) => {
+                for tile in &entry.tiles {
+                    self.by_tile.remove(tile);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Serialize one entry for transfer to another tab
+    /// The entry carries the first tile that stored it
+    #[wasm_bindgen]
+    pub fn export_entry(&self, source_hash: &str) -> Option<Vec<u8>> {
+        let hash = parse_hash(source_hash)?;
+        let entry = self.entries.get(&hash)?;
+        let (zoom, tile_x, tile_y) = entry.tiles[0];
+
+        let mut out = Vec::with_capacity(ENTRY_HEADER_LEN + entry.elevations.len() * 4);
+        out.extend_from_slice(ENTRY_MAGIC);
+        out.push(ENTRY_VERSION);
+        out.push(zoom);
+        out.extend_from_slice(&[0, 0]);
+        out.extend_from_slice(&tile_x.to_le_bytes());
+        out.extend_from_slice(&tile_y.to_le_bytes());
+        out.extend_from_slice(&hash.to_le_bytes());
+        let payload_start = ENTRY_HEADER_LEN;
+        out.extend_from_slice(&[0; 8]);
+        out.extend_from_slice(&(entry.elevations.len() as u32).to_le_bytes());
+        for value in &entry.elevations {
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+
+        let payload_hash = fnv1a64(&out[payload_start..]);
+        out[payload_start - 12..payload_start - 4].copy_from_slice(&payload_hash.to_le_bytes());
+        Some(out)
+    }
+
+    /// Validate and store an entry exported by another tab
+    /// Returns the entry's source hash
+    #[wasm_bindgen]
+    pub fn import_entry(&mut self, data: &[u8]) -> Result<String, Error> {
+        if data.len() < ENTRY_HEADER_LEN {
+            return Err(Error::Decode(format!(
+                "Cache entry too short: {} bytes",
+                data.len()
+            )));
+        }
+        if &data[0..4] != ENTRY_MAGIC {
+            return Err(Error::Decode("Invalid cache entry magic".into()));
+        }
+        if data[4] != ENTRY_VERSION {
+            return Err(Error::Decode(format!(
+                "Unsupported cache entry version: {}",
+                data[4]
+            )));
+        }
+
+        let zoom = data[5];
+        let tile_x = u32::from_le_bytes(data[8..12].try_into().unwrap());
+        let tile_y = u32::from_le_bytes(data[12..16].try_into().unwrap());
+        let source_hash = u64::from_le_bytes(data[16..24].try_into().unwrap());
+        let payload_hash = u64::from_le_bytes(data[24..32].try_into().unwrap());
+        let count = u32::from_le_bytes(data[32..36].try_into().unwrap()) as usize;
+
+        let payload = &data[ENTRY_HEADER_LEN..];
+        if count.checked_mul(4) != Some(payload.len()) {
+            return Err(Error::Decode(format!(
+                "Cache entry payload length mismatch: {} bytes for {} values",
+                payload.len(),
+                count
+            )));
+        }
+        if fnv1a64(payload) != payload_hash {
+            return Err(Error::Decode("Cache entry payload hash mismatch".into()));
+        }
+
+        let elevations = payload
+            .chunks_exact(4)
+            .map(|bytes| f32::from_le_bytes(bytes.try_into().unwrap()))
+            .collect();
+        self.store(source_hash, (zoom, tile_x, tile_y), elevations);
+        Ok(format_hash(source_hash))
+    }
+
+    #[wasm_bindgen]
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.by_tile.clear();
+    }
+}
+
+impl TileCache {
+    /// Cache elevations for a tile, detaching it from the entry it had before;
+    /// that entry goes once no tile shares it
+    fn store(&mut self, hash: u64, tile: TileKey, elevations: Vec<f32>) {
+        if let Some(previous) = self.by_tile.insert(tile, hash) {
+            if previous != hash {
+                if let Some(entry) = self.entries.get_mut(&previous) {
+                    entry.tiles.retain(|&shared| shared != tile);
+                    if entry.tiles.is_empty() {
+                        self.entries.remove(&previous);
+                    }
+                }
+            }
+        }
+        let entry = self.entries.entry(hash).or_insert_with(|| CacheEntry {
+            elevations: Vec::new(),
+            tiles: Vec::new(),
+        });
+        entry.elevations = elevations;
+        if !entry.tiles.contains(&tile) {
+            entry.tiles.push(tile);
+        }
+    }
+}
+
+/// 64-bit FNV-1a hash
+fn fnv1a64(data: &[u8]) -> u64 {
+    let mut hasher = Fnv1a::new();
+    hasher.write(data);
+    hasher.finish()
+}
+
+/// Incremental 64-bit FNV-1a, for hashing data that is not in one buffer
+pub(crate) struct Fnv1a(u64);
+
+impl Fnv1a {
+    pub fn new() -> Fnv1a {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+
+    pub fn write(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    /// Floats by bit pattern, with every NaN written as the same one so
+    /// hashes agree across engines
+    pub fn write_f32s(&mut self, values: &[f32]) {
+        self.write(&(values.len() as u64).to_le_bytes());
+        for &value in values {
+            let value = if value.is_nan() { f32::NAN } else { value };
+            self.write(&value.to_bits().to_le_bytes());
+        }
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+pub(crate) fn format_hash(hash: u64) -> String {
+    format!("{:016x}", hash)
+}
+
+fn parse_hash(hash: &str) -> Option<u64> {
+    u64::from_str_radix(hash, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiles_with_the_same_bytes_share_an_entry() {
+        let mut cache = TileCache::new();
+        let sea = TileCache::content_hash(b"sea");
+        cache.insert(10, 1, 2, &sea, &[0.0; 4]).unwrap();
+        cache.insert(10, 3, 4, &sea, &[0.0; 4]).unwrap();
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.get(10, 1, 2), Some(vec![0.0; 4]));
+        assert_eq!(cache.get(10, 3, 4), Some(vec![0.0; 4]));
+
+        // Replacing one tile keeps the shared entry for the other
+        let land = TileCache::content_hash(b"land");
+        cache.insert(10, 1, 2, &land, &[5.0; 4]).unwrap();
+        assert_eq!(cache.get(10, 1, 2), Some(vec![5.0; 4]));
+        assert_eq!(cache.get_by_hash(&sea), Some(vec![0.0; 4]));
+
+        cache.insert(10, 1, 2, &sea, &[0.0; 4]).unwrap();
+        assert!(!cache.contains_hash(&land));
+        assert!(cache.remove(&sea));
+        assert_eq!(cache.get(10, 1, 2), None);
+        assert_eq!(cache.get(10, 3, 4), None);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn exported_entries_import() {
+        let mut cache = TileCache::new();
+        let hash = TileCache::content_hash(b"png");
+        cache
+            .insert(12, 3640, 1612, &hash, &[1.5, -2.0, 3776.0])
+            .unwrap();
+        let data = cache.export_entry(&hash).unwrap();
+
+        let mut other = TileCache::new();
+        assert_eq!(other.import_entry(&data).unwrap(), hash);
+        assert_eq!(other.get(12, 3640, 1612), Some(vec![1.5, -2.0, 3776.0]));
+    }
+
+    #[test]
+    fn malformed_entries_are_rejected() {
+        let mut cache = TileCache::new();
+        let hash = TileCache::content_hash(b"png");
+        cache.insert(12, 3640, 1612, &hash, &[1.5, -2.0]).unwrap();
+        let data = cache.export_entry(&hash).unwrap();
+
+        let mut huge_count = data.clone();
+        huge_count[32..36].copy_from_slice(&u32::MAX.to_le_bytes());
+        let mut bad_magic = data.clone();
+        bad_magic[0] = b'X';
+        for entry in [
+            &data[..20],
+            &data[..data.len() - 4],
+            &huge_count,
+            &bad_magic,
+        ] {
+            assert!(matches!(cache.import_entry(entry), Err(Error::Decode(_))));
+        }
+        assert!(cache.insert(12, 0, 0, "not a hash", &[0.0]).is_err());
+    }
+}
@@ -0,0 +1,177 @@
+use wasm_bindgen::prelude::*;
+
+use crate::error::Error;
+use crate::heightfield::Heightfield;
+
+/// Width/height of a GSI DEM tile in pixels
+const TILE_DIM: usize = 256;
+const TILE_LEN: usize = TILE_DIM * TILE_DIM;
+
+/// Resampling filter used when synthesizing a child tile from its parent
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UpsampleFilter {
+    Bilinear = 0,
+    Bicubic = 1,
+}
+
+/// Reduction applied to each 2x2 block of child pixels when building a parent tile
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DownsampleMode {
+    /// Mean of the four pixels (smooth surfaces)
+    Average = 0,
+    /// Highest of the four pixels (keeps peaks and ridgelines)
+    Max = 1,
+}
+
+#[wasm_bindgen]
+pub struct TilePyramid;
+
+#[wasm_bindgen]
+impl TilePyramid {
+    /// Synthesize a missing child tile by upsampling one quadrant of its parent
+    /// parent: 256x256 heightmap of the tile one zoom level up
+    /// quadrant: 0=NW, 1=NE, 2=SW, 3=SE
+    ///   (child tile_x = 2 * parent_x + (quadrant & 1), child tile_y = 2 * parent_y + (quadrant >> 1))
+    /// Returns a 256x256 heightmap (65536 values)
+    #[wasm_bindgen]
+    pub fn upsample_child(
+        parent: &[f32],
+        quadrant: u8,
+        filter: UpsampleFilter,
+    ) -> Result<Vec<f32>, Error> {
+        if parent.len() != TILE_LEN {
+            return Err(Error::InvalidElevations(format!(
+                "Invalid parent elevation array length: {}, expected {}",
+                parent.len(),
+                TILE_LEN
+            )));
+        }
+        if quadrant > 3 {
+            return Err(Error::InvalidArgument("Invalid quadrant (0-3)".into()));
+        }
+
+        let parent = Heightfield {
+            data: parent,
+            width: TILE_DIM,
+            height: TILE_DIM,
+        };
+        let half = TILE_DIM / 2;
+        let offset_x = ((quadrant & 1) as usize * half) as f32;
+        let offset_y = ((quadrant >> 1) as usize * half) as f32;
+
+        let mut child = Vec::with_capacity(TILE_LEN);
+
+        for cy in 0..TILE_DIM {
+            // Child pixel centers mapped into parent pixel index space
+            let sy = offset_y + (cy as f32 + 0.5) / 2.0 - 0.5;
+            for cx in 0..TILE_DIM {
+                let sx = offset_x + (cx as f32 + 0.5) / 2.0 - 0.5;
+                let value = match filter {
+                    UpsampleFilter::Bilinear => parent.sample(sx, sy),
+                    UpsampleFilter::Bicubic => sample_bicubic(&parent, sx, sy),
+                };
+                child.push(value);
+            }
+        }
+
+        Ok(child)
+    }
+
+    /// Build a parent tile from its four children (inverse of upsample_child)
+    /// nw, ne, sw, se: 256x256 child heightmaps for quadrants 0-3
+    /// Returns a 256x256 heightmap (65536 values) one zoom level up
+    #[wasm_bindgen]
+    pub fn downsample_children(
+        nw: &[f32],
+        ne: &[f32],
+        sw: &[f32],
+        se: &[f32],
+        mode: DownsampleMode,
+    ) -> Result<Vec<f32>, Error> {
+        let children = [nw, ne, sw, se];
+        for (quadrant, child) in children.iter().enumerate() {
+            if child.len() != TILE_LEN {
+                return Err(Error::InvalidElevations(format!(
+                    "Invalid child elevation array length for quadrant {}: {}, expected {}",
+                    quadrant,
+                    child.len(),
+                    TILE_LEN
+                )));
+            }
+        }
+
+        let half = TILE_DIM / 2;
+        let mut parent = Vec::with_capacity(TILE_LEN);
+
+        for py in 0..TILE_DIM {
+            for px in 0..TILE_DIM {
+                let quadrant = (px / half) + 2 * (py / half);
+                let child = children[quadrant];
+                let cx = (px % half) * 2;
+                let cy = (py % half) * 2;
+
+                let block = [
+                    child[cy * TILE_DIM + cx],
+                    child[cy * TILE_DIM + cx + 1],
+                    child[(cy + 1) * TILE_DIM + cx],
+                    child[(cy + 1) * TILE_DIM + cx + 1],
+                ];
+                let value = match mode {
+                    DownsampleMode::Average => block.iter().sum::<f32>() / 4.0,
+                    DownsampleMode::Max => block.iter().copied().fold(f32::MIN, f32::max),
+                };
+                parent.push(value);
+            }
+        }
+
+        Ok(parent)
+    }
+}
+
+fn sample_bicubic(parent: &Heightfield, x: f32, y: f32) -> f32 {
+    let x0 = x.floor();
+    let y0 = y.floor();
+    let tx = x - x0;
+    let ty = y - y0;
+    let (x0, y0) = (x0 as isize, y0 as isize);
+
+    let mut rows = [0.0f32; 4];
+    for (i, row) in rows.iter_mut().enumerate() {
+        let py = y0 - 1 + i as isize;
+        *row = catmull_rom(
+            parent.get(x0 - 1, py),
+            parent.get(x0, py),
+            parent.get(x0 + 1, py),
+            parent.get(x0 + 2, py),
+            tx,
+        );
+    }
+    catmull_rom(rows[0], rows[1], rows[2], rows[3], ty)
+}
+
+/// Catmull-Rom spline through p1..p2 (interpolating, so source pixels are preserved)
+fn catmull_rom(p0: f32, p1: f32, p2: f32, p3: f32, t: f32) -> f32 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    0.5 * ((2.0 * p1)
+        + (-p0 + p2) * t
+        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+        + (-p0 + 3.0 * p1 - 3.0 * p2 + p3) * t3)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bilinear_upsampling_follows_a_ramp() {
+        let parent: Vec<f32> = (0..TILE_LEN).map(|i| (i % TILE_DIM) as f32).collect();
+        let child = TilePyramid::upsample_child(&parent, 1, UpsampleFilter::Bilinear).unwrap();
+        for cx in 1..TILE_DIM {
+            let expected = (TILE_DIM / 2) as f32 + (cx as f32 + 0.5) / 2.0 - 0.5;
+            assert_eq!(child[3 * TILE_DIM + cx].min(255.0), expected.min(255.0));
+        }
+    }
+}
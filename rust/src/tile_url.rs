@@ -0,0 +1,128 @@
+use wasm_bindgen::prelude::*;
+
+use crate::error::Error;
+use crate::validation::{check_tile_index, check_zoom};
+
+const GSI_BASE: &str = "https://cyberjapandata.gsi.go.jp/xyz";
+
+/// Slippy map tile URL template, e.g. "https://{s}.tile.example.com/{z}/{x}/{y}.png"
+/// {z}, {x} and {y} expand to the tile address and {s} to one of the
+/// subdomains, picked from the tile so a tile always maps to the same host
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct TileUrlBuilder {
+    template: String,
+    subdomains: Vec<String>,
+}
+
+#[wasm_bindgen]
+impl TileUrlBuilder {
+    /// template: URL containing {z}, {x} and {y}, and optionally {s}
+    #[wasm_bindgen(constructor)]
+    pub fn new(template: &str) -> Result<TileUrlBuilder, Error> {
+        let missing: Vec<&str> = ["{z}", "{x}", "{y}"]
+            .into_iter()
+            .filter(|placeholder| !template.contains(placeholder))
+            .collect();
+        if !missing.is_empty() {
+            return Err(Error::InvalidArgument(format!(
+                "Tile URL template {:?} lacks {}",
+                template,
+                missing.join(", ")
+            )));
+        }
+        Ok(TileUrlBuilder {
+            template: template.to_string(),
+            subdomains: Vec::new(),
+        })
+    }
+
+    /// GSI elevation tiles in PNG encoding (ElevationParser::parse_png)
+    #[wasm_bindgen]
+    pub fn gsi_dem_png() -> TileUrlBuilder {
+        TileUrlBuilder::gsi("dem_png/{z}/{x}/{y}.png")
+    }
+
+    /// GSI elevation tiles as text (ElevationParser::parse_txt)
+    #[wasm_bindgen]
+    pub fn gsi_dem_txt() -> TileUrlBuilder {
+        TileUrlBuilder::gsi("dem/{z}/{x}/{y}.txt")
+    }
+
+    /// GSI seamless orthophoto (JPEG)
+    #[wasm_bindgen]
+    pub fn gsi_ortho() -> TileUrlBuilder {
+        TileUrlBuilder::gsi("seamlessphoto/{z}/{x}/{y}.jpg")
+    }
+
+    /// GSI standard map (PNG)
+    #[wasm_bindgen]
+    pub fn gsi_std() -> TileUrlBuilder {
+        TileUrlBuilder::gsi("std/{z}/{x}/{y}.png")
+    }
+
+    /// Hosts substituted for {s}, e.g. ["a", "b", "c"]
+    #[wasm_bindgen]
+    pub fn set_subdomains(&mut self, subdomains: Vec<String>) {
+        self.subdomains = subdomains;
+    }
+
+    #[wasm_bindgen]
+    pub fn template(&self) -> String {
+        self.template.clone()
+    }
+
+    /// URL of a tile
+    #[wasm_bindgen]
+    pub fn url(&self, tile_x: u32, tile_y: u32, zoom: u8) -> Result<String, Error> {
+        check_zoom(zoom)?;
+        check_tile_index("tile_x", tile_x, zoom)?;
+        check_tile_index("tile_y", tile_y, zoom)?;
+        let mut url = self
+            .template
+            .replace("{z}", &zoom.to_string())
+            .replace("{x}", &tile_x.to_string())
+            .replace("{y}", &tile_y.to_string());
+        if url.contains("{s}") {
+            if self.subdomains.is_empty() {
+                return Err(Error::InvalidArgument(format!(
+                    "Tile URL template {:?} uses {{s}} but no subdomains are set",
+                    self.template
+                )));
+            }
+            let index = (tile_x as u64 + tile_y as u64) % self.subdomains.len() as u64;
+            url = url.replace("{s}", &self.subdomains[index as usize]);
+        }
+        Ok(url)
+    }
+}
+
+impl TileUrlBuilder {
+    fn gsi(path: &str) -> TileUrlBuilder {
+        TileUrlBuilder {
+            template: format!("{}/{}", GSI_BASE, path),
+            subdomains: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subdomains_follow_the_tile() {
+        let mut builder = TileUrlBuilder::new("https://{s}.example.com/{z}/{x}/{y}.png").unwrap();
+        builder.set_subdomains(vec!["a".into(), "b".into(), "c".into()]);
+        assert_eq!(
+            builder.url(1, 1, 1).unwrap(),
+            "https://c.example.com/1/1/1.png"
+        );
+        // Indices past the zoom get through when strict validation is off;
+        // their sum is past u32 and must not wrap
+        assert_eq!(
+            builder.url(u32::MAX, u32::MAX, 1).unwrap(),
+            format!("https://a.example.com/1/{0}/{0}.png", u32::MAX)
+        );
+    }
+}
@@ -0,0 +1,467 @@
+use wasm_bindgen::prelude::*;
+
+use crate::coordinate_transform::CoordinateTransform;
+use crate::error::Error;
+use crate::mosaic::Mosaic;
+
+/// Imported track resampled against the DEM, with per-segment grade and switchbacks
+#[wasm_bindgen]
+pub struct TrackAnalysis {
+    lats: Vec<f64>,
+    lons: Vec<f64>,
+    /// Elevation per point in meters (DEM where loaded, GPS otherwise)
+    elevations: Vec<f32>,
+    /// Cumulative horizontal distance per point in meters
+    distances: Vec<f32>,
+    /// Grade (rise / run) of each segment between consecutive points
+    grades: Vec<f32>,
+    /// Point indices at the apex of each detected switchback
+    switchbacks: Vec<u32>,
+}
+
+#[wasm_bindgen]
+impl TrackAnalysis {
+    /// Number of track points
+    #[wasm_bindgen]
+    pub fn count(&self) -> usize {
+        self.lats.len()
+    }
+
+    /// Get smoothed latitudes
+    #[wasm_bindgen]
+    pub fn get_lats(&self) -> Vec<f64> {
+        self.lats.clone()
+    }
+
+    /// Get smoothed longitudes
+    #[wasm_bindgen]
+    pub fn get_lons(&self) -> Vec<f64> {
+        self.lons.clone()
+    }
+
+    /// Get elevation per point in meters
+    #[wasm_bindgen]
+    pub fn get_elevations(&self) -> Vec<f32> {
+        self.elevations.clone()
+    }
+
+    /// Get cumulative horizontal distance per point in meters
+    #[wasm_bindgen]
+    pub fn get_distances(&self) -> Vec<f32> {
+        self.distances.clone()
+    }
+
+    /// Get grade of each segment (count - 1 values, positive uphill)
+    #[wasm_bindgen]
+    pub fn get_grades(&self) -> Vec<f32> {
+        self.grades.clone()
+    }
+
+    /// Get point indices of switchback apexes
+    #[wasm_bindgen]
+    pub fn get_switchbacks(&self) -> Vec<u32> {
+        self.switchbacks.clone()
+    }
+}
+
+#[wasm_bindgen]
+pub struct TrackAnalyzer {
+    smoothing_window: u32,
+    switchback_angle: f32,
+    switchback_span: f32,
+    switchback_grade: f32,
+}
+
+impl Default for TrackAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen]
+impl TrackAnalyzer {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> TrackAnalyzer {
+        TrackAnalyzer {
+            smoothing_window: 5,
+            switchback_angle: 120.0,
+            switchback_span: 60.0,
+            switchback_grade: 0.05,
+        }
+    }
+
+    /// Moving-average window (in points) applied to GPS positions; 1 disables smoothing
+    #[wasm_bindgen]
+    pub fn set_smoothing_window(&mut self, points: u32) {
+        self.smoothing_window = points.max(1);
+    }
+
+    /// Switchback detection thresholds
+    /// min_turn: heading change in degrees across the apex (e.g. 120)
+    /// span: path length in meters compared before and after the apex
+    /// min_grade: grade (rise / run) the path must hold through the turn
+    #[wasm_bindgen]
+    pub fn set_switchback_thresholds(&mut self, min_turn: f32, span: f32, min_grade: f32) {
+        self.switchback_angle = min_turn;
+        self.switchback_span = span.max(0.0);
+        self.switchback_grade = min_grade.max(0.0);
+    }
+
+    /// Extract track or route points from GPX text
+    /// Returns a flat (lat, lon, elevation) array; elevation is NaN where the point has none
+    #[wasm_bindgen]
+    pub fn parse_gpx(text: &str) -> Result<Vec<f64>, Error> {
+        let mut points = Vec::new();
+        let mut rest = text;
+        while let Some(start) = find_point_tag(rest) {
+            rest = &rest[start..];
+            let tag_end = rest
+                .find('>')
+                .ok_or_else(|| Error::Decode("Unterminated GPX point tag".into()))?;
+            let tag = &rest[..tag_end];
+            let lat = attribute(tag, "lat")
+                .ok_or_else(|| Error::Decode("GPX point without a valid lat attribute".into()))?;
+            let lon = attribute(tag, "lon")
+                .ok_or_else(|| Error::Decode("GPX point without a valid lon attribute".into()))?;
+
+            // Self-closing points have no children; otherwise read up to the closing tag
+            let body = if tag.ends_with('/') {
+                ""
+            } else {
+                let body = &rest[tag_end + 1..];
+                let close = find_point_close(body).unwrap_or(body.len());
+                &body[..close]
+            };
+            // NaN marks a missing elevation, so an explicit NaN or infinity is an error
+            let elevation =
+                match element_text(body, "ele").and_then(|text| text.trim().parse::<f64>().ok()) {
+                    Some(elevation) if !elevation.is_finite() => {
+                        return Err(Error::Decode(format!(
+                            "GPX point {} has a non-finite elevation",
+                            points.len() / 3
+                        )));
+                    }
+                    Some(elevation) => elevation,
+                    None => f64::NAN,
+                };
+
+            points.extend_from_slice(&[lat, lon, elevation]);
+            rest = &rest[tag_end + 1..];
+        }
+
+        if points.is_empty() {
+            return Err(Error::Decode(
+                "No track or route points found in GPX".into(),
+            ));
+        }
+        Ok(points)
+    }
+
+    /// Analyze a track against the loaded DEM tiles
+    /// lats, lons: track positions in degrees
+    /// elevations: GPS elevations in meters (may be empty, NaN where a point has none);
+    /// used only where the DEM has no tile
+    #[wasm_bindgen]
+    pub fn analyze(
+        &self,
+        mosaic: &Mosaic,
+        lats: &[f64],
+        lons: &[f64],
+        elevations: &[f32],
+    ) -> Result<TrackAnalysis, Error> {
+        if lats.len() != lons.len() {
+            return Err(Error::InvalidArgument(format!(
+                "Coordinate length mismatch: {} latitudes, {} longitudes",
+                lats.len(),
+                lons.len()
+            )));
+        }
+        if !elevations.is_empty() && elevations.len() != lats.len() {
+            return Err(Error::InvalidElevations(format!(
+                "Invalid elevation array length: {}, expected {}",
+                elevations.len(),
+                lats.len()
+            )));
+        }
+        if lats.len() < 2 {
+            return Err(Error::InvalidArgument(
+                "Track needs at least 2 points".into(),
+            ));
+        }
+        if let Some(i) = (0..lats.len()).find(|&i| !lats[i].is_finite() || !lons[i].is_finite()) {
+            return Err(Error::InvalidArgument(format!(
+                "Track point {} has a non-finite coordinate",
+                i
+            )));
+        }
+        if let Some(i) = elevations.iter().position(|e| e.is_infinite()) {
+            return Err(Error::InvalidElevations(format!(
+                "Track point {} has an infinite elevation",
+                i
+            )));
+        }
+
+        let half_window = (self.smoothing_window / 2) as usize;
+        let lats = moving_average(lats, half_window);
+        let lons = moving_average(lons, half_window);
+
+        // DEM elevations replace noisy GPS altitudes wherever a tile is loaded
+        let mut profile = Vec::with_capacity(lats.len());
+        for i in 0..lats.len() {
+            let elevation = mosaic
+                .elevation_at(lats[i], lons[i])
+                .or_else(|| elevations.get(i).copied().filter(|e| e.is_finite()))
+                .ok_or_else(|| {
+                    Error::InvalidArgument(format!(
+                        "Track point {} has no elevation: tile not loaded and no GPS elevation",
+                        i
+                    ))
+                })?;
+            profile.push(elevation as f64);
+        }
+        let profile = moving_average(&profile, half_window);
+
+        let mut distances = vec![0.0f64; lats.len()];
+        for i in 1..lats.len() {
+            distances[i] = distances[i - 1]
+                + CoordinateTransform::haversine_km(lats[i - 1], lons[i - 1], lats[i], lons[i])
+                    * 1000.0;
+        }
+
+        let grades = (1..lats.len())
+            .map(|i| {
+                let run = distances[i] - distances[i - 1];
+                if run < 0.5 {
+                    0.0
+                } else {
+                    ((profile[i] - profile[i - 1]) / run) as f32
+                }
+            })
+            .collect();
+
+        let switchbacks = self.detect_switchbacks(&lats, &lons, &profile, &distances);
+
+        Ok(TrackAnalysis {
+            elevations: profile.iter().map(|&e| e as f32).collect(),
+            distances: distances.iter().map(|&d| d as f32).collect(),
+            lats,
+            lons,
+            grades,
+            switchbacks,
+        })
+    }
+}
+
+impl TrackAnalyzer {
+    /// Hairpin turns on a sustained climb or descent
+    /// Each point is compared with the points half a span before and after it; runs of
+    /// consecutive qualifying points are reduced to the sharpest one
+    fn detect_switchbacks(
+        &self,
+        lats: &[f64],
+        lons: &[f64],
+        profile: &[f64],
+        distances: &[f64],
+    ) -> Vec<u32> {
+        let n = lats.len();
+        let reach = self.switchback_span as f64 / 2.0;
+        let heading = |a: usize, b: usize| {
+            let dx = (lons[b] - lons[a]) * ((lats[a] + lats[b]) / 2.0).to_radians().cos();
+            let dy = lats[b] - lats[a];
+            dx.atan2(dy)
+        };
+
+        let mut switchbacks = Vec::new();
+        let mut run: Option<(usize, f64)> = None;
+        let (mut before, mut after) = (0, 0);
+        for apex in 1..n - 1 {
+            while before + 1 < apex && distances[apex] - distances[before + 1] >= reach {
+                before += 1;
+            }
+            after = after.max(apex + 1);
+            while after + 1 < n && distances[after] - distances[apex] < reach {
+                after += 1;
+            }
+
+            let run_length = distances[after] - distances[before];
+            let qualifies = distances[apex] - distances[before] >= reach
+                && distances[after] - distances[apex] >= reach
+                && run_length > 0.0
+                && ((profile[after] - profile[before]) / run_length).abs()
+                    >= self.switchback_grade as f64;
+            let turn = if qualifies {
+                let change = (heading(apex, after) - heading(before, apex)).abs();
+                let change = change.min(std::f64::consts::TAU - change).to_degrees();
+                Some(change).filter(|&c| c >= self.switchback_angle as f64)
+            } else {
+                None
+            };
+
+            match (turn, run) {
+                (Some(turn), Some((_, best))) if turn <= best => {}
+                (Some(turn), _) => run = Some((apex, turn)),
+                (None, Some((index, _))) => {
+                    switchbacks.push(index as u32);
+                    run = None;
+                }
+                (None, None) => {}
+            }
+        }
+        if let Some((index, _)) = run {
+            switchbacks.push(index as u32);
+        }
+        switchbacks
+    }
+}
+
+/// Centered moving average with the window shrunk symmetrically near the ends
+fn moving_average(values: &[f64], half_window: usize) -> Vec<f64> {
+    let n = values.len();
+    (0..n)
+        .map(|i| {
+            let half = half_window.min(i).min(n - 1 - i);
+            let window = &values[i - half..=i + half];
+            window.iter().sum::<f64>() / window.len() as f64
+        })
+        .collect()
+}
+
+/// Byte offset of the next <trkpt or <rtept tag
+fn find_point_tag(text: &str) -> Option<usize> {
+    let track = text.find("<trkpt");
+    let route = text.find("<rtept");
+    match (track, route) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Byte offset of the closing </trkpt> or </rtept> tag
+fn find_point_close(text: &str) -> Option<usize> {
+    let track = text.find("</trkpt");
+    let route = text.find("</rtept");
+    match (track, route) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Finite numeric value of a name="value" (or name='value') attribute within a tag
+fn attribute(tag: &str, name: &str) -> Option<f64> {
+    let mut rest = tag;
+    while let Some(position) = rest.find(name) {
+        let preceded_by_space = rest[..position]
+            .chars()
+            .last()
+            .is_some_and(|c| c.is_whitespace());
+        let after = rest[position + name.len()..].trim_start();
+        if preceded_by_space {
+            if let Some(value) = after.strip_prefix('=') {
+                let value = value.trim_start();
+                let quote = value.chars().next()?;
+                if quote == '"' || quote == '\'' {
+                    let value = &value[1..];
+                    let end = value.find(quote)?;
+                    return value[..end]
+                        .trim()
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|value| value.is_finite());
+                }
+            }
+        }
+        rest = &rest[position + name.len()..];
+    }
+    None
+}
+
+/// Text content of the first <name>...</name> element
+fn element_text<'a>(text: &'a str, name: &str) -> Option<&'a str> {
+    let open = format!("<{}>", name);
+    let close = format!("</{}>", name);
+    let start = text.find(&open)? + open.len();
+    let end = text[start..].find(&close)? + start;
+    Some(&text[start..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GPX: &str = r#"<gpx><trk><trkseg>
+        <trkpt lat="35.3600" lon="138.7300"><ele>2300</ele></trkpt>
+        <trkpt lat="35.3610" lon="138.7300"><ele>2350</ele></trkpt>
+        <trkpt lat="35.3620" lon="138.7300"><ele>2400</ele></trkpt>
+        <trkpt lat="35.3630" lon="138.7300"/>
+    </trkseg></trk></gpx>"#;
+
+    #[test]
+    fn gpx_tracks_analyze_natively() {
+        let points = TrackAnalyzer::parse_gpx(GPX).unwrap();
+        assert_eq!(points.len(), 12);
+        assert!(points[11].is_nan());
+
+        // With no tiles loaded the GPS elevations are used
+        let mut analyzer = TrackAnalyzer::new();
+        analyzer.set_smoothing_window(1);
+        let (lats, lons): (Vec<f64>, Vec<f64>) = points[..9]
+            .chunks_exact(3)
+            .map(|point| (point[0], point[1]))
+            .unzip();
+        let elevations: Vec<f32> = points[..9].chunks_exact(3).map(|p| p[2] as f32).collect();
+        let analysis = analyzer
+            .analyze(&Mosaic::new(14), &lats, &lons, &elevations)
+            .unwrap();
+        assert_eq!(analysis.count(), 3);
+        assert_eq!(analysis.get_elevations(), [2300.0, 2350.0, 2400.0]);
+        let distances = analysis.get_distances();
+        assert!((distances[2] - 222.4).abs() < 1.0, "{}", distances[2]);
+        assert!(analysis.get_grades().iter().all(|&grade| grade > 0.4));
+
+        assert!(matches!(
+            analyzer.analyze(&Mosaic::new(14), &lats, &lons, &[]),
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn gpx_with_non_finite_values_is_rejected() {
+        for point in [
+            r#"<trkpt lat="NaN" lon="138.73"><ele>2300</ele></trkpt>"#,
+            r#"<trkpt lat="35.36" lon="inf"><ele>2300</ele></trkpt>"#,
+            r#"<trkpt lat="35.36" lon="138.73"><ele>NaN</ele></trkpt>"#,
+            r#"<trkpt lat="35.36" lon="138.73"><ele>-inf</ele></trkpt>"#,
+        ] {
+            assert!(
+                matches!(TrackAnalyzer::parse_gpx(point), Err(Error::Decode(_))),
+                "{}",
+                point
+            );
+        }
+    }
+
+    #[test]
+    fn tracks_with_non_finite_values_are_rejected() {
+        let analyzer = TrackAnalyzer::new();
+        let mosaic = Mosaic::new(14);
+        let elevations = [2300.0, 2350.0];
+        for (lats, lons) in [
+            ([35.36, f64::NAN], [138.73, 138.73]),
+            ([35.36, 35.37], [f64::INFINITY, 138.73]),
+        ] {
+            assert!(matches!(
+                analyzer.analyze(&mosaic, &lats, &lons, &elevations),
+                Err(Error::InvalidArgument(_))
+            ));
+        }
+        assert!(matches!(
+            analyzer.analyze(
+                &mosaic,
+                &[35.36, 35.37],
+                &[138.73, 138.73],
+                &[2300.0, f32::INFINITY]
+            ),
+            Err(Error::InvalidElevations(_))
+        ));
+    }
+}
@@ -0,0 +1,181 @@
+use std::f64::consts::FRAC_PI_4;
+
+use crate::globe::{WGS84_A, WGS84_B};
+
+/// Transverse Mercator projection of an ellipsoid (UTM and similar grids)
+/// Krüger's series to sixth order in the third flattening, sub-millimeter
+/// within a few thousand kilometers of the central meridian
+pub(crate) struct TransverseMercator {
+    /// Central meridian in degrees
+    lon0: f64,
+    false_easting: f64,
+    false_northing: f64,
+    eccentricity: f64,
+    /// Rectifying radius times the central meridian scale factor
+    radius: f64,
+    alpha: [f64; 6],
+    beta: [f64; 6],
+    /// Northing of the origin latitude before the false northing
+    origin_northing: f64,
+}
+
+impl TransverseMercator {
+    /// Projection on the WGS84 ellipsoid
+    pub fn wgs84(lon0: f64, lat0: f64, k0: f64, false_easting: f64, false_northing: f64) -> Self {
+        let flattening = 1.0 - WGS84_B / WGS84_A;
+        TransverseMercator::new(WGS84_A, flattening, lon0, lat0, k0)
+            .with_false_origin(false_easting, false_northing)
+    }
+
+    /// Projection on the GRS80 ellipsoid (JGD2000 / JGD2011)
+    pub fn grs80(lon0: f64, lat0: f64, k0: f64) -> Self {
+        TransverseMercator::new(WGS84_A, 1.0 / 298.257_222_101, lon0, lat0, k0)
+    }
+
+    pub fn new(a: f64, flattening: f64, lon0: f64, lat0: f64, k0: f64) -> Self {
+        let n = flattening / (2.0 - flattening);
+        let powers = [n, n * n, n.powi(3), n.powi(4), n.powi(5), n.powi(6)];
+        let series = |terms: [&[f64]; 6]| {
+            terms.map(|coefficients| {
+                // Terms of coefficient j start at n^(j+1)
+                let skip = 6 - coefficients.len();
+                coefficients
+                    .iter()
+                    .zip(&powers[skip..])
+                    .map(|(c, p)| c * p)
+                    .sum::<f64>()
+            })
+        };
+        let alpha = series([
+            &[
+                1.0 / 2.0,
+                -2.0 / 3.0,
+                5.0 / 16.0,
+                41.0 / 180.0,
+                -127.0 / 288.0,
+                7891.0 / 37800.0,
+            ],
+            &[
+                13.0 / 48.0,
+                -3.0 / 5.0,
+                557.0 / 1440.0,
+                281.0 / 630.0,
+                -1983433.0 / 1935360.0,
+            ],
+            &[
+                61.0 / 240.0,
+                -103.0 / 140.0,
+                15061.0 / 26880.0,
+                167603.0 / 181440.0,
+            ],
+            &[49561.0 / 161280.0, -179.0 / 168.0, 6601661.0 / 7257600.0],
+            &[34729.0 / 80640.0, -3418889.0 / 1995840.0],
+            &[212378941.0 / 319334400.0],
+        ]);
+        let beta = series([
+            &[
+                1.0 / 2.0,
+                -2.0 / 3.0,
+                37.0 / 96.0,
+                -1.0 / 360.0,
+                -81.0 / 512.0,
+                96199.0 / 604800.0,
+            ],
+            &[
+                1.0 / 48.0,
+                1.0 / 15.0,
+                -437.0 / 1440.0,
+                46.0 / 105.0,
+                -1118711.0 / 3870720.0,
+            ],
+            &[
+                17.0 / 480.0,
+                -37.0 / 840.0,
+                -209.0 / 4480.0,
+                5569.0 / 90720.0,
+            ],
+            &[4397.0 / 161280.0, -11.0 / 504.0, -830251.0 / 7257600.0],
+            &[4583.0 / 161280.0, -108847.0 / 3991680.0],
+            &[20648693.0 / 638668800.0],
+        ]);
+        let n2 = n * n;
+        let rectifying = a / (1.0 + n) * (1.0 + n2 / 4.0 + n2 * n2 / 64.0 + n2 * n2 * n2 / 256.0);
+
+        let mut projection = TransverseMercator {
+            lon0,
+            false_easting: 0.0,
+            false_northing: 0.0,
+            eccentricity: (flattening * (2.0 - flattening)).sqrt(),
+            radius: k0 * rectifying,
+            alpha,
+            beta,
+            origin_northing: 0.0,
+        };
+        projection.origin_northing = projection.forward(lat0, lon0).1;
+        projection
+    }
+
+    pub fn with_false_origin(mut self, false_easting: f64, false_northing: f64) -> Self {
+        self.false_easting = false_easting;
+        self.false_northing = false_northing;
+        self
+    }
+
+    /// (easting, northing) in meters of a lat/lon in degrees
+    pub fn forward(&self, lat: f64, lon: f64) -> (f64, f64) {
+        let chi = self.conformal_latitude(lat.to_radians());
+        let lambda = (lon - self.lon0).to_radians();
+        let xi = chi.tan().atan2(lambda.cos());
+        let eta = (lambda.sin() * chi.cos()).atanh();
+
+        let (mut x, mut y) = (eta, xi);
+        for (j, alpha) in self.alpha.iter().enumerate() {
+            let k = 2.0 * (j + 1) as f64;
+            x += alpha * (k * xi).cos() * (k * eta).sinh();
+            y += alpha * (k * xi).sin() * (k * eta).cosh();
+        }
+        (
+            self.false_easting + self.radius * x,
+            self.false_northing + self.radius * y - self.origin_northing,
+        )
+    }
+
+    /// (lat, lon) in degrees of an easting and northing in meters
+    pub fn inverse(&self, easting: f64, northing: f64) -> (f64, f64) {
+        let xi = (northing - self.false_northing + self.origin_northing) / self.radius;
+        let eta = (easting - self.false_easting) / self.radius;
+
+        let (mut xi1, mut eta1) = (xi, eta);
+        for (j, beta) in self.beta.iter().enumerate() {
+            let k = 2.0 * (j + 1) as f64;
+            xi1 -= beta * (k * xi).sin() * (k * eta).cosh();
+            eta1 -= beta * (k * xi).cos() * (k * eta).sinh();
+        }
+        let chi = (xi1.sin() / eta1.cosh()).asin();
+        let lambda = eta1.sinh().atan2(xi1.cos());
+        (
+            self.geodetic_latitude(chi).to_degrees(),
+            self.lon0 + lambda.to_degrees(),
+        )
+    }
+
+    fn conformal_latitude(&self, phi: f64) -> f64 {
+        let e = self.eccentricity;
+        let e_sin = e * phi.sin();
+        let stretch = ((1.0 - e_sin) / (1.0 + e_sin)).powf(e / 2.0);
+        2.0 * ((FRAC_PI_4 + phi / 2.0).tan() * stretch).atan() - 2.0 * FRAC_PI_4
+    }
+
+    /// Inverse of conformal_latitude by fixed-point iteration (the eccentricity
+    /// term shrinks the error about 150 times per step)
+    fn geodetic_latitude(&self, chi: f64) -> f64 {
+        let e = self.eccentricity;
+        let mut phi = chi;
+        for _ in 0..8 {
+            let e_sin = e * phi.sin();
+            let stretch = ((1.0 + e_sin) / (1.0 - e_sin)).powf(e / 2.0);
+            phi = 2.0 * ((FRAC_PI_4 + chi / 2.0).tan() * stretch).atan() - 2.0 * FRAC_PI_4;
+        }
+        phi
+    }
+}
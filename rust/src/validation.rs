@@ -0,0 +1,90 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::Error;
+
+/// Deepest zoom level GSI serves (and 2^zoom still fits tile indices)
+const MAX_ZOOM: u8 = 24;
+/// Web Mercator latitude limit in degrees
+pub(crate) const MAX_LATITUDE: f64 = 85.051_128_779_806_59;
+
+static STRICT: AtomicBool = AtomicBool::new(false);
+
+/// Strict input validation switch
+/// In strict mode these check zoom (0-24), latitude / longitude, tile indices
+/// and that sizes are finite and positive, failing with an Error naming the
+/// parameter and value instead of returning NaN or wrapped geometry:
+/// CoordinateTransform, the MeshGenerator entry points, Camera::visible_tiles,
+/// HeightfieldCollider::new, Mosaic::set_tile and TileUrlBuilder::url.
+/// Lookups that return undefined for bad input (Mosaic::elevation_at and the
+/// other Mosaic queries), the analysis types and the exporters ignore the
+/// switch. Off by default.
+#[wasm_bindgen]
+pub struct Validation;
+
+#[wasm_bindgen]
+impl Validation {
+    #[wasm_bindgen]
+    pub fn set_strict(enabled: bool) {
+        STRICT.store(enabled, Ordering::Relaxed);
+    }
+
+    #[wasm_bindgen]
+    pub fn is_strict() -> bool {
+        STRICT.load(Ordering::Relaxed)
+    }
+}
+
+fn check(
+    parameter: &'static str,
+    valid: bool,
+    value: impl ToString,
+    expected: impl FnOnce() -> String,
+) -> Result<(), Error> {
+    if valid || !Validation::is_strict() {
+        return Ok(());
+    }
+    Err(Error::InvalidParameter {
+        parameter,
+        value: value.to_string(),
+        expected: expected(),
+    })
+}
+
+pub(crate) fn check_zoom(zoom: u8) -> Result<(), Error> {
+    check("zoom", zoom <= MAX_ZOOM, zoom, || format!("0-{}", MAX_ZOOM))
+}
+
+/// Tile index at a zoom level; check the zoom first
+pub(crate) fn check_tile_index(parameter: &'static str, index: u32, zoom: u8) -> Result<(), Error> {
+    let tiles = 1u64 << zoom.min(MAX_ZOOM);
+    check(parameter, (index as u64) < tiles, index, || {
+        format!("0-{} at zoom {}", tiles - 1, zoom)
+    })
+}
+
+pub(crate) fn check_latitude(parameter: &'static str, latitude: f64) -> Result<(), Error> {
+    check(parameter, latitude.abs() <= 90.0, latitude, || {
+        "-90 to 90 degrees".into()
+    })
+}
+
+/// Latitude within the Web Mercator tile grid
+pub(crate) fn check_mercator_latitude(parameter: &'static str, latitude: f64) -> Result<(), Error> {
+    check(parameter, latitude.abs() <= MAX_LATITUDE, latitude, || {
+        format!("-{0} to {0} degrees", MAX_LATITUDE)
+    })
+}
+
+pub(crate) fn check_longitude(parameter: &'static str, longitude: f64) -> Result<(), Error> {
+    check(parameter, longitude.abs() <= 180.0, longitude, || {
+        "-180 to 180 degrees".into()
+    })
+}
+
+pub(crate) fn check_positive(parameter: &'static str, value: f64) -> Result<(), Error> {
+    check(parameter, value.is_finite() && value > 0.0, value, || {
+        "a finite number above 0".into()
+    })
+}
@@ -0,0 +1,314 @@
+use wasm_bindgen::prelude::*;
+
+use crate::error::Error;
+
+/// Patch format version written as the first byte of every patch
+const PATCH_VERSION: u8 = 1;
+
+/// Patch header: version (u8) + sequence (u32 LE) + field mask (u16 LE)
+const HEADER_LEN: usize = 7;
+
+// Field layout: indices 0-7 are f64, 8-9 are f32, 10 is the u32 layer mask
+const F64_FIELDS: usize = 8;
+const F32_FIELDS: usize = 2;
+const FIELD_COUNT: usize = F64_FIELDS + F32_FIELDS + 1;
+const LAYER_MASK_FIELD: usize = F64_FIELDS + F32_FIELDS;
+
+const VIEWPOINT_LAT: usize = 0;
+const VIEWPOINT_LON: usize = 1;
+const CAMERA_X: usize = 2;
+const TARGET_X: usize = 5;
+const FOV: usize = 0;
+const EXAGGERATION: usize = 1;
+
+/// Shared camera and layer state for collaborative viewing
+/// Peers exchange compact binary patches produced by diff() over a data channel
+#[wasm_bindgen]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ViewState {
+    /// viewpoint lat/lon (degrees), camera xyz, target xyz (world units)
+    f64_values: [f64; F64_FIELDS],
+    /// fov (degrees), vertical exaggeration
+    f32_values: [f32; F32_FIELDS],
+    layer_mask: u32,
+    sequence: u32,
+}
+
+impl Default for ViewState {
+    fn default() -> Self {
+        ViewState {
+            f64_values: [0.0; F64_FIELDS],
+            f32_values: [60.0, 1.0],
+            layer_mask: 0,
+            sequence: 0,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl ViewState {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> ViewState {
+        ViewState::default()
+    }
+
+    /// Local modification counter, incremented by every setter
+    #[wasm_bindgen]
+    pub fn sequence(&self) -> u32 {
+        self.sequence
+    }
+
+    #[wasm_bindgen]
+    pub fn set_viewpoint(&mut self, lat: f64, lon: f64) {
+        self.f64_values[VIEWPOINT_LAT] = lat;
+        self.f64_values[VIEWPOINT_LON] = lon;
+        self.touch();
+    }
+
+    #[wasm_bindgen]
+    pub fn set_camera_position(&mut self, x: f64, y: f64, z: f64) {
+        self.f64_values[CAMERA_X..CAMERA_X + 3].copy_from_slice(&[x, y, z]);
+        self.touch();
+    }
+
+    #[wasm_bindgen]
+    pub fn set_camera_target(&mut self, x: f64, y: f64, z: f64) {
+        self.f64_values[TARGET_X..TARGET_X + 3].copy_from_slice(&[x, y, z]);
+        self.touch();
+    }
+
+    #[wasm_bindgen]
+    pub fn set_fov(&mut self, fov_deg: f32) {
+        self.f32_values[FOV] = fov_deg;
+        self.touch();
+    }
+
+    #[wasm_bindgen]
+    pub fn set_exaggeration(&mut self, exaggeration: f32) {
+        self.f32_values[EXAGGERATION] = exaggeration;
+        self.touch();
+    }
+
+    /// Bitmask of visible layers (bit meaning is defined by the frontend)
+    #[wasm_bindgen]
+    pub fn set_layer_mask(&mut self, mask: u32) {
+        self.layer_mask = mask;
+        self.touch();
+    }
+
+    /// Get viewpoint as [lat, lon]
+    #[wasm_bindgen]
+    pub fn viewpoint(&self) -> Vec<f64> {
+        self.f64_values[VIEWPOINT_LAT..=VIEWPOINT_LON].to_vec()
+    }
+
+    /// Get camera position as [x, y, z]
+    #[wasm_bindgen]
+    pub fn camera_position(&self) -> Vec<f64> {
+        self.f64_values[CAMERA_X..CAMERA_X + 3].to_vec()
+    }
+
+    /// Get camera target as [x, y, z]
+    #[wasm_bindgen]
+    pub fn camera_target(&self) -> Vec<f64> {
+        self.f64_values[TARGET_X..TARGET_X + 3].to_vec()
+    }
+
+    #[wasm_bindgen]
+    pub fn fov(&self) -> f32 {
+        self.f32_values[FOV]
+    }
+
+    #[wasm_bindgen]
+    pub fn exaggeration(&self) -> f32 {
+        self.f32_values[EXAGGERATION]
+    }
+
+    #[wasm_bindgen]
+    pub fn layer_mask(&self) -> u32 {
+        self.layer_mask
+    }
+
+    /// Copy of this state (e.g. to remember what was last sent to a peer)
+    #[wasm_bindgen]
+    pub fn snapshot(&self) -> ViewState {
+        self.clone()
+    }
+
+    /// Encode the changes needed to turn this state into `newer`
+    /// Only fields whose bit patterns differ are written
+    #[wasm_bindgen]
+    pub fn diff(&self, newer: &ViewState) -> Vec<u8> {
+        let mut mask = 0u16;
+        for field in 0..FIELD_COUNT {
+            if self.field_bits(field) != newer.field_bits(field) {
+                mask |= 1 << field;
+            }
+        }
+        newer.encode(mask)
+    }
+
+    /// Encode the complete state as a patch (for peers joining mid-session)
+    #[wasm_bindgen]
+    pub fn to_patch(&self) -> Vec<u8> {
+        self.encode((1 << FIELD_COUNT) - 1)
+    }
+
+    /// Apply a patch produced by diff() or to_patch()
+    /// The local sequence becomes the larger of the two sequences
+    #[wasm_bindgen]
+    pub fn apply_patch(&mut self, patch: &[u8]) -> Result<(), Error> {
+        let decoded = decode(patch)?;
+        for (field, value) in decoded.fields {
+            self.set_field_bits(field, value);
+        }
+        self.sequence = self.sequence.max(decoded.sequence);
+        Ok(())
+    }
+
+    /// Combine two patches into one; fields present in `later` win
+    #[wasm_bindgen]
+    pub fn merge_patches(earlier: &[u8], later: &[u8]) -> Result<Vec<u8>, Error> {
+        let earlier = decode(earlier)?;
+        let later = decode(later)?;
+
+        let mut merged = ViewState::default();
+        let mut mask = 0u16;
+        for (field, value) in earlier.fields.into_iter().chain(later.fields) {
+            merged.set_field_bits(field, value);
+            mask |= 1 << field;
+        }
+        merged.sequence = earlier.sequence.max(later.sequence);
+        Ok(merged.encode(mask))
+    }
+}
+
+struct DecodedPatch {
+    sequence: u32,
+    fields: Vec<(usize, u64)>,
+}
+
+impl ViewState {
+    fn touch(&mut self) {
+        self.sequence = self.sequence.wrapping_add(1);
+    }
+
+    /// Raw bits of a field, widened to u64
+    fn field_bits(&self, field: usize) -> u64 {
+        if field < F64_FIELDS {
+            self.f64_values[field].to_bits()
+        } else if field < LAYER_MASK_FIELD {
+            self.f32_values[field - F64_FIELDS].to_bits() as u64
+        } else {
+            self.layer_mask as u64
+        }
+    }
+
+    fn set_field_bits(&mut self, field: usize, bits: u64) {
+        if field < F64_FIELDS {
+            self.f64_values[field] = f64::from_bits(bits);
+        } else if field < LAYER_MASK_FIELD {
+            self.f32_values[field - F64_FIELDS] = f32::from_bits(bits as u32);
+        } else {
+            self.layer_mask = bits as u32;
+        }
+    }
+
+    fn encode(&self, mask: u16) -> Vec<u8> {
+        let mut out = Vec::with_capacity(HEADER_LEN + 8 * F64_FIELDS + 4 * 3);
+        out.push(PATCH_VERSION);
+        out.extend_from_slice(&self.sequence.to_le_bytes());
+        out.extend_from_slice(&mask.to_le_bytes());
+
+        for field in 0..FIELD_COUNT {
+            if mask & (1 << field) == 0 {
+                continue;
+            }
+            let bits = self.field_bits(field);
+            if field < F64_FIELDS {
+                out.extend_from_slice(&bits.to_le_bytes());
+            } else {
+                out.extend_from_slice(&(bits as u32).to_le_bytes());
+            }
+        }
+        out
+    }
+}
+
+fn decode(patch: &[u8]) -> Result<DecodedPatch, Error> {
+    if patch.len() < HEADER_LEN {
+        return Err(Error::Decode(format!(
+            "View state patch too short: {} bytes",
+            patch.len()
+        )));
+    }
+    if patch[0] != PATCH_VERSION {
+        return Err(Error::Decode(format!(
+            "Unsupported view state patch version: {}",
+            patch[0]
+        )));
+    }
+
+    let sequence = u32::from_le_bytes([patch[1], patch[2], patch[3], patch[4]]);
+    let mask = u16::from_le_bytes([patch[5], patch[6]]);
+    if mask >> FIELD_COUNT != 0 {
+        return Err(Error::Decode(format!(
+            "Invalid view state field mask: {:#06x}",
+            mask
+        )));
+    }
+
+    let mut fields = Vec::new();
+    let mut offset = HEADER_LEN;
+    for field in 0..FIELD_COUNT {
+        if mask & (1 << field) == 0 {
+            continue;
+        }
+        let width = if field < F64_FIELDS { 8 } else { 4 };
+        let bytes = patch.get(offset..offset + width).ok_or_else(|| {
+            Error::Decode(format!("Truncated view state patch at field {}", field))
+        })?;
+        let bits = if width == 8 {
+            u64::from_le_bytes(bytes.try_into().unwrap())
+        } else {
+            u32::from_le_bytes(bytes.try_into().unwrap()) as u64
+        };
+        fields.push((field, bits));
+        offset += width;
+    }
+
+    if offset != patch.len() {
+        return Err(Error::Decode(format!(
+            "Trailing bytes in view state patch: {}",
+            patch.len() - offset
+        )));
+    }
+
+    Ok(DecodedPatch { sequence, fields })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patches_round_trip_and_reject_malformed_input() {
+        let base = ViewState::new();
+        let mut newer = base.clone();
+        newer.set_viewpoint(35.36, 138.73);
+        newer.set_layer_mask(5);
+
+        let mut peer = base.clone();
+        peer.apply_patch(&base.diff(&newer)).unwrap();
+        assert_eq!(peer.viewpoint(), [35.36, 138.73]);
+        assert_eq!(peer.layer_mask(), 5);
+
+        let patch = newer.to_patch();
+        for malformed in [&patch[..3], &patch[..patch.len() - 1]] {
+            assert!(matches!(peer.apply_patch(malformed), Err(Error::Decode(_))));
+        }
+        let mut trailing = patch.clone();
+        trailing.push(0);
+        assert!(ViewState::merge_patches(&patch, &trailing).is_err());
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+
+use crate::heightfield::Heightfield;
+
+/// Corner of a triangle while clipping: pixel x, pixel y, elevation - sea level
+type Corner = [f32; 3];
+
+/// Flat water surface at sea_level over the heightmap meshed at tile_size
+/// (generate_sized placement): vertices and triangles where the terrain is at
+/// or below sea level. Cells under water on all four corners are merged into
+/// rectangles; the shore cells are cut along the linear interpolation of the
+/// heights over the terrain grid's triangles, so the shoreline meets the
+/// full-detail terrain mesh. The plane is flat, so the T-junctions where the
+/// rectangles meet smaller triangles leave no cracks.
+pub(crate) fn water_geometry(
+    heightmap: &Heightfield,
+    tile_size: f32,
+    sea_level: f32,
+) -> (Vec<f32>, Vec<u32>) {
+    let (width, height) = (heightmap.width, heightmap.height);
+    let pixel_size = tile_size / width as f32;
+    let depth = pixel_size * height as f32;
+    // No data (NaN) counts as land just above sea level
+    let level = |x: usize, y: usize| {
+        let level = heightmap.data[y * width + x] - sea_level;
+        if level.is_nan() {
+            f32::MIN_POSITIVE
+        } else {
+            level
+        }
+    };
+    let wet = |x: usize, y: usize| level(x, y) <= 0.0;
+
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    let mut welded: HashMap<[u32; 2], u32> = HashMap::new();
+    let mut emit = |x: f32, y: f32, vertices: &mut Vec<f32>| {
+        *welded.entry([x.to_bits(), y.to_bits()]).or_insert_with(|| {
+            vertices.extend_from_slice(&[
+                x * pixel_size - tile_size / 2.0,
+                sea_level,
+                y * pixel_size - depth / 2.0,
+            ]);
+            (vertices.len() / 3 - 1) as u32
+        })
+    };
+
+    let (columns, rows) = (width - 1, height - 1);
+    let submerged =
+        |x: usize, y: usize| wet(x, y) && wet(x + 1, y) && wet(x, y + 1) && wet(x + 1, y + 1);
+
+    // Runs of submerged cells, grown downwards while the next row has the same run
+    let mut open: HashMap<(usize, usize), usize> = HashMap::new();
+    let mut rectangles = Vec::new();
+    for y in 0..=rows {
+        let mut runs = Vec::new();
+        let mut x = 0;
+        while y < rows && x < columns {
+            if submerged(x, y) {
+                let start = x;
+                while x < columns && submerged(x, y) {
+                    x += 1;
+                }
+                runs.push((start, x));
+            } else {
+                x += 1;
+            }
+        }
+        let mut next = HashMap::with_capacity(runs.len());
+        for run in runs {
+            next.insert(run, open.remove(&run).unwrap_or(y));
+        }
+        rectangles.extend(open.drain().map(|((x0, x1), y0)| (x0, y0, x1, y)));
+        open = next;
+    }
+    // HashMap order varies; sort for deterministic output
+    rectangles.sort_unstable();
+    for (x0, y0, x1, y1) in rectangles {
+        let [top_left, top_right, bottom_left, bottom_right] =
+            [(x0, y0), (x1, y0), (x0, y1), (x1, y1)]
+                .map(|(x, y)| emit(x as f32, y as f32, &mut vertices));
+        indices.extend_from_slice(&[top_left, bottom_left, top_right]);
+        indices.extend_from_slice(&[top_right, bottom_left, bottom_right]);
+    }
+
+    for y in 0..rows {
+        for x in 0..columns {
+            let corners = [(x, y), (x + 1, y), (x, y + 1), (x + 1, y + 1)];
+            let wet_corners = corners.iter().filter(|&&(x, y)| wet(x, y)).count();
+            if wet_corners == 0 || wet_corners == 4 {
+                continue;
+            }
+            let corner = |i: usize| -> Corner {
+                let (x, y) = corners[i];
+                [x as f32, y as f32, level(x, y)]
+            };
+            // Same diagonal and winding as the terrain grid
+            for triangle in [[0, 2, 1], [1, 2, 3]] {
+                let polygon = clip_wet(&triangle.map(corner));
+                if polygon.len() < 3 {
+                    continue;
+                }
+                let fan: Vec<u32> = polygon
+                    .iter()
+                    .map(|c| emit(c[0], c[1], &mut vertices))
+                    .collect();
+                for i in 1..fan.len() - 1 {
+                    let (a, b, c) = (fan[0], fan[i], fan[i + 1]);
+                    if a != b && b != c && a != c && area(&polygon, 0, i, i + 1) > 1e-9 {
+                        indices.extend_from_slice(&[a, b, c]);
+                    }
+                }
+            }
+        }
+    }
+    (vertices, indices)
+}
+
+/// Keep the part of a triangle at or below sea level (level <= 0)
+fn clip_wet(polygon: &[Corner]) -> Vec<Corner> {
+    let mut output = Vec::with_capacity(polygon.len() + 1);
+    for (i, current) in polygon.iter().enumerate() {
+        let previous = &polygon[(i + polygon.len() - 1) % polygon.len()];
+        let (inside, was_inside) = (current[2] <= 0.0, previous[2] <= 0.0);
+        if inside != was_inside {
+            // Ordered endpoints, so both triangles sharing the edge cut it alike
+            let (a, b) = if previous.map(f32::to_bits) < current.map(f32::to_bits) {
+                (previous, current)
+            } else {
+                (current, previous)
+            };
+            let t = a[2] / (a[2] - b[2]);
+            output.push([a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t, 0.0]);
+        }
+        if inside {
+            output.push(*current);
+        }
+    }
+    output
+}
+
+/// Twice the unsigned area of a fan triangle, in square pixels
+fn area(polygon: &[Corner], a: usize, b: usize, c: usize) -> f32 {
+    let (a, b, c) = (polygon[a], polygon[b], polygon[c]);
+    ((b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])).abs()
+}
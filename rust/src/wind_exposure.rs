@@ -0,0 +1,97 @@
+use wasm_bindgen::prelude::*;
+
+use crate::error::Error;
+use crate::heightfield::Heightfield;
+use crate::hydrology::D8_OFFSETS;
+
+/// Topographic exposure (TOPEX) index
+/// For each cell the horizon inclination is found in the 8 compass directions
+/// within a limiting distance and the angles are summed. Negative values mark
+/// exposed ridges and summits, positive values sheltered valleys and hollows.
+#[wasm_bindgen]
+pub struct WindExposure {
+    max_distance: f32,
+}
+
+#[wasm_bindgen]
+impl WindExposure {
+    /// max_distance: search radius for the horizon in meters (e.g. 2000)
+    #[wasm_bindgen(constructor)]
+    pub fn new(max_distance: f32) -> WindExposure {
+        WindExposure {
+            max_distance: max_distance.max(0.0),
+        }
+    }
+
+    /// Compute the TOPEX raster, in degrees (sum of 8 horizon angles)
+    /// elevations: width x height heightmap
+    /// cell_size: ground distance between pixels in meters
+    #[wasm_bindgen]
+    pub fn compute(
+        &self,
+        elevations: &[f32],
+        width: u32,
+        height: u32,
+        cell_size: f32,
+    ) -> Result<Vec<f32>, Error> {
+        let heightmap = Heightfield::from_slice(elevations, width, height)?;
+        if cell_size <= 0.0 {
+            return Err(Error::InvalidArgument("Cell size must be positive".into()));
+        }
+
+        let (width, height) = (width as usize, height as usize);
+        let mut topex = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let total: f32 = D8_OFFSETS
+                    .iter()
+                    .map(|&(dx, dy)| self.horizon_angle(&heightmap, x, y, dx, dy, cell_size))
+                    .sum();
+                topex.push(total.to_degrees());
+            }
+        }
+        Ok(topex)
+    }
+}
+
+impl WindExposure {
+    /// Steepest inclination (radians) from a cell to any cell along one direction
+    /// The horizon is flat (0) when the raster edge is reached immediately
+    fn horizon_angle(
+        &self,
+        heightmap: &Heightfield,
+        x: usize,
+        y: usize,
+        dx: isize,
+        dy: isize,
+        cell_size: f32,
+    ) -> f32 {
+        let origin = heightmap.data[y * heightmap.width + x];
+        let step = if dx != 0 && dy != 0 {
+            cell_size * std::f32::consts::SQRT_2
+        } else {
+            cell_size
+        };
+
+        let mut best: Option<f32> = None;
+        let (mut cx, mut cy) = (x as isize, y as isize);
+        let mut distance = 0.0;
+        loop {
+            cx += dx;
+            cy += dy;
+            distance += step;
+            if distance > self.max_distance
+                || cx < 0
+                || cy < 0
+                || cx >= heightmap.width as isize
+                || cy >= heightmap.height as isize
+            {
+                break;
+            }
+            let rise = heightmap.data[cy as usize * heightmap.width + cx as usize] - origin;
+            let angle = rise.atan2(distance);
+            best = Some(best.map_or(angle, |b| b.max(angle)));
+        }
+        best.unwrap_or(0.0)
+    }
+}